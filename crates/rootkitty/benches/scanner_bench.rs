@@ -1,7 +1,9 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use rootkitty::scanner::{Scanner, ScannerImpl};
+use rootkitty::source::{SyntheticSource, TreeShape};
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tempfile::TempDir;
 
 /// Create a deterministic directory structure for benchmarking
@@ -179,6 +181,18 @@ fn bench_scanner_comparison(c: &mut Criterion) {
         },
     );
 
+    // Benchmark two-phase implementation (gather with jwalk, then stat with rayon)
+    group.bench_with_input(
+        BenchmarkId::new("two_phase_impl", "10x15x30"),
+        &test_path,
+        |b, path| {
+            b.iter(|| {
+                let scanner = Scanner::new_with_impl(black_box(path), ScannerImpl::TwoPhase);
+                scanner.scan().unwrap()
+            })
+        },
+    );
+
     group.finish();
 }
 
@@ -237,11 +251,48 @@ fn bench_scanner_scaling(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark aggregation alone, on an in-memory tree with no disk I/O
+fn bench_scanner_synthetic(c: &mut Criterion) {
+    let mut group = c.benchmark_group("synthetic");
+    group.sample_size(10);
+
+    // 4 levels of 10 subdirectories with 10 files each = ~122,221 entries
+    let shape = TreeShape {
+        depth: 4,
+        fanout: 10,
+        files_per_dir: 10,
+        ..Default::default()
+    };
+    let source = Arc::new(SyntheticSource::new("/synthetic", shape));
+
+    for (impl_name, implementation) in [
+        ("custom", ScannerImpl::Custom),
+        ("walkdir", ScannerImpl::Walkdir),
+        ("hybrid", ScannerImpl::Hybrid),
+        ("two_phase", ScannerImpl::TwoPhase),
+    ] {
+        group.bench_with_input(
+            BenchmarkId::new(impl_name, "4x10x10"),
+            &source,
+            |b, source| {
+                b.iter(|| {
+                    let mut scanner = Scanner::new_with_impl("/synthetic", implementation);
+                    scanner.set_source(black_box(source.clone()));
+                    scanner.scan().unwrap()
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_scanner_directory_walk,
     bench_scanner_depth,
     bench_scanner_comparison,
-    bench_scanner_scaling
+    bench_scanner_scaling,
+    bench_scanner_synthetic
 );
 criterion_main!(benches);
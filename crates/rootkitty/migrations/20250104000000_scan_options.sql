@@ -0,0 +1,6 @@
+-- Migration: Store the options each scan was run with (exclude/include patterns etc.)
+-- Serialized as JSON so resumed and repeated scans use the same configuration
+-- Per-scan entry tables gain a `kind` column for marker entries; existing tables
+-- are upgraded on open by Database::upgrade_entry_tables
+
+ALTER TABLE scans ADD COLUMN scan_options TEXT;
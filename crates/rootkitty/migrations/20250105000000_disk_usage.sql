@@ -0,0 +1,6 @@
+-- Migration: Track allocated disk usage (st_blocks * 512) alongside apparent size
+-- NULL for scans recorded before this column existed; readers fall back to total_size
+-- Per-scan entry tables gain a `disk_usage` column, added on open by
+-- Database::upgrade_entry_tables
+
+ALTER TABLE scans ADD COLUMN total_disk_usage INTEGER;
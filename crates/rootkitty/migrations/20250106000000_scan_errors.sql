@@ -0,0 +1,16 @@
+-- Migration: Record paths the scanner could not read instead of dropping them
+-- error_count is NULL for scans recorded before errors were tracked
+
+CREATE TABLE IF NOT EXISTS scan_errors (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    scan_id INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    message TEXT NOT NULL,
+    FOREIGN KEY (scan_id) REFERENCES scans(id) ON DELETE CASCADE,
+    UNIQUE(scan_id, path)
+);
+
+CREATE INDEX idx_scan_errors_scan_id ON scan_errors(scan_id);
+
+ALTER TABLE scans ADD COLUMN error_count INTEGER;
@@ -0,0 +1,4 @@
+-- Migration: Store how long each scan took, for progress estimates on rescans
+-- Summed over pauses and resumes; NULL for scans recorded before durations were stored
+
+ALTER TABLE scans ADD COLUMN duration_ms INTEGER;
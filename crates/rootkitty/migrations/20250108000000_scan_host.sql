@@ -0,0 +1,4 @@
+-- Migration: Record the machine a remote scan ran on (`rootkitty scan ssh://host/path`)
+-- NULL for scans of the local filesystem
+
+ALTER TABLE scans ADD COLUMN host TEXT;
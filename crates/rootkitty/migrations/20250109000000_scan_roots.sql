@@ -0,0 +1,5 @@
+-- Migration: Record the paths a multi-root scan (`rootkitty scan /home /var`) covers,
+-- as a JSON array; root_path holds their common ancestor
+-- NULL for scans of a single root
+
+ALTER TABLE scans ADD COLUMN roots TEXT;
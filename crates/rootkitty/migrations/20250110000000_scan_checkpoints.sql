@@ -0,0 +1,10 @@
+-- Migration: Record the directories a scan has finished as it goes, so a paused
+-- scan resumes by skipping them instead of loading every path it recorded
+-- Rows are dropped once the scan completes
+
+CREATE TABLE IF NOT EXISTS scan_checkpoints (
+    scan_id INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    FOREIGN KEY (scan_id) REFERENCES scans(id) ON DELETE CASCADE,
+    PRIMARY KEY (scan_id, path)
+);
@@ -0,0 +1,6 @@
+-- Migration: Count the files and directories a depth-limited scan (--max-depth)
+-- summarized instead of storing, so an interrupted scan's totals can still be
+-- recovered from the database
+
+ALTER TABLE scans ADD COLUMN summarized_files INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE scans ADD COLUMN summarized_dirs INTEGER NOT NULL DEFAULT 0;
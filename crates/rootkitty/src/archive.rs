@@ -0,0 +1,361 @@
+//! Listing the members of tar and zip archives as virtual entries
+//!
+//! With archive browsing enabled the scanner records the members of each
+//! archive it finds below the archive's own entry, so the archive unfolds like
+//! a directory. Members are flagged [`EntryKind::InArchive`]: their size is
+//! the uncompressed size and their disk usage the compressed size, which for
+//! compressed tarballs (one stream for the whole archive) is the member's
+//! share of the archive file. Directories missing from the listing are filled
+//! in and sized from the members below them.
+//!
+//! Members never count towards scan or directory totals; the archive file
+//! itself already does.
+
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+use crate::scanner::{EntryKind, FileEntry};
+
+/// Archive formats whose members can be listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+    TarZst,
+    Zip,
+}
+
+impl ArchiveFormat {
+    /// Recognize an archive by its file name
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(ArchiveFormat::TarZst)
+        } else if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+/// One member as listed by the archive, before directories are filled in
+struct Member {
+    size: u64,
+    compressed: u64,
+    is_dir: bool,
+    modified_at: Option<DateTime<Utc>>,
+}
+
+/// List the members of `archive`, read from `file`, as entries below it
+///
+/// Reading a compressed tarball means decompressing all of it, as its
+/// members are only found by reading through the stream.
+pub fn member_entries(
+    archive: &FileEntry,
+    format: ArchiveFormat,
+    file: File,
+) -> Result<Vec<FileEntry>> {
+    let reader = BufReader::new(file);
+    let members = match format {
+        ArchiveFormat::Tar => read_tar(reader)?,
+        ArchiveFormat::TarGz => read_tar(flate2::read::MultiGzDecoder::new(reader))?,
+        ArchiveFormat::TarZst => read_tar(ruzstd::decoding::StreamingDecoder::new(reader)?)?,
+        ArchiveFormat::Zip => read_zip(reader)?,
+    };
+
+    // A tarball is compressed as a whole, so members get a share of the file
+    let members = match format {
+        ArchiveFormat::TarGz | ArchiveFormat::TarZst => {
+            let total: u64 = members.values().map(|m| m.size).sum();
+            members
+                .into_iter()
+                .map(|(path, mut member)| {
+                    member.compressed = if total > 0 {
+                        (member.size as u128 * archive.size as u128 / total as u128) as u64
+                    } else {
+                        0
+                    };
+                    (path, member)
+                })
+                .collect()
+        }
+        ArchiveFormat::Tar | ArchiveFormat::Zip => members,
+    };
+
+    Ok(build_entries(archive, members))
+}
+
+/// Member path relative to the archive root, or None if it would escape it
+fn member_path(path: &Path) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+            Component::ParentDir => return None,
+        }
+    }
+    (!relative.as_os_str().is_empty()).then_some(relative)
+}
+
+fn read_tar<R: Read>(reader: R) -> Result<BTreeMap<PathBuf, Member>> {
+    use tar::EntryType;
+
+    let mut archive = tar::Archive::new(reader);
+    let mut members = BTreeMap::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let header = entry.header();
+        let is_dir = match header.entry_type() {
+            EntryType::Directory => true,
+            EntryType::XGlobalHeader | EntryType::XHeader => continue,
+            _ => false,
+        };
+        let Some(path) = member_path(&entry.path()?) else {
+            continue;
+        };
+        let size = if is_dir { 0 } else { entry.size() };
+        let modified_at = header
+            .mtime()
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs as i64, 0));
+        // A later copy of the same path replaces the earlier one, as on extraction
+        members.insert(
+            path,
+            Member {
+                size,
+                compressed: size,
+                is_dir,
+                modified_at,
+            },
+        );
+    }
+    Ok(members)
+}
+
+fn read_zip<R: Read + std::io::Seek>(reader: R) -> Result<BTreeMap<PathBuf, Member>> {
+    let mut archive = zip::ZipArchive::new(reader)?;
+    let mut members = BTreeMap::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        let Some(path) = member_path(Path::new(file.name())) else {
+            continue;
+        };
+        let is_dir = file.is_dir();
+        let modified_at = file
+            .last_modified()
+            .and_then(|dt| NaiveDateTime::try_from(dt).ok())
+            .map(|dt| dt.and_utc());
+        members.insert(
+            path,
+            Member {
+                size: if is_dir { 0 } else { file.size() },
+                compressed: if is_dir { 0 } else { file.compressed_size() },
+                is_dir,
+                modified_at,
+            },
+        );
+    }
+    Ok(members)
+}
+
+/// Turn listed members into entries, filling in and sizing their directories
+fn build_entries(archive: &FileEntry, mut members: BTreeMap<PathBuf, Member>) -> Vec<FileEntry> {
+    // Every ancestor of a member is a directory, listed or not
+    let files: Vec<(PathBuf, u64, u64)> = members
+        .iter()
+        .filter(|(_, member)| !member.is_dir)
+        .map(|(path, member)| (path.clone(), member.size, member.compressed))
+        .collect();
+    for (path, size, compressed) in files {
+        for dir in path.ancestors().skip(1) {
+            if dir.as_os_str().is_empty() {
+                break;
+            }
+            let member = members.entry(dir.to_path_buf()).or_insert(Member {
+                size: 0,
+                compressed: 0,
+                is_dir: true,
+                modified_at: None,
+            });
+            member.is_dir = true;
+            member.size += size;
+            member.compressed += compressed;
+        }
+    }
+
+    members
+        .into_iter()
+        .map(|(relative, member)| {
+            let path = archive.path.join(&relative);
+            FileEntry {
+                name: relative
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                parent_path: path.parent().map(Path::to_path_buf),
+                path,
+                size: member.size,
+                disk_usage: member.compressed,
+                is_dir: member.is_dir,
+                modified_at: member.modified_at,
+                depth: archive.depth + relative.components().count(),
+                kind: EntryKind::InArchive,
+                stat: None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn list(path: &Path, format: ArchiveFormat) -> Result<Vec<FileEntry>> {
+        member_entries(&archive_entry(path), format, File::open(path).unwrap())
+    }
+
+    fn archive_entry(path: &Path) -> FileEntry {
+        let size = std::fs::metadata(path).unwrap().len();
+        FileEntry {
+            path: path.to_path_buf(),
+            name: path.file_name().unwrap().to_string_lossy().to_string(),
+            parent_path: path.parent().map(Path::to_path_buf),
+            size,
+            disk_usage: size,
+            is_dir: false,
+            modified_at: None,
+            depth: 1,
+            kind: EntryKind::Normal,
+            stat: None,
+        }
+    }
+
+    fn tar_bytes() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, data) in [
+            ("docs/readme.txt", &b"hello"[..]),
+            ("docs/img/a.png", &[0u8; 300][..]),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(1_700_000_000);
+            header.set_cksum();
+            builder.append_data(&mut header, path, data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    fn find<'a>(entries: &'a [FileEntry], archive: &Path, member: &str) -> &'a FileEntry {
+        entries
+            .iter()
+            .find(|e| e.path == archive.join(member))
+            .unwrap_or_else(|| panic!("{} not listed", member))
+    }
+
+    #[test]
+    fn test_format_from_name() {
+        assert_eq!(
+            ArchiveFormat::from_name("a.TAR.GZ"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_name("a.tgz"),
+            Some(ArchiveFormat::TarGz)
+        );
+        assert_eq!(
+            ArchiveFormat::from_name("a.tar.zst"),
+            Some(ArchiveFormat::TarZst)
+        );
+        assert_eq!(ArchiveFormat::from_name("a.zip"), Some(ArchiveFormat::Zip));
+        assert_eq!(ArchiveFormat::from_name("a.gz"), None);
+    }
+
+    #[test]
+    fn test_tarball_members() {
+        let temp_dir = TempDir::new().unwrap();
+        let tar_path = temp_dir.path().join("plain.tar");
+        std::fs::write(&tar_path, tar_bytes()).unwrap();
+        let gz_path = temp_dir.path().join("packed.tar.gz");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&tar_bytes()).unwrap();
+        std::fs::write(&gz_path, encoder.finish().unwrap()).unwrap();
+        let zst_path = temp_dir.path().join("packed.tar.zst");
+        let compressed = ruzstd::encoding::compress_to_vec(
+            &tar_bytes()[..],
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+        std::fs::write(&zst_path, compressed).unwrap();
+
+        let entries = list(&tar_path, ArchiveFormat::Tar).unwrap();
+        assert_eq!(entries.len(), 4);
+        let docs = find(&entries, &tar_path, "docs");
+        assert!(docs.is_dir);
+        assert_eq!(docs.size, 305);
+        assert_eq!(docs.depth, 2);
+        assert_eq!(docs.parent_path.as_deref(), Some(tar_path.as_path()));
+        let png = find(&entries, &tar_path, "docs/img/a.png");
+        assert_eq!((png.size, png.disk_usage, png.depth), (300, 300, 4));
+        assert_eq!(png.kind, EntryKind::InArchive);
+        assert!(png.modified_at.is_some());
+
+        for (path, format) in [
+            (&gz_path, ArchiveFormat::TarGz),
+            (&zst_path, ArchiveFormat::TarZst),
+        ] {
+            let archive = archive_entry(path);
+            let entries = member_entries(&archive, format, File::open(path).unwrap()).unwrap();
+            let docs = find(&entries, path, "docs");
+            assert_eq!(docs.size, 305);
+            // The whole file is shared out between the members
+            let shares: u64 = entries
+                .iter()
+                .filter(|e| !e.is_dir)
+                .map(|e| e.disk_usage)
+                .sum();
+            assert!(shares <= archive.size && shares + 2 >= archive.size);
+        }
+    }
+
+    #[test]
+    fn test_zip_members() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("bundle.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        writer.add_directory("empty/", options).unwrap();
+        writer.start_file("src/main.rs", options).unwrap();
+        writer.write_all(b"fn main() {}").unwrap();
+        // Members can't escape the archive
+        writer.start_file("../evil", options).unwrap();
+        writer.finish().unwrap();
+
+        let entries = list(&zip_path, ArchiveFormat::Zip).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(find(&entries, &zip_path, "empty").is_dir);
+        let main = find(&entries, &zip_path, "src/main.rs");
+        assert_eq!((main.size, main.disk_usage), (12, 12));
+        assert_eq!(find(&entries, &zip_path, "src").size, 12);
+    }
+
+    #[test]
+    fn test_corrupt_archive_is_an_error() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("broken.zip");
+        std::fs::write(&path, b"not a zip").unwrap();
+        assert!(list(&path, ArchiveFormat::Zip).is_err());
+    }
+}
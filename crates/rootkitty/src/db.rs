@@ -1,15 +1,29 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
 use sqlx::Row;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use tokio::sync::mpsc;
 
-use crate::scanner::{FileEntry, ScanStats};
+use crate::scanner::{
+    EntryKind, EntryStat, FileEntry, HardLinkPolicy, LinkAccounting, PreviousScan, ResumeState,
+    ScanError, ScanErrorKind, ScanOptions, ScanStats,
+};
 
 pub enum ActorMessage {
     InsertBatch(Vec<FileEntry>),
+    InsertErrors(Vec<ScanError>),
+    /// Directories finished since the last batch (see `Database::insert_checkpoints`)
+    InsertCheckpoints(Vec<PathBuf>),
+    /// Files and directories below the depth limit since the last batch, which
+    /// are counted but not stored (see `Database::add_summarized_counts`)
+    CountSummarized {
+        files: u64,
+        dirs: u64,
+    },
     Shutdown,
 }
 
@@ -36,6 +50,17 @@ impl DatabaseActor {
                         self.db.insert_file_entries(self.scan_id, &entries).await?;
                     }
                 }
+                ActorMessage::InsertErrors(errors) => {
+                    self.db.insert_scan_errors(self.scan_id, &errors).await?;
+                }
+                ActorMessage::InsertCheckpoints(dirs) => {
+                    self.db.insert_checkpoints(self.scan_id, &dirs).await?;
+                }
+                ActorMessage::CountSummarized { files, dirs } => {
+                    self.db
+                        .add_summarized_counts(self.scan_id, files, dirs)
+                        .await?;
+                }
                 ActorMessage::Shutdown => {
                     break;
                 }
@@ -52,13 +77,65 @@ pub struct Scan {
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub total_size: i64,
+    /// Allocated bytes on disk (equal to `total_size` for scans that predate it)
+    pub total_disk_usage: i64,
     pub total_files: i64,
     pub total_dirs: i64,
     pub status: String,
     pub entries_table: Option<String>,
+    /// Options the scan was run with (None for scans that predate stored options)
+    pub options: Option<ScanOptions>,
+    /// Number of paths that could not be read (0 for scans that predate error tracking)
+    pub error_count: i64,
+    /// Time spent scanning, summed over resumes (None for scans that predate it)
+    pub duration_ms: Option<i64>,
+    /// Machine a remote scan ran on (`user@host`); None for the local filesystem
+    pub host: Option<String>,
+    /// Paths scanned together below `root_path` (empty for single-root scans)
+    pub roots: Vec<String>,
+}
+
+impl Scan {
+    /// How long the scan took
+    /// Older completed scans fall back to wall-clock time, which includes any pauses
+    pub fn duration(&self) -> Option<std::time::Duration> {
+        let millis = self.duration_ms.or_else(|| {
+            self.completed_at
+                .map(|completed| (completed - self.started_at).num_milliseconds())
+        })?;
+        Some(std::time::Duration::from_millis(millis.max(0) as u64))
+    }
+
+    /// Root path, prefixed with the host for remote scans (`host:/path`), or
+    /// the roots of a multi-root scan (`/home, /var`)
+    pub fn location(&self) -> String {
+        match &self.host {
+            Some(host) => format!("{}:{}", host, self.root_path),
+            None if self.is_multi_root() => self.roots.join(", "),
+            None => self.root_path.clone(),
+        }
+    }
+
+    /// Whether several roots were scanned together below `root_path`
+    pub fn is_multi_root(&self) -> bool {
+        !self.roots.is_empty()
+    }
+
+    /// Entries the scan counted, a guide to how big a rescan will be
+    pub fn entry_count(&self) -> u64 {
+        (self.total_files + self.total_dirs).max(0) as u64
+    }
 }
 
+/// A path recorded as unreadable during a scan
 #[derive(Debug, Clone)]
+pub struct StoredScanError {
+    pub path: String,
+    pub kind: ScanErrorKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct StoredFileEntry {
     pub id: i64,
     #[allow(dead_code)]
@@ -68,10 +145,159 @@ pub struct StoredFileEntry {
     #[allow(dead_code)]
     pub parent_path: Option<String>,
     pub size: i64,
+    /// Allocated bytes on disk (equal to `size` for scans that predate it)
+    pub disk_usage: i64,
     pub is_dir: bool,
     #[allow(dead_code)]
     pub modified_at: Option<DateTime<Utc>>,
     pub depth: i64,
+    pub kind: EntryKind,
+    /// Ownership, permissions and inode details (None for scans that predate them)
+    pub stat: Option<EntryStat>,
+}
+
+impl StoredFileEntry {
+    /// Whether the entry unfolds in the file tree: directories, and archives
+    /// whose members were listed
+    pub fn has_children(&self) -> bool {
+        self.is_dir || self.kind == EntryKind::Archive
+    }
+}
+
+/// Which size to report: apparent size (file length) or allocated disk usage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizeMode {
+    #[default]
+    Apparent,
+    DiskUsage,
+}
+
+impl SizeMode {
+    pub fn toggle(&self) -> Self {
+        match self {
+            SizeMode::Apparent => SizeMode::DiskUsage,
+            SizeMode::DiskUsage => SizeMode::Apparent,
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        match self {
+            SizeMode::Apparent => "Apparent size",
+            SizeMode::DiskUsage => "Disk usage (allocated)",
+        }
+    }
+
+    pub fn entry_size(&self, entry: &StoredFileEntry) -> i64 {
+        self.select(entry.size, entry.disk_usage)
+    }
+
+    pub fn scan_size(&self, scan: &Scan) -> i64 {
+        self.select(scan.total_size, scan.total_disk_usage)
+    }
+
+    /// Pick the size matching this mode from an (apparent, allocated) pair
+    pub fn select(&self, size: i64, disk_usage: i64) -> i64 {
+        match self {
+            SizeMode::Apparent => size,
+            SizeMode::DiskUsage => disk_usage,
+        }
+    }
+
+    /// SQL expression to order entries by
+    fn order_column(&self) -> &'static str {
+        match self {
+            SizeMode::Apparent => "size",
+            SizeMode::DiskUsage => "COALESCE(disk_usage, size)",
+        }
+    }
+}
+
+/// Whether ownership is aggregated per user or per group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OwnerGrouping {
+    #[default]
+    User,
+    Group,
+}
+
+impl OwnerGrouping {
+    pub fn toggle(&self) -> Self {
+        match self {
+            OwnerGrouping::User => OwnerGrouping::Group,
+            OwnerGrouping::Group => OwnerGrouping::User,
+        }
+    }
+
+    pub fn display_name(&self) -> &str {
+        match self {
+            OwnerGrouping::User => "User",
+            OwnerGrouping::Group => "Group",
+        }
+    }
+
+    /// Entry column holding the owner ID
+    fn column(&self) -> &'static str {
+        match self {
+            OwnerGrouping::User => "uid",
+            OwnerGrouping::Group => "gid",
+        }
+    }
+}
+
+/// Files owned by one user or group within a scan
+///
+/// Only files are counted: directory sizes are cumulative and would count
+/// other owners' data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnerUsage {
+    pub owner_id: u32,
+    pub files: i64,
+    pub size: i64,
+    pub disk_usage: i64,
+}
+
+/// Files owned by one user or group directly inside a directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnerDirUsage {
+    pub path: String,
+    pub files: i64,
+    pub size: i64,
+    pub disk_usage: i64,
+}
+
+/// Files with one extension within a scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionUsage {
+    /// Lowercased, without the dot; empty for files without an extension
+    pub extension: String,
+    pub files: i64,
+    pub size: i64,
+    pub disk_usage: i64,
+}
+
+/// Content hashes of a file, computed by the duplicate finder
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileHashes {
+    /// Hash of the file's size and its first and last blocks
+    pub partial: Option<String>,
+    /// BLAKE3 hash of the whole file
+    pub full: Option<String>,
+}
+
+/// SQL expression for a file's lowercased extension: what follows the last
+/// dot of its name, or '' when there is none (dotfiles like `.bashrc` included)
+const EXTENSION_SQL: &str = "CASE WHEN length(rtrim(name, replace(name, '.', ''))) <= 1 THEN ''
+    ELSE lower(substr(name, length(rtrim(name, replace(name, '.', ''))) + 1)) END";
+
+/// SQL condition for entries whose sizes add up to their directory's total:
+/// files, archives and summarized directories (whose contents weren't stored)
+const COUNTED_SQL: &str =
+    "(is_dir = 0 AND (kind IS NULL OR kind = 'archive')) OR kind = 'summarized'";
+
+/// Prefix shared by the paths below `dir`
+fn descendant_prefix(dir: &str) -> String {
+    format!("{}/", dir.trim_end_matches('/'))
 }
 
 #[derive(Clone)]
@@ -79,6 +305,27 @@ pub struct Database {
     pool: SqlitePool,
 }
 
+/// Columns selected from per-scan entry tables (see `row_to_entry`)
+const ENTRY_COLUMNS: &str = "id, path, name, parent_path, size, disk_usage, is_dir, modified_at, depth, kind, uid, gid, mode, inode, device, nlink, accessed_at, changed_at";
+
+/// Columns selected from the scans table (see `row_to_scan`)
+const SCAN_COLUMNS: &str = "id, root_path, started_at, completed_at, total_size, total_disk_usage, total_files, total_dirs, status, entries_table, scan_options, error_count, duration_ms, host, roots";
+
+/// Columns added to entry tables after they were first created
+/// Tables from older scans are upgraded on open so they stay readable
+const ENTRY_COLUMN_UPGRADES: &[(&str, &str)] = &[
+    ("kind", "TEXT"),
+    ("disk_usage", "INTEGER"),
+    ("uid", "INTEGER"),
+    ("gid", "INTEGER"),
+    ("mode", "INTEGER"),
+    ("inode", "INTEGER"),
+    ("device", "INTEGER"),
+    ("nlink", "INTEGER"),
+    ("accessed_at", "TEXT"),
+    ("changed_at", "TEXT"),
+];
+
 impl Database {
     /// Create a Database from an existing pool (useful for testing)
     #[doc(hidden)]
@@ -104,6 +351,7 @@ impl Database {
     fn row_to_entry(row: &sqlx::sqlite::SqliteRow, scan_id: i64) -> StoredFileEntry {
         use sqlx::Row;
         let modified_at_str: Option<String> = row.get("modified_at");
+        let size: i64 = row.get("size");
 
         StoredFileEntry {
             id: row.get("id"),
@@ -111,7 +359,8 @@ impl Database {
             path: row.get("path"),
             name: row.get("name"),
             parent_path: row.get("parent_path"),
-            size: row.get("size"),
+            size,
+            disk_usage: row.get::<Option<i64>, _>("disk_usage").unwrap_or(size),
             is_dir: row.get("is_dir"),
             modified_at: modified_at_str.and_then(|s| {
                 DateTime::parse_from_rfc3339(&s)
@@ -119,7 +368,105 @@ impl Database {
                     .map(|dt| dt.with_timezone(&Utc))
             }),
             depth: row.get("depth"),
+            kind: EntryKind::from_db_str(row.get::<Option<String>, _>("kind").as_deref()),
+            stat: Self::row_to_stat(row),
+        }
+    }
+
+    /// Parse the stat columns of an entry row (all NULL for markers and older scans)
+    fn row_to_stat(row: &sqlx::sqlite::SqliteRow) -> Option<EntryStat> {
+        let inode: Option<i64> = row.get("inode");
+        let parse_time = |column: &str| {
+            row.get::<Option<String>, _>(column).and_then(|s| {
+                DateTime::parse_from_rfc3339(&s)
+                    .ok()
+                    .map(|dt| dt.with_timezone(&Utc))
+            })
+        };
+
+        Some(EntryStat {
+            uid: row.get::<Option<i64>, _>("uid").unwrap_or(0) as u32,
+            gid: row.get::<Option<i64>, _>("gid").unwrap_or(0) as u32,
+            mode: row.get::<Option<i64>, _>("mode").unwrap_or(0) as u32,
+            inode: inode? as u64,
+            device: row.get::<Option<i64>, _>("device").unwrap_or(0) as u64,
+            nlink: row.get::<Option<i64>, _>("nlink").unwrap_or(0) as u64,
+            accessed_at: parse_time("accessed_at"),
+            changed_at: parse_time("changed_at"),
+        })
+    }
+
+    /// Helper to parse a row into Scan
+    fn row_to_scan(row: &sqlx::sqlite::SqliteRow) -> Scan {
+        let started_at_str: String = row.get("started_at");
+        let completed_at_str: Option<String> = row.get("completed_at");
+        let options_str: Option<String> = row.get("scan_options");
+        let roots_str: Option<String> = row.get("roots");
+        let total_size: i64 = row.get("total_size");
+
+        Scan {
+            id: row.get("id"),
+            root_path: row.get("root_path"),
+            started_at: DateTime::parse_from_rfc3339(&started_at_str)
+                .unwrap()
+                .with_timezone(&Utc),
+            completed_at: completed_at_str.and_then(|s| {
+                DateTime::parse_from_rfc3339(&s)
+                    .ok()
+                    .map(|dt| dt.with_timezone(&Utc))
+            }),
+            total_size,
+            total_disk_usage: row
+                .get::<Option<i64>, _>("total_disk_usage")
+                .unwrap_or(total_size),
+            total_files: row.get("total_files"),
+            total_dirs: row.get("total_dirs"),
+            status: row.get("status"),
+            entries_table: row.get("entries_table"),
+            options: options_str.and_then(|s| serde_json::from_str(&s).ok()),
+            error_count: row.get::<Option<i64>, _>("error_count").unwrap_or(0),
+            duration_ms: row.get("duration_ms"),
+            host: row.get("host"),
+            roots: roots_str
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Add any columns missing from entry tables created by older versions
+    async fn upgrade_entry_tables(&self) -> Result<()> {
+        let mut tables: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT entries_table FROM scans WHERE entries_table IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        tables.push("file_entries".to_string());
+
+        for table in tables {
+            let columns: Vec<String> = sqlx::query(&format!("PRAGMA table_info({})", table))
+                .fetch_all(&self.pool)
+                .await?
+                .iter()
+                .map(|row| row.get::<String, _>("name"))
+                .collect();
+
+            // Table doesn't exist (e.g. dropped with its scan)
+            if columns.is_empty() {
+                continue;
+            }
+
+            for (column, column_type) in ENTRY_COLUMN_UPGRADES {
+                if !columns.iter().any(|c| c == column) {
+                    let alter = format!(
+                        "ALTER TABLE {} ADD COLUMN {} {}",
+                        table, column, column_type
+                    );
+                    sqlx::query(&alter).execute(&self.pool).await?;
+                }
+            }
         }
+
+        Ok(())
     }
 
     pub async fn new<P: AsRef<Path>>(db_path: P) -> Result<Self> {
@@ -146,23 +493,40 @@ impl Database {
             .await
             .context("Failed to run migrations")?;
 
-        Ok(Self { pool })
+        let db = Self { pool };
+        db.upgrade_entry_tables()
+            .await
+            .context("Failed to upgrade entry tables")?;
+
+        Ok(db)
     }
 
     pub async fn create_scan(&self, root_path: &Path) -> Result<i64> {
+        self.create_scan_with_options(root_path, &ScanOptions::default())
+            .await
+    }
+
+    /// Create a scan record, storing the options it runs with
+    pub async fn create_scan_with_options(
+        &self,
+        root_path: &Path,
+        options: &ScanOptions,
+    ) -> Result<i64> {
         // Canonicalize the path to store absolute paths, resolving ".", "..", "~", etc.
         let canonical_path = root_path
             .canonicalize()
             .unwrap_or_else(|_| root_path.to_path_buf());
         let root_path_str = canonical_path.display().to_string();
         let started_at = Utc::now().to_rfc3339();
+        let options_json = serde_json::to_string(options)?;
 
         // Insert scan record first to get the ID
         let result = sqlx::query(
-            "INSERT INTO scans (root_path, started_at, status) VALUES (?, ?, 'running')",
+            "INSERT INTO scans (root_path, started_at, status, scan_options) VALUES (?, ?, 'running', ?)",
         )
         .bind(&root_path_str)
         .bind(&started_at)
+        .bind(&options_json)
         .execute(&self.pool)
         .await?;
 
@@ -177,9 +541,19 @@ impl Database {
                 name TEXT NOT NULL,
                 parent_path TEXT,
                 size INTEGER NOT NULL,
+                disk_usage INTEGER,
                 is_dir INTEGER NOT NULL,
                 modified_at TEXT,
-                depth INTEGER NOT NULL
+                depth INTEGER NOT NULL,
+                kind TEXT,
+                uid INTEGER,
+                gid INTEGER,
+                mode INTEGER,
+                inode INTEGER,
+                device INTEGER,
+                nlink INTEGER,
+                accessed_at TEXT,
+                changed_at TEXT
             )",
             table_name
         );
@@ -215,76 +589,745 @@ impl Database {
         Ok(scan_id)
     }
 
+    /// Record where and when an imported scan was taken, once the export has been read
+    /// The root is stored as given, since it names a path on the machine that was scanned
+    pub async fn set_import_origin(
+        &self,
+        scan_id: i64,
+        root_path: &Path,
+        scanned_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE scans SET root_path = ?, started_at = COALESCE(?, started_at) WHERE id = ?",
+        )
+        .bind(root_path.display().to_string())
+        .bind(scanned_at.map(|t| t.to_rfc3339()))
+        .bind(scan_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record where a remote scan ran, in place of the local path it was created with
+    pub async fn set_remote_origin(&self, scan_id: i64, host: &str, root_path: &str) -> Result<()> {
+        sqlx::query("UPDATE scans SET host = ?, root_path = ? WHERE id = ?")
+            .bind(host)
+            .bind(root_path)
+            .bind(scan_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record the roots of a multi-root scan, which is created for their common ancestor
+    pub async fn set_scan_roots(&self, scan_id: i64, roots: &[PathBuf]) -> Result<()> {
+        let roots: Vec<String> = roots.iter().map(|r| r.display().to_string()).collect();
+        sqlx::query("UPDATE scans SET roots = ? WHERE id = ?")
+            .bind(serde_json::to_string(&roots)?)
+            .bind(scan_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     pub async fn complete_scan(&self, scan_id: i64, stats: &ScanStats) -> Result<()> {
         let completed_at = Utc::now().to_rfc3339();
 
         sqlx::query(
-            "UPDATE scans SET completed_at = ?, total_size = ?, total_files = ?, total_dirs = ?, status = 'completed' WHERE id = ?"
+            "UPDATE scans SET completed_at = ?, total_size = ?, total_disk_usage = ?, total_files = ?, total_dirs = ?, status = 'completed',
+                error_count = (SELECT COUNT(*) FROM scan_errors WHERE scan_id = ?),
+                duration_ms = COALESCE(duration_ms, 0) + ? WHERE id = ?"
         )
         .bind(&completed_at)
         .bind(stats.total_size as i64)
+        .bind(stats.total_disk_usage as i64)
         .bind(stats.total_files as i64)
         .bind(stats.total_dirs as i64)
         .bind(scan_id)
+        .bind(stats.duration.as_millis() as i64)
+        .bind(scan_id)
         .execute(&self.pool)
         .await?;
 
+        // Checkpoints only matter while there is something left to resume
+        sqlx::query("DELETE FROM scan_checkpoints WHERE scan_id = ?")
+            .bind(scan_id)
+            .execute(&self.pool)
+            .await?;
+
         Ok(())
     }
 
     pub async fn pause_scan(&self, scan_id: i64, stats: &ScanStats) -> Result<()> {
         sqlx::query(
-            "UPDATE scans SET total_size = ?, total_files = ?, total_dirs = ?, status = 'paused' WHERE id = ?"
+            "UPDATE scans SET total_size = ?, total_disk_usage = ?, total_files = ?, total_dirs = ?, status = 'paused',
+                error_count = (SELECT COUNT(*) FROM scan_errors WHERE scan_id = ?),
+                duration_ms = COALESCE(duration_ms, 0) + ? WHERE id = ?"
         )
         .bind(stats.total_size as i64)
+        .bind(stats.total_disk_usage as i64)
         .bind(stats.total_files as i64)
         .bind(stats.total_dirs as i64)
         .bind(scan_id)
+        .bind(stats.duration.as_millis() as i64)
+        .bind(scan_id)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
+    /// Count files and directories a depth-limited scan summarized instead of storing
+    pub async fn add_summarized_counts(&self, scan_id: i64, files: u64, dirs: u64) -> Result<()> {
+        sqlx::query(
+            "UPDATE scans SET summarized_files = summarized_files + ?,
+                summarized_dirs = summarized_dirs + ? WHERE id = ?",
+        )
+        .bind(files as i64)
+        .bind(dirs as i64)
+        .bind(scan_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
     /// Calculate scan statistics from file_entries in the database
-    /// Useful for recovering stats from interrupted scans
+    /// Useful for recovering stats from interrupted scans; the contents of
+    /// summarized directories come from the counts kept while scanning.
+    /// Directory sizes already include their contents, so sizes are only
+    /// summed over entries whose parent has no row of its own
     pub async fn calculate_scan_stats(&self, scan_id: i64) -> Result<ScanStats> {
         let table_name = self.get_entries_table(scan_id).await?;
         let query_str = format!(
             "SELECT
-                COALESCE(SUM(size), 0) as total_size,
+                COALESCE(SUM(CASE WHEN outermost THEN size END), 0) as total_size,
+                COALESCE(SUM(CASE WHEN outermost THEN COALESCE(disk_usage, size) END), 0)
+                    as total_disk_usage,
                 COALESCE(SUM(CASE WHEN is_dir = 0 THEN 1 ELSE 0 END), 0) as total_files,
                 COALESCE(SUM(CASE WHEN is_dir = 1 THEN 1 ELSE 0 END), 0) as total_dirs
-             FROM {}",
-            table_name
+             FROM (
+                SELECT e.size, e.disk_usage, e.is_dir,
+                       NOT EXISTS (SELECT 1 FROM {table} p WHERE p.path = e.parent_path)
+                           as outermost
+                FROM {table} e
+                WHERE e.kind IS NULL OR e.kind IN ('summarized', 'archive')
+             )",
+            table = table_name
         );
 
         let row = sqlx::query(&query_str).fetch_one(&self.pool).await?;
+        let error_count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM scan_errors WHERE scan_id = ?")
+                .bind(scan_id)
+                .fetch_one(&self.pool)
+                .await?;
+        let (summarized_files, summarized_dirs): (i64, i64) =
+            sqlx::query_as("SELECT summarized_files, summarized_dirs FROM scans WHERE id = ?")
+                .bind(scan_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .unwrap_or_default();
 
         Ok(ScanStats {
             total_size: row.get::<i64, _>("total_size") as u64,
-            total_files: row.get::<i64, _>("total_files") as u64,
-            total_dirs: row.get::<i64, _>("total_dirs") as u64,
+            total_disk_usage: row.get::<i64, _>("total_disk_usage") as u64,
+            total_files: (row.get::<i64, _>("total_files") + summarized_files) as u64,
+            total_dirs: (row.get::<i64, _>("total_dirs") + summarized_dirs) as u64,
+            error_count: error_count as u64,
+            reused_dirs: 0,
+            duration: std::time::Duration::ZERO,
         })
     }
 
-    /// Get all scanned paths for a given scan_id
-    /// Used to skip already-scanned paths when resuming
-    pub async fn get_scanned_paths(
+    /// Record unreadable paths for a scan
+    /// A path is only stored once per scan, so resumed scans don't duplicate errors
+    pub async fn insert_scan_errors(&self, scan_id: i64, errors: &[ScanError]) -> Result<()> {
+        if errors.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for error in errors {
+            sqlx::query(
+                "INSERT OR IGNORE INTO scan_errors (scan_id, path, kind, message) VALUES (?, ?, ?, ?)",
+            )
+            .bind(scan_id)
+            .bind(error.path.display().to_string())
+            .bind(error.kind.as_db_str())
+            .bind(&error.message)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Record directories a scan has finished: every entry below them is stored
+    /// Sent after the entries themselves, so a paused scan never has a
+    /// checkpoint for a directory whose contents are missing
+    pub async fn insert_checkpoints(&self, scan_id: i64, dirs: &[PathBuf]) -> Result<()> {
+        if dirs.is_empty() {
+            return Ok(());
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for dir in dirs {
+            sqlx::query("INSERT OR IGNORE INTO scan_checkpoints (scan_id, path) VALUES (?, ?)")
+                .bind(scan_id)
+                .bind(dir.display().to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Unreadable paths recorded for a scan, sorted by path
+    pub async fn get_scan_errors(&self, scan_id: i64) -> Result<Vec<StoredScanError>> {
+        let rows = sqlx::query(
+            "SELECT path, kind, message FROM scan_errors WHERE scan_id = ? ORDER BY path",
+        )
+        .bind(scan_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| StoredScanError {
+                path: row.get("path"),
+                kind: ScanErrorKind::from_db_str(row.get::<String, _>("kind").as_str()),
+                message: row.get("message"),
+            })
+            .collect())
+    }
+
+    /// Aggregate file counts and sizes per user or group, largest first
+    /// Entries without ownership information (older scans, markers) are skipped
+    pub async fn get_owner_usage(
+        &self,
+        scan_id: i64,
+        grouping: OwnerGrouping,
+        size_mode: SizeMode,
+    ) -> Result<Vec<OwnerUsage>> {
+        let table_name = self.get_entries_table(scan_id).await?;
+        let query_str = format!(
+            "SELECT {column} as owner_id,
+                COUNT(*) as files,
+                SUM(size) as size,
+                SUM(COALESCE(disk_usage, size)) as disk_usage
+             FROM {table}
+             WHERE is_dir = 0 AND (kind IS NULL OR kind = 'archive') AND {column} IS NOT NULL
+             GROUP BY {column}
+             ORDER BY SUM({order}) DESC",
+            column = grouping.column(),
+            table = table_name,
+            order = size_mode.order_column(),
+        );
+
+        let rows = sqlx::query(&query_str).fetch_all(&self.pool).await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| OwnerUsage {
+                owner_id: row.get::<i64, _>("owner_id") as u32,
+                files: row.get("files"),
+                size: row.get("size"),
+                disk_usage: row.get("disk_usage"),
+            })
+            .collect())
+    }
+
+    /// Directories holding the most data owned by one user or group
+    /// Only files directly inside each directory are counted, so a directory's
+    /// size leaves out its subdirectories and each file is listed under one directory
+    pub async fn get_owner_directories(
+        &self,
+        scan_id: i64,
+        grouping: OwnerGrouping,
+        owner_id: u32,
+        size_mode: SizeMode,
+        limit: i64,
+    ) -> Result<Vec<OwnerDirUsage>> {
+        let table_name = self.get_entries_table(scan_id).await?;
+        let query_str = format!(
+            "SELECT parent_path,
+                COUNT(*) as files,
+                SUM(size) as size,
+                SUM(COALESCE(disk_usage, size)) as disk_usage
+             FROM {table}
+             WHERE is_dir = 0 AND (kind IS NULL OR kind = 'archive') AND {column} = ? AND parent_path IS NOT NULL
+             GROUP BY parent_path
+             ORDER BY SUM({order}) DESC
+             LIMIT ?",
+            column = grouping.column(),
+            table = table_name,
+            order = size_mode.order_column(),
+        );
+
+        let rows = sqlx::query(&query_str)
+            .bind(owner_id as i64)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| OwnerDirUsage {
+                path: row.get("parent_path"),
+                files: row.get("files"),
+                size: row.get("size"),
+                disk_usage: row.get("disk_usage"),
+            })
+            .collect())
+    }
+
+    /// Aggregate file counts and sizes per extension, largest first
+    pub async fn get_extension_usage(
         &self,
         scan_id: i64,
-    ) -> Result<std::collections::HashSet<String>> {
+        size_mode: SizeMode,
+    ) -> Result<Vec<ExtensionUsage>> {
         let table_name = self.get_entries_table(scan_id).await?;
-        let query_str = format!("SELECT path FROM {}", table_name);
+        let query_str = format!(
+            "SELECT {extension} as extension,
+                COUNT(*) as files,
+                SUM(size) as size,
+                SUM(COALESCE(disk_usage, size)) as disk_usage
+             FROM {table}
+             WHERE is_dir = 0 AND (kind IS NULL OR kind = 'archive')
+             GROUP BY 1
+             ORDER BY SUM({order}) DESC, 1",
+            extension = EXTENSION_SQL,
+            table = table_name,
+            order = size_mode.order_column(),
+        );
 
         let rows = sqlx::query(&query_str).fetch_all(&self.pool).await?;
 
-        let paths = rows
+        Ok(rows
+            .iter()
+            .map(|row| ExtensionUsage {
+                extension: row.get("extension"),
+                files: row.get("files"),
+                size: row.get("size"),
+                disk_usage: row.get("disk_usage"),
+            })
+            .collect())
+    }
+
+    /// Largest files whose extension isn't one of `extensions` (lowercased, without the dot)
+    pub async fn get_largest_files_excluding_extensions(
+        &self,
+        scan_id: i64,
+        extensions: &[String],
+        size_mode: SizeMode,
+        limit: i64,
+    ) -> Result<Vec<StoredFileEntry>> {
+        let table_name = self.get_entries_table(scan_id).await?;
+        let placeholders = vec!["?"; extensions.len()].join(", ");
+        let query_str = format!(
+            "SELECT {columns} FROM {table}
+             WHERE is_dir = 0 AND (kind IS NULL OR kind = 'archive') AND ({extension}) NOT IN ({placeholders})
+             ORDER BY {order} DESC
+             LIMIT ?",
+            columns = ENTRY_COLUMNS,
+            table = table_name,
+            extension = EXTENSION_SQL,
+            placeholders = placeholders,
+            order = size_mode.order_column(),
+        );
+
+        let mut query = sqlx::query(&query_str);
+        for extension in extensions {
+            query = query.bind(extension);
+        }
+        let rows = query.bind(limit).fetch_all(&self.pool).await?;
+        Ok(rows
+            .iter()
+            .map(|row| Self::row_to_entry(row, scan_id))
+            .collect())
+    }
+
+    /// Files that share their size with another file of the scan, largest first
+    /// Empty files, directories and markers are never candidates for duplicates
+    pub async fn get_duplicate_candidates(
+        &self,
+        scan_id: i64,
+        min_size: u64,
+    ) -> Result<Vec<StoredFileEntry>> {
+        let table_name = self.get_entries_table(scan_id).await?;
+        let query_str = format!(
+            "SELECT {columns} FROM {table}
+             WHERE is_dir = 0 AND (kind IS NULL OR kind = 'archive') AND size >= ?1 AND size IN (
+                 SELECT size FROM {table}
+                 WHERE is_dir = 0 AND (kind IS NULL OR kind = 'archive') AND size >= ?1
+                 GROUP BY size HAVING COUNT(*) > 1
+             )
+             ORDER BY size DESC, path",
+            columns = ENTRY_COLUMNS,
+            table = table_name,
+        );
+
+        let rows = sqlx::query(&query_str)
+            .bind(min_size.max(1) as i64)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows
+            .iter()
+            .map(|row| Self::row_to_entry(row, scan_id))
+            .collect())
+    }
+
+    /// Create the per-scan table of content hashes, if the scan doesn't have one yet
+    pub async fn ensure_hashes_table(&self, scan_id: i64) -> Result<()> {
+        let create_table_sql = format!(
+            "CREATE TABLE IF NOT EXISTS scan_hashes_{} (
+                path TEXT PRIMARY KEY,
+                partial_hash TEXT,
+                full_hash TEXT
+            )",
+            scan_id
+        );
+        sqlx::query(&create_table_sql).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Hashes computed so far for a scan's files, by path
+    pub async fn get_file_hashes(&self, scan_id: i64) -> Result<HashMap<String, FileHashes>> {
+        let query_str = format!(
+            "SELECT path, partial_hash, full_hash FROM scan_hashes_{}",
+            scan_id
+        );
+        let rows = sqlx::query(&query_str).fetch_all(&self.pool).await?;
+        Ok(rows
+            .iter()
+            .map(|row| {
+                let hashes = FileHashes {
+                    partial: row.get("partial_hash"),
+                    full: row.get("full_hash"),
+                };
+                (row.get("path"), hashes)
+            })
+            .collect())
+    }
+
+    /// Store computed hashes, keeping any already stored for the other stage
+    pub async fn store_file_hashes(
+        &self,
+        scan_id: i64,
+        hashes: &[(String, FileHashes)],
+    ) -> Result<()> {
+        let query_str = format!(
+            "INSERT INTO scan_hashes_{} (path, partial_hash, full_hash) VALUES (?, ?, ?)
+             ON CONFLICT(path) DO UPDATE SET
+                 partial_hash = COALESCE(excluded.partial_hash, partial_hash),
+                 full_hash = COALESCE(excluded.full_hash, full_hash)",
+            scan_id
+        );
+
+        let mut tx = self.pool.begin().await?;
+        for (path, file_hashes) in hashes {
+            sqlx::query(&query_str)
+                .bind(path)
+                .bind(&file_hashes.partial)
+                .bind(&file_hashes.full)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Most recent completed scan of a directory, the default base for incremental rescans
+    /// Multi-root scans with the directory as their top don't count
+    pub async fn get_latest_completed_scan(&self, root_path: &Path) -> Result<Option<Scan>> {
+        // Match the canonical form stored by create_scan_with_options
+        let canonical_path = root_path
+            .canonicalize()
+            .unwrap_or_else(|_| root_path.to_path_buf());
+        let query_str = format!(
+            "SELECT {} FROM scans WHERE root_path = ? AND host IS NULL AND roots IS NULL
+             AND status = 'completed' ORDER BY started_at DESC LIMIT 1",
+            SCAN_COLUMNS
+        );
+        let row = sqlx::query(&query_str)
+            .bind(canonical_path.display().to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(Self::row_to_scan))
+    }
+
+    /// Look up a scan's directories for an incremental rescan, one at a time
+    /// as the rescan reaches them
+    ///
+    /// The rescan runs on a blocking thread, so each lookup blocks on this
+    /// runtime; a directory that fails to load is scanned afresh.
+    pub async fn load_previous_scan(&self, scan_id: i64) -> Result<PreviousScan> {
+        let table_name = self.get_entries_table(scan_id).await?;
+        let root = self
+            .get_scan(scan_id)
+            .await?
+            .context("Scan not found")?
+            .root_path;
+        let unreadable = self
+            .get_scan_errors(scan_id)
+            .await?
+            .into_iter()
+            .map(|error| PathBuf::from(error.path));
+
+        // Archive members are listed afresh from their archive
+        let query_str = format!(
+            "SELECT {} FROM {} WHERE (path = ?1 OR parent_path = ?1) AND kind IS NOT 'in_archive'",
+            ENTRY_COLUMNS, table_name
+        );
+        let db = self.clone();
+        let runtime = tokio::runtime::Handle::current();
+        Ok(PreviousScan::new(unreadable, move |relative| {
+            // Joining an empty path would add a trailing separator
+            let path = if relative.as_os_str().is_empty() {
+                root.clone()
+            } else {
+                Path::new(&root).join(relative).display().to_string()
+            };
+            let rows = runtime
+                .block_on(sqlx::query(&query_str).bind(&path).fetch_all(&db.pool))
+                .ok()?;
+
+            let (dir, children): (Vec<_>, Vec<_>) = rows
+                .iter()
+                .map(|row| Self::stored_entry(row, scan_id))
+                .partition(|entry| entry.path.as_os_str() == path.as_str());
+            Some((dir.into_iter().next()?, children))
+        }))
+    }
+
+    /// A stored row as the scanner's entry
+    fn stored_entry(row: &sqlx::sqlite::SqliteRow, scan_id: i64) -> FileEntry {
+        let entry = Self::row_to_entry(row, scan_id);
+        FileEntry {
+            path: PathBuf::from(entry.path),
+            name: entry.name,
+            parent_path: entry.parent_path.map(PathBuf::from),
+            size: entry.size as u64,
+            disk_usage: entry.disk_usage as u64,
+            is_dir: entry.is_dir,
+            modified_at: entry.modified_at,
+            depth: entry.depth as usize,
+            kind: entry.kind,
+            stat: entry.stat,
+        }
+    }
+
+    /// What a paused scan recorded, for resuming it (strictly below `dir`,
+    /// when only that subtree is resumed)
+    ///
+    /// Directories the scan finished come from its checkpoints; the only paths
+    /// loaded one by one are those recorded directly inside directories it
+    /// hadn't finished. Archive members are left out: resuming never walks
+    /// into an archive whose own path is recorded.
+    pub async fn get_resume_state(&self, scan_id: i64, dir: Option<&str>) -> Result<ResumeState> {
+        let table_name = self.get_entries_table(scan_id).await?;
+        let prefix = dir.map(descendant_prefix).unwrap_or_default();
+        let prefix_len = prefix.chars().count() as i64;
+
+        // Only the outermost: those whose parent (the path up to its last '/')
+        // wasn't finished, or lies outside `dir`
+        let completed: Vec<String> = sqlx::query_scalar(
+            "SELECT path FROM scan_checkpoints AS c
+             WHERE scan_id = ?1 AND substr(path, 1, ?2) = ?3
+                AND NOT EXISTS (SELECT 1 FROM scan_checkpoints p
+                    WHERE p.scan_id = ?1 AND substr(p.path, 1, ?2) = ?3
+                        AND p.path = rtrim(rtrim(c.path, replace(c.path, '/', '')), '/'))",
+        )
+        .bind(scan_id)
+        .bind(prefix_len)
+        .bind(&prefix)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let query_str = format!(
+            "SELECT path FROM {} AS e
+             WHERE (kind IS NULL OR kind != 'in_archive') AND substr(path, 1, ?) = ?
+                AND NOT EXISTS (SELECT 1 FROM scan_checkpoints c
+                    WHERE c.scan_id = ? AND c.path IN (e.path, e.parent_path))",
+            table_name
+        );
+        let recorded: Vec<String> = sqlx::query_scalar(&query_str)
+            .bind(prefix_len)
+            .bind(&prefix)
+            .bind(scan_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(ResumeState::new(
+            completed.into_iter().map(PathBuf::from),
+            recorded,
+        ))
+    }
+
+    /// Id of the latest entry recorded for a scan (0 if there are none)
+    /// Entries recorded by a later run have higher ids.
+    pub async fn last_entry_id(&self, scan_id: i64) -> Result<i64> {
+        let table_name = self.get_entries_table(scan_id).await?;
+        let query_str = format!("SELECT COALESCE(MAX(id), 0) FROM {}", table_name);
+        let id = sqlx::query_scalar(&query_str).fetch_one(&self.pool).await?;
+        Ok(id)
+    }
+
+    /// Fix up directory sizes once `subtree` of a paused scan (or all of it) has
+    /// been resumed
+    ///
+    /// The resumed run skipped the entries recorded before it (those with ids up
+    /// to `last_entry_id`), so the directories it recorded only count what it
+    /// found. The skipped entries are added back to them bottom-up, hard links
+    /// counted by `hard_links`. The subtree's row from an earlier run, if any, is
+    /// replaced, and ancestors with rows of their own change by the difference.
+    /// Only the recorded directories and the skipped entries directly inside
+    /// them are loaded.
+    pub async fn recompute_dir_sizes(
+        &self,
+        scan_id: i64,
+        subtree: &str,
+        last_entry_id: i64,
+        hard_links: HardLinkPolicy,
+    ) -> Result<()> {
+        let table_name = self.get_entries_table(scan_id).await?;
+        let prefix = descendant_prefix(subtree);
+        let recorded_dirs = format!(
+            "SELECT {} FROM {} WHERE id > ?1 AND is_dir = 1 AND kind IS NULL
+                AND (path = ?2 OR substr(path, 1, ?3) = ?4)",
+            ENTRY_COLUMNS, table_name
+        );
+        // Earlier entries inside those directories, and the subtree's earlier row
+        let earlier_entries = format!(
+            "SELECT {columns} FROM {table} WHERE id <= ?1 AND (path = ?2 OR parent_path IN (
+                SELECT path FROM {table} WHERE id > ?1 AND is_dir = 1 AND kind IS NULL
+                    AND (path = ?2 OR substr(path, 1, ?3) = ?4)))
+             ORDER BY path",
+            columns = ENTRY_COLUMNS,
+            table = table_name
+        );
+        let prefix_len = prefix.chars().count() as i64;
+        let rows = sqlx::query(&recorded_dirs)
+            .bind(last_entry_id)
+            .bind(subtree)
+            .bind(prefix_len)
+            .bind(&prefix)
+            .fetch_all(&self.pool)
+            .await?;
+        let recorded: Vec<StoredFileEntry> = rows
             .iter()
-            .map(|row| row.get::<String, _>("path"))
+            .map(|row| Self::row_to_entry(row, scan_id))
+            .collect();
+        let rows = sqlx::query(&earlier_entries)
+            .bind(last_entry_id)
+            .bind(subtree)
+            .bind(prefix_len)
+            .bind(&prefix)
+            .fetch_all(&self.pool)
+            .await?;
+        let earlier: Vec<StoredFileEntry> = rows
+            .iter()
+            .map(|row| Self::row_to_entry(row, scan_id))
             .collect();
 
-        Ok(paths)
+        // What the skipped entries add to each directory this run recorded
+        let mut added: HashMap<String, (i64, i64)> = recorded
+            .iter()
+            .map(|entry| (entry.path.clone(), (0, 0)))
+            .collect();
+        let links = LinkAccounting::new(hard_links);
+        for entry in &earlier {
+            let Some(totals) = entry
+                .parent_path
+                .as_ref()
+                .and_then(|parent| added.get_mut(parent))
+            else {
+                continue;
+            };
+            let counted = match entry.kind {
+                EntryKind::Excluded
+                | EntryKind::MountPoint
+                | EntryKind::InArchive
+                | EntryKind::Unfinished => continue,
+                _ if entry.is_dir => (entry.size, entry.disk_usage),
+                EntryKind::Normal | EntryKind::Summarized | EntryKind::Archive => {
+                    let (size, disk_usage) = links.contribution(
+                        entry.stat.as_ref(),
+                        entry.size as u64,
+                        entry.disk_usage as u64,
+                    );
+                    (size as i64, disk_usage as i64)
+                }
+            };
+            totals.0 += counted.0;
+            totals.1 += counted.1;
+        }
+
+        // Deepest first, so each directory passes its change on to its parent
+        let mut dirs: Vec<&StoredFileEntry> = recorded.iter().collect();
+        dirs.sort_by_key(|entry| std::cmp::Reverse(entry.depth));
+
+        let mut tx = self.pool.begin().await?;
+        let update = format!(
+            "UPDATE {} SET size = ?, disk_usage = ? WHERE id = ?",
+            table_name
+        );
+        let mut subtree_totals = None;
+        for dir in dirs {
+            let (size, disk_usage) = added[&dir.path];
+            if let Some(parent) = dir.parent_path.as_ref().and_then(|p| added.get_mut(p)) {
+                parent.0 += size;
+                parent.1 += disk_usage;
+            }
+            let totals = (dir.size + size, dir.disk_usage + disk_usage);
+            if size != 0 || disk_usage != 0 {
+                sqlx::query(&update)
+                    .bind(totals.0)
+                    .bind(totals.1)
+                    .bind(dir.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            if dir.path == subtree {
+                subtree_totals = Some(totals);
+            }
+        }
+
+        if let Some((size, disk_usage)) = subtree_totals {
+            let before = earlier.iter().find(|entry| entry.path == subtree);
+            if let Some(before) = before {
+                let delete = format!("DELETE FROM {} WHERE id = ?", table_name);
+                sqlx::query(&delete)
+                    .bind(before.id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            let (size_change, usage_change) = before.map_or((size, disk_usage), |before| {
+                (size - before.size, disk_usage - before.disk_usage)
+            });
+            let update_ancestor = format!(
+                "UPDATE {} SET size = size + ?, disk_usage = COALESCE(disk_usage, size) + ?
+                 WHERE path = ? AND is_dir = 1",
+                table_name
+            );
+            let mut current = Path::new(subtree).parent();
+            while let Some(dir) = current {
+                sqlx::query(&update_ancestor)
+                    .bind(size_change)
+                    .bind(usage_change)
+                    .bind(dir.display().to_string())
+                    .execute(&mut *tx)
+                    .await?;
+                current = dir.parent();
+            }
+        }
+        tx.commit().await?;
+
+        Ok(())
     }
 
     pub async fn delete_scan(&self, scan_id: i64) -> Result<()> {
@@ -303,12 +1346,29 @@ impl Database {
                 .await?;
         }
 
+        // Drop the duplicate finder's hashes, if it ran
+        sqlx::query(&format!("DROP TABLE IF EXISTS scan_hashes_{}", scan_id))
+            .execute(&self.pool)
+            .await?;
+
         // Delete cleanup items
         sqlx::query("DELETE FROM cleanup_items WHERE scan_id = ?")
             .bind(scan_id)
             .execute(&self.pool)
             .await?;
 
+        // Delete recorded scan errors
+        sqlx::query("DELETE FROM scan_errors WHERE scan_id = ?")
+            .bind(scan_id)
+            .execute(&self.pool)
+            .await?;
+
+        // Delete checkpoints of a scan deleted while paused
+        sqlx::query("DELETE FROM scan_checkpoints WHERE scan_id = ?")
+            .bind(scan_id)
+            .execute(&self.pool)
+            .await?;
+
         // Delete the scan itself
         sqlx::query("DELETE FROM scans WHERE id = ?")
             .bind(scan_id)
@@ -349,10 +1409,12 @@ impl Database {
             let path_str = entry.path.display().to_string();
             let parent_str = entry.parent_path.as_ref().map(|p| p.display().to_string());
             let modified_str = entry.modified_at.map(|dt| dt.to_rfc3339());
+            let stat = entry.stat.as_ref();
 
             let query_str = format!(
-                "INSERT INTO {} (path, name, parent_path, size, is_dir, modified_at, depth)
-                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+                "INSERT INTO {} (path, name, parent_path, size, disk_usage, is_dir, modified_at, depth, kind,
+                                 uid, gid, mode, inode, device, nlink, accessed_at, changed_at)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 table_name
             );
 
@@ -361,9 +1423,19 @@ impl Database {
                 .bind(&entry.name)
                 .bind(&parent_str)
                 .bind(entry.size as i64)
+                .bind(entry.disk_usage as i64)
                 .bind(entry.is_dir)
                 .bind(&modified_str)
                 .bind(entry.depth as i64)
+                .bind(entry.kind.as_db_str())
+                .bind(stat.map(|st| st.uid as i64))
+                .bind(stat.map(|st| st.gid as i64))
+                .bind(stat.map(|st| st.mode as i64))
+                .bind(stat.map(|st| st.inode as i64))
+                .bind(stat.map(|st| st.device as i64))
+                .bind(stat.map(|st| st.nlink as i64))
+                .bind(stat.and_then(|st| st.accessed_at).map(|dt| dt.to_rfc3339()))
+                .bind(stat.and_then(|st| st.changed_at).map(|dt| dt.to_rfc3339()))
                 .execute(&mut *tx)
                 .await?;
         }
@@ -373,87 +1445,48 @@ impl Database {
     }
 
     pub async fn list_scans(&self) -> Result<Vec<Scan>> {
-        let rows = sqlx::query(
-            "SELECT id, root_path, started_at, completed_at, total_size, total_files, total_dirs, status, entries_table
-             FROM scans ORDER BY started_at DESC"
-        )
-        .fetch_all(&self.pool)
-        .await?;
-
-        let scans = rows
-            .iter()
-            .map(|row| {
-                let started_at_str: String = row.get("started_at");
-                let completed_at_str: Option<String> = row.get("completed_at");
-
-                Scan {
-                    id: row.get("id"),
-                    root_path: row.get("root_path"),
-                    started_at: DateTime::parse_from_rfc3339(&started_at_str)
-                        .unwrap()
-                        .with_timezone(&Utc),
-                    completed_at: completed_at_str.and_then(|s| {
-                        DateTime::parse_from_rfc3339(&s)
-                            .ok()
-                            .map(|dt| dt.with_timezone(&Utc))
-                    }),
-                    total_size: row.get("total_size"),
-                    total_files: row.get("total_files"),
-                    total_dirs: row.get("total_dirs"),
-                    status: row.get("status"),
-                    entries_table: row.get("entries_table"),
-                }
-            })
-            .collect();
+        let query_str = format!(
+            "SELECT {} FROM scans ORDER BY started_at DESC",
+            SCAN_COLUMNS
+        );
+        let rows = sqlx::query(&query_str).fetch_all(&self.pool).await?;
 
-        Ok(scans)
+        Ok(rows.iter().map(Self::row_to_scan).collect())
     }
 
     pub async fn get_scan(&self, scan_id: i64) -> Result<Option<Scan>> {
-        let row = sqlx::query(
-            "SELECT id, root_path, started_at, completed_at, total_size, total_files, total_dirs, status, entries_table
-             FROM scans WHERE id = ?"
-        )
-        .bind(scan_id)
-        .fetch_optional(&self.pool)
-        .await?;
-
-        let scan = row.map(|row| {
-            let started_at_str: String = row.get("started_at");
-            let completed_at_str: Option<String> = row.get("completed_at");
-
-            Scan {
-                id: row.get("id"),
-                root_path: row.get("root_path"),
-                started_at: DateTime::parse_from_rfc3339(&started_at_str)
-                    .unwrap()
-                    .with_timezone(&Utc),
-                completed_at: completed_at_str.and_then(|s| {
-                    DateTime::parse_from_rfc3339(&s)
-                        .ok()
-                        .map(|dt| dt.with_timezone(&Utc))
-                }),
-                total_size: row.get("total_size"),
-                total_files: row.get("total_files"),
-                total_dirs: row.get("total_dirs"),
-                status: row.get("status"),
-                entries_table: row.get("entries_table"),
-            }
-        });
+        let query_str = format!("SELECT {} FROM scans WHERE id = ?", SCAN_COLUMNS);
+        let row = sqlx::query(&query_str)
+            .bind(scan_id)
+            .fetch_optional(&self.pool)
+            .await?;
 
-        Ok(scan)
+        Ok(row.as_ref().map(Self::row_to_scan))
     }
 
+    #[allow(dead_code)]
     pub async fn get_largest_entries(
         &self,
         scan_id: i64,
         limit: i64,
+    ) -> Result<Vec<StoredFileEntry>> {
+        self.get_largest_entries_by(scan_id, limit, SizeMode::Apparent)
+            .await
+    }
+
+    /// Get the largest entries of a scan, measured by apparent size or disk usage
+    pub async fn get_largest_entries_by(
+        &self,
+        scan_id: i64,
+        limit: i64,
+        size_mode: SizeMode,
     ) -> Result<Vec<StoredFileEntry>> {
         let table_name = self.get_entries_table(scan_id).await?;
         let query_str = format!(
-            "SELECT id, path, name, parent_path, size, is_dir, modified_at, depth
-             FROM {} ORDER BY size DESC LIMIT ?",
-            table_name
+            "SELECT {} FROM {} ORDER BY {} DESC LIMIT ?",
+            ENTRY_COLUMNS,
+            table_name,
+            size_mode.order_column()
         );
 
         let rows = sqlx::query(&query_str)
@@ -472,9 +1505,8 @@ impl Database {
     pub async fn get_root_entry(&self, scan_id: i64) -> Result<Option<StoredFileEntry>> {
         let table_name = self.get_entries_table(scan_id).await?;
         let query_str = format!(
-            "SELECT id, path, name, parent_path, size, is_dir, modified_at, depth
-             FROM {} WHERE depth = 0 LIMIT 1",
-            table_name
+            "SELECT {} FROM {} WHERE depth = 0 LIMIT 1",
+            ENTRY_COLUMNS, table_name
         );
 
         let row = sqlx::query(&query_str).fetch_optional(&self.pool).await?;
@@ -491,9 +1523,8 @@ impl Database {
 
         let rows = if let Some(parent) = parent_path {
             let query_str = format!(
-                "SELECT id, path, name, parent_path, size, is_dir, modified_at, depth
-                 FROM {} WHERE parent_path = ? ORDER BY size DESC",
-                table_name
+                "SELECT {} FROM {} WHERE parent_path = ? ORDER BY size DESC",
+                ENTRY_COLUMNS, table_name
             );
             sqlx::query(&query_str)
                 .bind(parent)
@@ -501,19 +1532,130 @@ impl Database {
                 .await?
         } else {
             let query_str = format!(
-                "SELECT id, path, name, parent_path, size, is_dir, modified_at, depth
-                 FROM {} WHERE parent_path IS NULL ORDER BY size DESC",
-                table_name
+                "SELECT {} FROM {} WHERE parent_path IS NULL ORDER BY size DESC",
+                ENTRY_COLUMNS, table_name
             );
             sqlx::query(&query_str).fetch_all(&self.pool).await?
         };
 
-        let entries = rows
+        let entries = rows
+            .iter()
+            .map(|row| Self::row_to_entry(row, scan_id))
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// The root of a paused scan that hadn't finished it, sized by the entries
+    /// recorded so far (None if nothing was recorded)
+    pub async fn get_unfinished_root(
+        &self,
+        scan_id: i64,
+        root_path: &str,
+    ) -> Result<Option<StoredFileEntry>> {
+        let table_name = self.get_entries_table(scan_id).await?;
+        let query_str = format!(
+            "SELECT COUNT(*) AS entries,
+                COALESCE(SUM(CASE WHEN {counted} THEN size ELSE 0 END), 0) AS size,
+                COALESCE(SUM(CASE WHEN {counted} THEN COALESCE(disk_usage, size) ELSE 0 END), 0) AS disk_usage
+             FROM {table}",
+            counted = COUNTED_SQL,
+            table = table_name
+        );
+        let row = sqlx::query(&query_str).fetch_one(&self.pool).await?;
+        if row.get::<i64, _>("entries") == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(StoredFileEntry {
+            scan_id,
+            path: root_path.to_string(),
+            name: root_path.to_string(),
+            size: row.get("size"),
+            disk_usage: row.get("disk_usage"),
+            is_dir: true,
+            kind: EntryKind::Unfinished,
+            ..Default::default()
+        }))
+    }
+
+    /// Directories directly below `parent` that a paused scan entered but hadn't
+    /// finished: entries were recorded below them, but not the directory itself
+    ///
+    /// They are sized by the files recorded so far, so a paused scan can be
+    /// browsed before it's resumed.
+    pub async fn get_unfinished_dirs(
+        &self,
+        scan_id: i64,
+        parent: &str,
+    ) -> Result<Vec<StoredFileEntry>> {
+        let table_name = self.get_entries_table(scan_id).await?;
+        let prefix = descendant_prefix(parent);
+        // `rest` is the path below `parent`; its first component names the
+        // directory, and its slashes how far below that directory the entry is
+        let query_str = format!(
+            "SELECT below.name AS name,
+                MIN(below.depth - (length(below.rest) - length(replace(below.rest, '/', '')))) AS depth,
+                SUM(CASE WHEN {counted} THEN below.size ELSE 0 END) AS size,
+                SUM(CASE WHEN {counted} THEN COALESCE(below.disk_usage, below.size) ELSE 0 END) AS disk_usage
+             FROM (
+                SELECT substr(rest, 1, instr(rest, '/') - 1) AS name, rest, depth, size, disk_usage, is_dir, kind
+                FROM (SELECT substr(path, ?1 + 1) AS rest, depth, size, disk_usage, is_dir, kind
+                      FROM {table} WHERE substr(path, 1, ?1) = ?2)
+                WHERE instr(rest, '/') > 0
+             ) AS below
+             WHERE NOT EXISTS (SELECT 1 FROM {table} AS entry WHERE entry.path = ?2 || below.name)
+             GROUP BY below.name
+             ORDER BY size DESC",
+            counted = COUNTED_SQL,
+            table = table_name
+        );
+        let rows = sqlx::query(&query_str)
+            .bind(prefix.chars().count() as i64)
+            .bind(&prefix)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let dirs = rows
             .iter()
-            .map(|row| Self::row_to_entry(row, scan_id))
+            .map(|row| {
+                let name: String = row.get("name");
+                StoredFileEntry {
+                    scan_id,
+                    path: format!("{}{}", prefix, name),
+                    name,
+                    parent_path: Some(parent.to_string()),
+                    size: row.get("size"),
+                    disk_usage: row.get("disk_usage"),
+                    is_dir: true,
+                    depth: row.get("depth"),
+                    kind: EntryKind::Unfinished,
+                    ..Default::default()
+                }
+            })
             .collect();
 
-        Ok(entries)
+        Ok(dirs)
+    }
+
+    /// Paths in a scan that are hard links to the file with this device and inode
+    pub async fn get_hard_links(
+        &self,
+        scan_id: i64,
+        device: u64,
+        inode: u64,
+    ) -> Result<Vec<String>> {
+        let table_name = self.get_entries_table(scan_id).await?;
+        let query_str = format!(
+            "SELECT path FROM {} WHERE device = ? AND inode = ? AND is_dir = 0 ORDER BY path",
+            table_name
+        );
+        let paths = sqlx::query_scalar(&query_str)
+            .bind(device as i64)
+            .bind(inode as i64)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(paths)
     }
 
     /// Get all descendants of a directory recursively (all files/dirs under the parent path)
@@ -528,11 +1670,11 @@ impl Database {
         let prefix_pattern = format!("{}/%", parent_path);
 
         let query_str = format!(
-            "SELECT id, path, name, parent_path, size, is_dir, modified_at, depth
+            "SELECT {}
              FROM {}
              WHERE path LIKE ? OR parent_path = ?
              ORDER BY path",
-            table_name
+            ENTRY_COLUMNS, table_name
         );
 
         let rows = sqlx::query(&query_str)
@@ -591,11 +1733,11 @@ impl Database {
             .collect::<Vec<_>>()
             .join(",");
         let query_str = format!(
-            "SELECT id, path, name, parent_path, size, is_dir, modified_at, depth
+            "SELECT {}
              FROM {}
              WHERE path IN ({})
              ORDER BY size DESC",
-            table_name, placeholders
+            ENTRY_COLUMNS, table_name, placeholders
         );
 
         let mut query = sqlx::query(&query_str);
@@ -664,10 +1806,18 @@ mod tests {
                 started_at TEXT NOT NULL,
                 completed_at TEXT,
                 total_size INTEGER NOT NULL DEFAULT 0,
+                total_disk_usage INTEGER,
                 total_files INTEGER NOT NULL DEFAULT 0,
                 total_dirs INTEGER NOT NULL DEFAULT 0,
                 status TEXT NOT NULL DEFAULT 'running' CHECK(status IN ('running', 'completed', 'failed', 'paused')),
-                entries_table TEXT
+                entries_table TEXT,
+                scan_options TEXT,
+                error_count INTEGER,
+                duration_ms INTEGER,
+                host TEXT,
+                roots TEXT,
+                summarized_files INTEGER NOT NULL DEFAULT 0,
+                summarized_dirs INTEGER NOT NULL DEFAULT 0
             );
             CREATE INDEX idx_scans_started_at ON scans(started_at DESC);
             CREATE INDEX idx_scans_root_path ON scans(root_path);
@@ -699,6 +1849,24 @@ mod tests {
                 UNIQUE(scan_id, entry_path)
             );
             CREATE INDEX idx_cleanup_items_scan_id ON cleanup_items(scan_id);
+
+            CREATE TABLE IF NOT EXISTS scan_errors (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                scan_id INTEGER NOT NULL,
+                path TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                message TEXT NOT NULL,
+                FOREIGN KEY (scan_id) REFERENCES scans(id) ON DELETE CASCADE,
+                UNIQUE(scan_id, path)
+            );
+            CREATE INDEX idx_scan_errors_scan_id ON scan_errors(scan_id);
+
+            CREATE TABLE IF NOT EXISTS scan_checkpoints (
+                scan_id INTEGER NOT NULL,
+                path TEXT NOT NULL,
+                FOREIGN KEY (scan_id) REFERENCES scans(id) ON DELETE CASCADE,
+                PRIMARY KEY (scan_id, path)
+            );
             "#,
         )
         .execute(&pool)
@@ -714,9 +1882,12 @@ mod tests {
             name: name.to_string(),
             parent_path: Some(PathBuf::from("/test")),
             size,
+            disk_usage: size.div_ceil(4096) * 4096,
             is_dir,
             modified_at: Some(Utc::now()),
             depth: 1,
+            kind: EntryKind::Normal,
+            stat: None,
         }
     }
 
@@ -746,8 +1917,12 @@ mod tests {
 
         let stats = ScanStats {
             total_size: 1000,
+            total_disk_usage: 4096,
             total_files: 10,
             total_dirs: 5,
+            error_count: 0,
+            reused_dirs: 0,
+            duration: std::time::Duration::ZERO,
         };
 
         db.complete_scan(scan_id, &stats).await.unwrap();
@@ -755,6 +1930,7 @@ mod tests {
         let scan = db.get_scan(scan_id).await.unwrap().unwrap();
         assert_eq!(scan.status, "completed");
         assert_eq!(scan.total_size, 1000);
+        assert_eq!(scan.total_disk_usage, 4096);
         assert_eq!(scan.total_files, 10);
         assert_eq!(scan.total_dirs, 5);
         assert!(scan.completed_at.is_some());
@@ -951,4 +2127,602 @@ mod tests {
         assert_eq!(scan.total_size, 3072); // 1024 + 2048
         assert!(scan.completed_at.is_none());
     }
+
+    #[tokio::test]
+    async fn test_scan_options_round_trip() {
+        let db = create_test_db().await;
+
+        let options = ScanOptions {
+            follow_symlinks: true,
+            exclude: vec![".git/objects".to_string()],
+            include: vec!["keep.log".to_string()],
+            one_file_system: true,
+            max_depth: Some(3),
+            from_list: false,
+            imported_from: None,
+            hard_links: crate::scanner::HardLinkPolicy::Proportional,
+            archives: true,
+        };
+        let scan_id = db
+            .create_scan_with_options(&PathBuf::from("/test"), &options)
+            .await
+            .unwrap();
+
+        let scan = db.get_scan(scan_id).await.unwrap().unwrap();
+        assert_eq!(scan.options, Some(options));
+    }
+
+    #[tokio::test]
+    async fn test_marker_entries_excluded_from_stats() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/test")).await.unwrap();
+
+        let mut marker = create_test_entry("node_modules", 0, true);
+        marker.kind = EntryKind::Excluded;
+        let entries = vec![create_test_entry("file1.txt", 100, false), marker];
+        db.insert_file_entries(scan_id, &entries).await.unwrap();
+
+        let stored = db
+            .get_entries_by_parent(scan_id, Some("/test"))
+            .await
+            .unwrap();
+        let stored_marker = stored.iter().find(|e| e.name == "node_modules").unwrap();
+        assert_eq!(stored_marker.kind, EntryKind::Excluded);
+
+        let stats = db.calculate_scan_stats(scan_id).await.unwrap();
+        assert_eq!(stats.total_files, 1);
+        assert_eq!(stats.total_dirs, 0);
+    }
+
+    #[tokio::test]
+    async fn test_summarized_directories_count_towards_stats() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/test")).await.unwrap();
+
+        let mut summarized = create_test_entry("target", 5000, true);
+        summarized.kind = EntryKind::Summarized;
+        db.insert_file_entries(scan_id, &[summarized])
+            .await
+            .unwrap();
+
+        let stored = db
+            .get_entries_by_parent(scan_id, Some("/test"))
+            .await
+            .unwrap();
+        assert_eq!(stored[0].kind, EntryKind::Summarized);
+        assert!(!stored[0].kind.is_marker());
+
+        let stats = db.calculate_scan_stats(scan_id).await.unwrap();
+        assert_eq!(stats.total_dirs, 1);
+        assert_eq!(stats.total_size, 5000);
+
+        // Its contents were counted while scanning, but never stored
+        db.add_summarized_counts(scan_id, 3, 1).await.unwrap();
+        db.add_summarized_counts(scan_id, 2, 0).await.unwrap();
+        let stats = db.calculate_scan_stats(scan_id).await.unwrap();
+        assert_eq!((stats.total_files, stats.total_dirs), (5, 2));
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_entry_tables_keeps_old_scans_readable() {
+        let db = create_test_db().await;
+
+        // Simulate a scan created before the kind column existed
+        sqlx::query(
+            "CREATE TABLE scan_entries_old (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                path TEXT NOT NULL,
+                name TEXT NOT NULL,
+                parent_path TEXT,
+                size INTEGER NOT NULL,
+                is_dir INTEGER NOT NULL,
+                modified_at TEXT,
+                depth INTEGER NOT NULL
+            )",
+        )
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO scan_entries_old (path, name, parent_path, size, is_dir, depth)
+             VALUES ('/old', '/old', NULL, 42, 1, 0)",
+        )
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        let scan_id = sqlx::query(
+            "INSERT INTO scans (root_path, started_at, status, entries_table)
+             VALUES ('/old', '2025-01-01T00:00:00+00:00', 'completed', 'scan_entries_old')",
+        )
+        .execute(&db.pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+
+        db.upgrade_entry_tables().await.unwrap();
+        // Upgrading twice is a no-op
+        db.upgrade_entry_tables().await.unwrap();
+
+        let scan = db.get_scan(scan_id).await.unwrap().unwrap();
+        assert!(scan.options.is_none());
+        let root = db.get_root_entry(scan_id).await.unwrap().unwrap();
+        assert_eq!(root.size, 42);
+        assert_eq!(root.kind, EntryKind::Normal);
+        // Disk usage wasn't recorded, so it falls back to the apparent size
+        assert_eq!(root.disk_usage, 42);
+        assert!(root.stat.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_entry_stat_round_trip() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/test")).await.unwrap();
+
+        let stat = EntryStat {
+            uid: 1000,
+            gid: 100,
+            mode: 0o100644,
+            inode: u64::MAX / 2,
+            device: 2049,
+            nlink: 3,
+            accessed_at: Some(Utc::now()),
+            changed_at: None,
+        };
+        let mut with_stat = create_test_entry("owned.txt", 10, false);
+        with_stat.stat = Some(stat);
+        let without_stat = create_test_entry("marker", 0, true);
+        db.insert_file_entries(scan_id, &[with_stat, without_stat])
+            .await
+            .unwrap();
+
+        let entries = db
+            .get_entries_by_parent(scan_id, Some("/test"))
+            .await
+            .unwrap();
+        let owned = entries.iter().find(|e| e.name == "owned.txt").unwrap();
+        assert_eq!(owned.stat, Some(stat));
+        assert_eq!(owned.stat.unwrap().mode_string(), "-rw-r--r--");
+        let marker = entries.iter().find(|e| e.name == "marker").unwrap();
+        assert!(marker.stat.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_hard_links() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/test")).await.unwrap();
+
+        let linked = |name: &str, inode: u64| {
+            let mut entry = create_test_entry(name, 10, false);
+            entry.stat = Some(EntryStat {
+                inode,
+                device: 7,
+                nlink: 2,
+                ..Default::default()
+            });
+            entry
+        };
+        let entries = vec![linked("b", 42), linked("a", 42), linked("other", 43)];
+        db.insert_file_entries(scan_id, &entries).await.unwrap();
+
+        let paths = db.get_hard_links(scan_id, 7, 42).await.unwrap();
+        assert_eq!(paths, vec!["/test/a".to_string(), "/test/b".to_string()]);
+        assert!(db.get_hard_links(scan_id, 8, 42).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_extension_usage() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/test")).await.unwrap();
+
+        let mut marker = create_test_entry("skipped.iso", 0, true);
+        marker.kind = EntryKind::Excluded;
+        let entries = vec![
+            create_test_entry("movie.MKV", 5000, false),
+            create_test_entry("clip.mkv", 1000, false),
+            create_test_entry("archive.tar.gz", 3000, false),
+            create_test_entry("Makefile", 100, false),
+            create_test_entry(".bashrc", 50, false),
+            create_test_entry("trailing.", 10, false),
+            create_test_entry("build", 9999, true),
+            marker,
+        ];
+        db.insert_file_entries(scan_id, &entries).await.unwrap();
+
+        let usage = db
+            .get_extension_usage(scan_id, SizeMode::Apparent)
+            .await
+            .unwrap();
+        let summary: Vec<(&str, i64, i64)> = usage
+            .iter()
+            .map(|u| (u.extension.as_str(), u.files, u.size))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("mkv", 2, 6000), ("gz", 1, 3000), ("", 3, 160)]
+        );
+
+        let largest = db
+            .get_largest_files_excluding_extensions(
+                scan_id,
+                &["mkv".to_string()],
+                SizeMode::Apparent,
+                2,
+            )
+            .await
+            .unwrap();
+        let names: Vec<&str> = largest.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["archive.tar.gz", "Makefile"]);
+    }
+
+    #[tokio::test]
+    async fn test_owner_usage_and_drill_down() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/test")).await.unwrap();
+
+        let owned = |name: &str, parent: &str, size: u64, uid: u32, gid: u32| {
+            let mut entry = create_test_entry(name, size, false);
+            entry.path = PathBuf::from(parent).join(name);
+            entry.parent_path = Some(PathBuf::from(parent));
+            entry.stat = Some(EntryStat {
+                uid,
+                gid,
+                ..Default::default()
+            });
+            entry
+        };
+        let mut dir = create_test_entry("build", 10_000, true);
+        dir.stat = Some(EntryStat {
+            uid: 1000,
+            ..Default::default()
+        });
+        let entries = vec![
+            dir,
+            owned("a.o", "/test/build", 3000, 1000, 100),
+            owned("b.o", "/test/build", 2000, 1000, 100),
+            owned("notes.txt", "/test/docs", 500, 1000, 100),
+            owned("db.bin", "/test/data", 4000, 0, 0),
+            // Recorded before ownership was captured
+            create_test_entry("legacy", 9999, false),
+        ];
+        db.insert_file_entries(scan_id, &entries).await.unwrap();
+
+        let users = db
+            .get_owner_usage(scan_id, OwnerGrouping::User, SizeMode::Apparent)
+            .await
+            .unwrap();
+        assert_eq!(users.len(), 2);
+        assert_eq!(users[0].owner_id, 1000);
+        assert_eq!(users[0].files, 3);
+        assert_eq!(users[0].size, 5500);
+        assert_eq!(users[1].owner_id, 0);
+        assert_eq!(users[1].size, 4000);
+
+        let groups = db
+            .get_owner_usage(scan_id, OwnerGrouping::Group, SizeMode::Apparent)
+            .await
+            .unwrap();
+        assert_eq!(groups[0].owner_id, 100);
+
+        let dirs = db
+            .get_owner_directories(scan_id, OwnerGrouping::User, 1000, SizeMode::Apparent, 10)
+            .await
+            .unwrap();
+        assert_eq!(dirs.len(), 2);
+        assert_eq!(dirs[0].path, "/test/build");
+        assert_eq!(dirs[0].files, 2);
+        assert_eq!(dirs[0].size, 5000);
+        assert_eq!(dirs[1].path, "/test/docs");
+    }
+
+    #[tokio::test]
+    async fn test_disk_usage_ordering_and_stats() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/test")).await.unwrap();
+
+        // A sparse image is large but mostly unallocated; tiny files round up to a block
+        let mut sparse = create_test_entry("disk.img", 1_000_000, false);
+        sparse.disk_usage = 8192;
+        let mut tiny = create_test_entry("tiny.txt", 10, false);
+        tiny.disk_usage = 16384;
+        db.insert_file_entries(scan_id, &[sparse, tiny])
+            .await
+            .unwrap();
+
+        let by_size = db
+            .get_largest_entries_by(scan_id, 10, SizeMode::Apparent)
+            .await
+            .unwrap();
+        assert_eq!(by_size[0].name, "disk.img");
+
+        let by_usage = db
+            .get_largest_entries_by(scan_id, 10, SizeMode::DiskUsage)
+            .await
+            .unwrap();
+        assert_eq!(by_usage[0].name, "tiny.txt");
+        assert_eq!(SizeMode::DiskUsage.entry_size(&by_usage[0]), 16384);
+
+        let stats = db.calculate_scan_stats(scan_id).await.unwrap();
+        assert_eq!(stats.total_size, 1_000_010);
+        assert_eq!(stats.total_disk_usage, 24576);
+
+        db.pause_scan(scan_id, &stats).await.unwrap();
+        let scan = db.get_scan(scan_id).await.unwrap().unwrap();
+        assert_eq!(SizeMode::Apparent.scan_size(&scan), 1_000_010);
+        assert_eq!(SizeMode::DiskUsage.scan_size(&scan), 24576);
+    }
+
+    #[tokio::test]
+    async fn test_scan_errors_are_recorded_once() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/test")).await.unwrap();
+
+        let denied = ScanError {
+            path: PathBuf::from("/test/private"),
+            kind: ScanErrorKind::PermissionDenied,
+            message: "Permission denied (os error 13)".to_string(),
+        };
+        let vanished = ScanError {
+            path: PathBuf::from("/test/tmp/gone"),
+            kind: ScanErrorKind::NotFound,
+            message: "No such file or directory (os error 2)".to_string(),
+        };
+        db.insert_scan_errors(scan_id, &[vanished, denied.clone()])
+            .await
+            .unwrap();
+        // A resumed scan hitting the same path again doesn't duplicate it
+        db.insert_scan_errors(scan_id, &[denied]).await.unwrap();
+
+        let errors = db.get_scan_errors(scan_id).await.unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].path, "/test/private");
+        assert_eq!(errors[0].kind, ScanErrorKind::PermissionDenied);
+        assert_eq!(errors[1].kind, ScanErrorKind::NotFound);
+
+        let stats = db.calculate_scan_stats(scan_id).await.unwrap();
+        assert_eq!(stats.error_count, 2);
+        db.complete_scan(scan_id, &stats).await.unwrap();
+        let scan = db.get_scan(scan_id).await.unwrap().unwrap();
+        assert_eq!(scan.error_count, 2);
+
+        db.delete_scan(scan_id).await.unwrap();
+        assert!(db.get_scan_errors(scan_id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_incremental_base_scan() {
+        let db = create_test_db().await;
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs/readme.txt"), b"hello").unwrap();
+
+        let scanner =
+            crate::scanner::Scanner::new_with_impl(root, crate::scanner::ScannerImpl::Custom);
+        let (entries, stats) = scanner.scan().unwrap();
+        let base_id = db.create_scan(root).await.unwrap();
+        db.insert_file_entries(base_id, &entries).await.unwrap();
+        db.complete_scan(base_id, &stats).await.unwrap();
+
+        // Scans still running are never picked as a base
+        db.create_scan(root).await.unwrap();
+        let latest = db.get_latest_completed_scan(root).await.unwrap().unwrap();
+        assert_eq!(latest.id, base_id);
+
+        // Directories are looked up as the rescan reaches them, so it runs off the runtime
+        let previous = db.load_previous_scan(base_id).await.unwrap();
+        let rescan_root = root.to_path_buf();
+        let (reused_entries, reused_stats) = tokio::task::spawn_blocking(move || {
+            let mut scanner = crate::scanner::Scanner::new_with_impl(
+                &rescan_root,
+                crate::scanner::ScannerImpl::Custom,
+            );
+            scanner.reuse_unchanged(previous);
+            scanner.scan()
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(reused_stats.reused_dirs, 2);
+        assert_eq!(reused_stats.total_size, stats.total_size);
+        assert_eq!(reused_entries.len(), entries.len());
+    }
+
+    fn entry_at(path: &str, size: u64, is_dir: bool) -> FileEntry {
+        let path = PathBuf::from(path);
+        FileEntry {
+            name: path.file_name().unwrap().to_string_lossy().to_string(),
+            parent_path: path.parent().map(Path::to_path_buf),
+            depth: path.components().count() - 2,
+            path,
+            ..create_test_entry("", size, is_dir)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unfinished_dirs_of_paused_scan() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/t")).await.unwrap();
+        // Paused before /t and /t/p were recorded; /t/a was finished
+        let entries = vec![
+            entry_at("/t/a", 7, true),
+            entry_at("/t/a/done.txt", 7, false),
+            entry_at("/t/p/q/deep.txt", 10, false),
+            entry_at("/t/p/shallow.txt", 5, false),
+        ];
+        db.insert_file_entries(scan_id, &entries).await.unwrap();
+
+        assert!(db.get_root_entry(scan_id).await.unwrap().is_none());
+        let root = db
+            .get_unfinished_root(scan_id, "/t")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(root.kind, EntryKind::Unfinished);
+        assert_eq!((root.depth, root.size), (0, 22));
+
+        let dirs = db.get_unfinished_dirs(scan_id, "/t").await.unwrap();
+        assert_eq!(dirs.len(), 1);
+        assert_eq!(dirs[0].path, "/t/p");
+        assert_eq!(dirs[0].parent_path.as_deref(), Some("/t"));
+        assert_eq!((dirs[0].depth, dirs[0].size), (1, 15));
+
+        let dirs = db.get_unfinished_dirs(scan_id, "/t/p").await.unwrap();
+        assert_eq!(dirs.len(), 1);
+        assert_eq!((dirs[0].name.as_str(), dirs[0].depth), ("q", 2));
+        assert!(db
+            .get_unfinished_dirs(scan_id, "/t/a")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resume_state_skips_checkpointed_dirs() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/t")).await.unwrap();
+        // Paused with /t/a finished and /t/p partway through
+        let entries = vec![
+            entry_at("/t/a/sub/x.txt", 1, false),
+            entry_at("/t/a/sub", 1, true),
+            entry_at("/t/a/done.txt", 7, false),
+            entry_at("/t/a", 8, true),
+            entry_at("/t/p/q/deep.txt", 10, false),
+            entry_at("/t/p/shallow.txt", 5, false),
+        ];
+        db.insert_file_entries(scan_id, &entries).await.unwrap();
+        let finished = [PathBuf::from("/t/a/sub"), PathBuf::from("/t/a")];
+        db.insert_checkpoints(scan_id, &finished).await.unwrap();
+        // Sent again by a later run
+        db.insert_checkpoints(scan_id, &finished[1..])
+            .await
+            .unwrap();
+
+        // /t/a is skipped whole; only the paths in unfinished directories are loaded
+        let resume = db.get_resume_state(scan_id, None).await.unwrap();
+        assert_eq!(resume.len(), 3);
+        assert!(resume.contains(Path::new("/t/a")));
+        assert!(resume.contains(Path::new("/t/p/q/deep.txt")));
+        assert!(resume.contains(Path::new("/t/p/shallow.txt")));
+        assert!(!resume.contains(Path::new("/t/a/done.txt")));
+
+        let resume = db.get_resume_state(scan_id, Some("/t/p")).await.unwrap();
+        assert_eq!(resume.len(), 2);
+        assert!(!resume.contains(Path::new("/t/a")));
+
+        // Resuming finished /t/a on its own still skips /t/a/sub
+        let resume = db.get_resume_state(scan_id, Some("/t/a")).await.unwrap();
+        assert_eq!(resume.len(), 1);
+        assert!(resume.contains(Path::new("/t/a/sub")));
+
+        // Completing the scan drops its checkpoints
+        let stats = db.calculate_scan_stats(scan_id).await.unwrap();
+        db.complete_scan(scan_id, &stats).await.unwrap();
+        let resume = db.get_resume_state(scan_id, None).await.unwrap();
+        assert_eq!(resume.len(), entries.len());
+    }
+
+    #[tokio::test]
+    async fn test_recompute_dir_sizes_after_subtree_resume() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/t")).await.unwrap();
+        let earlier = vec![
+            entry_at("/t/a/old.txt", 100, false),
+            entry_at("/t/a/sub/x.txt", 50, false),
+            entry_at("/t/a/sub", 50, true),
+            entry_at("/t/a", 150, true),
+            entry_at("/t", 999, true),
+        ];
+        db.insert_file_entries(scan_id, &earlier).await.unwrap();
+        let last_entry_id = db.last_entry_id(scan_id).await.unwrap();
+
+        let resume = db.get_resume_state(scan_id, Some("/t/a")).await.unwrap();
+        assert_eq!(resume.len(), 3);
+        assert!(!resume.contains(Path::new("/t/a")));
+
+        // The resumed run only counts what it found: one new file in /t/a
+        let resumed = vec![
+            entry_at("/t/a/new.txt", 30, false),
+            entry_at("/t/a", 30, true),
+        ];
+        db.insert_file_entries(scan_id, &resumed).await.unwrap();
+        db.recompute_dir_sizes(scan_id, "/t/a", last_entry_id, HardLinkPolicy::default())
+            .await
+            .unwrap();
+
+        let children = db.get_entries_by_parent(scan_id, Some("/t")).await.unwrap();
+        assert_eq!(children.len(), 1, "the earlier /t/a row is replaced");
+        assert_eq!(children[0].size, 180);
+        let root = db.get_root_entry(scan_id).await.unwrap().unwrap();
+        assert_eq!(root.size, 999 + 30);
+    }
+
+    #[tokio::test]
+    async fn test_subtree_resume_keeps_whole_scan_totals() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/t")).await.unwrap();
+        let earlier = vec![
+            entry_at("/t/a/old.txt", 100, false),
+            entry_at("/t/a", 100, true),
+            entry_at("/t/b/big.txt", 1000, false),
+            entry_at("/t/b", 1000, true),
+            entry_at("/t", 1100, true),
+        ];
+        db.insert_file_entries(scan_id, &earlier).await.unwrap();
+        let stats = db.calculate_scan_stats(scan_id).await.unwrap();
+        assert_eq!(stats.total_size, 1100, "directory sizes aren't added twice");
+        db.pause_scan(scan_id, &stats).await.unwrap();
+        let last_entry_id = db.last_entry_id(scan_id).await.unwrap();
+
+        // Resuming /t/a finds one new file; the run's own stats only cover /t/a
+        let resumed = vec![
+            entry_at("/t/a/new.txt", 30, false),
+            entry_at("/t/a", 30, true),
+        ];
+        db.insert_file_entries(scan_id, &resumed).await.unwrap();
+        db.recompute_dir_sizes(scan_id, "/t/a", last_entry_id, HardLinkPolicy::default())
+            .await
+            .unwrap();
+        let mut totals = db.calculate_scan_stats(scan_id).await.unwrap();
+        totals.duration = std::time::Duration::from_millis(250);
+        db.pause_scan(scan_id, &totals).await.unwrap();
+
+        let scan = db.get_scan(scan_id).await.unwrap().unwrap();
+        assert_eq!(scan.status, "paused");
+        assert_eq!(scan.total_size, 1130);
+        assert_eq!(scan.total_files, 3);
+        assert_eq!(scan.total_dirs, 3);
+        assert_eq!(scan.duration_ms, Some(250));
+    }
+
+    #[tokio::test]
+    async fn test_scan_duration_accumulates_across_resumes() {
+        let db = create_test_db().await;
+        let scan_id = db.create_scan(&PathBuf::from("/test")).await.unwrap();
+        let mut stats = ScanStats {
+            total_size: 100,
+            total_disk_usage: 4096,
+            total_files: 3,
+            total_dirs: 1,
+            error_count: 0,
+            reused_dirs: 0,
+            duration: std::time::Duration::from_millis(1500),
+        };
+
+        db.pause_scan(scan_id, &stats).await.unwrap();
+        stats.duration = std::time::Duration::from_millis(500);
+        db.complete_scan(scan_id, &stats).await.unwrap();
+
+        let scan = db.get_scan(scan_id).await.unwrap().unwrap();
+        assert_eq!(scan.duration_ms, Some(2000));
+        assert_eq!(scan.duration(), Some(std::time::Duration::from_secs(2)));
+        assert_eq!(scan.entry_count(), 4);
+
+        // Scans from before durations were stored fall back to wall-clock time
+        let older = Scan {
+            duration_ms: None,
+            completed_at: Some(scan.started_at + chrono::Duration::seconds(90)),
+            ..scan
+        };
+        assert_eq!(older.duration(), Some(std::time::Duration::from_secs(90)));
+    }
 }
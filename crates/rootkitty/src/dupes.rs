@@ -0,0 +1,331 @@
+//! Finding duplicate files in a scan
+//!
+//! Candidates are narrowed in stages, each more expensive than the last:
+//! files of the same size, then files whose first and last blocks hash the
+//! same, then files whose whole content has the same BLAKE3 hash. Hashes are
+//! stored in a per-scan table as they are computed, so an interrupted search
+//! picks up where it left off.
+
+use anyhow::{bail, Result};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::db::{Database, FileHashes, StoredFileEntry};
+
+/// Bytes hashed from each end of a file in the partial stage
+const EDGE_BYTES: u64 = 64 * 1024;
+
+/// Files hashed between saves to the database
+const HASH_BATCH: usize = 256;
+
+/// Smallest file searched by default; duplicates below this rarely matter
+pub const DEFAULT_MIN_SIZE: u64 = 1024 * 1024;
+
+/// Files with identical content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// Size of each copy
+    pub size: u64,
+    /// BLAKE3 hash of the content, in hex
+    pub hash: String,
+    /// Paths of the copies, sorted
+    pub paths: Vec<String>,
+}
+
+impl DuplicateGroup {
+    /// Bytes freed by keeping a single copy
+    pub fn reclaimable(&self) -> u64 {
+        self.size * (self.paths.len() as u64 - 1)
+    }
+}
+
+/// Outcome of a duplicate search
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateReport {
+    /// Largest savings first
+    pub groups: Vec<DuplicateGroup>,
+    /// Files hashed in this run (hashes stored by earlier runs are reused)
+    pub hashed: usize,
+    /// Files that couldn't be read or no longer match the scanned size
+    pub skipped: Vec<String>,
+}
+
+impl DuplicateReport {
+    pub fn reclaimable(&self) -> u64 {
+        self.groups.iter().map(|g| g.reclaimable()).sum()
+    }
+}
+
+/// Which hash a stage of the search computes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashStage {
+    /// Size plus the first and last blocks
+    Partial,
+    /// The whole content
+    Full,
+}
+
+impl HashStage {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HashStage::Partial => "Hashing first and last blocks",
+            HashStage::Full => "Hashing full contents",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Candidate {
+    path: String,
+    size: u64,
+}
+
+/// Find groups of files with identical content among a scan's files of at least `min_size` bytes
+///
+/// `progress` is called with the stage, files done and files to do as hashing proceeds.
+pub async fn find_duplicates(
+    db: &Database,
+    scan_id: i64,
+    min_size: u64,
+    mut progress: impl FnMut(HashStage, usize, usize),
+) -> Result<DuplicateReport> {
+    // Hashing reads the files, which are only here for local scans
+    if let Some(host) = db.get_scan(scan_id).await?.and_then(|scan| scan.host) {
+        bail!(
+            "Scan {} ran on {}, so its files can't be read here",
+            scan_id,
+            host
+        );
+    }
+    let entries = db.get_duplicate_candidates(scan_id, min_size).await?;
+    db.ensure_hashes_table(scan_id).await?;
+    let stored = db.get_file_hashes(scan_id).await?;
+    let mut report = DuplicateReport::default();
+
+    // Stage 1: same size (hard links to one file aren't copies of it)
+    let by_size = group_by(collapse_hard_links(entries), |c| c.size.to_string());
+
+    // Stage 2: same first and last blocks
+    let to_hash: Vec<Candidate> = by_size.into_iter().flatten().collect();
+    let partial = hash_stage(
+        db,
+        scan_id,
+        to_hash,
+        HashStage::Partial,
+        &stored,
+        &mut report,
+        &mut progress,
+    )
+    .await?;
+    let by_partial = group_by(partial, |(c, hash)| format!("{}:{}", c.size, hash));
+
+    // Stage 3: same content. The partial hash already covers small files entirely
+    let mut confirmed = Vec::new();
+    let mut to_hash = Vec::new();
+    for group in by_partial {
+        for (candidate, hash) in group {
+            if candidate.size <= 2 * EDGE_BYTES {
+                confirmed.push((candidate, hash));
+            } else {
+                to_hash.push(candidate);
+            }
+        }
+    }
+    let full = hash_stage(
+        db,
+        scan_id,
+        to_hash,
+        HashStage::Full,
+        &stored,
+        &mut report,
+        &mut progress,
+    )
+    .await?;
+    confirmed.extend(full);
+
+    report.groups = group_by(confirmed, |(c, hash)| format!("{}:{}", c.size, hash))
+        .into_iter()
+        .map(|group| {
+            let (size, hash) = (group[0].0.size, group[0].1.clone());
+            let mut paths: Vec<String> = group.into_iter().map(|(c, _)| c.path).collect();
+            paths.sort();
+            DuplicateGroup { size, hash, paths }
+        })
+        .collect();
+    report.groups.sort_by(|a, b| {
+        b.reclaimable()
+            .cmp(&a.reclaimable())
+            .then_with(|| a.paths.cmp(&b.paths))
+    });
+    Ok(report)
+}
+
+/// Keep one path per hard-linked file
+fn collapse_hard_links(entries: Vec<StoredFileEntry>) -> Vec<Candidate> {
+    let mut seen_links = HashSet::new();
+    entries
+        .into_iter()
+        .filter(|entry| {
+            entry
+                .stat
+                .filter(|st| st.nlink > 1)
+                .is_none_or(|st| seen_links.insert((st.device, st.inode)))
+        })
+        .map(|entry| Candidate {
+            path: entry.path,
+            size: entry.size as u64,
+        })
+        .collect()
+}
+
+/// Group items by key, keeping only groups of two or more
+fn group_by<T>(items: impl IntoIterator<Item = T>, key: impl Fn(&T) -> String) -> Vec<Vec<T>> {
+    let mut groups: HashMap<String, Vec<T>> = HashMap::new();
+    for item in items {
+        groups.entry(key(&item)).or_default().push(item);
+    }
+    groups.into_values().filter(|g| g.len() > 1).collect()
+}
+
+/// Hash candidates for one stage, reusing stored hashes and saving new ones in batches
+async fn hash_stage(
+    db: &Database,
+    scan_id: i64,
+    candidates: Vec<Candidate>,
+    stage: HashStage,
+    stored: &HashMap<String, FileHashes>,
+    report: &mut DuplicateReport,
+    progress: &mut impl FnMut(HashStage, usize, usize),
+) -> Result<Vec<(Candidate, String)>> {
+    let total = candidates.len();
+    let mut hashed = Vec::with_capacity(total);
+    let mut pending = Vec::new();
+    for candidate in candidates {
+        let known = stored.get(&candidate.path).and_then(|h| match stage {
+            HashStage::Partial => h.partial.clone(),
+            HashStage::Full => h.full.clone(),
+        });
+        match known {
+            Some(hash) => hashed.push((candidate, hash)),
+            None => pending.push(candidate),
+        }
+    }
+    progress(stage, hashed.len(), total);
+
+    while !pending.is_empty() {
+        let batch: Vec<Candidate> = pending.drain(..pending.len().min(HASH_BATCH)).collect();
+        let results = tokio::task::spawn_blocking(move || {
+            batch
+                .into_par_iter()
+                .map(|c| {
+                    let hash = hash_file(Path::new(&c.path), c.size, stage);
+                    (c, hash)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await?;
+
+        let mut to_store = Vec::new();
+        for (candidate, hash) in results {
+            match hash {
+                Ok(Some(hash)) => {
+                    let file_hashes = match stage {
+                        HashStage::Partial => FileHashes {
+                            partial: Some(hash.clone()),
+                            full: None,
+                        },
+                        HashStage::Full => FileHashes {
+                            partial: None,
+                            full: Some(hash.clone()),
+                        },
+                    };
+                    to_store.push((candidate.path.clone(), file_hashes));
+                    hashed.push((candidate, hash));
+                }
+                Ok(None) | Err(_) => report.skipped.push(candidate.path),
+            }
+        }
+        report.hashed += to_store.len();
+        db.store_file_hashes(scan_id, &to_store).await?;
+        progress(stage, total - pending.len(), total);
+    }
+
+    Ok(hashed)
+}
+
+/// Hash a file for a stage, or None if its size no longer matches the scan
+fn hash_file(path: &Path, expected_size: u64, stage: HashStage) -> io::Result<Option<String>> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    if size != expected_size {
+        return Ok(None);
+    }
+
+    let mut hasher = blake3::Hasher::new();
+    match stage {
+        HashStage::Partial => {
+            hasher.update(&size.to_le_bytes());
+            let head = size.min(EDGE_BYTES);
+            io::copy(&mut (&mut file).take(head), &mut hasher)?;
+            if size > head {
+                // The tail, without hashing any of the head twice
+                let tail_start = (size - EDGE_BYTES).max(head);
+                file.seek(SeekFrom::Start(tail_start))?;
+                io::copy(&mut file.take(size - tail_start), &mut hasher)?;
+            }
+        }
+        HashStage::Full => {
+            io::copy(&mut file, &mut hasher)?;
+        }
+    }
+    Ok(Some(hasher.finalize().to_hex().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_partial_hash_covers_edges_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let size = 4 * EDGE_BYTES as usize;
+        let a = vec![7u8; size];
+        let mut b = a.clone();
+        // Differs in the middle only
+        b[size / 2] = 8;
+        let mut c = a.clone();
+        c[size - 1] = 8;
+        for (name, contents) in [("a", &a), ("b", &b), ("c", &c)] {
+            std::fs::write(temp_dir.path().join(name), contents).unwrap();
+        }
+
+        let hash = |name: &str, stage| {
+            hash_file(&temp_dir.path().join(name), size as u64, stage)
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(hash("a", HashStage::Partial), hash("b", HashStage::Partial));
+        assert_ne!(hash("a", HashStage::Partial), hash("c", HashStage::Partial));
+        assert_ne!(hash("a", HashStage::Full), hash("b", HashStage::Full));
+
+        // A file that changed size since the scan isn't hashed
+        assert_eq!(
+            hash_file(&temp_dir.path().join("a"), 10, HashStage::Full).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reclaimable_keeps_one_copy() {
+        let group = DuplicateGroup {
+            size: 100,
+            hash: String::new(),
+            paths: vec!["/a".into(), "/b".into(), "/c".into()],
+        };
+        assert_eq!(group.reclaimable(), 200);
+    }
+}
@@ -0,0 +1,396 @@
+//! Breaking a scan down by file type
+//!
+//! Files are classified by extension into the categories configured under
+//! `[types]` in `settings.toml`. Per-extension totals are aggregated by the
+//! database, so a breakdown never loads the scan's files into memory.
+//! Optionally, a sample of the largest files whose extension is unknown is
+//! identified from its first bytes instead.
+
+use anyhow::Result;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::db::{Database, ExtensionUsage, SizeMode};
+
+/// Category of files whose extension no category lists
+pub const OTHER: &str = "Other";
+
+/// Bytes read from a file to identify it; enough to reach a tar header's magic
+const SNIFF_BYTES: usize = 512;
+
+/// Leading bytes that identify common formats: (offset, magic, implied extension)
+const SIGNATURES: &[(usize, &[u8], &str)] = &[
+    (0, b"\x89PNG\r\n\x1a\n", "png"),
+    (0, b"\xff\xd8\xff", "jpg"),
+    (0, b"GIF8", "gif"),
+    (0, b"%PDF-", "pdf"),
+    (0, b"PK\x03\x04", "zip"),
+    (0, b"\x1f\x8b", "gz"),
+    (0, b"\x28\xb5\x2f\xfd", "zst"),
+    (0, b"\xfd7zXZ\x00", "xz"),
+    (0, b"BZh", "bz2"),
+    (0, b"7z\xbc\xaf\x27\x1c", "7z"),
+    (0, b"SQLite format 3\x00", "sqlite"),
+    (0, b"\x7fELF", "elf"),
+    (0, b"\x1a\x45\xdf\xa3", "mkv"),
+    (0, b"OggS", "ogg"),
+    (0, b"fLaC", "flac"),
+    (0, b"ID3", "mp3"),
+    (0, b"QFI\xfb", "qcow2"),
+    (4, b"ftyp", "mp4"),
+    (257, b"ustar", "tar"),
+];
+
+/// A named group of extensions, e.g. "Video" for mp4, mkv and mov
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Category {
+    pub name: String,
+    /// Extensions without the leading dot; matched case-insensitively
+    pub extensions: Vec<String>,
+}
+
+impl Category {
+    fn new(name: &str, extensions: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            extensions: extensions.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+}
+
+/// The categories used when `settings.toml` doesn't list any
+pub fn default_categories() -> Vec<Category> {
+    vec![
+        Category::new(
+            "Video",
+            &[
+                "mp4", "m4v", "mkv", "mov", "avi", "webm", "wmv", "flv", "mpg", "mpeg",
+            ],
+        ),
+        Category::new(
+            "Audio",
+            &[
+                "mp3", "flac", "wav", "ogg", "opus", "m4a", "aac", "wma", "aiff",
+            ],
+        ),
+        Category::new(
+            "Images",
+            &[
+                "jpg", "jpeg", "png", "gif", "webp", "heic", "tif", "tiff", "bmp", "svg", "raw",
+                "cr2", "nef", "psd", "ico",
+            ],
+        ),
+        Category::new(
+            "Documents",
+            &[
+                "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "odt", "ods", "odp", "txt",
+                "md", "rtf", "epub", "csv",
+            ],
+        ),
+        Category::new(
+            "Archives",
+            &[
+                "zip", "tar", "gz", "tgz", "bz2", "xz", "zst", "7z", "rar", "jar", "whl", "deb",
+                "rpm",
+            ],
+        ),
+        Category::new(
+            "Disk images",
+            &["iso", "img", "qcow2", "vmdk", "vdi", "vhd", "vhdx", "dmg"],
+        ),
+        Category::new(
+            "Build output",
+            &[
+                "o",
+                "obj",
+                "a",
+                "lib",
+                "rlib",
+                "rmeta",
+                "d",
+                "class",
+                "pyc",
+                "pyo",
+                "pdb",
+                "incremental",
+            ],
+        ),
+        Category::new("Executables", &["so", "dll", "dylib", "exe", "elf", "wasm"]),
+        Category::new(
+            "Source code",
+            &[
+                "rs", "c", "h", "cc", "cpp", "hpp", "go", "py", "js", "ts", "tsx", "jsx", "java",
+                "kt", "rb", "php", "swift", "sh", "html", "css", "json", "toml", "yaml", "yml",
+            ],
+        ),
+        Category::new("Logs", &["log", "trace", "journal"]),
+        Category::new(
+            "Databases",
+            &["db", "sqlite", "sqlite3", "mdb", "ldb", "wal", "parquet"],
+        ),
+    ]
+}
+
+/// Files of one category within a scan
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeUsage {
+    pub name: String,
+    pub files: i64,
+    pub size: i64,
+    pub disk_usage: i64,
+}
+
+impl TypeUsage {
+    fn add(&mut self, files: i64, size: i64, disk_usage: i64) {
+        self.files += files;
+        self.size += size;
+        self.disk_usage += disk_usage;
+    }
+}
+
+/// A scan's files broken down by category and by extension
+#[derive(Debug, Clone, Default)]
+pub struct TypeReport {
+    /// Largest first, including [`OTHER`] when some files match no category
+    pub categories: Vec<TypeUsage>,
+    /// Largest first, with the category of each extension
+    pub extensions: Vec<(ExtensionUsage, String)>,
+    /// Files with an unknown extension that were identified from their content
+    pub sniffed: usize,
+}
+
+impl TypeReport {
+    /// Extensions belonging to one category, largest first
+    pub fn extensions_in<'a>(
+        &'a self,
+        category: &'a str,
+    ) -> impl Iterator<Item = &'a ExtensionUsage> + 'a {
+        self.extensions
+            .iter()
+            .filter(move |(_, name)| name == category)
+            .map(|(usage, _)| usage)
+    }
+}
+
+/// Break a scan's files down by type
+///
+/// Up to `sniff_sample` of the largest files whose extension no category lists
+/// are read from disk and, when their first bytes are recognized, counted
+/// towards the category of the extension their content implies. Remote scans
+/// are never sniffed.
+pub async fn type_breakdown(
+    db: &Database,
+    scan_id: i64,
+    categories: &[Category],
+    size_mode: SizeMode,
+    sniff_sample: usize,
+) -> Result<TypeReport> {
+    let extensions = db.get_extension_usage(scan_id, size_mode).await?;
+
+    // Files of a remote scan aren't here to be read
+    let remote = db
+        .get_scan(scan_id)
+        .await?
+        .is_some_and(|scan| scan.host.is_some());
+    let mut sniffed = Vec::new();
+    if sniff_sample > 0 && !remote {
+        let known: Vec<String> = lookup_table(categories).into_keys().collect();
+        let sample = db
+            .get_largest_files_excluding_extensions(scan_id, &known, size_mode, sniff_sample as i64)
+            .await?;
+        sniffed = tokio::task::spawn_blocking(move || {
+            sample
+                .into_par_iter()
+                .filter_map(|entry| {
+                    let extension = sniff_file(Path::new(&entry.path)).ok().flatten()?;
+                    Some((entry.size, entry.disk_usage, extension))
+                })
+                .collect::<Vec<_>>()
+        })
+        .await?;
+    }
+
+    Ok(classify(extensions, categories, &sniffed, size_mode))
+}
+
+/// Map lowercased extensions to the first category listing them
+fn lookup_table(categories: &[Category]) -> HashMap<String, &str> {
+    let mut table = HashMap::new();
+    for category in categories {
+        for extension in &category.extensions {
+            table
+                .entry(extension.trim_start_matches('.').to_lowercase())
+                .or_insert(category.name.as_str());
+        }
+    }
+    table
+}
+
+/// Total per-extension usage by category
+///
+/// `sniffed` holds the (size, disk usage, implied extension) of files that
+/// were identified from their content; they move from [`OTHER`] to the
+/// category of the implied extension.
+fn classify(
+    extensions: Vec<ExtensionUsage>,
+    categories: &[Category],
+    sniffed: &[(i64, i64, &'static str)],
+    size_mode: SizeMode,
+) -> TypeReport {
+    let table = lookup_table(categories);
+    let mut totals: HashMap<&str, TypeUsage> = HashMap::new();
+    let mut report = TypeReport::default();
+    for extension in extensions {
+        let category = table
+            .get(extension.extension.as_str())
+            .copied()
+            .unwrap_or(OTHER);
+        totals.entry(category).or_default().add(
+            extension.files,
+            extension.size,
+            extension.disk_usage,
+        );
+        report.extensions.push((extension, category.to_string()));
+    }
+
+    for &(size, disk_usage, implied) in sniffed {
+        let Some(category) = table.get(implied).copied() else {
+            continue;
+        };
+        totals.entry(OTHER).or_default().add(-1, -size, -disk_usage);
+        totals.entry(category).or_default().add(1, size, disk_usage);
+        report.sniffed += 1;
+    }
+
+    // Categories without files are left out of the report
+    report.categories = totals
+        .into_iter()
+        .filter(|(_, usage)| usage.files > 0)
+        .map(|(name, usage)| TypeUsage {
+            name: name.to_string(),
+            ..usage
+        })
+        .collect();
+    report.categories.sort_by(|a, b| {
+        size_mode
+            .select(b.size, b.disk_usage)
+            .cmp(&size_mode.select(a.size, a.disk_usage))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    report
+}
+
+/// The extension implied by a file's first bytes, if they're recognized
+fn sniff_file(path: &Path) -> io::Result<Option<&'static str>> {
+    let mut header = Vec::with_capacity(SNIFF_BYTES);
+    File::open(path)?
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut header)?;
+    Ok(sniff(&header))
+}
+
+fn sniff(header: &[u8]) -> Option<&'static str> {
+    SIGNATURES
+        .iter()
+        .find(|(offset, magic, _)| header.get(*offset..offset + magic.len()) == Some(*magic))
+        .map(|(_, _, extension)| *extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(extension: &str, files: i64, size: i64) -> ExtensionUsage {
+        ExtensionUsage {
+            extension: extension.to_string(),
+            files,
+            size,
+            disk_usage: size,
+        }
+    }
+
+    #[test]
+    fn test_classify_by_extension() {
+        let categories = vec![
+            Category::new("Video", &["mp4", "MKV"]),
+            Category::new("Logs", &[".log"]),
+            // An extension belongs to the first category listing it
+            Category::new("Text", &["log", "txt"]),
+        ];
+        let extensions = vec![
+            usage("mkv", 2, 5000),
+            usage("log", 10, 3000),
+            usage("mp4", 1, 1000),
+            usage("", 4, 400),
+            usage("dat", 1, 100),
+        ];
+
+        let report = classify(extensions, &categories, &[], SizeMode::Apparent);
+        let names: Vec<&str> = report.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Video", "Logs", OTHER]);
+        assert_eq!(report.categories[0].files, 3);
+        assert_eq!(report.categories[0].size, 6000);
+        assert_eq!(report.categories[2].size, 500);
+
+        let other: Vec<&str> = report
+            .extensions_in(OTHER)
+            .map(|e| e.extension.as_str())
+            .collect();
+        assert_eq!(other, vec!["", "dat"]);
+    }
+
+    #[test]
+    fn test_sniffed_files_move_out_of_other() {
+        let categories = vec![Category::new("Images", &["png"])];
+        let extensions = vec![usage("", 3, 900), usage("png", 1, 50)];
+
+        let sniffed = [(400, 400, "png"), (100, 100, "elf")];
+        let report = classify(extensions, &categories, &sniffed, SizeMode::Apparent);
+        assert_eq!(report.sniffed, 1);
+        assert_eq!(
+            report.categories,
+            vec![
+                TypeUsage {
+                    name: "Other".to_string(),
+                    files: 2,
+                    size: 500,
+                    disk_usage: 500,
+                },
+                TypeUsage {
+                    name: "Images".to_string(),
+                    files: 2,
+                    size: 450,
+                    disk_usage: 450,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sniff_signatures() {
+        assert_eq!(sniff(b"\x89PNG\r\n\x1a\nrest"), Some("png"));
+        assert_eq!(sniff(b"\x7fELF\x02\x01"), Some("elf"));
+        assert_eq!(sniff(b"\x00\x00\x00\x18ftypmp42"), Some("mp4"));
+        let mut tar = vec![0u8; 512];
+        tar[257..262].copy_from_slice(b"ustar");
+        assert_eq!(sniff(&tar), Some("tar"));
+        assert_eq!(sniff(b"plain text"), None);
+        assert_eq!(sniff(b""), None);
+    }
+
+    #[test]
+    fn test_default_categories_list_each_extension_once() {
+        let categories = default_categories();
+        let listed: usize = categories.iter().map(|c| c.extensions.len()).sum();
+        assert_eq!(lookup_table(&categories).len(), listed);
+        // Every recognized signature implies an extension some category lists
+        let table = lookup_table(&categories);
+        for (_, _, extension) in SIGNATURES {
+            assert!(table.contains_key(*extension), "{}", extension);
+        }
+    }
+}
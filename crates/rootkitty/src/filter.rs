@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::Path;
+
+/// Compiled exclude/include patterns for a scan
+///
+/// Patterns follow gitignore conventions:
+/// - `name` matches an entry with that name at any depth
+/// - `a/b` (or `/a`) is anchored to the scan root
+/// - a trailing `/` restricts the pattern to directories
+/// - `*`, `?`, `[...]` and `**` behave like in gitignore
+///
+/// An entry is excluded when it matches an exclude pattern and no include
+/// pattern. Excluded directories are not descended into.
+#[derive(Debug, Clone, Default)]
+pub struct ScanFilter {
+    exclude: PatternSet,
+    include: PatternSet,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PatternSet {
+    any: GlobSet,
+    dirs_only: GlobSet,
+    is_empty: bool,
+}
+
+impl ScanFilter {
+    pub fn new(exclude: &[String], include: &[String]) -> Result<Self> {
+        Ok(Self {
+            exclude: PatternSet::new(exclude)?,
+            include: PatternSet::new(include)?,
+        })
+    }
+
+    /// Returns true if no exclude patterns are configured
+    pub fn is_empty(&self) -> bool {
+        self.exclude.is_empty
+    }
+
+    /// Check whether an entry should be excluded
+    /// `relative_path` is the entry's path relative to the scan root
+    pub fn is_excluded(&self, relative_path: &Path, is_dir: bool) -> bool {
+        if self.exclude.is_empty || relative_path.as_os_str().is_empty() {
+            return false;
+        }
+        self.exclude.matches(relative_path, is_dir) && !self.include.matches(relative_path, is_dir)
+    }
+}
+
+impl PatternSet {
+    fn new(patterns: &[String]) -> Result<Self> {
+        let mut any = GlobSetBuilder::new();
+        let mut dirs_only = GlobSetBuilder::new();
+        let mut is_empty = true;
+
+        for raw in patterns {
+            let pattern = raw.trim();
+            if pattern.is_empty() {
+                continue;
+            }
+
+            let (pattern, dir_only) = match pattern.strip_suffix('/') {
+                Some(p) => (p, true),
+                None => (pattern, false),
+            };
+
+            // Patterns containing a separator are anchored to the scan root,
+            // bare names match at any depth (same as gitignore)
+            let glob_str = if let Some(anchored) = pattern.strip_prefix('/') {
+                anchored.to_string()
+            } else if pattern.contains('/') {
+                pattern.to_string()
+            } else {
+                format!("**/{}", pattern)
+            };
+
+            let glob = GlobBuilder::new(&glob_str)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("Invalid pattern: {}", raw))?;
+
+            if dir_only {
+                dirs_only.add(glob);
+            } else {
+                any.add(glob);
+            }
+            is_empty = false;
+        }
+
+        Ok(Self {
+            any: any.build()?,
+            dirs_only: dirs_only.build()?,
+            is_empty,
+        })
+    }
+
+    fn matches(&self, relative_path: &Path, is_dir: bool) -> bool {
+        if self.is_empty {
+            return false;
+        }
+        self.any.is_match(relative_path) || (is_dir && self.dirs_only.is_match(relative_path))
+    }
+}
+
+/// Parse a gitignore-style pattern file into (exclude, include) pattern lists
+///
+/// Blank lines and lines starting with `#` are ignored, and lines starting
+/// with `!` re-include entries that would otherwise be excluded.
+pub fn parse_pattern_file(contents: &str) -> (Vec<String>, Vec<String>) {
+    let mut exclude = Vec::new();
+    let mut include = Vec::new();
+
+    for line in contents.lines() {
+        let line = line.trim_end();
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(negated) = line.strip_prefix('!') {
+            include.push(negated.to_string());
+        } else {
+            exclude.push(line.to_string());
+        }
+    }
+
+    (exclude, include)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(exclude: &[&str], include: &[&str]) -> ScanFilter {
+        let exclude: Vec<String> = exclude.iter().map(|s| s.to_string()).collect();
+        let include: Vec<String> = include.iter().map(|s| s.to_string()).collect();
+        ScanFilter::new(&exclude, &include).unwrap()
+    }
+
+    #[test]
+    fn test_bare_name_matches_at_any_depth() {
+        let f = filter(&["node_modules"], &[]);
+        assert!(f.is_excluded(Path::new("node_modules"), true));
+        assert!(f.is_excluded(Path::new("web/app/node_modules"), true));
+        assert!(!f.is_excluded(Path::new("web/node_modules_backup"), true));
+    }
+
+    #[test]
+    fn test_anchored_patterns() {
+        let f = filter(&["/proc", ".git/objects"], &[]);
+        assert!(f.is_excluded(Path::new("proc"), true));
+        assert!(!f.is_excluded(Path::new("home/proc"), true));
+        assert!(f.is_excluded(Path::new(".git/objects"), true));
+        assert!(!f.is_excluded(Path::new("sub/.git/objects"), true));
+    }
+
+    #[test]
+    fn test_wildcards() {
+        let f = filter(&["*.log", "**/.snapshots"], &[]);
+        assert!(f.is_excluded(Path::new("var/app.log"), false));
+        assert!(!f.is_excluded(Path::new("var/app.log.gz"), false));
+        assert!(f.is_excluded(Path::new("a/b/.snapshots"), true));
+    }
+
+    #[test]
+    fn test_dir_only_patterns() {
+        let f = filter(&["build/"], &[]);
+        assert!(f.is_excluded(Path::new("build"), true));
+        assert!(!f.is_excluded(Path::new("build"), false));
+    }
+
+    #[test]
+    fn test_include_overrides_exclude() {
+        let f = filter(&["*.log"], &["keep.log"]);
+        assert!(f.is_excluded(Path::new("a.log"), false));
+        assert!(!f.is_excluded(Path::new("logs/keep.log"), false));
+    }
+
+    #[test]
+    fn test_root_is_never_excluded() {
+        let f = filter(&["**"], &[]);
+        assert!(!f.is_excluded(Path::new(""), true));
+    }
+
+    #[test]
+    fn test_parse_pattern_file() {
+        let (exclude, include) =
+            parse_pattern_file("# comment\n\n.git/objects\n!important.log\n*.log\n");
+        assert_eq!(exclude, vec![".git/objects", "*.log"]);
+        assert_eq!(include, vec!["important.log"]);
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_an_error() {
+        assert!(ScanFilter::new(&["[".to_string()], &[]).is_err());
+    }
+}
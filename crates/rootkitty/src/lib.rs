@@ -1,6 +1,16 @@
 // Library exports for testing and potential reuse
 
+pub mod archive;
 pub mod db;
+pub mod dupes;
+pub mod filetypes;
+pub mod filter;
+pub mod ncdu;
+pub mod owners;
+pub mod remote;
 pub mod scanner;
 pub mod settings;
+pub mod source;
+pub mod throttle;
 pub mod ui;
+pub mod workers;
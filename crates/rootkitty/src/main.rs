@@ -1,15 +1,29 @@
+mod archive;
 mod db;
+mod dupes;
+mod filetypes;
+mod filter;
+mod ncdu;
+mod owners;
+mod remote;
 mod scanner;
 mod settings;
+mod source;
+mod throttle;
 mod ui;
-
-use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
-
-use crate::db::{ActorMessage, Database, DatabaseActor};
-use crate::scanner::{ProgressUpdate, Scanner};
-use crate::settings::Settings;
+mod workers;
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::db::{ActorMessage, Database, DatabaseActor, OwnerGrouping, Scan, SizeMode};
+use crate::owners::OwnerNames;
+use crate::remote::{AgentRequest, RemoteTarget};
+use crate::scanner::{HardLinkPolicy, ProgressUpdate, ScanOptions, Scanner, ScannerImpl};
+use crate::settings::{Settings, ThrottleSettings};
+use crate::source::{DiskSource, FixtureSource, FsSource, SyntheticSource, TreeShape};
 use crate::ui::App;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
@@ -35,26 +49,128 @@ struct Cli {
 enum Commands {
     /// Scan a directory and store results
     Scan {
-        /// Path to scan
-        path: PathBuf,
+        /// Path to scan (with --from-stdin, the root of the listed paths; defaults to
+        /// their common ancestor), or ssh://[user@]host/path to scan another machine.
+        /// Several paths are scanned as one scan below their common ancestor.
+        #[arg(required_unless_present = "from_stdin")]
+        paths: Vec<PathBuf>,
         /// Follow symbolic links during scanning
         #[arg(short = 'L', long)]
         follow_symlinks: bool,
+        /// Skip paths matching a gitignore-style pattern (repeatable)
+        #[arg(long, value_name = "PATTERN")]
+        exclude: Vec<String>,
+        /// Read exclude patterns from a file (one per line, `!` re-includes)
+        #[arg(long, value_name = "FILE")]
+        exclude_from: Vec<PathBuf>,
+        /// Re-include paths matched by an exclude pattern (repeatable)
+        #[arg(long, value_name = "PATTERN")]
+        include: Vec<String>,
+        /// Don't descend into directories on other filesystems
+        #[arg(short = 'x', long)]
+        one_file_system: bool,
+        /// Only store entries down to this depth (deeper ones still count towards totals)
+        #[arg(long, value_name = "N")]
+        max_depth: Option<usize>,
+        /// How files with several hard links count towards totals: once (at the
+        /// first path, like du), every (at each path, like du -l) or proportional
+        #[arg(long, value_name = "POLICY")]
+        hard_links: Option<HardLinkPolicy>,
+        /// List the members of .tar, .tar.gz, .tar.zst and .zip files as entries below them
+        #[arg(long)]
+        archives: bool,
+        /// Copy unchanged directories from an earlier scan of the same path
+        #[arg(long, value_name = "SCAN_ID", conflicts_with = "incremental")]
+        incremental_from: Option<i64>,
+        /// Like --incremental-from, using the latest completed scan of the same path
+        #[arg(long)]
+        incremental: bool,
+        /// Record only the paths listed on stdin, one per line (e.g. from `find` or `fd`)
+        #[arg(long, conflicts_with_all = [
+            "exclude", "exclude_from", "include", "one_file_system", "incremental", "incremental_from",
+        ])]
+        from_stdin: bool,
+        /// Paths on stdin are separated by NUL bytes (`find -print0`, `fd -0`)
+        #[arg(short = '0', long, requires = "from_stdin")]
+        null: bool,
+        /// Command that starts the agent for an ssh:// path (default from settings:
+        /// `ssh {host} rootkitty agent scan {path}`)
+        #[arg(long, value_name = "COMMAND")]
+        remote_command: Option<String>,
+        /// Make at most this many metadata calls per second, to leave the disk to other work
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        max_stats_per_sec: Option<u32>,
+        /// Scan at idle I/O priority, so other processes get the disk first (Linux only)
+        #[arg(long)]
+        io_nice: bool,
+        /// Number of scan workers (default from settings; 0 for one per core)
+        #[arg(long, value_name = "N")]
+        threads: Option<usize>,
+        /// How to walk the tree: two_phase, hybrid, custom or walkdir
+        #[arg(long = "impl", value_name = "IMPL")]
+        implementation: Option<ScannerImpl>,
+    },
+    /// Resume a paused scan
+    Resume {
+        /// Scan ID
+        scan_id: i64,
+        /// Only resume this directory; the rest of the scan stays paused
+        #[arg(long, value_name = "PATH")]
+        subtree: Option<PathBuf>,
     },
-    /// Run a demo scan (simulated, no real filesystem access)
+    /// Run a demo scan of a made-up tree (in-memory database, no real filesystem access)
+    ///
+    /// The tree is generated as it is scanned, so large shapes work for load testing.
     DemoScan {
         /// Follow symbolic links during scanning
         #[arg(short = 'L', long)]
         follow_symlinks: bool,
+        /// Levels of directories below the root
+        #[arg(long, default_value_t = 3)]
+        depth: usize,
+        /// Subdirectories in each directory above the bottom level
+        #[arg(long, default_value_t = 5)]
+        fanout: usize,
+        /// Files in every directory
+        #[arg(long, default_value_t = 20)]
+        files: usize,
+        /// Time each simulated metadata call takes, in microseconds
+        #[arg(long, default_value_t = 200, value_name = "US")]
+        latency_us: u64,
+        /// Scan a tree recorded from disk instead of making one up
+        #[arg(long, value_name = "FILE")]
+        fixture: Option<PathBuf>,
+        /// Record PATH from disk into the --fixture file first
+        #[arg(long, value_name = "PATH", requires = "fixture")]
+        record: Option<PathBuf>,
+        /// Number of scan workers (0 for one per core)
+        #[arg(long, default_value_t = 0, value_name = "N")]
+        threads: usize,
+        /// How to walk the tree: two_phase, hybrid, custom or walkdir
+        #[arg(long = "impl", value_name = "IMPL", default_value = "hybrid")]
+        implementation: ScannerImpl,
     },
     /// Launch the interactive TUI
     Browse,
     /// List all scans
-    List,
+    List {
+        /// Report allocated disk usage instead of apparent size
+        #[arg(short = 'u', long)]
+        disk_usage: bool,
+    },
     /// Show details of a specific scan
     Show {
         /// Scan ID
         scan_id: i64,
+        /// Report allocated disk usage instead of apparent size
+        #[arg(short = 'u', long)]
+        disk_usage: bool,
+        /// Include mode, link count, owner and inode for each entry (like `ls -li`)
+        #[arg(short = 'l', long)]
+        long: bool,
+        /// List the paths that could not be read
+        #[arg(short, long)]
+        errors: bool,
     },
     /// Compare two scans
     Diff {
@@ -62,15 +178,112 @@ enum Commands {
         scan_id_1: i64,
         /// Second scan ID
         scan_id_2: i64,
+        /// Report allocated disk usage instead of apparent size
+        #[arg(short = 'u', long)]
+        disk_usage: bool,
+    },
+    /// Show how much space each user or group owns in a scan
+    Owners {
+        /// Scan ID
+        scan_id: i64,
+        /// Aggregate by group instead of by user
+        #[arg(short, long)]
+        group: bool,
+        /// List the largest directories of one user (or group, with --group)
+        #[arg(short, long, value_name = "NAME|ID")]
+        owner: Option<String>,
+        /// Number of directories to list with --owner
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: i64,
+        /// Report allocated disk usage instead of apparent size
+        #[arg(short = 'u', long)]
+        disk_usage: bool,
+    },
+    /// Find files with identical content in a scan
+    Dupes {
+        /// Scan ID
+        scan_id: i64,
+        /// Ignore files smaller than this many bytes
+        #[arg(long, value_name = "BYTES", default_value_t = dupes::DEFAULT_MIN_SIZE)]
+        min_size: u64,
+        /// Number of groups to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+        /// Mark every copy but the first of each group for cleanup
+        #[arg(long)]
+        mark: bool,
+    },
+    /// Break a scan down by file type and extension
+    Types {
+        /// Scan ID
+        scan_id: i64,
+        /// Number of extensions to list
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+        /// Identify up to N of the largest files with unknown extensions by their content
+        #[arg(long, value_name = "N")]
+        sniff: Option<usize>,
+        /// Report allocated disk usage instead of apparent size
+        #[arg(short = 'u', long)]
+        disk_usage: bool,
+    },
+    /// Import a scan exported by another disk usage tool
+    Import {
+        /// Export file to read (`-` for stdin)
+        file: PathBuf,
+        /// Format of the export
+        #[arg(long, value_enum, default_value_t = ExchangeFormat::Ncdu)]
+        format: ExchangeFormat,
+    },
+    /// Export a scan for another disk usage tool
+    Export {
+        /// Scan ID
+        scan_id: i64,
+        /// Format to write
+        #[arg(long, value_enum, default_value_t = ExchangeFormat::Ncdu)]
+        format: ExchangeFormat,
+        /// File to write (default: stdout)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
     },
     /// Compact the database (reclaim disk space from deleted scans)
     Compact,
+    /// Run on a remote machine by `scan ssh://...`
+    #[command(hide = true)]
+    Agent {
+        #[command(subcommand)]
+        command: AgentCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum AgentCommand {
+    /// Scan a path and stream the entries to stdout, one JSON record per line
+    Scan {
+        /// Path to scan
+        path: PathBuf,
+    },
+}
+
+/// Formats shared with other disk usage tools
+#[derive(Clone, Copy, ValueEnum)]
+enum ExchangeFormat {
+    /// ncdu's JSON export (`ncdu -o`)
+    Ncdu,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    // The agent streams to stdout and never touches a database
+    if let Some(Commands::Agent {
+        command: AgentCommand::Scan { path },
+    }) = &cli.command
+    {
+        return remote::run_agent(path).await;
+    }
+
     let db_path = shellexpand::tilde(&cli.db).to_string();
     let db = Database::new(&db_path)
         .await
@@ -79,23 +292,55 @@ async fn main() -> Result<()> {
     match cli.command {
         None => {
             // Default to TUI if no command provided
-            // Load settings
-            let settings_path = if let Some(config) = &cli.config {
-                PathBuf::from(shellexpand::tilde(config).to_string())
-            } else {
-                Settings::default_path()
-            };
-
-            let settings = Settings::load(&settings_path).context("Failed to load settings")?;
+            let (settings, settings_path) = load_settings(&cli.config)?;
 
             let mut app = App::new(db, settings, settings_path, PathBuf::from(&db_path));
             app.run().await?;
         }
-        Some(Commands::DemoScan { follow_symlinks }) => {
-            println!("Running demo scan (in-memory database)...");
+        Some(Commands::DemoScan {
+            follow_symlinks,
+            depth,
+            fanout,
+            files,
+            latency_us,
+            fixture,
+            record,
+            threads,
+            implementation,
+        }) => {
+            let (demo_path, source): (PathBuf, Arc<dyn FsSource>) = match fixture {
+                Some(fixture) => {
+                    if let Some(path) = record {
+                        let path = std::path::absolute(&path)?;
+                        println!("Recording {} into {}...", path.display(), fixture.display());
+                        FixtureSource::record(&DiskSource, &path)?.save(&fixture)?;
+                    }
+                    let source = FixtureSource::load(&fixture)?;
+                    println!("Replaying {} (in-memory database)...", fixture.display());
+                    (source.root().to_path_buf(), Arc::new(source))
+                }
+                None => {
+                    let shape = TreeShape {
+                        depth,
+                        fanout,
+                        files_per_dir: files,
+                        latency: std::time::Duration::from_micros(latency_us),
+                        ..Default::default()
+                    };
+                    println!(
+                        "Running demo scan of {} files in {} directories (in-memory database)...",
+                        shape.files(),
+                        shape.dirs()
+                    );
+                    let demo_path = PathBuf::from("/demo");
+                    let source = SyntheticSource::new(&demo_path, shape);
+                    (demo_path, Arc::new(source))
+                }
+            };
+            print_workers(threads, implementation);
+
             // Use in-memory database for demo
             let demo_db = Database::new(":memory:").await?;
-            let demo_path = PathBuf::from("/demo");
             let scan_id = demo_db.create_scan(&demo_path).await?;
 
             // Create channel for streaming entries to database actor
@@ -111,47 +356,7 @@ async fn main() -> Result<()> {
             // Spawn progress monitor task
             let progress_handle = tokio::spawn(async move {
                 while let Some(progress) = progress_rx.recv().await {
-                    // Clear previous output (up to 5 lines)
-                    print!("\r\x1B[J"); // Clear from cursor to end of screen
-
-                    // Show summary line
-                    print!("Progress: {} entries scanned", progress.files_scanned);
-
-                    if progress.active_workers > 0 {
-                        print!(" | {} parallel workers", progress.active_workers);
-                    }
-                    println!();
-
-                    // Show active directories (limit to top 4 for readability)
-                    let max_display = 4;
-                    if !progress.active_dirs.is_empty() {
-                        for (dir_path, done, total) in progress.active_dirs.iter().take(max_display)
-                        {
-                            let percentage = if *total > 0 {
-                                (*done as f64 / *total as f64 * 100.0) as usize
-                            } else {
-                                0
-                            };
-
-                            // Show intelligently truncated path
-                            let display_path = smart_truncate_path(dir_path, 70);
-
-                            println!("  [{}/{}] {:>3}% {}", done, total, percentage, display_path);
-                        }
-
-                        if progress.active_dirs.len() > max_display {
-                            println!(
-                                "  ... and {} more directories",
-                                progress.active_dirs.len() - max_display
-                            );
-                        }
-                    } else if !progress.current_path.is_empty() {
-                        // If no per-directory progress, just show current path
-                        let display_path = smart_truncate_path(&progress.current_path, 70);
-                        println!("  Scanning: {}", display_path);
-                    }
-
-                    std::io::Write::flush(&mut std::io::stdout()).ok();
+                    print_progress(&progress);
                 }
             });
 
@@ -160,13 +365,16 @@ async fn main() -> Result<()> {
             let cancelled = Arc::new(AtomicBool::new(false));
             let cancelled_clone = cancelled.clone();
             let scan_result = tokio::task::spawn_blocking(move || {
-                let scanner = Scanner::with_sender_demo(
+                let mut scanner = Scanner::with_sender(
                     &demo_path,
                     tx_clone,
                     Some(progress_tx),
                     cancelled_clone,
                     follow_symlinks,
                 );
+                scanner.set_source(source);
+                scanner.set_threads(threads);
+                scanner.set_implementation(implementation);
                 scanner.scan()
             })
             .await?;
@@ -182,6 +390,13 @@ async fn main() -> Result<()> {
             println!("  Files: {}", stats.total_files);
             println!("  Directories: {}", stats.total_dirs);
             println!("  Total size: {} bytes", stats.total_size);
+            println!("  Disk usage: {} bytes", stats.total_disk_usage);
+            if stats.error_count > 0 {
+                println!(
+                    "  Unreadable paths: {} (not included in totals)",
+                    stats.error_count
+                );
+            }
 
             // Signal actor to shutdown and wait for it to finish
             tx.send(ActorMessage::Shutdown).await?;
@@ -194,11 +409,203 @@ async fn main() -> Result<()> {
             println!("Demo scan {} saved to in-memory database", scan_id);
         }
         Some(Commands::Scan {
-            path,
+            paths,
             follow_symlinks,
+            exclude,
+            exclude_from,
+            include,
+            one_file_system,
+            max_depth,
+            hard_links,
+            archives,
+            incremental_from,
+            incremental,
+            from_stdin,
+            null,
+            remote_command,
+            max_stats_per_sec,
+            io_nice,
+            threads,
+            implementation,
         }) => {
-            println!("Scanning: {}", path.display());
-            let scan_id = db.create_scan(&path).await?;
+            // Settings provide the defaults, command-line flags add to them
+            let (settings, _) = load_settings(&cli.config)?;
+            let mut options = ScanOptions::from_settings(&settings.scan);
+            options.follow_symlinks |= follow_symlinks;
+            options.one_file_system |= one_file_system;
+            options.max_depth = max_depth;
+            options.archives |= archives;
+            if let Some(hard_links) = hard_links {
+                options.hard_links = hard_links;
+            }
+            options.exclude.extend(exclude);
+            options.include.extend(include);
+            for file in &exclude_from {
+                let contents = std::fs::read_to_string(file)
+                    .with_context(|| format!("Failed to read {}", file.display()))?;
+                let (file_exclude, file_include) = filter::parse_pattern_file(&contents);
+                options.exclude.extend(file_exclude);
+                options.include.extend(file_include);
+            }
+
+            // Validate patterns before creating the scan record
+            filter::ScanFilter::new(&options.exclude, &options.include)?;
+
+            let mut throttle = settings.scan.throttle.clone();
+            throttle.io_nice |= io_nice;
+            if max_stats_per_sec.is_some() {
+                throttle.max_stats_per_sec = max_stats_per_sec;
+            }
+            let threads = threads.unwrap_or(settings.scan.threads);
+            let implementation = implementation.unwrap_or(settings.scan.implementation);
+
+            if let Some(target) = paths
+                .iter()
+                .find_map(|p| p.to_str().and_then(RemoteTarget::parse))
+            {
+                if paths.len() > 1 {
+                    bail!("Remote scans have a single root");
+                }
+                if incremental || incremental_from.is_some() {
+                    bail!("Remote scans can't be incremental");
+                }
+                let command = remote_command.unwrap_or(settings.scan.remote_command);
+                let request = AgentRequest {
+                    options,
+                    throttle,
+                    threads,
+                    implementation,
+                };
+                return scan_remote(&db, &target?, &command, &request).await;
+            }
+
+            // A listed scan records what it's given, so walk options don't apply
+            let listed = if from_stdin {
+                let mut input = Vec::new();
+                std::io::stdin()
+                    .read_to_end(&mut input)
+                    .context("Failed to read paths from stdin")?;
+                let paths = scanner::parse_path_list(&input, null)?;
+                if paths.is_empty() {
+                    bail!("No paths on stdin");
+                }
+                options.exclude.clear();
+                options.include.clear();
+                options.one_file_system = false;
+                options.from_list = true;
+                Some(paths)
+            } else {
+                None
+            };
+            // Several roots are scanned below their common ancestor
+            let roots = if paths.len() > 1 {
+                if listed.is_some() {
+                    bail!("--from-stdin takes a single root");
+                }
+                if incremental || incremental_from.is_some() {
+                    bail!("Multi-root scans can't be incremental");
+                }
+                let (top, roots) = scanner::resolve_roots(&paths)?;
+                Some((top, roots)).filter(|(_, roots)| roots.len() > 1)
+            } else {
+                None
+            };
+            let path = match (paths.into_iter().next(), &listed, &roots) {
+                (_, _, Some((top, _))) => top.clone(),
+                (Some(path), _, None) => path,
+                (None, Some(paths), None) => scanner::common_ancestor(paths)
+                    .context("Listed paths have no common ancestor")?,
+                (None, None, None) => bail!("No path to scan"),
+            };
+            let roots = roots.map(|(_, roots)| roots);
+
+            match &roots {
+                Some(roots) => {
+                    println!("Scanning {} roots below {}:", roots.len(), path.display());
+                    for root in roots {
+                        println!("  {}", root.display());
+                    }
+                }
+                None => println!("Scanning: {}", path.display()),
+            }
+            if let Some(paths) = &listed {
+                println!("  Recording {} listed paths", paths.len());
+            }
+            if !options.exclude.is_empty() {
+                println!("  Excluding: {}", options.exclude.join(", "));
+            }
+            if let Some(max_depth) = options.max_depth {
+                println!("  Storing entries down to depth {}", max_depth);
+            }
+            if options.hard_links != HardLinkPolicy::default() {
+                println!("  Hard links: {}", options.hard_links.display_name());
+            }
+            if options.archives {
+                println!("  Listing archive members");
+            }
+            print_workers(threads, implementation);
+            print_throttle(&throttle);
+            if throttle.io_nice {
+                if let Err(e) = throttle::lower_io_priority() {
+                    println!("  Warning: {:#}", e);
+                }
+            }
+
+            // Pick the scan to copy unchanged directories from
+            let base_scan = match incremental_from {
+                Some(base_id) => {
+                    let scan = db
+                        .get_scan(base_id)
+                        .await?
+                        .with_context(|| format!("Scan {} not found", base_id))?;
+                    check_incremental_base(&scan, &path, &options)?;
+                    Some(scan)
+                }
+                None if incremental => match db.get_latest_completed_scan(&path).await? {
+                    Some(scan) if check_incremental_base(&scan, &path, &options).is_ok() => {
+                        Some(scan)
+                    }
+                    Some(scan) => {
+                        println!(
+                            "  Scan {} used different options; running a full scan",
+                            scan.id
+                        );
+                        None
+                    }
+                    None => {
+                        println!("  No completed scan of this path yet; running a full scan");
+                        None
+                    }
+                },
+                None => None,
+            };
+            let previous = match &base_scan {
+                Some(scan) => {
+                    println!("  Reusing unchanged directories from scan {}", scan.id);
+                    Some(db.load_previous_scan(scan.id).await?)
+                }
+                None => None,
+            };
+
+            // The last completed scan of this path predicts how long this one will take
+            // (a listed scan counts its entries up front instead)
+            let prediction = db
+                .get_latest_completed_scan(&path)
+                .await?
+                .filter(|_| listed.is_none() && roots.is_none())
+                .and_then(|scan| Some((scan.entry_count(), scan.duration()?)));
+            if let Some((entries, duration)) = prediction {
+                println!(
+                    "  Last scan: {} entries in {}",
+                    entries,
+                    format_duration(duration)
+                );
+            }
+
+            let scan_id = db.create_scan_with_options(&path, &options).await?;
+            if let Some(roots) = &roots {
+                db.set_scan_roots(scan_id, roots).await?;
+            }
 
             // Create channel for streaming entries to database actor
             let (tx, rx) = mpsc::channel(100);
@@ -213,47 +620,7 @@ async fn main() -> Result<()> {
             // Spawn progress monitor task
             let progress_handle = tokio::spawn(async move {
                 while let Some(progress) = progress_rx.recv().await {
-                    // Clear previous output (up to 5 lines)
-                    print!("\r\x1B[J"); // Clear from cursor to end of screen
-
-                    // Show summary line
-                    print!("Progress: {} entries scanned", progress.files_scanned);
-
-                    if progress.active_workers > 0 {
-                        print!(" | {} parallel workers", progress.active_workers);
-                    }
-                    println!();
-
-                    // Show active directories (limit to top 4 for readability)
-                    let max_display = 4;
-                    if !progress.active_dirs.is_empty() {
-                        for (dir_path, done, total) in progress.active_dirs.iter().take(max_display)
-                        {
-                            let percentage = if *total > 0 {
-                                (*done as f64 / *total as f64 * 100.0) as usize
-                            } else {
-                                0
-                            };
-
-                            // Show intelligently truncated path
-                            let display_path = smart_truncate_path(dir_path, 70);
-
-                            println!("  [{}/{}] {:>3}% {}", done, total, percentage, display_path);
-                        }
-
-                        if progress.active_dirs.len() > max_display {
-                            println!(
-                                "  ... and {} more directories",
-                                progress.active_dirs.len() - max_display
-                            );
-                        }
-                    } else if !progress.current_path.is_empty() {
-                        // If no per-directory progress, just show current path
-                        let display_path = smart_truncate_path(&progress.current_path, 70);
-                        println!("  Scanning: {}", display_path);
-                    }
-
-                    std::io::Write::flush(&mut std::io::stdout()).ok();
+                    print_progress(&progress);
                 }
             });
 
@@ -263,14 +630,29 @@ async fn main() -> Result<()> {
             let cancelled = Arc::new(AtomicBool::new(false));
             let cancelled_clone = cancelled.clone();
             let scan_result = tokio::task::spawn_blocking(move || {
-                let scanner = Scanner::with_sender(
+                let mut scanner = Scanner::with_options(
                     &path_clone,
                     tx_clone,
                     Some(progress_tx),
                     cancelled_clone,
-                    follow_symlinks,
-                );
-                scanner.scan()
+                    &options,
+                )?;
+                if let Some(previous) = previous {
+                    scanner.reuse_unchanged(previous);
+                }
+                if let Some((entries, duration)) = prediction {
+                    scanner.predict_from_previous(entries, duration);
+                }
+                if let Some(limit) = throttle.stat_limit() {
+                    scanner.limit_stats(limit);
+                }
+                scanner.set_threads(threads);
+                scanner.set_implementation(implementation);
+                match (listed, roots) {
+                    (Some(paths), _) => scanner.scan_listed(paths),
+                    (None, Some(roots)) => scanner.scan_roots(&roots),
+                    (None, None) => scanner.scan(),
+                }
             })
             .await?;
 
@@ -285,6 +667,17 @@ async fn main() -> Result<()> {
             println!("  Files: {}", stats.total_files);
             println!("  Directories: {}", stats.total_dirs);
             println!("  Total size: {} bytes", stats.total_size);
+            println!("  Disk usage: {} bytes", stats.total_disk_usage);
+            println!("  Duration: {}", format_duration(stats.duration));
+            if stats.error_count > 0 {
+                println!(
+                    "  Unreadable paths: {} (not included in totals)",
+                    stats.error_count
+                );
+            }
+            if base_scan.is_some() {
+                println!("  Reused directories: {}", stats.reused_dirs);
+            }
 
             // Signal actor to shutdown and wait for it to finish
             tx.send(ActorMessage::Shutdown).await?;
@@ -296,62 +689,326 @@ async fn main() -> Result<()> {
             db.complete_scan(scan_id, &stats).await?;
             println!("Scan {} saved to database", scan_id);
         }
-        Some(Commands::Browse) => {
-            // Load settings
-            let settings_path = if let Some(config) = &cli.config {
-                PathBuf::from(shellexpand::tilde(config).to_string())
-            } else {
-                Settings::default_path()
+        Some(Commands::Resume { scan_id, subtree }) => {
+            let (settings, _) = load_settings(&cli.config)?;
+            let scan = db
+                .get_scan(scan_id)
+                .await?
+                .with_context(|| format!("Scan {} not found", scan_id))?;
+            if let Some(host) = &scan.host {
+                bail!(
+                    "Scan {} ran on {}; run it again with 'rootkitty scan'",
+                    scan_id,
+                    host
+                );
+            }
+            if scan.status != "paused" {
+                bail!("Scan {} is not paused", scan_id);
+            }
+
+            // Resume with the options the scan was started with, so the same
+            // paths are excluded (scans predating stored options use current settings)
+            let options = scan
+                .options
+                .clone()
+                .unwrap_or_else(|| ScanOptions::from_settings(&settings.scan));
+            let hard_links = options.hard_links;
+            let path = PathBuf::from(&scan.root_path);
+            let roots: Vec<PathBuf> = scan.roots.iter().map(PathBuf::from).collect();
+
+            // The subtree, and the root it lies in; resuming the root resumes everything
+            let subtree = match subtree {
+                Some(subtree) => {
+                    let subtree = subtree
+                        .canonicalize()
+                        .with_context(|| format!("Failed to resolve {}", subtree.display()))?;
+                    let root = if roots.is_empty() {
+                        Some(&path).filter(|root| subtree.starts_with(root))
+                    } else {
+                        roots.iter().find(|root| subtree.starts_with(root))
+                    };
+                    let Some(root) = root else {
+                        bail!("{} is not part of scan {}", subtree.display(), scan_id);
+                    };
+                    Some((root.clone(), subtree)).filter(|(_, subtree)| *subtree != path)
+                }
+                None => None,
+            };
+
+            let resume = match &subtree {
+                Some((_, subtree)) => {
+                    println!("Resuming {} of scan {}", subtree.display(), scan_id);
+                    db.get_resume_state(scan_id, Some(&subtree.display().to_string()))
+                        .await?
+                }
+                None => {
+                    println!("Resuming scan {}: {}", scan_id, scan.location());
+                    db.get_resume_state(scan_id, None).await?
+                }
             };
+            if !resume.is_empty() {
+                println!(
+                    "  Skipping {} finished directories and already-scanned paths",
+                    resume.len()
+                );
+            }
 
-            let settings = Settings::load(&settings_path).context("Failed to load settings")?;
+            let throttle = settings.scan.throttle.clone();
+            let threads = settings.scan.threads;
+            let implementation = settings.scan.implementation;
+            print_workers(threads, implementation);
+            print_throttle(&throttle);
+            if throttle.io_nice {
+                if let Err(e) = throttle::lower_io_priority() {
+                    println!("  Warning: {:#}", e);
+                }
+            }
+
+            // Entries recorded from here on belong to this run
+            let last_entry_id = db.last_entry_id(scan_id).await?;
+
+            // Create channel for streaming entries to database actor
+            let (tx, rx) = mpsc::channel(100);
+
+            // Create channel for progress updates
+            let (progress_tx, mut progress_rx) = mpsc::unbounded_channel::<ProgressUpdate>();
+
+            // Spawn database actor to handle inserts
+            let actor = DatabaseActor::new(db.clone(), scan_id, rx);
+            let actor_handle = tokio::spawn(async move { actor.run().await });
+
+            // Spawn progress monitor task
+            let progress_handle = tokio::spawn(async move {
+                while let Some(progress) = progress_rx.recv().await {
+                    print_progress(&progress);
+                }
+            });
+
+            // Scan with streaming (runs in blocking thread to not block tokio runtime)
+            let tx_clone = tx.clone();
+            let subtree_clone = subtree.clone();
+            let cancelled = Arc::new(AtomicBool::new(false));
+            let cancelled_clone = cancelled.clone();
+            let scan_result = tokio::task::spawn_blocking(move || {
+                let mut scanner = Scanner::with_options(
+                    &path,
+                    tx_clone,
+                    Some(progress_tx),
+                    cancelled_clone,
+                    &options,
+                )?;
+                if let Some(limit) = throttle.stat_limit() {
+                    scanner.limit_stats(limit);
+                }
+                scanner.set_threads(threads);
+                scanner.set_implementation(implementation);
+                match subtree_clone {
+                    Some((root, subtree)) => scanner.scan_subtree_resuming(&root, &subtree, resume),
+                    None if roots.is_empty() => scanner.scan_resuming(resume),
+                    None => scanner.scan_roots_resuming(&roots, resume),
+                }
+            })
+            .await?;
+
+            let (_, stats) = scan_result?;
+
+            // Wait for progress task to finish
+            drop(progress_handle);
+
+            // Signal actor to shutdown and wait for it to finish
+            tx.send(ActorMessage::Shutdown).await?;
+            drop(tx);
+            actor_handle.await??;
+
+            // Directories this run recorded only count what it found
+            let resumed = match &subtree {
+                Some((_, subtree)) => subtree.display().to_string(),
+                None => scan.root_path.clone(),
+            };
+            db.recompute_dir_sizes(scan_id, &resumed, last_entry_id, hard_links)
+                .await?;
+
+            // Clear progress output and show completion
+            print!("\r\x1B[J"); // Clear from cursor to end of screen
+            match &subtree {
+                Some(_) => {
+                    // The run only saw this branch; the scan's totals cover all of it
+                    let mut totals = db.calculate_scan_stats(scan_id).await?;
+                    totals.duration = stats.duration;
+                    db.pause_scan(scan_id, &totals).await?;
+                    println!("Resumed {}", resumed);
+                }
+                None => {
+                    db.complete_scan(scan_id, &stats).await?;
+                    println!("Scan complete!");
+                }
+            }
+            println!("  Files: {}", stats.total_files);
+            println!("  Directories: {}", stats.total_dirs);
+            println!("  Total size: {} bytes", stats.total_size);
+            println!("  Duration: {}", format_duration(stats.duration));
+            if stats.error_count > 0 {
+                println!(
+                    "  Unreadable paths: {} (not included in totals)",
+                    stats.error_count
+                );
+            }
+            if subtree.is_some() {
+                println!(
+                    "The rest of scan {} is still paused; run 'rootkitty resume {}' to finish it",
+                    scan_id, scan_id
+                );
+            } else {
+                println!("Scan {} saved to database", scan_id);
+            }
+        }
+        Some(Commands::Browse) => {
+            let (settings, settings_path) = load_settings(&cli.config)?;
 
             let mut app = App::new(db, settings, settings_path, PathBuf::from(&db_path));
             app.run().await?;
         }
-        Some(Commands::List) => {
+        Some(Commands::List { disk_usage }) => {
+            let size_mode = size_mode(disk_usage);
             let scans = db.list_scans().await?;
             if scans.is_empty() {
                 println!("No scans found. Run 'rootkitty scan <path>' to create one.");
             } else {
+                let size_header = match size_mode {
+                    SizeMode::Apparent => "Size (MB)",
+                    SizeMode::DiskUsage => "Disk (MB)",
+                };
                 println!(
-                    "{:<5} {:<40} {:<12} {:<12} {:<20}",
-                    "ID", "Path", "Files", "Size (MB)", "Date"
+                    "{:<5} {:<40} {:<12} {:<12} {:<20} {:<10}",
+                    "ID", "Path", "Files", size_header, "Date", "Duration"
                 );
-                println!("{}", "-".repeat(90));
+                println!("{}", "-".repeat(101));
                 for scan in scans {
-                    let size_mb = scan.total_size as f64 / 1_048_576.0;
+                    let size_mb = size_mode.scan_size(&scan) as f64 / 1_048_576.0;
+                    let duration = scan
+                        .duration()
+                        .map(format_duration)
+                        .unwrap_or_else(|| "-".to_string());
                     println!(
-                        "{:<5} {:<40} {:<12} {:<12.2} {:<20}",
+                        "{:<5} {:<40} {:<12} {:<12.2} {:<20} {:<10}",
                         scan.id,
-                        scan.root_path,
+                        scan.location(),
                         scan.total_files,
                         size_mb,
-                        scan.started_at.format("%Y-%m-%d %H:%M:%S")
+                        scan.started_at.format("%Y-%m-%d %H:%M:%S"),
+                        duration
                     );
                 }
             }
         }
-        Some(Commands::Show { scan_id }) => {
+        Some(Commands::Show {
+            scan_id,
+            disk_usage,
+            long,
+            errors,
+        }) => {
+            let size_mode = size_mode(disk_usage);
             let scan = db.get_scan(scan_id).await?;
             if let Some(scan) = scan {
                 println!("Scan ID: {}", scan.id);
+                if let Some(host) = &scan.host {
+                    println!("Host: {}", host);
+                }
                 println!("Root path: {}", scan.root_path);
+                if scan.is_multi_root() {
+                    println!("Roots: {}", scan.roots.join(", "));
+                }
                 println!("Started: {}", scan.started_at.format("%Y-%m-%d %H:%M:%S"));
                 if let Some(completed) = scan.completed_at {
                     println!("Completed: {}", completed.format("%Y-%m-%d %H:%M:%S"));
                 }
                 println!("Status: {}", scan.status);
+                if let Some(duration) = scan.duration() {
+                    println!("Duration: {}", format_duration(duration));
+                }
                 println!("Files: {}", scan.total_files);
                 println!("Directories: {}", scan.total_dirs);
                 println!("Total size: {:.2} MB", scan.total_size as f64 / 1_048_576.0);
+                println!(
+                    "Disk usage: {:.2} MB",
+                    scan.total_disk_usage as f64 / 1_048_576.0
+                );
+                if let Some(options) = &scan.options {
+                    if options.from_list {
+                        println!("Source: paths listed on stdin");
+                    }
+                    if let Some(format) = &options.imported_from {
+                        println!("Source: imported from {} export", format);
+                    }
+                    if !options.exclude.is_empty() {
+                        println!("Excluded: {}", options.exclude.join(", "));
+                    }
+                    if !options.include.is_empty() {
+                        println!("Re-included: {}", options.include.join(", "));
+                    }
+                    if options.one_file_system {
+                        println!("One file system: yes (other mounts not scanned)");
+                    }
+                    if let Some(max_depth) = options.max_depth {
+                        println!(
+                            "Max depth: {} (deeper entries counted but not stored)",
+                            max_depth
+                        );
+                    }
+                    if options.hard_links != HardLinkPolicy::default() {
+                        println!("Hard links: {}", options.hard_links.display_name());
+                    }
+                    if options.archives {
+                        println!("Archives: members listed");
+                    }
+                }
+                if scan.error_count > 0 {
+                    println!(
+                        "\n⚠️  {} paths could not be read; totals are incomplete.",
+                        scan.error_count
+                    );
+                    if !errors {
+                        println!("  Run 'rootkitty show {} --errors' to list them.", scan.id);
+                    }
+                }
+                if errors {
+                    println!("\nUnreadable paths:");
+                    let scan_errors = db.get_scan_errors(scan_id).await?;
+                    if scan_errors.is_empty() {
+                        println!("  None");
+                    }
+                    for error in scan_errors {
+                        println!(
+                            "  {} ({}: {})",
+                            error.path,
+                            error.kind.label(),
+                            error.message
+                        );
+                    }
+                }
 
-                println!("\nLargest files:");
-                let entries = db.get_largest_entries(scan_id, 20).await?;
+                println!(
+                    "\nLargest files ({}):",
+                    size_mode.display_name().to_lowercase()
+                );
+                let entries = db.get_largest_entries_by(scan_id, 20, size_mode).await?;
                 for entry in entries {
-                    let size_str = format_size(entry.size as u64);
+                    let size_str = format_size(size_mode.entry_size(&entry) as u64);
                     let type_icon = if entry.is_dir { "📁" } else { "📄" };
-                    println!("  {} {} ({})", type_icon, entry.path, size_str);
+                    match (&entry.stat, long) {
+                        (Some(stat), true) => println!(
+                            "  {} {:>10} {} {:>3} {:>6} {:>6} {:>10} {}",
+                            type_icon,
+                            stat.inode,
+                            stat.mode_string(),
+                            stat.nlink,
+                            stat.uid,
+                            stat.gid,
+                            size_str,
+                            entry.path
+                        ),
+                        _ => println!("  {} {} ({})", type_icon, entry.path, size_str),
+                    }
                 }
             } else {
                 println!("Scan {} not found", scan_id);
@@ -360,7 +1017,9 @@ async fn main() -> Result<()> {
         Some(Commands::Diff {
             scan_id_1,
             scan_id_2,
+            disk_usage,
         }) => {
+            let size_mode = size_mode(disk_usage);
             let scan1 = db.get_scan(scan_id_1).await?;
             let scan2 = db.get_scan(scan_id_2).await?;
 
@@ -370,16 +1029,22 @@ async fn main() -> Result<()> {
                     println!("\nScan 1: {}", s1.root_path);
                     println!("  Date: {}", s1.started_at.format("%Y-%m-%d %H:%M:%S"));
                     println!("  Files: {}", s1.total_files);
-                    println!("  Size: {:.2} MB", s1.total_size as f64 / 1_048_576.0);
+                    println!(
+                        "  Size: {:.2} MB",
+                        size_mode.scan_size(&s1) as f64 / 1_048_576.0
+                    );
 
                     println!("\nScan 2: {}", s2.root_path);
                     println!("  Date: {}", s2.started_at.format("%Y-%m-%d %H:%M:%S"));
                     println!("  Files: {}", s2.total_files);
-                    println!("  Size: {:.2} MB", s2.total_size as f64 / 1_048_576.0);
+                    println!(
+                        "  Size: {:.2} MB",
+                        size_mode.scan_size(&s2) as f64 / 1_048_576.0
+                    );
 
                     println!("\nDifferences:");
                     let file_diff = s2.total_files - s1.total_files;
-                    let size_diff = s2.total_size - s1.total_size;
+                    let size_diff = size_mode.scan_size(&s2) - size_mode.scan_size(&s1);
 
                     println!("  Files: {:+}", file_diff);
                     println!("  Size: {:+.2} MB", size_diff as f64 / 1_048_576.0);
@@ -397,6 +1062,337 @@ async fn main() -> Result<()> {
                 }
             }
         }
+        Some(Commands::Owners {
+            scan_id,
+            group,
+            owner,
+            limit,
+            disk_usage,
+        }) => {
+            let size_mode = size_mode(disk_usage);
+            let grouping = if group {
+                OwnerGrouping::Group
+            } else {
+                OwnerGrouping::User
+            };
+            let names = OwnerNames::load();
+
+            if db.get_scan(scan_id).await?.is_none() {
+                println!("Scan {} not found", scan_id);
+                return Ok(());
+            }
+
+            if let Some(owner) = owner {
+                let owner_id = names.resolve(grouping, &owner).with_context(|| {
+                    format!(
+                        "Unknown {}: {}",
+                        grouping.display_name().to_lowercase(),
+                        owner
+                    )
+                })?;
+                let dirs = db
+                    .get_owner_directories(scan_id, grouping, owner_id, size_mode, limit)
+                    .await?;
+                println!(
+                    "Largest directories for {} {}, counting files directly inside ({}):",
+                    grouping.display_name().to_lowercase(),
+                    names.name(grouping, owner_id),
+                    size_mode.display_name().to_lowercase()
+                );
+                if dirs.is_empty() {
+                    println!("  No files owned in this scan");
+                }
+                for dir in dirs {
+                    println!(
+                        "  {:>10} {:>8} files  {}",
+                        format_size(size_mode.select(dir.size, dir.disk_usage) as u64),
+                        dir.files,
+                        dir.path
+                    );
+                }
+                return Ok(());
+            }
+
+            let owners = db.get_owner_usage(scan_id, grouping, size_mode).await?;
+            if owners.is_empty() {
+                println!("Scan {} has no ownership information.", scan_id);
+                println!("Scans made before rootkitty recorded owners need to be rescanned.");
+                return Ok(());
+            }
+
+            let total: i64 = owners
+                .iter()
+                .map(|o| size_mode.select(o.size, o.disk_usage))
+                .sum();
+            println!(
+                "{:<16} {:>10} {:>6} {:>10}",
+                grouping.display_name().to_uppercase(),
+                "SIZE",
+                "%",
+                "FILES"
+            );
+            for usage in owners {
+                let size = size_mode.select(usage.size, usage.disk_usage);
+                let percent = if total > 0 {
+                    size as f64 * 100.0 / total as f64
+                } else {
+                    0.0
+                };
+                println!(
+                    "{:<16} {:>10} {:>5.1}% {:>10}",
+                    names.name(grouping, usage.owner_id),
+                    format_size(size as u64),
+                    percent,
+                    usage.files
+                );
+            }
+        }
+        Some(Commands::Dupes {
+            scan_id,
+            min_size,
+            limit,
+            mark,
+        }) => {
+            if db.get_scan(scan_id).await?.is_none() {
+                println!("Scan {} not found", scan_id);
+                return Ok(());
+            }
+
+            let report = dupes::find_duplicates(&db, scan_id, min_size, |stage, done, total| {
+                print!("\r\x1B[J{}: {}/{} files", stage.label(), done, total);
+                let _ = std::io::Write::flush(&mut std::io::stdout());
+            })
+            .await?;
+            print!("\r\x1B[J");
+
+            if report.groups.is_empty() {
+                println!(
+                    "No duplicates of {} or more in scan {}",
+                    format_size(min_size),
+                    scan_id
+                );
+            } else {
+                println!(
+                    "{} groups of duplicates in scan {}, {} reclaimable:",
+                    report.groups.len(),
+                    scan_id,
+                    format_size(report.reclaimable())
+                );
+                for group in report.groups.iter().take(limit) {
+                    println!();
+                    println!(
+                        "{} copies of {} ({} reclaimable)",
+                        group.paths.len(),
+                        format_size(group.size),
+                        format_size(group.reclaimable())
+                    );
+                    for path in &group.paths {
+                        println!("  {}", path);
+                    }
+                }
+                if report.groups.len() > limit {
+                    println!();
+                    println!("... and {} more groups", report.groups.len() - limit);
+                }
+            }
+            if !report.skipped.is_empty() {
+                println!();
+                println!(
+                    "{} files were skipped (unreadable or changed since the scan)",
+                    report.skipped.len()
+                );
+            }
+
+            if mark {
+                let mut marked = 0;
+                for group in &report.groups {
+                    let (kept, copies) = group.paths.split_first().unwrap();
+                    let reason = format!("Duplicate of {}", kept);
+                    for path in copies {
+                        db.mark_for_cleanup(scan_id, path, Some(&reason)).await?;
+                        marked += 1;
+                    }
+                }
+                println!();
+                println!(
+                    "Marked {} copies for cleanup (kept the first of each group)",
+                    marked
+                );
+            }
+        }
+        Some(Commands::Types {
+            scan_id,
+            limit,
+            sniff,
+            disk_usage,
+        }) => {
+            let size_mode = size_mode(disk_usage);
+            let (settings, _) = load_settings(&cli.config)?;
+            let sniff = sniff.unwrap_or(settings.types.sniff_sample);
+
+            if db.get_scan(scan_id).await?.is_none() {
+                println!("Scan {} not found", scan_id);
+                return Ok(());
+            }
+
+            let report = filetypes::type_breakdown(
+                &db,
+                scan_id,
+                &settings.types.categories,
+                size_mode,
+                sniff,
+            )
+            .await?;
+            if report.categories.is_empty() {
+                println!("Scan {} has no files", scan_id);
+                return Ok(());
+            }
+
+            let total: i64 = report
+                .categories
+                .iter()
+                .map(|c| size_mode.select(c.size, c.disk_usage))
+                .sum();
+            let percent = |size: i64| {
+                if total > 0 {
+                    size as f64 * 100.0 / total as f64
+                } else {
+                    0.0
+                }
+            };
+            println!(
+                "{:<16} {:>10} {:>6} {:>10}",
+                "CATEGORY", "SIZE", "%", "FILES"
+            );
+            for category in &report.categories {
+                let size = size_mode.select(category.size, category.disk_usage);
+                println!(
+                    "{:<16} {:>10} {:>5.1}% {:>10}",
+                    category.name,
+                    format_size(size as u64),
+                    percent(size),
+                    category.files
+                );
+            }
+
+            println!();
+            println!(
+                "{:<16} {:<16} {:>10} {:>6} {:>10}",
+                "EXTENSION", "CATEGORY", "SIZE", "%", "FILES"
+            );
+            for (usage, category) in report.extensions.iter().take(limit) {
+                let size = size_mode.select(usage.size, usage.disk_usage);
+                let extension = if usage.extension.is_empty() {
+                    "(none)".to_string()
+                } else {
+                    format!(".{}", usage.extension)
+                };
+                println!(
+                    "{:<16} {:<16} {:>10} {:>5.1}% {:>10}",
+                    extension,
+                    category,
+                    format_size(size as u64),
+                    percent(size),
+                    usage.files
+                );
+            }
+            if report.extensions.len() > limit {
+                println!(
+                    "... and {} more extensions",
+                    report.extensions.len() - limit
+                );
+            }
+            if sniff > 0 {
+                println!();
+                println!(
+                    "Identified {} files with unknown extensions by their content",
+                    report.sniffed
+                );
+            }
+        }
+        Some(Commands::Import { file, format }) => {
+            let reader: Box<dyn Read + Send> = if file.as_os_str() == "-" {
+                Box::new(std::io::stdin())
+            } else {
+                Box::new(
+                    std::fs::File::open(&file)
+                        .with_context(|| format!("Failed to open {}", file.display()))?,
+                )
+            };
+            let ExchangeFormat::Ncdu = format;
+            let options = ScanOptions {
+                imported_from: Some("ncdu".to_string()),
+                ..Default::default()
+            };
+
+            // The root path is only known once the export has been read
+            println!("Importing: {}", file.display());
+            let scan_id = db.create_scan_with_options(Path::new(""), &options).await?;
+
+            // Entries are streamed to the database actor as they are read
+            let (tx, rx) = mpsc::channel(100);
+            let actor = DatabaseActor::new(db.clone(), scan_id, rx);
+            let actor_handle = tokio::spawn(async move { actor.run().await });
+
+            let import_tx = tx.clone();
+            let imported =
+                tokio::task::spawn_blocking(move || ncdu::NcduImport::new(import_tx).read(reader))
+                    .await?;
+
+            tx.send(ActorMessage::Shutdown).await?;
+            drop(tx);
+            actor_handle.await??;
+
+            let imported = match imported {
+                Ok(imported) => imported,
+                Err(e) => {
+                    // Don't leave a half-imported scan behind
+                    db.delete_scan(scan_id).await?;
+                    return Err(e);
+                }
+            };
+            let stats = imported.stats;
+            println!("Import complete!");
+            println!("  Root path: {}", imported.root_path.display());
+            println!("  Files: {}", stats.total_files);
+            println!("  Directories: {}", stats.total_dirs);
+            println!("  Total size: {} bytes", stats.total_size);
+            println!("  Disk usage: {} bytes", stats.total_disk_usage);
+            if stats.error_count > 0 {
+                println!(
+                    "  Unreadable paths: {} (not included in totals)",
+                    stats.error_count
+                );
+            }
+
+            db.set_import_origin(scan_id, &imported.root_path, imported.scanned_at)
+                .await?;
+            db.complete_scan(scan_id, &stats).await?;
+            println!("Scan {} saved to database", scan_id);
+        }
+        Some(Commands::Export {
+            scan_id,
+            format,
+            output,
+        }) => {
+            let ExchangeFormat::Ncdu = format;
+            match &output {
+                Some(file) => {
+                    let writer = std::fs::File::create(file)
+                        .with_context(|| format!("Failed to create {}", file.display()))?;
+                    let written = ncdu::export(&db, scan_id, writer).await?;
+                    println!(
+                        "Exported {} entries of scan {} to {}",
+                        written,
+                        scan_id,
+                        file.display()
+                    );
+                }
+                None => {
+                    ncdu::export(&db, scan_id, std::io::stdout().lock()).await?;
+                }
+            }
+        }
         Some(Commands::Compact) => {
             println!("Compacting database...");
             println!("  Running VACUUM to reclaim freed space...");
@@ -404,11 +1400,249 @@ async fn main() -> Result<()> {
             println!("✓ Database compacted successfully");
             println!("  The database file and WAL have been optimized");
         }
+        Some(Commands::Agent { .. }) => unreachable!("the agent runs before the database opens"),
     }
 
     Ok(())
 }
 
+/// Redraw the progress of a running scan below the cursor
+fn print_progress(progress: &ProgressUpdate) {
+    // Clear previous output (up to 5 lines)
+    print!("\r\x1B[J"); // Clear from cursor to end of screen
+
+    // Show summary line
+    if progress.gathering {
+        print!("Counting entries: {} found", progress.files_scanned);
+    } else {
+        print!("Progress: {} entries scanned", progress.files_scanned);
+    }
+    match (progress.total_estimate, progress.percent_done) {
+        (Some(total), Some(percent)) => print!(" of {} ({:.0}%)", total, percent),
+        (Some(total), None) => print!(" (~{} expected)", total),
+        _ => {}
+    }
+    if let Some(eta) = progress.eta {
+        print!(" | ETA {}", format_duration(eta));
+    }
+
+    if !progress.worker_utilization.is_empty() {
+        print!(
+            " | {} of {} workers busy [{}]",
+            progress.active_workers,
+            progress.worker_utilization.len(),
+            workers::utilization_bars(&progress.worker_utilization)
+        );
+    } else if progress.active_workers > 0 {
+        print!(" | {} parallel workers", progress.active_workers);
+    }
+    if let Some((rate, limit)) = progress.stat_rate {
+        print!(" | {}/{} stats/s", rate, limit);
+    }
+    println!();
+
+    // Show active directories (limit to top 4 for readability)
+    let max_display = 4;
+    if !progress.active_dirs.is_empty() {
+        for (dir_path, done, total) in progress.active_dirs.iter().take(max_display) {
+            let percentage = if *total > 0 {
+                (*done as f64 / *total as f64 * 100.0) as usize
+            } else {
+                0
+            };
+
+            // Show intelligently truncated path
+            let display_path = smart_truncate_path(dir_path, 70);
+
+            println!("  [{}/{}] {:>3}% {}", done, total, percentage, display_path);
+        }
+
+        if progress.active_dirs.len() > max_display {
+            println!(
+                "  ... and {} more directories",
+                progress.active_dirs.len() - max_display
+            );
+        }
+    } else if !progress.current_path.is_empty() {
+        // If no per-directory progress, just show current path
+        let display_path = smart_truncate_path(&progress.current_path, 70);
+        println!("  Scanning: {}", display_path);
+    }
+
+    std::io::Write::flush(&mut std::io::stdout()).ok();
+}
+
+/// Mention a scan's implementation and pool size when they aren't the defaults
+fn print_workers(threads: usize, implementation: ScannerImpl) {
+    if implementation != ScannerImpl::default() {
+        println!("  Implementation: {}", implementation.display_name());
+    }
+    if threads > 0 {
+        println!("  Workers: {}", threads);
+    }
+}
+
+/// Mention the limits a scan runs under
+fn print_throttle(throttle: &ThrottleSettings) {
+    if let Some(limit) = throttle.stat_limit() {
+        println!("  Throttled to {} stats per second", limit);
+    }
+    if throttle.io_nice {
+        println!("  Low I/O priority");
+    }
+}
+
+/// Scan another machine by running the agent there and storing what it streams back
+async fn scan_remote(
+    db: &Database,
+    target: &RemoteTarget,
+    command: &str,
+    request: &AgentRequest,
+) -> Result<()> {
+    let options = &request.options;
+    println!("Scanning: {}:{}", target.host, target.path);
+    if !options.exclude.is_empty() {
+        println!("  Excluding: {}", options.exclude.join(", "));
+    }
+    if let Some(max_depth) = options.max_depth {
+        println!("  Storing entries down to depth {}", max_depth);
+    }
+    if options.hard_links != HardLinkPolicy::default() {
+        println!("  Hard links: {}", options.hard_links.display_name());
+    }
+    if options.archives {
+        println!("  Listing archive members");
+    }
+    print_workers(request.threads, request.implementation);
+    print_throttle(&request.throttle);
+
+    // The root path is the remote one, recorded once the scan is done
+    let scan_id = db.create_scan_with_options(Path::new(""), options).await?;
+
+    let (tx, rx) = mpsc::channel(100);
+    let actor = DatabaseActor::new(db.clone(), scan_id, rx);
+    let actor_handle = tokio::spawn(async move { actor.run().await });
+
+    let (progress_tx, mut progress_rx) = mpsc::unbounded_channel::<ProgressUpdate>();
+    let progress_handle = tokio::spawn(async move {
+        while let Some(progress) = progress_rx.recv().await {
+            print!("\r\x1B[J"); // Clear from cursor to end of screen
+            print!("Progress: {} entries scanned", progress.files_scanned);
+            if let Some((rate, limit)) = progress.stat_rate {
+                print!(" | {}/{} stats/s", rate, limit);
+            }
+            println!();
+            if !progress.current_path.is_empty() {
+                let display_path = smart_truncate_path(&progress.current_path, 70);
+                println!("  Scanning: {}", display_path);
+            }
+            std::io::Write::flush(&mut std::io::stdout()).ok();
+        }
+    });
+
+    let received = async {
+        let mut child = target.spawn(command, request).await?;
+        let stdout = child.stdout.take().context("Agent has no stdout")?;
+        let stats = remote::receive(
+            tokio::io::BufReader::new(stdout),
+            tx.clone(),
+            Some(progress_tx),
+        )
+        .await;
+        let status = child.wait().await?;
+        match stats {
+            Ok(stats) if status.success() => Ok(stats),
+            Ok(_) => bail!("Agent exited with {}", status),
+            Err(e) if status.success() => Err(e),
+            Err(e) => Err(e.context(format!("Agent exited with {}", status))),
+        }
+    }
+    .await;
+
+    tx.send(ActorMessage::Shutdown).await?;
+    drop(tx);
+    actor_handle.await??;
+    drop(progress_handle);
+    print!("\r\x1B[J"); // Clear from cursor to end of screen
+
+    let stats = match received {
+        Ok(stats) => stats,
+        Err(e) => {
+            // Don't leave a half-received scan behind
+            db.delete_scan(scan_id).await?;
+            return Err(e);
+        }
+    };
+
+    println!("Scan complete!");
+    println!("  Files: {}", stats.total_files);
+    println!("  Directories: {}", stats.total_dirs);
+    println!("  Total size: {} bytes", stats.total_size);
+    println!("  Disk usage: {} bytes", stats.total_disk_usage);
+    println!("  Duration: {}", format_duration(stats.duration));
+    if stats.error_count > 0 {
+        println!(
+            "  Unreadable paths: {} (not included in totals)",
+            stats.error_count
+        );
+    }
+
+    db.set_remote_origin(scan_id, &target.host, &target.path)
+        .await?;
+    db.complete_scan(scan_id, &stats).await?;
+    println!("Scan {} saved to database", scan_id);
+    Ok(())
+}
+
+/// Check that a scan can serve as the base of an incremental rescan of `path`
+fn check_incremental_base(scan: &Scan, path: &Path, options: &ScanOptions) -> Result<()> {
+    if scan.status != "completed" {
+        bail!(
+            "Scan {} is {}; only completed scans can be reused",
+            scan.id,
+            scan.status
+        );
+    }
+    let canonical_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if Path::new(&scan.root_path) != canonical_path {
+        bail!(
+            "Scan {} is of {}, not {}",
+            scan.id,
+            scan.root_path,
+            canonical_path.display()
+        );
+    }
+    // Scans from before options were stored used the defaults
+    if scan.options.clone().unwrap_or_default() != *options {
+        bail!(
+            "Scan {} used different scan options (symlinks, excludes, filesystems, depth or hard links)",
+            scan.id
+        );
+    }
+    Ok(())
+}
+
+/// Load settings from `--config` or the default location
+fn load_settings(config: &Option<String>) -> Result<(Settings, PathBuf)> {
+    let settings_path = if let Some(config) = config {
+        PathBuf::from(shellexpand::tilde(config).to_string())
+    } else {
+        Settings::default_path()
+    };
+
+    let settings = Settings::load(&settings_path).context("Failed to load settings")?;
+    Ok((settings, settings_path))
+}
+
+/// Size accounting selected by a `--disk-usage` flag
+fn size_mode(disk_usage: bool) -> SizeMode {
+    if disk_usage {
+        SizeMode::DiskUsage
+    } else {
+        SizeMode::Apparent
+    }
+}
+
 fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
@@ -428,6 +1662,18 @@ fn format_size(bytes: u64) -> String {
     }
 }
 
+/// Format a duration compactly, e.g. "1h 05m", "3m 20s" or "42s"
+fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
 /// Intelligently truncate a path by preserving the base and end, compressing the middle
 fn smart_truncate_path(path: &str, max_len: usize) -> String {
     if path.len() <= max_len {
@@ -0,0 +1,714 @@
+//! Reading and writing ncdu's JSON export format (`ncdu -o`)
+//!
+//! An export is `[major, minor, {metadata}, root]`. A directory is an array
+//! whose first element describes the directory itself, followed by its
+//! children: objects for files and nested arrays for subdirectories.
+//! Directory sizes in an export are only the directory's own, so totals are
+//! summed on import the same way the scanner does it, and split back into
+//! each directory's own share on export.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+use crate::db::{ActorMessage, Database, StoredFileEntry};
+use crate::scanner::{EntryKind, EntryStat, FileEntry, ScanError, ScanErrorKind, ScanStats};
+
+/// Entries sent to the database per batch
+const BUFFER_SIZE: usize = 1000;
+
+/// One file or directory as described in an export
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct NcduInfo {
+    name: String,
+    #[serde(skip_serializing_if = "is_zero")]
+    asize: u64,
+    #[serde(skip_serializing_if = "is_zero")]
+    dsize: u64,
+    /// Only present when it differs from the parent directory's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dev: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ino: Option<u64>,
+    /// ncdu 1.x flags files with more than one link
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    hlnkc: bool,
+    /// ncdu 2.x records the link count instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nlink: Option<u64>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    read_error: bool,
+    /// Why the entry wasn't scanned: `pattern`, `otherfs`, `kernfs` or `frmlnk`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    excluded: Option<String>,
+    // Extended information, only exported by `ncdu -e`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mode: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mtime: Option<i64>,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+impl NcduInfo {
+    fn kind(&self) -> EntryKind {
+        match self.excluded.as_deref() {
+            None => EntryKind::Normal,
+            Some("otherfs") | Some("kernfs") => EntryKind::MountPoint,
+            Some(_) => EntryKind::Excluded,
+        }
+    }
+
+    /// Owner and mode are only known for extended exports
+    fn stat(&self, device: Option<u64>) -> Option<EntryStat> {
+        let nlink = self.nlink.unwrap_or(if self.hlnkc { 2 } else { 1 });
+        Some(EntryStat {
+            uid: self.uid?,
+            gid: self.gid?,
+            mode: self.mode?,
+            inode: self.ino.unwrap_or(0),
+            device: device.unwrap_or(0),
+            nlink,
+            accessed_at: None,
+            changed_at: None,
+        })
+    }
+
+    /// Device and inode of a file with several links, counted only once
+    fn hard_link(&self, device: Option<u64>) -> Option<(u64, u64)> {
+        let linked = self.hlnkc || self.nlink.is_some_and(|n| n > 1);
+        linked.then_some((device.unwrap_or(0), self.ino?))
+    }
+
+    /// Describe a stored entry, given its own share of the sizes
+    fn from_stored(
+        entry: &StoredFileEntry,
+        own_size: (u64, u64),
+        parent_device: Option<u64>,
+        read_error: bool,
+    ) -> Self {
+        let stat = entry.stat.as_ref();
+        let device = stat.map(|st| st.device);
+        let linked = !entry.is_dir && stat.is_some_and(|st| st.nlink > 1);
+        Self {
+            name: entry.name.clone(),
+            asize: own_size.0,
+            dsize: own_size.1,
+            dev: device.filter(|dev| Some(*dev) != parent_device),
+            ino: stat.map(|st| st.inode),
+            hlnkc: linked,
+            nlink: stat.map(|st| st.nlink).filter(|_| linked),
+            read_error,
+            excluded: match entry.kind {
+                EntryKind::Excluded => Some("pattern".to_string()),
+                EntryKind::MountPoint => Some("otherfs".to_string()),
+                // Archive members aren't exported: an archive is written as a file
+                EntryKind::Normal
+                | EntryKind::Summarized
+                | EntryKind::Archive
+                | EntryKind::InArchive
+                | EntryKind::Unfinished => None,
+            },
+            uid: stat.map(|st| st.uid),
+            gid: stat.map(|st| st.gid),
+            mode: stat.map(|st| st.mode),
+            mtime: entry.modified_at.map(|t| t.timestamp()),
+        }
+    }
+}
+
+/// The export's metadata object
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct NcduMetadata {
+    timestamp: Option<i64>,
+}
+
+/// A scan read from an export
+pub struct NcduImported {
+    /// Root directory as recorded on the machine that ran ncdu
+    pub root_path: PathBuf,
+    /// When ncdu ran, if the export says
+    pub scanned_at: Option<DateTime<Utc>>,
+    pub stats: ScanStats,
+}
+
+/// Reads an ncdu export, streaming entries to a `DatabaseActor`
+///
+/// Entries are sent as soon as they are read, so exports of any size are
+/// imported without holding them in memory.
+pub struct NcduImport {
+    sender: mpsc::Sender<ActorMessage>,
+    entries: Vec<FileEntry>,
+    errors: Vec<ScanError>,
+    error_count: u64,
+    seen_links: HashSet<(u64, u64)>,
+    root_path: Option<PathBuf>,
+    scanned_at: Option<DateTime<Utc>>,
+    total_size: u64,
+    total_disk_usage: u64,
+    total_files: u64,
+    total_dirs: u64,
+}
+
+impl NcduImport {
+    pub fn new(sender: mpsc::Sender<ActorMessage>) -> Self {
+        Self {
+            sender,
+            entries: Vec::new(),
+            errors: Vec::new(),
+            error_count: 0,
+            seen_links: HashSet::new(),
+            root_path: None,
+            scanned_at: None,
+            total_size: 0,
+            total_disk_usage: 0,
+            total_files: 0,
+            total_dirs: 0,
+        }
+    }
+
+    pub fn read<R: Read>(mut self, reader: R) -> Result<NcduImported> {
+        let started = Instant::now();
+        let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+        ExportSeed(&mut self)
+            .deserialize(&mut deserializer)
+            .context("Invalid ncdu export")?;
+        deserializer
+            .end()
+            .context("Unexpected data after the ncdu export")?;
+        self.flush()?;
+
+        let root_path = self
+            .root_path
+            .context("The ncdu export has no root directory")?;
+        Ok(NcduImported {
+            root_path,
+            scanned_at: self.scanned_at,
+            stats: ScanStats {
+                total_size: self.total_size,
+                total_disk_usage: self.total_disk_usage,
+                total_files: self.total_files,
+                total_dirs: self.total_dirs,
+                error_count: self.error_count,
+                reused_dirs: 0,
+                duration: started.elapsed(),
+            },
+        })
+    }
+
+    fn record_error(&mut self, path: &Path) {
+        self.errors.push(ScanError {
+            path: path.to_path_buf(),
+            kind: ScanErrorKind::Other,
+            message: "ncdu could not read this path".to_string(),
+        });
+        self.error_count += 1;
+    }
+
+    fn add_entry(&mut self, entry: FileEntry) -> Result<()> {
+        self.entries.push(entry);
+        if self.entries.len() >= BUFFER_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        let batch: Vec<FileEntry> = self.entries.drain(..).collect();
+        if !batch.is_empty() {
+            self.sender
+                .blocking_send(ActorMessage::InsertBatch(batch))?;
+        }
+        let errors: Vec<ScanError> = self.errors.drain(..).collect();
+        if !errors.is_empty() {
+            self.sender
+                .blocking_send(ActorMessage::InsertErrors(errors))?;
+        }
+        Ok(())
+    }
+
+    /// Record a file (or marker), returning its (apparent size, disk usage)
+    fn add_file(
+        &mut self,
+        info: NcduInfo,
+        path: PathBuf,
+        depth: usize,
+        device: Option<u64>,
+    ) -> Result<(u64, u64)> {
+        let kind = info.kind();
+        if kind.is_marker() {
+            let entry = file_entry(&info, path, depth, device, kind == EntryKind::MountPoint);
+            self.add_entry(FileEntry {
+                size: 0,
+                disk_usage: 0,
+                stat: None,
+                kind,
+                ..entry
+            })?;
+            return Ok((0, 0));
+        }
+        if info.read_error {
+            self.record_error(&path);
+            return Ok((0, 0));
+        }
+
+        self.total_files += 1;
+        let first_link = info
+            .hard_link(device)
+            .is_none_or(|link| self.seen_links.insert(link));
+        let contribution = if first_link {
+            (info.asize, info.dsize)
+        } else {
+            (0, 0)
+        };
+        self.total_size += contribution.0;
+        self.total_disk_usage += contribution.1;
+
+        self.add_entry(file_entry(&info, path, depth, device, false))?;
+        Ok(contribution)
+    }
+}
+
+/// Build the stored entry for an exported file or directory
+fn file_entry(
+    info: &NcduInfo,
+    path: PathBuf,
+    depth: usize,
+    device: Option<u64>,
+    is_dir: bool,
+) -> FileEntry {
+    let name = if depth == 0 {
+        path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("/")
+            .to_string()
+    } else {
+        info.name.clone()
+    };
+
+    FileEntry {
+        parent_path: path.parent().map(|p| p.to_path_buf()),
+        path,
+        name,
+        size: info.asize,
+        disk_usage: info.dsize,
+        is_dir,
+        modified_at: info.mtime.and_then(|t| DateTime::from_timestamp(t, 0)),
+        depth,
+        kind: EntryKind::Normal,
+        stat: info.stat(device),
+    }
+}
+
+/// Write a stored scan as an ncdu export, returning the number of entries written
+///
+/// The hierarchy is rebuilt one directory at a time through `parent_path`, so
+/// only the children of the directories currently being written are held in
+/// memory. Directories summarized at a depth limit are written as directories
+/// whose own size is their total, which keeps totals right in ncdu.
+pub async fn export<W: Write>(db: &Database, scan_id: i64, writer: W) -> Result<u64> {
+    let scan = db
+        .get_scan(scan_id)
+        .await?
+        .with_context(|| format!("Scan {} not found", scan_id))?;
+    let root = db
+        .get_root_entry(scan_id)
+        .await?
+        .with_context(|| format!("Scan {} has no entries", scan_id))?;
+
+    // Unreadable paths are flagged with `read_error`, next to their siblings
+    let mut unreadable: HashMap<String, Vec<String>> = HashMap::new();
+    for error in db.get_scan_errors(scan_id).await? {
+        let path = Path::new(&error.path);
+        if let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+            unreadable
+                .entry(parent.display().to_string())
+                .or_default()
+                .push(name.to_string_lossy().to_string());
+        }
+    }
+
+    let mut export = NcduExport {
+        db,
+        scan_id,
+        root_path: scan.root_path,
+        out: BufWriter::new(writer),
+        unreadable,
+        written: 0,
+    };
+    let metadata = serde_json::json!({
+        "progname": "rootkitty",
+        "progver": env!("CARGO_PKG_VERSION"),
+        "timestamp": scan.started_at.timestamp(),
+    });
+    write!(export.out, "[1,2,{},", metadata)?;
+
+    let mut stack = vec![export.open_dir(&root, None).await?];
+    while let Some(dir) = stack.last_mut() {
+        let device = dir.device;
+        match dir.children.next() {
+            Some(child) if child.is_dir && !child.kind.is_marker() => {
+                export.out.write_all(b",")?;
+                let frame = export.open_dir(&child, device).await?;
+                stack.push(frame);
+            }
+            Some(child) => {
+                export.out.write_all(b",")?;
+                let read_error = export.take_unreadable(&child);
+                let own_size = (child.size as u64, child.disk_usage as u64);
+                export.write_info(&NcduInfo::from_stored(&child, own_size, device, read_error))?;
+            }
+            None => {
+                export.out.write_all(b"]")?;
+                stack.pop();
+            }
+        }
+    }
+    export.out.write_all(b"]\n")?;
+    export.out.flush()?;
+
+    Ok(export.written)
+}
+
+/// State of an export in progress
+struct NcduExport<'a, W: Write> {
+    db: &'a Database,
+    scan_id: i64,
+    /// ncdu names the root by its full path
+    root_path: String,
+    out: BufWriter<W>,
+    /// Names of unreadable paths by parent directory, removed once written
+    unreadable: HashMap<String, Vec<String>>,
+    written: u64,
+}
+
+/// A directory whose children are being written
+struct ExportDir {
+    children: std::vec::IntoIter<StoredFileEntry>,
+    device: Option<u64>,
+}
+
+impl<W: Write> NcduExport<'_, W> {
+    fn write_info(&mut self, info: &NcduInfo) -> Result<()> {
+        serde_json::to_writer(&mut self.out, info)?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Whether `entry` is recorded as unreadable (forgetting it, so it's written once)
+    fn take_unreadable(&mut self, entry: &StoredFileEntry) -> bool {
+        let Some(names) = entry
+            .parent_path
+            .as_ref()
+            .and_then(|parent| self.unreadable.get_mut(parent))
+        else {
+            return false;
+        };
+        let before = names.len();
+        names.retain(|name| *name != entry.name);
+        names.len() != before
+    }
+
+    /// Write the opening of a directory and any unreadable paths in it that
+    /// have no entry of their own
+    async fn open_dir(
+        &mut self,
+        entry: &StoredFileEntry,
+        parent_device: Option<u64>,
+    ) -> Result<ExportDir> {
+        let children = self
+            .db
+            .get_entries_by_parent(self.scan_id, Some(&entry.path))
+            .await?;
+        let children_size: i64 = children.iter().map(|c| c.size).sum();
+        let children_usage: i64 = children.iter().map(|c| c.disk_usage).sum();
+        // Hard links counted once can make the children add up to more
+        let own_size = (
+            entry.size.saturating_sub(children_size).max(0) as u64,
+            entry.disk_usage.saturating_sub(children_usage).max(0) as u64,
+        );
+
+        let read_error = self.take_unreadable(entry);
+        let mut info = NcduInfo::from_stored(entry, own_size, parent_device, read_error);
+        if entry.depth == 0 {
+            info.name = self.root_path.clone();
+        }
+        let device = info.dev.or(parent_device);
+        self.out.write_all(b"[")?;
+        self.write_info(&info)?;
+
+        let entry_names: HashSet<&str> = children.iter().map(|c| c.name.as_str()).collect();
+        let missing: Vec<String> = self
+            .unreadable
+            .get(&entry.path)
+            .into_iter()
+            .flatten()
+            .filter(|name| !entry_names.contains(name.as_str()))
+            .cloned()
+            .collect();
+        for name in missing {
+            self.out.write_all(b",")?;
+            self.write_info(&NcduInfo {
+                name,
+                read_error: true,
+                ..Default::default()
+            })?;
+        }
+
+        Ok(ExportDir {
+            children: children.into_iter(),
+            device,
+        })
+    }
+}
+
+/// The top-level `[major, minor, {metadata}, root]` array
+struct ExportSeed<'a>(&'a mut NcduImport);
+
+impl<'de> DeserializeSeed<'de> for ExportSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ExportSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an ncdu export: [major, minor, metadata, root]")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let major: u64 = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        if major != 1 {
+            return Err(de::Error::custom(format!(
+                "unsupported export version {} (only version 1 is supported)",
+                major
+            )));
+        }
+        seq.next_element::<IgnoredAny>()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        let metadata: NcduMetadata = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+        self.0.scanned_at = metadata
+            .timestamp
+            .and_then(|t| DateTime::from_timestamp(t, 0));
+        let root = NodeSeed {
+            import: &mut *self.0,
+            parent: None,
+            depth: 0,
+            device: None,
+        };
+        seq.next_element_seed(root)?
+            .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(())
+    }
+}
+
+/// A file (object) or directory (array) and everything below it
+///
+/// Deserializes to the node's (apparent size, disk usage) contribution.
+struct NodeSeed<'a> {
+    import: &'a mut NcduImport,
+    parent: Option<&'a Path>,
+    depth: usize,
+    /// Inherited from the parent when the node doesn't record its own
+    device: Option<u64>,
+}
+
+impl NodeSeed<'_> {
+    fn path(&self, info: &NcduInfo) -> PathBuf {
+        match self.parent {
+            Some(parent) => parent.join(&info.name),
+            None => PathBuf::from(&info.name),
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for NodeSeed<'_> {
+    type Value = (u64, u64);
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<(u64, u64), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for NodeSeed<'_> {
+    type Value = (u64, u64);
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a file object or a directory array")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<(u64, u64), A::Error> {
+        let info = NcduInfo::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        let path = self.path(&info);
+        let device = info.dev.or(self.device);
+        if self.depth == 0 {
+            self.import.root_path = Some(path.clone());
+        }
+        self.import
+            .add_file(info, path, self.depth, device)
+            .map_err(de::Error::custom)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(u64, u64), A::Error> {
+        let info: NcduInfo = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let path = self.path(&info);
+        let device = info.dev.or(self.device);
+        if self.depth == 0 {
+            self.import.root_path = Some(path.clone());
+        }
+        if info.read_error {
+            self.import.record_error(&path);
+        }
+
+        // A directory's own blocks count towards itself, like the scanner does
+        let (mut size, mut disk_usage) = (0, info.dsize);
+        loop {
+            let child = NodeSeed {
+                import: &mut *self.import,
+                parent: Some(&path),
+                depth: self.depth + 1,
+                device,
+            };
+            let Some((child_size, child_usage)) = seq.next_element_seed(child)? else {
+                break;
+            };
+            size += child_size;
+            disk_usage += child_usage;
+        }
+
+        self.import.total_dirs += 1;
+        self.import.total_disk_usage += info.dsize;
+        let entry = FileEntry {
+            size,
+            disk_usage,
+            ..file_entry(&info, path, self.depth, device, true)
+        };
+        self.import.add_entry(entry).map_err(de::Error::custom)?;
+        Ok((size, disk_usage))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"[1, 2, {"progname": "ncdu", "progver": "1.19", "timestamp": 1700000000},
+        [{"name": "/data", "asize": 4096, "dsize": 4096, "dev": 2049, "ino": 2},
+            {"name": "a.txt", "asize": 100, "dsize": 4096, "ino": 11, "mtime": 1700000000},
+            [{"name": "sub", "asize": 4096, "dsize": 4096, "ino": 12},
+                {"name": "big.bin", "asize": 10000, "dsize": 12288, "ino": 13, "hlnkc": true},
+                {"name": "link.bin", "asize": 10000, "dsize": 12288, "ino": 13, "hlnkc": true},
+                {"name": "secret", "read_error": true}
+            ],
+            [{"name": "locked", "asize": 4096, "dsize": 4096, "ino": 14, "read_error": true}],
+            {"name": "cache", "excluded": "pattern"},
+            {"name": "proc", "excluded": "kernfs"}
+        ]
+    ]"#;
+
+    /// Import an export, collecting what it sends to the database
+    fn import(export: &str) -> Result<(NcduImported, Vec<FileEntry>, Vec<ScanError>)> {
+        let (tx, mut rx) = mpsc::channel(16);
+        let imported = NcduImport::new(tx).read(export.as_bytes())?;
+
+        let (mut entries, mut errors) = (Vec::new(), Vec::new());
+        while let Ok(message) = rx.try_recv() {
+            match message {
+                ActorMessage::InsertBatch(batch) => entries.extend(batch),
+                ActorMessage::InsertErrors(batch) => errors.extend(batch),
+                _ => {}
+            }
+        }
+        Ok((imported, entries, errors))
+    }
+
+    #[test]
+    fn test_import_sums_directories() {
+        let (imported, entries, errors) = import(EXPORT).unwrap();
+        assert_eq!(imported.root_path, PathBuf::from("/data"));
+        assert_eq!(imported.scanned_at.unwrap().timestamp(), 1700000000);
+
+        let find = |path: &str| entries.iter().find(|e| e.path == Path::new(path)).unwrap();
+        let root = find("/data");
+        assert_eq!((root.name.as_str(), root.depth), ("data", 0));
+        assert!(root.is_dir);
+        // The hard-linked file counts once
+        assert_eq!(root.size, 100 + 10000);
+        assert_eq!(root.disk_usage, 3 * 4096 + 4096 + 12288);
+        let sub = find("/data/sub");
+        assert_eq!(
+            (sub.size, sub.disk_usage, sub.depth),
+            (10000, 4096 + 12288, 1)
+        );
+        assert_eq!(find("/data/sub/link.bin").size, 10000);
+        assert!(find("/data/a.txt").modified_at.is_some());
+
+        assert_eq!(find("/data/cache").kind, EntryKind::Excluded);
+        assert_eq!(find("/data/proc").kind, EntryKind::MountPoint);
+
+        let stats = &imported.stats;
+        assert_eq!((stats.total_files, stats.total_dirs), (3, 3));
+        assert_eq!(stats.total_size, root.size);
+        assert_eq!(stats.total_disk_usage, root.disk_usage);
+
+        let errors: Vec<_> = errors.iter().map(|e| e.path.clone()).collect();
+        assert_eq!(
+            errors,
+            vec![
+                PathBuf::from("/data/sub/secret"),
+                PathBuf::from("/data/locked")
+            ]
+        );
+        assert_eq!(stats.error_count, 2);
+    }
+
+    #[test]
+    fn test_import_extended_info() {
+        let export = r#"[1, 0, {}, [{"name": "/home", "dev": 7, "ino": 2, "uid": 0, "gid": 0, "mode": 16877},
+            {"name": "f", "asize": 5, "dsize": 4096, "ino": 3, "uid": 1000, "gid": 100, "mode": 33188}]]"#;
+        let (_, entries, _) = import(export).unwrap();
+        let file = entries.iter().find(|e| e.name == "f").unwrap();
+        let stat = file.stat.unwrap();
+        assert_eq!(
+            (stat.uid, stat.gid, stat.inode, stat.device),
+            (1000, 100, 3, 7)
+        );
+        assert_eq!(stat.mode_string(), "-rw-r--r--");
+    }
+
+    #[test]
+    fn test_import_rejects_other_formats() {
+        assert!(import("[2, 0, {}, []]").is_err());
+        assert!(import("{\"name\": \"x\"}").is_err());
+        assert!(import("[1, 0, {}]").is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//! Resolve numeric user and group IDs to names
+
+use std::collections::HashMap;
+use std::fs;
+
+use crate::db::OwnerGrouping;
+
+/// User and group names read from `/etc/passwd` and `/etc/group`
+///
+/// IDs without an entry (deleted accounts, directory services such as LDAP)
+/// are shown as plain numbers.
+#[derive(Debug, Clone, Default)]
+pub struct OwnerNames {
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+}
+
+impl OwnerNames {
+    /// Load names from the local account databases
+    /// Missing or unreadable files just leave IDs unresolved
+    pub fn load() -> Self {
+        let passwd = fs::read_to_string("/etc/passwd").unwrap_or_default();
+        let group = fs::read_to_string("/etc/group").unwrap_or_default();
+        Self::from_contents(&passwd, &group)
+    }
+
+    pub fn from_contents(passwd: &str, group: &str) -> Self {
+        Self {
+            users: parse_id_file(passwd),
+            groups: parse_id_file(group),
+        }
+    }
+
+    /// Name for a user or group ID, falling back to the number
+    pub fn name(&self, grouping: OwnerGrouping, id: u32) -> String {
+        let names = match grouping {
+            OwnerGrouping::User => &self.users,
+            OwnerGrouping::Group => &self.groups,
+        };
+        names.get(&id).cloned().unwrap_or_else(|| id.to_string())
+    }
+
+    /// Look up an ID from a name or a numeric string
+    pub fn resolve(&self, grouping: OwnerGrouping, name_or_id: &str) -> Option<u32> {
+        if let Ok(id) = name_or_id.parse() {
+            return Some(id);
+        }
+        let names = match grouping {
+            OwnerGrouping::User => &self.users,
+            OwnerGrouping::Group => &self.groups,
+        };
+        names
+            .iter()
+            .find(|(_, name)| name.as_str() == name_or_id)
+            .map(|(id, _)| *id)
+    }
+}
+
+/// Parse `name:password:id:...` lines (the layout shared by passwd and group)
+fn parse_id_file(contents: &str) -> HashMap<u32, String> {
+    let mut names = HashMap::new();
+    for line in contents.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split(':');
+        let (Some(name), Some(_), Some(id)) = (fields.next(), fields.next(), fields.next()) else {
+            continue;
+        };
+        if let Ok(id) = id.parse() {
+            // Keep the first name when several share an ID, like getpwuid
+            names.entry(id).or_insert_with(|| name.to_string());
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWD: &str = "# local accounts\n\
+        root:x:0:0:root:/root:/bin/bash\n\
+        alice:x:1000:1000:Alice:/home/alice:/bin/zsh\n\
+        toor:x:0:0:alias:/root:/bin/sh\n\
+        broken line\n";
+    const GROUP: &str = "root:x:0:\nbuilders:x:1500:alice,bob\n";
+
+    #[test]
+    fn test_names_are_resolved() {
+        let names = OwnerNames::from_contents(PASSWD, GROUP);
+        assert_eq!(names.name(OwnerGrouping::User, 1000), "alice");
+        assert_eq!(names.name(OwnerGrouping::User, 0), "root");
+        assert_eq!(names.name(OwnerGrouping::Group, 1500), "builders");
+    }
+
+    #[test]
+    fn test_unknown_ids_fall_back_to_numbers() {
+        let names = OwnerNames::from_contents(PASSWD, GROUP);
+        assert_eq!(names.name(OwnerGrouping::User, 4242), "4242");
+        assert_eq!(names.name(OwnerGrouping::Group, 1000), "1000");
+    }
+
+    #[test]
+    fn test_resolve_name_or_id() {
+        let names = OwnerNames::from_contents(PASSWD, GROUP);
+        assert_eq!(names.resolve(OwnerGrouping::User, "alice"), Some(1000));
+        assert_eq!(names.resolve(OwnerGrouping::User, "4242"), Some(4242));
+        assert_eq!(names.resolve(OwnerGrouping::Group, "builders"), Some(1500));
+        assert_eq!(names.resolve(OwnerGrouping::User, "nobody-here"), None);
+    }
+}
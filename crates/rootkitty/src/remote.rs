@@ -0,0 +1,678 @@
+//! Scanning another machine through a streaming agent
+//!
+//! `rootkitty agent scan <path>` runs the scanner where the files are and
+//! writes what it finds to stdout, one JSON record per line. `rootkitty scan
+//! ssh://host/path` starts the agent through a command (`ssh host rootkitty
+//! agent scan ...` by default) and stores the records as they arrive, so the
+//! remote machine needs the rootkitty binary but no database.
+//!
+//! The scan options and throttle settings are sent to the agent as a single
+//! JSON line on its stdin, so the throttle applies on the remote machine.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::process::{Child, Command};
+use tokio::sync::mpsc;
+
+use crate::db::ActorMessage;
+use crate::scanner::{
+    EntryKind, EntryStat, FileEntry, ProgressUpdate, ScanError, ScanErrorKind, ScanOptions,
+    ScanStats, Scanner, ScannerImpl,
+};
+use crate::settings::ThrottleSettings;
+
+/// Command used to start the agent when none is configured
+///
+/// `{host}` is replaced by the host of the `ssh://` URL and `{path}` by the
+/// path, quoted for the remote shell that ssh runs the command in. Commands
+/// that don't go through a shell can use `{raw_path}` instead.
+pub const DEFAULT_COMMAND: &str = "ssh {host} rootkitty agent scan {path}";
+
+/// Entries sent to the database per batch
+const BUFFER_SIZE: usize = 1000;
+
+/// One line of the agent's output
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "t", rename_all = "snake_case")]
+enum Record {
+    Entry(WireEntry),
+    Error {
+        p: PathBuf,
+        k: String,
+        m: String,
+    },
+    /// Files and directories below the depth limit, counted but not sent
+    Summarized {
+        files: u64,
+        dirs: u64,
+    },
+    Progress {
+        files: u64,
+        size: u64,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        path: String,
+        /// Current and maximum stats per second, when throttled
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rate: Option<(u64, u32)>,
+    },
+    /// Last line of a finished scan
+    Done(WireStats),
+}
+
+/// What the agent is asked to do, read from its stdin
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AgentRequest {
+    #[serde(flatten)]
+    pub options: ScanOptions,
+    #[serde(default)]
+    pub throttle: ThrottleSettings,
+    /// Workers to scan on (0 for one per core of the remote machine)
+    #[serde(default)]
+    pub threads: usize,
+    #[serde(default)]
+    pub implementation: ScannerImpl,
+}
+
+/// A `FileEntry` with short field names and defaults left out
+/// The parent path is the path's parent, so it isn't sent
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct WireEntry {
+    p: PathBuf,
+    n: String,
+    #[serde(skip_serializing_if = "is_zero")]
+    s: u64,
+    #[serde(skip_serializing_if = "is_zero")]
+    du: u64,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    dir: bool,
+    #[serde(skip_serializing_if = "is_zero_usize")]
+    d: usize,
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    mt: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    k: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    st: Option<WireStat>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct WireStat {
+    uid: u32,
+    gid: u32,
+    mode: u32,
+    ino: u64,
+    dev: u64,
+    nl: u64,
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    at: Option<DateTime<Utc>>,
+    #[serde(
+        with = "chrono::serde::ts_nanoseconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    ct: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct WireStats {
+    size: u64,
+    du: u64,
+    files: u64,
+    dirs: u64,
+    errors: u64,
+    ms: u64,
+}
+
+fn is_zero(n: &u64) -> bool {
+    *n == 0
+}
+
+fn is_zero_usize(n: &usize) -> bool {
+    *n == 0
+}
+
+impl From<&FileEntry> for WireEntry {
+    fn from(entry: &FileEntry) -> Self {
+        Self {
+            p: entry.path.clone(),
+            n: entry.name.clone(),
+            s: entry.size,
+            du: entry.disk_usage,
+            dir: entry.is_dir,
+            d: entry.depth,
+            mt: entry.modified_at,
+            k: entry.kind.as_db_str().map(str::to_string),
+            st: entry.stat.map(|stat| WireStat {
+                uid: stat.uid,
+                gid: stat.gid,
+                mode: stat.mode,
+                ino: stat.inode,
+                dev: stat.device,
+                nl: stat.nlink,
+                at: stat.accessed_at,
+                ct: stat.changed_at,
+            }),
+        }
+    }
+}
+
+impl From<WireEntry> for FileEntry {
+    fn from(wire: WireEntry) -> Self {
+        Self {
+            parent_path: wire.p.parent().map(Path::to_path_buf),
+            path: wire.p,
+            name: wire.n,
+            size: wire.s,
+            disk_usage: wire.du,
+            is_dir: wire.dir,
+            modified_at: wire.mt,
+            depth: wire.d,
+            kind: EntryKind::from_db_str(wire.k.as_deref()),
+            stat: wire.st.map(|st| EntryStat {
+                uid: st.uid,
+                gid: st.gid,
+                mode: st.mode,
+                inode: st.ino,
+                device: st.dev,
+                nlink: st.nl,
+                accessed_at: st.at,
+                changed_at: st.ct,
+            }),
+        }
+    }
+}
+
+impl From<&ScanStats> for WireStats {
+    fn from(stats: &ScanStats) -> Self {
+        Self {
+            size: stats.total_size,
+            du: stats.total_disk_usage,
+            files: stats.total_files,
+            dirs: stats.total_dirs,
+            errors: stats.error_count,
+            ms: stats.duration.as_millis() as u64,
+        }
+    }
+}
+
+impl From<WireStats> for ScanStats {
+    fn from(wire: WireStats) -> Self {
+        Self {
+            total_size: wire.size,
+            total_disk_usage: wire.du,
+            total_files: wire.files,
+            total_dirs: wire.dirs,
+            error_count: wire.errors,
+            reused_dirs: 0,
+            duration: Duration::from_millis(wire.ms),
+        }
+    }
+}
+
+async fn write_record<W: AsyncWrite + Unpin>(writer: &mut W, record: &Record) -> Result<()> {
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    Ok(())
+}
+
+/// Run the agent side of a remote scan: scan `path` and stream it to `writer`
+pub async fn agent_scan<W: AsyncWrite + Unpin>(
+    path: &Path,
+    request: AgentRequest,
+    mut writer: W,
+) -> Result<ScanStats> {
+    let (tx, mut rx) = mpsc::channel(100);
+    let (progress_tx, mut progress_rx) = mpsc::unbounded_channel::<ProgressUpdate>();
+
+    let path = path.to_path_buf();
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let scan_handle = tokio::task::spawn_blocking(move || {
+        let AgentRequest {
+            options,
+            throttle,
+            threads,
+            implementation,
+        } = request;
+        let mut scanner = Scanner::with_options(&path, tx, Some(progress_tx), cancelled, &options)?;
+        scanner.set_threads(threads);
+        scanner.set_implementation(implementation);
+        if let Some(limit) = throttle.stat_limit() {
+            scanner.limit_stats(limit);
+        }
+        scanner.scan()
+    });
+
+    // Forward entries as they come, with progress in between
+    let mut progress_open = true;
+    loop {
+        tokio::select! {
+            message = rx.recv() => match message {
+                Some(ActorMessage::InsertBatch(entries)) => {
+                    for entry in &entries {
+                        write_record(&mut writer, &Record::Entry(entry.into())).await?;
+                    }
+                }
+                Some(ActorMessage::InsertErrors(errors)) => {
+                    for error in errors {
+                        let record = Record::Error {
+                            p: error.path,
+                            k: error.kind.as_db_str().to_string(),
+                            m: error.message,
+                        };
+                        write_record(&mut writer, &record).await?;
+                    }
+                }
+                Some(ActorMessage::CountSummarized { files, dirs }) => {
+                    write_record(&mut writer, &Record::Summarized { files, dirs }).await?;
+                }
+                // Remote scans are run again rather than resumed
+                Some(ActorMessage::InsertCheckpoints(_)) => {}
+                Some(ActorMessage::Shutdown) | None => break,
+            },
+            progress = progress_rx.recv(), if progress_open => match progress {
+                Some(progress) => {
+                    let record = Record::Progress {
+                        files: progress.files_scanned,
+                        size: progress.total_size,
+                        path: progress.current_path,
+                        rate: progress.stat_rate,
+                    };
+                    write_record(&mut writer, &record).await?;
+                    writer.flush().await?;
+                }
+                None => progress_open = false,
+            },
+        }
+    }
+
+    let (_, stats) = scan_handle.await??;
+    write_record(&mut writer, &Record::Done((&stats).into())).await?;
+    writer.flush().await?;
+    Ok(stats)
+}
+
+/// Read an agent's output, forwarding entries and errors to the database actor
+///
+/// Fails if the stream ends before the agent reported the scan as done.
+pub async fn receive<R: AsyncBufRead + Unpin>(
+    reader: R,
+    sender: mpsc::Sender<ActorMessage>,
+    progress_sender: Option<mpsc::UnboundedSender<ProgressUpdate>>,
+) -> Result<ScanStats> {
+    let mut lines = reader.lines();
+    let mut batch = Vec::with_capacity(BUFFER_SIZE);
+    let mut dirs_scanned = 0;
+    let mut line_number = 0;
+
+    while let Some(line) = lines.next_line().await? {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Record = serde_json::from_str(&line)
+            .with_context(|| format!("Unexpected output from agent on line {}", line_number))?;
+        match record {
+            Record::Entry(wire) => {
+                if wire.dir {
+                    dirs_scanned += 1;
+                }
+                batch.push(wire.into());
+                if batch.len() >= BUFFER_SIZE {
+                    let full = std::mem::replace(&mut batch, Vec::with_capacity(BUFFER_SIZE));
+                    sender.send(ActorMessage::InsertBatch(full)).await?;
+                }
+            }
+            Record::Error { p, k, m } => {
+                let error = ScanError {
+                    path: p,
+                    kind: ScanErrorKind::from_db_str(&k),
+                    message: m,
+                };
+                sender.send(ActorMessage::InsertErrors(vec![error])).await?;
+            }
+            Record::Summarized { files, dirs } => {
+                sender
+                    .send(ActorMessage::CountSummarized { files, dirs })
+                    .await?;
+            }
+            Record::Progress {
+                files,
+                size,
+                path,
+                rate,
+            } => {
+                if let Some(progress_sender) = &progress_sender {
+                    let _ = progress_sender.send(ProgressUpdate {
+                        files_scanned: files,
+                        dirs_scanned,
+                        total_size: size,
+                        current_path: path,
+                        active_dirs: Vec::new(),
+                        active_workers: 0,
+                        worker_utilization: Vec::new(),
+                        gathering: false,
+                        total_estimate: None,
+                        percent_done: None,
+                        eta: None,
+                        stat_rate: rate,
+                    });
+                }
+            }
+            Record::Done(stats) => {
+                if !batch.is_empty() {
+                    sender.send(ActorMessage::InsertBatch(batch)).await?;
+                }
+                return Ok(stats.into());
+            }
+        }
+    }
+
+    bail!("Agent stopped before the scan finished")
+}
+
+/// Where a remote scan runs, parsed from `ssh://[user@]host/path`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteTarget {
+    /// `host` or `user@host`, as passed to ssh
+    pub host: String,
+    /// Path on the remote machine; `~/...` is relative to the remote home directory
+    pub path: String,
+}
+
+impl RemoteTarget {
+    /// Parse a scan argument, returning None if it isn't an `ssh://` URL
+    pub fn parse(arg: &str) -> Option<Result<Self>> {
+        let rest = arg.strip_prefix("ssh://")?;
+        Some(Self::parse_url(rest).with_context(|| format!("Invalid remote path {}", arg)))
+    }
+
+    fn parse_url(rest: &str) -> Result<Self> {
+        let (host, path) = match rest.find('/') {
+            Some(slash) => rest.split_at(slash),
+            None => bail!("expected ssh://host/path"),
+        };
+        if host.is_empty() {
+            bail!("missing host");
+        }
+        // ssh would take it as an option, e.g. -oProxyCommand=...
+        if host.starts_with('-') {
+            bail!("host can't start with '-'");
+        }
+        if host.rsplit('@').next().is_some_and(|h| h.contains(':')) {
+            bail!("ports aren't supported; set one for the host in ~/.ssh/config");
+        }
+        // ssh://host/~/dir is relative to the home directory, like scp
+        let path = match path.strip_prefix("/~") {
+            Some(home) if home.is_empty() || home.starts_with('/') => format!("~{}", home),
+            _ => path.to_string(),
+        };
+        Ok(Self {
+            host: host.to_string(),
+            path,
+        })
+    }
+
+    /// Arguments of the command that starts the agent, from a template like
+    /// [`DEFAULT_COMMAND`]
+    /// Placeholders are filled in one pass, so text substituted for one is
+    /// never read as another.
+    pub fn command_args(&self, template: &str) -> Result<Vec<String>> {
+        let quoted = shell_quote(&self.path);
+        let placeholders = [
+            ("{host}", self.host.as_str()),
+            ("{raw_path}", self.path.as_str()),
+            ("{path}", quoted.as_str()),
+        ];
+        let args: Vec<String> = template
+            .split_whitespace()
+            .map(|arg| {
+                let mut filled = String::new();
+                let mut rest = arg;
+                while !rest.is_empty() {
+                    match placeholders
+                        .iter()
+                        .find(|(placeholder, _)| rest.starts_with(placeholder))
+                    {
+                        Some((placeholder, value)) => {
+                            filled.push_str(value);
+                            rest = &rest[placeholder.len()..];
+                        }
+                        None => {
+                            let next = rest.chars().next().map_or(1, char::len_utf8);
+                            filled.push_str(&rest[..next]);
+                            rest = &rest[next..];
+                        }
+                    }
+                }
+                filled
+            })
+            .collect();
+        if args.is_empty() {
+            bail!("Remote command is empty");
+        }
+        Ok(args)
+    }
+
+    /// Start the agent, sending it the scan options and throttle
+    /// Its stdout carries the scan; stderr is passed through to ours.
+    pub async fn spawn(&self, template: &str, request: &AgentRequest) -> Result<Child> {
+        let args = self.command_args(template)?;
+        let mut child = Command::new(&args[0])
+            .args(&args[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to run {}", args[0]))?;
+
+        let mut stdin = child.stdin.take().context("Agent has no stdin")?;
+        let mut line = serde_json::to_vec(request)?;
+        line.push(b'\n');
+        stdin.write_all(&line).await?;
+        // Closing stdin tells the agent the options are complete
+        drop(stdin);
+
+        Ok(child)
+    }
+}
+
+/// Read the request an agent was started with from its stdin
+/// Defaults apply when nothing was sent, e.g. when run by hand.
+pub fn read_agent_request() -> Result<AgentRequest> {
+    use std::io::{BufRead, IsTerminal};
+
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return Ok(AgentRequest::default());
+    }
+    let mut line = String::new();
+    stdin.lock().read_line(&mut line)?;
+    if line.trim().is_empty() {
+        return Ok(AgentRequest::default());
+    }
+    serde_json::from_str(&line).context("Invalid scan options on stdin")
+}
+
+/// Stream a scan of `path` to stdout, for `rootkitty agent scan`
+pub async fn run_agent(path: &Path) -> Result<()> {
+    let request = read_agent_request()?;
+    if request.throttle.io_nice {
+        // Only stdout carries the scan, so the warning reaches the user through ssh
+        if let Err(e) = crate::throttle::lower_io_priority() {
+            eprintln!("Warning: {:#}", e);
+        }
+    }
+    let stdout = tokio::io::BufWriter::new(tokio::io::stdout());
+    agent_scan(path, request, stdout).await?;
+    Ok(())
+}
+
+/// Quote a path for a POSIX shell, leaving a leading `~/` outside the quotes
+/// so the remote shell still expands it
+fn shell_quote(path: &str) -> String {
+    let (home, rest) = match path.strip_prefix("~/") {
+        Some(rest) => ("~/", rest),
+        None if path == "~" => return path.to_string(),
+        None => ("", path),
+    };
+    let safe = |c: char| c.is_ascii_alphanumeric() || "/._-+,:@%=".contains(c);
+    if !rest.is_empty() && rest.chars().all(safe) {
+        return path.to_string();
+    }
+    format!("{}'{}'", home, rest.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_target() {
+        assert!(RemoteTarget::parse("/local/path").is_none());
+
+        let target = RemoteTarget::parse("ssh://me@box/srv/data")
+            .unwrap()
+            .unwrap();
+        assert_eq!(target.host, "me@box");
+        assert_eq!(target.path, "/srv/data");
+
+        let home = RemoteTarget::parse("ssh://box/~/projects")
+            .unwrap()
+            .unwrap();
+        assert_eq!(home.path, "~/projects");
+
+        assert!(RemoteTarget::parse("ssh://box").unwrap().is_err());
+        assert!(RemoteTarget::parse("ssh:///srv").unwrap().is_err());
+        assert!(RemoteTarget::parse("ssh://box:2222/srv").unwrap().is_err());
+        assert!(RemoteTarget::parse("ssh://-oProxyCommand=sh/x")
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn test_command_args_quote_paths() {
+        let target = RemoteTarget {
+            host: "box".to_string(),
+            path: "~/My Files/it's".to_string(),
+        };
+        assert_eq!(
+            target.command_args(DEFAULT_COMMAND).unwrap(),
+            vec![
+                "ssh",
+                "box",
+                "rootkitty",
+                "agent",
+                "scan",
+                r"~/'My Files/it'\''s'"
+            ]
+        );
+        assert_eq!(
+            target
+                .command_args("rootkitty agent scan {raw_path}")
+                .unwrap()[3],
+            "~/My Files/it's"
+        );
+        assert!(target.command_args("  ").is_err());
+
+        // A path that looks like a placeholder is left as it is
+        let literal = RemoteTarget {
+            host: "box".to_string(),
+            path: "/srv/{host}{path}".to_string(),
+        };
+        assert_eq!(
+            literal.command_args("scan {raw_path}").unwrap()[1],
+            "/srv/{host}{path}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_agent_stream_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("a.txt"), b"hello").unwrap();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("sub/b.bin"), vec![0u8; 2048]).unwrap();
+
+        let mut output = Vec::new();
+        let sent = agent_scan(root, AgentRequest::default(), &mut output)
+            .await
+            .unwrap();
+
+        let (tx, mut rx) = mpsc::channel(100);
+        let received = receive(&output[..], tx, None).await.unwrap();
+        assert_eq!(received.total_size, sent.total_size);
+        assert_eq!(received.total_files, 2);
+        assert_eq!(received.total_dirs, sent.total_dirs);
+
+        let mut entries = Vec::new();
+        while let Ok(ActorMessage::InsertBatch(batch)) = rx.try_recv() {
+            entries.extend(batch);
+        }
+        let file = entries
+            .iter()
+            .find(|e| e.path == root.join("sub/b.bin"))
+            .unwrap();
+        assert_eq!(file.size, 2048);
+        assert_eq!(file.depth, 2);
+        assert_eq!(
+            file.parent_path.as_deref(),
+            Some(root.join("sub").as_path())
+        );
+        assert!(file.modified_at.is_some());
+        #[cfg(unix)]
+        assert_eq!(file.stat.unwrap().nlink, 1);
+    }
+
+    #[tokio::test]
+    async fn test_receive_rejects_truncated_stream() {
+        let (tx, _rx) = mpsc::channel(100);
+        let output = br#"{"t":"entry","p":"/srv","n":"srv","dir":true}"#;
+        let err = receive(&output[..], tx, None).await.unwrap_err();
+        assert!(err.to_string().contains("before the scan finished"));
+    }
+
+    #[test]
+    fn test_agent_request_accepts_bare_options() {
+        // Agents also understand the plain options sent before throttling existed
+        let request: AgentRequest =
+            serde_json::from_str(r#"{"one_file_system":true,"max_depth":2}"#).unwrap();
+        assert!(request.options.one_file_system);
+        assert_eq!(request.options.max_depth, Some(2));
+        assert_eq!(request.throttle, ThrottleSettings::default());
+        assert_eq!(request.implementation, ScannerImpl::TwoPhase);
+
+        let request = AgentRequest {
+            options: ScanOptions::default(),
+            throttle: ThrottleSettings {
+                max_stats_per_sec: Some(500),
+                io_nice: true,
+            },
+            threads: 4,
+            implementation: ScannerImpl::Hybrid,
+        };
+        let line = serde_json::to_string(&request).unwrap();
+        let parsed: AgentRequest = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed.throttle, request.throttle);
+        assert_eq!(parsed.options, request.options);
+        assert_eq!(parsed.threads, 4);
+        assert_eq!(parsed.implementation, ScannerImpl::Hybrid);
+    }
+}
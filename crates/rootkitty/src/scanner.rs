@@ -1,29 +1,394 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use chrono::{DateTime, Utc};
 use rayon::prelude::*;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
+use crate::filter::ScanFilter;
+use crate::source::{DiskSource, FsSource, Metadata, WalkEntry, WalkOptions};
+use crate::throttle::Throttle;
+use crate::workers::WorkerPool;
+
 #[derive(Debug, Clone)]
 pub struct FileEntry {
     pub path: PathBuf,
     pub name: String,
     pub parent_path: Option<PathBuf>,
+    /// Apparent size in bytes (file length, summed for directories)
     pub size: u64,
+    /// Bytes allocated on disk (`st_blocks * 512`), summed like `du` for directories
+    pub disk_usage: u64,
     pub is_dir: bool,
     pub modified_at: Option<DateTime<Utc>>,
     pub depth: usize,
+    pub kind: EntryKind,
+    /// Ownership, permissions and inode details (None for markers and on non-Unix platforms)
+    pub stat: Option<EntryStat>,
+}
+
+/// Unix metadata recorded for each entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct EntryStat {
+    pub uid: u32,
+    pub gid: u32,
+    /// Full `st_mode`, including the file type bits
+    pub mode: u32,
+    pub inode: u64,
+    pub device: u64,
+    pub nlink: u64,
+    pub accessed_at: Option<DateTime<Utc>>,
+    pub changed_at: Option<DateTime<Utc>>,
+}
+
+impl EntryStat {
+    #[cfg(unix)]
+    pub fn from_metadata(metadata: &fs::Metadata) -> Option<Self> {
+        use std::os::unix::fs::MetadataExt;
+        Some(Self {
+            uid: metadata.uid(),
+            gid: metadata.gid(),
+            mode: metadata.mode(),
+            inode: metadata.ino(),
+            device: metadata.dev(),
+            nlink: metadata.nlink(),
+            accessed_at: DateTime::from_timestamp(metadata.atime(), metadata.atime_nsec() as u32),
+            changed_at: DateTime::from_timestamp(metadata.ctime(), metadata.ctime_nsec() as u32),
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn from_metadata(_metadata: &fs::Metadata) -> Option<Self> {
+        None
+    }
+
+    /// Permissions in `ls -l` form, e.g. `drwxr-xr-x`
+    pub fn mode_string(&self) -> String {
+        let file_type = match self.mode & 0o170000 {
+            0o040000 => 'd',
+            0o120000 => 'l',
+            0o020000 => 'c',
+            0o060000 => 'b',
+            0o010000 => 'p',
+            0o140000 => 's',
+            _ => '-',
+        };
+
+        let mut out = String::with_capacity(10);
+        out.push(file_type);
+        for shift in [6, 3, 0] {
+            let bits = (self.mode >> shift) & 0o7;
+            out.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+            out.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+            out.push(if bits & 0o1 != 0 { 'x' } else { '-' });
+        }
+        out
+    }
+
+    /// Permission bits only (e.g. 0o755)
+    pub fn permissions(&self) -> u32 {
+        self.mode & 0o7777
+    }
+}
+
+/// What a recorded entry represents
+///
+/// Marker kinds stand in for paths the scanner deliberately did not
+/// descend into, so they show up in the tree instead of silently missing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EntryKind {
+    #[default]
+    Normal,
+    /// Matched an exclude pattern; contents were not scanned
+    Excluded,
+    /// Directory on a different filesystem (one-file-system mode); not scanned
+    MountPoint,
+    /// Directory at the depth limit; its contents were counted but not stored
+    Summarized,
+    /// Tar or zip file whose members were listed below it
+    Archive,
+    /// Member of an archive, or a directory inside one; see `archive`
+    /// Doesn't count towards totals, and its disk usage is its compressed size
+    InArchive,
+    /// Directory a paused scan entered but hadn't finished; never stored, only
+    /// synthesized so paused scans can be browsed (see `Database::get_unfinished_dirs`)
+    Unfinished,
+}
+
+impl EntryKind {
+    /// Value stored in the `kind` column (NULL for normal entries)
+    pub fn as_db_str(&self) -> Option<&'static str> {
+        match self {
+            EntryKind::Normal => None,
+            EntryKind::Excluded => Some("excluded"),
+            EntryKind::MountPoint => Some("mount_point"),
+            EntryKind::Summarized => Some("summarized"),
+            EntryKind::Archive => Some("archive"),
+            EntryKind::InArchive => Some("in_archive"),
+            EntryKind::Unfinished => Some("unfinished"),
+        }
+    }
+
+    pub fn from_db_str(s: Option<&str>) -> Self {
+        match s {
+            Some("excluded") => EntryKind::Excluded,
+            Some("mount_point") => EntryKind::MountPoint,
+            Some("summarized") => EntryKind::Summarized,
+            Some("archive") => EntryKind::Archive,
+            Some("in_archive") => EntryKind::InArchive,
+            Some("unfinished") => EntryKind::Unfinished,
+            _ => EntryKind::Normal,
+        }
+    }
+
+    /// Markers are placeholders that do not count towards scan totals
+    pub fn is_marker(&self) -> bool {
+        matches!(self, EntryKind::Excluded | EntryKind::MountPoint)
+    }
+
+    /// Short label shown next to marker entries
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            EntryKind::Normal
+            | EntryKind::Summarized
+            | EntryKind::Archive
+            | EntryKind::InArchive
+            | EntryKind::Unfinished => None,
+            EntryKind::Excluded => Some("excluded"),
+            EntryKind::MountPoint => Some("mount point"),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ScanStats {
     pub total_size: u64,
+    pub total_disk_usage: u64,
     pub total_files: u64,
     pub total_dirs: u64,
+    /// Paths that could not be read; totals are incomplete when non-zero
+    pub error_count: u64,
+    /// Unchanged directories whose files were copied from an earlier scan
+    pub reused_dirs: u64,
+    /// Time spent scanning in this run
+    pub duration: Duration,
+}
+
+/// A path the scanner could not read
+///
+/// Unreadable entries don't contribute to totals, so these record how
+/// incomplete a scan is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanError {
+    pub path: PathBuf,
+    pub kind: ScanErrorKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanErrorKind {
+    PermissionDenied,
+    /// Missing, e.g. removed during the scan or a dangling symlink being followed
+    NotFound,
+    /// Symlink pointing back at one of its ancestors (only when following symlinks)
+    SymlinkLoop,
+    Other,
+}
+
+impl ScanErrorKind {
+    fn from_io(kind: io::ErrorKind) -> Self {
+        match kind {
+            io::ErrorKind::PermissionDenied => ScanErrorKind::PermissionDenied,
+            io::ErrorKind::NotFound => ScanErrorKind::NotFound,
+            _ => ScanErrorKind::Other,
+        }
+    }
+
+    pub fn as_db_str(&self) -> &'static str {
+        match self {
+            ScanErrorKind::PermissionDenied => "permission_denied",
+            ScanErrorKind::NotFound => "not_found",
+            ScanErrorKind::SymlinkLoop => "symlink_loop",
+            ScanErrorKind::Other => "other",
+        }
+    }
+
+    pub fn from_db_str(s: &str) -> Self {
+        match s {
+            "permission_denied" => ScanErrorKind::PermissionDenied,
+            "not_found" => ScanErrorKind::NotFound,
+            "symlink_loop" => ScanErrorKind::SymlinkLoop,
+            _ => ScanErrorKind::Other,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScanErrorKind::PermissionDenied => "permission denied",
+            ScanErrorKind::NotFound => "not found",
+            ScanErrorKind::SymlinkLoop => "symlink loop",
+            ScanErrorKind::Other => "I/O error",
+        }
+    }
+}
+
+impl ScanError {
+    pub fn from_io(path: &Path, error: &io::Error) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            kind: ScanErrorKind::from_io(error.kind()),
+            message: error.to_string(),
+        }
+    }
+
+    /// Build from the parts of a walkdir/jwalk error (both expose the same accessors)
+    /// `fallback` is used when the error doesn't carry a path
+    pub(crate) fn from_walk_error(
+        path: Option<&Path>,
+        loop_ancestor: Option<&Path>,
+        io_error: Option<&io::Error>,
+        fallback: &Path,
+        message: String,
+    ) -> Self {
+        let path = path.unwrap_or(fallback);
+        match (loop_ancestor, io_error) {
+            (Some(ancestor), _) => Self {
+                path: path.to_path_buf(),
+                kind: ScanErrorKind::SymlinkLoop,
+                message: format!("links back to {}", ancestor.display()),
+            },
+            (None, Some(error)) => Self::from_io(path, error),
+            (None, None) => Self {
+                path: path.to_path_buf(),
+                kind: ScanErrorKind::Other,
+                message,
+            },
+        }
+    }
+}
+
+/// Options that control what a scan records
+///
+/// These are persisted with the scan so that resuming or repeating it
+/// uses the same configuration.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScanOptions {
+    pub follow_symlinks: bool,
+    /// Gitignore-style patterns for paths to skip
+    pub exclude: Vec<String>,
+    /// Patterns that re-include paths matched by `exclude`
+    pub include: Vec<String>,
+    /// Don't descend into directories on other filesystems (like `du -x`)
+    pub one_file_system: bool,
+    /// Only store entries down to this depth (the root is depth 0)
+    /// Everything below is still counted towards directory totals
+    pub max_depth: Option<usize>,
+    /// Built from a list of paths (`scan --from-stdin`) rather than a walk of the root
+    pub from_list: bool,
+    /// Format of the export the scan was imported from (e.g. `ncdu`), if it wasn't scanned here
+    pub imported_from: Option<String>,
+    /// How files with several hard links count towards totals
+    pub hard_links: HardLinkPolicy,
+    /// List the members of tar and zip archives as entries below them
+    pub archives: bool,
+}
+
+impl ScanOptions {
+    pub fn from_settings(settings: &crate::settings::ScanSettings) -> Self {
+        Self {
+            follow_symlinks: settings.follow_symlinks,
+            exclude: settings.exclude.clone(),
+            include: settings.include.clone(),
+            one_file_system: settings.one_file_system,
+            max_depth: None,
+            from_list: false,
+            imported_from: None,
+            hard_links: settings.hard_links,
+            archives: settings.archives,
+        }
+    }
+}
+
+/// How a file with several hard links counts towards directory and scan totals
+///
+/// Its own entry always shows its full size; the policy only decides what
+/// each of its paths adds to the directories above it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum HardLinkPolicy {
+    /// At the first of its paths the scan reaches, like `du`
+    #[default]
+    Once,
+    /// At every path, like `du -l`
+    Every,
+    /// Split evenly between its links, so each path carries 1/nlink of it
+    /// (links outside the scan take their share with them)
+    Proportional,
+}
+
+impl HardLinkPolicy {
+    pub fn toggle(&self) -> Self {
+        match self {
+            HardLinkPolicy::Once => HardLinkPolicy::Every,
+            HardLinkPolicy::Every => HardLinkPolicy::Proportional,
+            HardLinkPolicy::Proportional => HardLinkPolicy::Once,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            HardLinkPolicy::Once => "Count once",
+            HardLinkPolicy::Every => "Count every link",
+            HardLinkPolicy::Proportional => "Split between links",
+        }
+    }
+}
+
+/// Applies a [`HardLinkPolicy`] as a scan measures files
+#[derive(Debug, Default)]
+pub(crate) struct LinkAccounting {
+    policy: HardLinkPolicy,
+    /// Device and inode of linked files already counted
+    seen: Mutex<HashSet<(u64, u64)>>,
+}
+
+impl LinkAccounting {
+    pub(crate) fn new(policy: HardLinkPolicy) -> Self {
+        Self {
+            policy,
+            seen: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// The (apparent size, disk usage) a file's path adds to totals
+    pub(crate) fn contribution(
+        &self,
+        stat: Option<&EntryStat>,
+        size: u64,
+        disk_usage: u64,
+    ) -> (u64, u64) {
+        let Some(stat) = stat.filter(|st| st.nlink > 1) else {
+            return (size, disk_usage);
+        };
+        match self.policy {
+            HardLinkPolicy::Every => (size, disk_usage),
+            HardLinkPolicy::Proportional => (size / stat.nlink, disk_usage / stat.nlink),
+            HardLinkPolicy::Once => {
+                if self.seen.lock().unwrap().insert((stat.device, stat.inode)) {
+                    (size, disk_usage)
+                } else {
+                    (0, 0)
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -37,50 +402,445 @@ pub struct ProgressUpdate {
     pub current_path: String,
     /// Currently active directories being scanned (path -> (files_done, total_files))
     pub active_dirs: Vec<(String, usize, usize)>,
-    /// Approximate number of active parallel workers
+    /// Workers' worth of time spent on I/O since the last update
+    /// (the sum of `worker_utilization`, rounded)
     pub active_workers: usize,
+    /// Share of time (0-1) each worker of the scan's pool spent on I/O recently
+    pub worker_utilization: Vec<f64>,
+    /// Still enumerating entries; `files_scanned` counts entries found so far
+    pub gathering: bool,
+    /// Entries the scan expects to record, once it has counted them
+    pub total_estimate: Option<u64>,
+    /// Share of `total_estimate` recorded so far (0-100)
+    pub percent_done: Option<f64>,
+    /// Estimated time left, extrapolated from the rate so far
+    pub eta: Option<Duration>,
+    /// Metadata calls per second over the last second, and the limit, when throttled
+    pub stat_rate: Option<(u64, u32)>,
 }
 
 const BUFFER_SIZE: usize = 1000;
 const PROGRESS_UPDATE_INTERVAL: u64 = 100; // Send progress every N entries
+/// Entries a two-phase scan measures before recording the directories they finish
+const MEASURE_CHUNK_SIZE: usize = 10_000;
 
 /// Scanning implementation to use
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
 pub enum ScannerImpl {
-    /// Custom rayon-based parallel implementation
-    #[allow(dead_code)]
+    /// Recursive walk that reads large directories in parallel
     Custom,
-    /// walkdir-based single-threaded implementation
-    #[allow(dead_code)]
+    /// Single-threaded walkdir traversal
     Walkdir,
-    /// Hybrid: walkdir for traversal + rayon for parallelism
+    /// Parallel jwalk traversal, then parallel stats
     Hybrid,
+    /// Enumerate everything first, then stat in parallel, so progress has a known total
+    #[default]
+    TwoPhase,
+}
+
+impl ScannerImpl {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ScannerImpl::Custom => "custom",
+            ScannerImpl::Walkdir => "walkdir",
+            ScannerImpl::Hybrid => "hybrid",
+            ScannerImpl::TwoPhase => "two_phase",
+        }
+    }
+
+    pub fn toggle(&self) -> Self {
+        match self {
+            ScannerImpl::TwoPhase => ScannerImpl::Hybrid,
+            ScannerImpl::Hybrid => ScannerImpl::Custom,
+            ScannerImpl::Custom => ScannerImpl::Walkdir,
+            ScannerImpl::Walkdir => ScannerImpl::TwoPhase,
+        }
+    }
+}
+
+/// Decides which entries are recorded as markers instead of being scanned
+#[derive(Debug, Clone, Default)]
+struct MarkerRules {
+    root_path: PathBuf,
+    filter: ScanFilter,
+    /// Don't descend into directories on other filesystems
+    one_file_system: bool,
+    /// Device of the scan root, set when staying on one filesystem
+    root_device: Option<u64>,
+}
+
+impl MarkerRules {
+    /// Look up the root's device on the source it's scanned through
+    fn locate_root(&mut self, source: &dyn FsSource) {
+        self.root_device = if self.one_file_system {
+            source
+                .metadata(&self.root_path, true)
+                .ok()
+                .and_then(|m| m.device())
+        } else {
+            None
+        };
+    }
+
+    fn is_empty(&self) -> bool {
+        self.filter.is_empty() && self.root_device.is_none()
+    }
+
+    /// Classify an entry below the scan root
+    /// `metadata` is only consulted for directories in one-file-system mode
+    fn classify(
+        &self,
+        path: &Path,
+        is_dir: bool,
+        metadata: impl FnOnce() -> Option<Metadata>,
+    ) -> EntryKind {
+        if !self.filter.is_empty() {
+            if let Ok(relative) = path.strip_prefix(&self.root_path) {
+                if self.filter.is_excluded(relative, is_dir) {
+                    return EntryKind::Excluded;
+                }
+            }
+        }
+
+        if is_dir && self.root_device.is_some() && path != self.root_path {
+            let device = metadata().and_then(|m| m.device());
+            if device.is_some() && device != self.root_device {
+                return EntryKind::MountPoint;
+            }
+        }
+
+        EntryKind::Normal
+    }
+}
+
+/// Split a list of paths as printed by `find` or `fd` (`-print0`/`-0` when NUL-separated)
+/// Empty entries are skipped and relative paths are made absolute
+pub fn parse_path_list(input: &[u8], null_separated: bool) -> Result<Vec<PathBuf>> {
+    let separator = if null_separated { b'\0' } else { b'\n' };
+    input
+        .split(|byte| *byte == separator)
+        .map(|raw| {
+            if null_separated {
+                raw
+            } else {
+                raw.strip_suffix(b"\r").unwrap_or(raw)
+            }
+        })
+        .filter(|raw| !raw.is_empty())
+        .map(|raw| Ok(std::path::absolute(path_from_bytes(raw))?))
+        .collect()
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Deepest directory containing all of the given paths
+/// A listed directory can be the answer itself (`find .` lists the root first)
+pub fn common_ancestor(paths: &[PathBuf]) -> Option<PathBuf> {
+    let (first, rest) = paths.split_first()?;
+    let mut ancestor = first.as_path();
+    for path in rest {
+        while !path.starts_with(ancestor) {
+            ancestor = ancestor.parent()?;
+        }
+    }
+    if !ancestor.is_dir() {
+        ancestor = ancestor.parent()?;
+    }
+    Some(ancestor.to_path_buf())
+}
+
+/// Roots of a multi-root scan, canonicalized and sorted, and the top they're scanned below
+/// Fails when a root doesn't exist or is inside another root, which would scan it twice
+pub fn resolve_roots(paths: &[PathBuf]) -> Result<(PathBuf, Vec<PathBuf>)> {
+    let mut roots = paths
+        .iter()
+        .map(|path| {
+            path.canonicalize()
+                .with_context(|| format!("Can't scan {}", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    roots.sort();
+    roots.dedup();
+    for root in &roots {
+        if let Some(outer) = roots.iter().find(|o| *o != root && root.starts_with(o)) {
+            bail!("{} is inside {}", root.display(), outer.display());
+        }
+    }
+    let top = common_ancestor(&roots).context("Roots have no common ancestor")?;
+    Ok((top, roots))
+}
+
+/// Running totals shared by the recursive scanner's workers
+#[derive(Debug, Default)]
+struct ScanTotals {
+    size: AtomicU64,
+    disk_usage: AtomicU64,
+    files: AtomicU64,
+    dirs: AtomicU64,
+    reused_dirs: AtomicU64,
+    links: LinkAccounting,
+}
+
+impl ScanTotals {
+    fn new(hard_links: HardLinkPolicy) -> Self {
+        Self {
+            links: LinkAccounting::new(hard_links),
+            ..Default::default()
+        }
+    }
+
+    fn to_stats(&self, error_count: u64) -> ScanStats {
+        ScanStats {
+            total_size: self.size.load(Ordering::Relaxed),
+            total_disk_usage: self.disk_usage.load(Ordering::Relaxed),
+            total_files: self.files.load(Ordering::Relaxed),
+            total_dirs: self.dirs.load(Ordering::Relaxed),
+            error_count,
+            reused_dirs: self.reused_dirs.load(Ordering::Relaxed),
+            duration: Duration::ZERO,
+        }
+    }
+}
+
+/// Directories recorded by an earlier scan of the same root, for incremental rescans
+///
+/// A directory whose inode, mtime and ctime are unchanged still has the same
+/// children, so its files are copied from the earlier scan instead of being
+/// statted again. Subdirectories are always checked, since changes deeper down
+/// don't touch their ancestors. File contents rewritten in place aren't noticed.
+///
+/// Each directory is looked up only when the rescan reaches it, so just one
+/// directory's children are held at a time.
+pub struct PreviousScan {
+    lookup: Box<PreviousLookup>,
+    /// Directories containing unreadable paths, which are never reused so
+    /// their errors are recorded again
+    incomplete: HashSet<PathBuf>,
+}
+
+/// A directory's own entry and its children, by path relative to the earlier scan's root
+pub type PreviousLookup = dyn Fn(&Path) -> Option<(FileEntry, Vec<FileEntry>)> + Send + Sync;
+
+impl PreviousScan {
+    /// Look up directories with `lookup`; one it can't find is scanned afresh
+    pub fn new(
+        unreadable: impl IntoIterator<Item = PathBuf>,
+        lookup: impl Fn(&Path) -> Option<(FileEntry, Vec<FileEntry>)> + Send + Sync + 'static,
+    ) -> Self {
+        let mut incomplete = HashSet::new();
+        for path in unreadable {
+            if let Some(parent) = path.parent() {
+                incomplete.insert(parent.to_path_buf());
+            }
+            incomplete.insert(path);
+        }
+
+        Self {
+            lookup: Box::new(lookup),
+            incomplete,
+        }
+    }
+
+    /// Index the entries of a completed scan held in memory
+    #[cfg(test)]
+    fn from_entries(entries: Vec<FileEntry>) -> Self {
+        let Some(root) = entries
+            .iter()
+            .find(|e| e.depth == 0)
+            .map(|e| e.path.clone())
+        else {
+            return Self::new(Vec::new(), |_| None);
+        };
+
+        let mut dirs: HashMap<PathBuf, (FileEntry, Vec<FileEntry>)> = entries
+            .iter()
+            .filter(|e| e.is_dir)
+            .filter_map(|e| {
+                Some((
+                    e.path.strip_prefix(&root).ok()?.to_path_buf(),
+                    (e.clone(), Vec::new()),
+                ))
+            })
+            .collect();
+        for entry in entries {
+            let parent = entry
+                .parent_path
+                .as_ref()
+                .and_then(|p| p.strip_prefix(&root).ok());
+            if let Some((_, children)) = parent.and_then(|p| dirs.get_mut(p)) {
+                children.push(entry);
+            }
+        }
+
+        Self::new(Vec::new(), move |relative| dirs.get(relative).cloned())
+    }
+
+    /// Children recorded for a directory, if it hasn't changed since
+    fn unchanged_children(
+        &self,
+        relative: &Path,
+        stat: &EntryStat,
+        modified_at: Option<DateTime<Utc>>,
+    ) -> Option<Vec<FileEntry>> {
+        let (dir, children) = (self.lookup)(relative)?;
+        // Summarized directories and markers have no stored children
+        if !dir.is_dir || dir.kind != EntryKind::Normal || self.incomplete.contains(&dir.path) {
+            return None;
+        }
+        let previous = dir.stat?;
+        // Implementations store mtime with different precision, so compare seconds
+        let unchanged = previous.inode == stat.inode
+            && previous.device == stat.device
+            && previous.changed_at.is_some()
+            && previous.changed_at == stat.changed_at
+            && dir.modified_at.map(|t| t.timestamp()) == modified_at.map(|t| t.timestamp());
+        unchanged.then_some(children)
+    }
+}
+
+/// What a paused scan recorded before it stopped, for resuming it
+///
+/// Scans checkpoint each directory once it and everything below it are
+/// recorded (see `Database::get_resume_state`), so resuming skips those whole
+/// instead of loading every path below them. Only paths recorded directly in
+/// directories that weren't finished are listed one by one.
+#[derive(Debug, Clone, Default)]
+pub struct ResumeState {
+    /// Finished directories, none below another
+    completed_dirs: HashSet<PathBuf>,
+    /// Paths recorded in directories that weren't finished
+    recorded: HashSet<String>,
+}
+
+impl ResumeState {
+    /// Keeps only the outermost of `completed_dirs`, since the walk never
+    /// reaches the others
+    pub fn new(
+        completed_dirs: impl IntoIterator<Item = PathBuf>,
+        recorded: impl IntoIterator<Item = String>,
+    ) -> Self {
+        let mut completed_dirs: Vec<PathBuf> = completed_dirs.into_iter().collect();
+        // Ancestors sort before their descendants
+        completed_dirs.sort_by_key(|dir| dir.components().count());
+        let mut outermost = HashSet::new();
+        for dir in completed_dirs {
+            if !dir.ancestors().skip(1).any(|a| outermost.contains(a)) {
+                outermost.insert(dir);
+            }
+        }
+        Self {
+            completed_dirs: outermost,
+            recorded: recorded.into_iter().collect(),
+        }
+    }
+
+    /// Whether `path` was recorded before the pause, so it isn't scanned again
+    pub(crate) fn contains(&self, path: &Path) -> bool {
+        self.completed_dirs.contains(path) || self.recorded.contains(&path.display().to_string())
+    }
+
+    /// Number of paths skipped by their own (finished directories count once)
+    pub fn len(&self) -> usize {
+        self.completed_dirs.len() + self.recorded.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Every path listed was recorded; none are known to be finished directories
+impl From<HashSet<String>> for ResumeState {
+    fn from(recorded: HashSet<String>) -> Self {
+        Self {
+            completed_dirs: HashSet::new(),
+            recorded,
+        }
+    }
+}
+
+/// An entry found by the gather phase of a two-phase scan, not yet statted
+struct GatheredEntry {
+    path: PathBuf,
+    depth: usize,
+    is_dir: bool,
+    kind: EntryKind,
+}
+
+/// What the measure phase learned about a gathered entry
+struct MeasuredEntry {
+    size: u64,
+    disk_usage: u64,
+    modified_at: Option<DateTime<Utc>>,
+    stat: Option<EntryStat>,
 }
 
 pub struct Scanner {
     root_path: PathBuf,
+    /// Depth of `root_path` below the top of a multi-root scan (0 otherwise)
+    base_depth: usize,
     entries: Arc<Mutex<Vec<FileEntry>>>,
+    /// Unreadable paths (buffered until the next flush when streaming)
+    errors: Arc<Mutex<Vec<ScanError>>>,
+    /// Directories finished since the last flush, sent after their entries
+    checkpoints: Arc<Mutex<Vec<PathBuf>>>,
+    /// Files and directories below the depth limit counted since the last
+    /// flush, which are never stored
+    summarized: Arc<Mutex<(u64, u64)>>,
+    /// Number of errors recorded so far
+    error_count: Arc<AtomicU64>,
     sender: Option<mpsc::Sender<crate::db::ActorMessage>>,
     progress_sender: Option<mpsc::UnboundedSender<ProgressUpdate>>,
     entries_processed: Arc<AtomicU64>,
     /// Track active directories: path -> (completed, total)
     active_dirs: Arc<Mutex<HashMap<String, (usize, usize)>>>,
-    /// Approximate count of active parallel workers
-    active_workers: Arc<AtomicUsize>,
-    /// If true, run a simulated demo scan instead of real filesystem scan
-    demo_mode: bool,
+    /// Where the tree is read from
+    source: Arc<dyn FsSource>,
     /// Cancellation flag - when set to true, scanner should stop
     cancelled: Arc<AtomicBool>,
     /// Implementation to use for scanning
     implementation: ScannerImpl,
     /// Whether to follow symbolic links during scanning
     follow_symlinks: bool,
+    /// How files with several hard links count towards totals
+    hard_links: HardLinkPolicy,
+    /// Whether archive members are recorded below their archives
+    archives: bool,
+    /// Deepest level whose entries are stored (None stores everything)
+    max_depth: Option<usize>,
+    /// Earlier scan to copy unchanged directories from
+    previous: Option<PreviousScan>,
+    /// Exclude patterns and mount point detection for marker entries
+    markers: Arc<MarkerRules>,
     /// Calculated total size (updated after scan processing completes)
     calculated_total_size: Arc<AtomicU64>,
     /// Calculated total files (updated after scan processing completes)
     calculated_total_files: Arc<AtomicU64>,
     /// Calculated total dirs (updated after scan processing completes)
     calculated_total_dirs: Arc<AtomicU64>,
+    /// Entries the scan expects to record (0 while unknown)
+    progress_total: Arc<AtomicU64>,
+    /// When entries started being counted against `progress_total`
+    progress_started: Arc<Mutex<Option<Instant>>>,
+    /// Entry count and duration of an earlier scan of the same root
+    prediction: Option<(u64, Duration)>,
+    /// Rate limit on metadata calls, shared by all workers
+    throttle: Option<Arc<Throttle>>,
+    /// Size of the scan's thread pool (0 for one worker per core)
+    threads: usize,
+    /// The scan's thread pool, started when the scan is
+    workers: OnceLock<WorkerPool>,
 }
 
 impl Scanner {
@@ -91,47 +851,40 @@ impl Scanner {
         cancelled: Arc<AtomicBool>,
         follow_symlinks: bool,
     ) -> Self {
-        Self {
-            root_path: root_path.as_ref().to_path_buf(),
-            entries: Arc::new(Mutex::new(Vec::new())),
-            sender: Some(sender),
+        let options = ScanOptions {
+            follow_symlinks,
+            ..Default::default()
+        };
+        Self::from_parts(
+            root_path.as_ref(),
+            Some(sender),
             progress_sender,
-            entries_processed: Arc::new(AtomicU64::new(0)),
-            active_dirs: Arc::new(Mutex::new(HashMap::new())),
-            active_workers: Arc::new(AtomicUsize::new(0)),
-            demo_mode: false,
             cancelled,
-            implementation: ScannerImpl::Hybrid,
-            follow_symlinks,
-            calculated_total_size: Arc::new(AtomicU64::new(0)),
-            calculated_total_files: Arc::new(AtomicU64::new(0)),
-            calculated_total_dirs: Arc::new(AtomicU64::new(0)),
-        }
+            ScannerImpl::TwoPhase,
+            &options,
+            ScanFilter::default(),
+        )
     }
 
-    pub fn with_sender_demo<P: AsRef<Path>>(
+    /// Streaming constructor that applies the full set of scan options
+    /// Fails if the exclude/include patterns cannot be compiled
+    pub fn with_options<P: AsRef<Path>>(
         root_path: P,
         sender: mpsc::Sender<crate::db::ActorMessage>,
         progress_sender: Option<mpsc::UnboundedSender<ProgressUpdate>>,
         cancelled: Arc<AtomicBool>,
-        follow_symlinks: bool,
-    ) -> Self {
-        Self {
-            root_path: root_path.as_ref().to_path_buf(),
-            entries: Arc::new(Mutex::new(Vec::new())),
-            sender: Some(sender),
+        options: &ScanOptions,
+    ) -> Result<Self> {
+        let filter = ScanFilter::new(&options.exclude, &options.include)?;
+        Ok(Self::from_parts(
+            root_path.as_ref(),
+            Some(sender),
             progress_sender,
-            entries_processed: Arc::new(AtomicU64::new(0)),
-            active_dirs: Arc::new(Mutex::new(HashMap::new())),
-            active_workers: Arc::new(AtomicUsize::new(0)),
-            demo_mode: true,
             cancelled,
-            implementation: ScannerImpl::Hybrid,
-            follow_symlinks,
-            calculated_total_size: Arc::new(AtomicU64::new(0)),
-            calculated_total_files: Arc::new(AtomicU64::new(0)),
-            calculated_total_dirs: Arc::new(AtomicU64::new(0)),
-        }
+            ScannerImpl::TwoPhase,
+            options,
+            filter,
+        ))
     }
 
     /// Simple constructor for benchmarking - collects entries in memory
@@ -144,52 +897,375 @@ impl Scanner {
     /// Constructor for benchmarking with a specific implementation
     #[allow(dead_code)]
     pub fn new_with_impl<P: AsRef<Path>>(root_path: P, implementation: ScannerImpl) -> Self {
+        Self::from_parts(
+            root_path.as_ref(),
+            None,
+            None,
+            Arc::new(AtomicBool::new(false)),
+            implementation,
+            &ScanOptions::default(), // Default to not following symlinks for benchmarks
+            ScanFilter::default(),
+        )
+    }
+
+    /// In-memory constructor with a specific implementation and scan options
+    #[allow(dead_code)]
+    pub fn new_with_options<P: AsRef<Path>>(
+        root_path: P,
+        implementation: ScannerImpl,
+        options: &ScanOptions,
+    ) -> Result<Self> {
+        let filter = ScanFilter::new(&options.exclude, &options.include)?;
+        Ok(Self::from_parts(
+            root_path.as_ref(),
+            None,
+            None,
+            Arc::new(AtomicBool::new(false)),
+            implementation,
+            options,
+            filter,
+        ))
+    }
+
+    fn from_parts(
+        root_path: &Path,
+        sender: Option<mpsc::Sender<crate::db::ActorMessage>>,
+        progress_sender: Option<mpsc::UnboundedSender<ProgressUpdate>>,
+        cancelled: Arc<AtomicBool>,
+        implementation: ScannerImpl,
+        options: &ScanOptions,
+        filter: ScanFilter,
+    ) -> Self {
+        let mut markers = MarkerRules {
+            root_path: root_path.to_path_buf(),
+            filter,
+            one_file_system: options.one_file_system,
+            root_device: None,
+        };
+        markers.locate_root(&DiskSource);
         Self {
-            root_path: root_path.as_ref().to_path_buf(),
+            root_path: root_path.to_path_buf(),
+            base_depth: 0,
             entries: Arc::new(Mutex::new(Vec::new())),
-            sender: None,
-            progress_sender: None,
+            errors: Arc::new(Mutex::new(Vec::new())),
+            checkpoints: Arc::new(Mutex::new(Vec::new())),
+            summarized: Arc::new(Mutex::new((0, 0))),
+            error_count: Arc::new(AtomicU64::new(0)),
+            sender,
+            progress_sender,
             entries_processed: Arc::new(AtomicU64::new(0)),
             active_dirs: Arc::new(Mutex::new(HashMap::new())),
-            active_workers: Arc::new(AtomicUsize::new(0)),
-            demo_mode: false,
-            cancelled: Arc::new(AtomicBool::new(false)),
+            source: Arc::new(DiskSource),
+            cancelled,
             implementation,
-            follow_symlinks: false, // Default to false for benchmarks
+            follow_symlinks: options.follow_symlinks,
+            hard_links: options.hard_links,
+            archives: options.archives,
+            max_depth: options.max_depth,
+            previous: None,
+            markers: Arc::new(markers),
             calculated_total_size: Arc::new(AtomicU64::new(0)),
             calculated_total_files: Arc::new(AtomicU64::new(0)),
             calculated_total_dirs: Arc::new(AtomicU64::new(0)),
+            progress_total: Arc::new(AtomicU64::new(0)),
+            progress_started: Arc::new(Mutex::new(None)),
+            prediction: None,
+            throttle: None,
+            threads: 0,
+            workers: OnceLock::new(),
         }
     }
 
-    pub fn scan(&self) -> Result<(Vec<FileEntry>, ScanStats)> {
-        // Check if this is a demo scan
-        if self.demo_mode {
-            return self.demo_scan();
-        }
+    /// Predict progress from an earlier scan of the same root
+    /// Used until the scan knows its own total (two-phase scans count entries first)
+    pub fn predict_from_previous(&mut self, entries: u64, duration: Duration) {
+        self.prediction = Some((entries, duration));
+    }
 
-        // Dispatch based on implementation
-        match self.implementation {
-            ScannerImpl::Custom => self.scan_custom(),
-            ScannerImpl::Walkdir => self.scan_walkdir(),
-            ScannerImpl::Hybrid => self.scan_hybrid(),
-        }
+    /// Note when scanning starts, for durations and predicted ETAs
+    fn start_clock(&self) -> Instant {
+        let started = Instant::now();
+        *self.progress_started.lock().unwrap() = Some(started);
+        started
     }
 
-    fn scan_custom(&self) -> Result<(Vec<FileEntry>, ScanStats)> {
-        let total_size = AtomicU64::new(0);
-        let total_files = AtomicU64::new(0);
-        let total_dirs = AtomicU64::new(0);
+    /// Record how long a scan run took in its stats
+    fn timed(
+        result: Result<(Vec<FileEntry>, ScanStats)>,
+        started: Instant,
+    ) -> Result<(Vec<FileEntry>, ScanStats)> {
+        result.map(|(entries, mut stats)| {
+            stats.duration = started.elapsed();
+            (entries, stats)
+        })
+    }
 
-        self.scan_recursive(&self.root_path, 0, &total_size, &total_files, &total_dirs)?;
+    /// Report progress against a known number of entries from now on
+    fn set_progress_total(&self, total: u64) {
+        self.progress_total.store(total, Ordering::Relaxed);
+        *self.progress_started.lock().unwrap() = Some(Instant::now());
+    }
 
-        // Store calculated stats for progress updates during final flush
-        self.calculated_total_size
-            .store(total_size.load(Ordering::Relaxed), Ordering::Relaxed);
-        self.calculated_total_files
-            .store(total_files.load(Ordering::Relaxed), Ordering::Relaxed);
-        self.calculated_total_dirs
-            .store(total_dirs.load(Ordering::Relaxed), Ordering::Relaxed);
+    /// Total, percentage and time left after `done` entries
+    /// Comes from the scan's own total once known, otherwise from the prediction
+    fn estimate_progress(&self, done: u64) -> (Option<u64>, Option<f64>, Option<Duration>) {
+        let total = self.progress_total.load(Ordering::Relaxed);
+        if total == 0 {
+            return self.predict_progress(done);
+        }
+
+        let percent = (done as f64 / total as f64 * 100.0).min(100.0);
+        let eta = self.progress_started.lock().unwrap().and_then(|started| {
+            if done == 0 {
+                return None;
+            }
+            let remaining = total.saturating_sub(done);
+            Some(started.elapsed().mul_f64(remaining as f64 / done as f64))
+        });
+        (Some(total), Some(percent), eta)
+    }
+
+    /// Assume the scan is as big and as fast as the previous one
+    fn predict_progress(&self, done: u64) -> (Option<u64>, Option<f64>, Option<Duration>) {
+        let Some((expected, duration)) = self.prediction.filter(|(expected, _)| *expected > 0)
+        else {
+            return (None, None, None);
+        };
+
+        let fraction = done as f64 / expected as f64;
+        if fraction >= 1.0 {
+            // Bigger than last time, so there's no telling how much is left
+            return (Some(expected), Some(99.0), None);
+        }
+        (
+            Some(expected),
+            Some(fraction * 100.0),
+            Some(duration.mul_f64(1.0 - fraction)),
+        )
+    }
+
+    /// Expected total and time left while a two-phase scan is still counting
+    fn predict_gathering(&self) -> (Option<u64>, Option<Duration>) {
+        let Some((expected, duration)) = self.prediction else {
+            return (None, None);
+        };
+        let elapsed = self
+            .progress_started
+            .lock()
+            .unwrap()
+            .map(|started| started.elapsed())
+            .unwrap_or_default();
+        (Some(expected), duration.checked_sub(elapsed))
+    }
+
+    /// Make at most `max_per_sec` metadata calls per second across all workers
+    pub fn limit_stats(&mut self, max_per_sec: u32) {
+        self.throttle = Some(Arc::new(Throttle::new(max_per_sec)));
+    }
+
+    /// Scan with a different implementation than the constructor's
+    pub fn set_implementation(&mut self, implementation: ScannerImpl) {
+        self.implementation = implementation;
+    }
+
+    /// Read the tree from `source` instead of the disk
+    pub fn set_source(&mut self, source: Arc<dyn FsSource>) {
+        Arc::make_mut(&mut self.markers).locate_root(source.as_ref());
+        self.source = source;
+    }
+
+    /// Scan on a pool of this many workers (0 for one per core)
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads;
+    }
+
+    /// Start the scan's thread pool, unless it's running already
+    fn start_workers(&self) -> Result<&WorkerPool> {
+        if let Some(workers) = self.workers.get() {
+            return Ok(workers);
+        }
+        let workers = WorkerPool::new(self.threads)?;
+        Ok(self.workers.get_or_init(|| workers))
+    }
+
+    /// Run `f` on the scan's thread pool
+    fn in_pool<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        match self.workers.get() {
+            Some(workers) => workers.install(f),
+            None => f(),
+        }
+    }
+
+    /// Run an I/O call, counting its time towards the worker making it
+    fn io<T>(&self, call: impl FnOnce() -> T) -> T {
+        match self.workers.get() {
+            Some(workers) => workers.time(call),
+            None => call(),
+        }
+    }
+
+    /// Run a metadata call, waiting for the throttle first
+    fn stat<T>(&self, call: impl FnOnce() -> T) -> T {
+        if let Some(throttle) = &self.throttle {
+            throttle.wait();
+        }
+        self.io(call)
+    }
+
+    /// Busy-equivalent worker count and each worker's utilization
+    fn worker_load(&self) -> (usize, Vec<f64>) {
+        let Some(workers) = self.workers.get() else {
+            return (0, Vec::new());
+        };
+        let utilization = workers.utilization();
+        let busy = utilization.iter().sum::<f64>().round() as usize;
+        (busy, utilization)
+    }
+
+    /// Metadata from the source, following symlinks if the scan does
+    fn path_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.stat(|| self.source.metadata(path, self.follow_symlinks))
+    }
+
+    /// Current and maximum metadata call rate, when throttled
+    fn stat_rate(&self) -> Option<(u64, u32)> {
+        self.throttle
+            .as_ref()
+            .map(|throttle| (throttle.rate(), throttle.limit()))
+    }
+
+    /// Copy unchanged directories from an earlier scan of the same root
+    /// Incremental scans always use the recursive implementation, which visits
+    /// one directory at a time
+    pub fn reuse_unchanged(&mut self, previous: PreviousScan) {
+        self.previous = Some(previous);
+    }
+
+    fn record_error(&self, error: ScanError) {
+        self.errors.lock().unwrap().push(error);
+        self.error_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Whether entries at this depth are stored (deeper ones are only counted)
+    fn stores_depth(&self, depth: usize) -> bool {
+        self.max_depth.is_none_or(|max| depth <= max)
+    }
+
+    /// Drop entries below the depth limit, marking directories at the limit
+    /// whose contents were dropped as summarized
+    fn summarize_below_max_depth(&self, entries: &mut Vec<FileEntry>) {
+        let Some(max_depth) = self.max_depth else {
+            return;
+        };
+
+        let summarized: HashSet<PathBuf> = entries
+            .iter()
+            .filter(|e| e.depth == max_depth + 1)
+            .filter_map(|e| e.parent_path.clone())
+            .collect();
+        for entry in entries.iter().filter(|e| e.depth > max_depth) {
+            if !entry.kind.is_marker() {
+                self.count_summarized(entry.is_dir);
+            }
+        }
+        entries.retain(|e| e.depth <= max_depth);
+        for entry in entries.iter_mut() {
+            if entry.depth == max_depth && entry.is_dir && summarized.contains(&entry.path) {
+                entry.kind = EntryKind::Summarized;
+            }
+        }
+    }
+
+    /// The entry followed by its archive members, when it's an archive to list
+    /// Members deeper than the depth limit aren't stored, and an archive that
+    /// can't be read is kept as a plain file.
+    fn with_archive_members(&self, mut entry: FileEntry) -> Vec<FileEntry> {
+        // Archives reused from an earlier scan are listed again
+        let listable = self.archives
+            && !entry.is_dir
+            && matches!(entry.kind, EntryKind::Normal | EntryKind::Archive)
+            && self.stores_depth(entry.depth + 1);
+        let Some(format) = listable
+            .then(|| crate::archive::ArchiveFormat::from_name(&entry.name))
+            .flatten()
+        else {
+            return vec![entry];
+        };
+        let members = self
+            .source
+            .open(&entry.path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| crate::archive::member_entries(&entry, format, file));
+        match members {
+            Ok(mut members) => {
+                members.retain(|member| self.stores_depth(member.depth));
+                entry.kind = EntryKind::Archive;
+                let mut entries = Vec::with_capacity(members.len() + 1);
+                entries.push(entry);
+                entries.extend(members);
+                entries
+            }
+            Err(_) => {
+                entry.kind = EntryKind::Normal;
+                vec![entry]
+            }
+        }
+    }
+
+    /// Errors recorded by an in-memory scan (streaming scans send them to the database)
+    #[allow(dead_code)]
+    pub fn errors(&self) -> Vec<ScanError> {
+        self.errors.lock().unwrap().clone()
+    }
+
+    /// Build the placeholder entry recorded in place of a path that isn't scanned
+    fn marker_entry(path: &Path, is_dir: bool, depth: usize, kind: EntryKind) -> FileEntry {
+        FileEntry {
+            path: path.to_path_buf(),
+            name: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            parent_path: path.parent().map(|p| p.to_path_buf()),
+            size: 0,
+            disk_usage: 0,
+            is_dir,
+            modified_at: None,
+            depth,
+            kind,
+            stat: None,
+        }
+    }
+
+    pub fn scan(&self) -> Result<(Vec<FileEntry>, ScanStats)> {
+        self.start_workers()?;
+        let started = self.start_clock();
+        Self::timed(self.run_scan(), started)
+    }
+
+    fn run_scan(&self) -> Result<(Vec<FileEntry>, ScanStats)> {
+        if self.previous.is_some() {
+            return self.scan_custom();
+        }
+
+        // Dispatch based on implementation
+        match self.implementation {
+            ScannerImpl::Custom => self.scan_custom(),
+            ScannerImpl::Walkdir => self.scan_walkdir(),
+            ScannerImpl::Hybrid => self.scan_hybrid(),
+            ScannerImpl::TwoPhase => self.scan_two_phase(None),
+        }
+    }
+
+    fn scan_custom(&self) -> Result<(Vec<FileEntry>, ScanStats)> {
+        let totals = ScanTotals::new(self.hard_links);
+
+        self.in_pool(|| self.scan_recursive(&self.root_path, self.base_depth, &totals, None))?;
+
+        // Store calculated stats for progress updates during final flush
+        let stats = totals.to_stats(self.error_count.load(Ordering::Relaxed));
+        self.store_calculated_stats(&stats);
 
         // Final flush of any remaining buffered entries
         self.flush_buffer()?;
@@ -202,73 +1278,110 @@ impl Scanner {
             self.entries.lock().unwrap().clone()
         };
 
-        let stats = ScanStats {
-            total_size: total_size.load(Ordering::Relaxed),
-            total_files: total_files.load(Ordering::Relaxed),
-            total_dirs: total_dirs.load(Ordering::Relaxed),
-        };
-
         Ok((entries, stats))
     }
 
-    fn scan_walkdir(&self) -> Result<(Vec<FileEntry>, ScanStats)> {
-        use walkdir::WalkDir;
+    fn store_calculated_stats(&self, stats: &ScanStats) {
+        self.calculated_total_size
+            .store(stats.total_size, Ordering::Relaxed);
+        self.calculated_total_files
+            .store(stats.total_files, Ordering::Relaxed);
+        self.calculated_total_dirs
+            .store(stats.total_dirs, Ordering::Relaxed);
+    }
 
+    fn scan_walkdir(&self) -> Result<(Vec<FileEntry>, ScanStats)> {
         let mut total_size = 0u64;
+        let mut total_disk_usage = 0u64;
         let mut total_files = 0u64;
         let mut total_dirs = 0u64;
 
-        // Build a map to track directory sizes
+        // Build maps to track directory sizes (apparent and allocated)
         let mut dir_sizes: HashMap<PathBuf, u64> = HashMap::new();
+        let mut dir_disk_usage: HashMap<PathBuf, u64> = HashMap::new();
         let mut entries = Vec::new();
+        let links = LinkAccounting::new(self.hard_links);
 
         // First pass: collect all entries and calculate file sizes
-        let walker = WalkDir::new(&self.root_path)
-            .follow_links(self.follow_symlinks)
-            .into_iter()
-            .filter_map(|e| e.ok());
+        for entry in self.source.walk(&self.root_path, &self.walk_options(None)) {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(e) => {
+                    self.record_error(e);
+                    continue;
+                }
+            };
 
-        for entry in walker {
             if self.cancelled.load(Ordering::Relaxed) {
                 return Err(anyhow::anyhow!("Scan cancelled"));
             }
 
-            let path = entry.path().to_path_buf();
-            let metadata = match entry.metadata() {
-                Ok(m) => m,
-                Err(_) => continue,
-            };
-
             // Skip symlinks if follow_symlinks is false
-            if !self.follow_symlinks && metadata.is_symlink() {
+            if !self.follow_symlinks && entry.is_symlink {
+                continue;
+            }
+
+            // Excluded paths and foreign mounts are recorded as markers and not descended into
+            let path = entry.path;
+            let depth = entry.depth + self.base_depth;
+            if entry.kind.is_marker() {
+                entries.push(Self::marker_entry(&path, entry.is_dir, depth, entry.kind));
                 continue;
             }
 
-            let is_dir = metadata.is_dir();
-            let file_size = if is_dir { 0 } else { metadata.len() };
+            let metadata = match self.path_metadata(&path) {
+                Ok(m) => m,
+                Err(e) => {
+                    self.record_error(ScanError::from_io(&path, &e));
+                    continue;
+                }
+            };
+
+            let is_dir = metadata.is_dir;
+            let file_size = if is_dir { 0 } else { metadata.len };
+            let disk_usage = metadata.disk_usage;
+            let stat = metadata.stat;
+            let (counted_size, counted_usage) = if is_dir {
+                (0, disk_usage)
+            } else {
+                links.contribution(stat.as_ref(), file_size, disk_usage)
+            };
 
             if is_dir {
                 total_dirs += 1;
                 dir_sizes.insert(path.clone(), 0);
             } else {
                 total_files += 1;
-                total_size += file_size;
+                total_size += counted_size;
 
                 // Add file size to all parent directories
                 let mut current_parent = path.parent();
                 while let Some(parent) = current_parent {
-                    *dir_sizes.entry(parent.to_path_buf()).or_insert(0) += file_size;
+                    *dir_sizes.entry(parent.to_path_buf()).or_insert(0) += counted_size;
                     current_parent = parent.parent();
                 }
             }
 
-            let name = entry.file_name().to_string_lossy().to_string();
+            // Allocated blocks count towards the directory itself and all its
+            // ancestors, like `du` does
+            total_disk_usage += counted_usage;
+            let mut current_dir = if is_dir {
+                Some(path.as_path())
+            } else {
+                path.parent()
+            };
+            while let Some(dir) = current_dir {
+                *dir_disk_usage.entry(dir.to_path_buf()).or_insert(0) += counted_usage;
+                current_dir = dir.parent();
+            }
+
+            let name = path
+                .file_name()
+                .unwrap_or(path.as_os_str())
+                .to_string_lossy()
+                .to_string();
             let parent_path = path.parent().map(|p| p.to_path_buf());
-            let modified_at = metadata.modified().ok().map(|t| {
-                let system_time: std::time::SystemTime = t;
-                DateTime::<Utc>::from(system_time)
-            });
-            let depth = entry.depth();
+            let modified_at = metadata.modified_at;
 
             entries.push(FileEntry {
                 path: path.clone(),
@@ -279,95 +1392,259 @@ impl Scanner {
                 } else {
                     file_size
                 },
+                disk_usage,
                 is_dir,
                 modified_at,
                 depth,
+                kind: EntryKind::Normal,
+                stat,
             });
         }
 
         // Second pass: update directory sizes
         for entry in &mut entries {
-            if entry.is_dir {
+            if entry.is_dir && !entry.kind.is_marker() {
                 entry.size = *dir_sizes.get(&entry.path).unwrap_or(&0);
+                entry.disk_usage = *dir_disk_usage.get(&entry.path).unwrap_or(&0);
             }
         }
+        self.summarize_below_max_depth(&mut entries);
 
         let stats = ScanStats {
             total_size,
+            total_disk_usage,
             total_files,
             total_dirs,
+            error_count: self.error_count.load(Ordering::Relaxed),
+            reused_dirs: 0,
+            duration: Duration::ZERO,
         };
 
-        // Store entries if not streaming
-        if self.sender.is_none() {
-            *self.entries.lock().unwrap() = entries.clone();
+        // Add all entries via add_entry to handle streaming (and list archive
+        // members). In reverse walk order each directory comes after everything
+        // below it and is checkpointed as it's added.
+        for entry in entries.into_iter().rev() {
+            if self.cancelled.load(Ordering::Relaxed) {
+                self.flush_buffer()?;
+                return Err(anyhow::anyhow!("Scan cancelled"));
+            }
+            let finished = (entry.is_dir && !entry.kind.is_marker()).then(|| entry.path.clone());
+            self.add_entry(entry);
+            if let Some(dir) = finished {
+                self.checkpoint(&dir);
+            }
         }
 
+        // Flush any remaining buffered entries
+        self.flush_buffer()?;
+
+        let entries = if self.sender.is_some() {
+            Vec::new()
+        } else {
+            std::mem::take(&mut *self.entries.lock().unwrap())
+        };
+
         Ok((entries, stats))
     }
 
+    /// How the source walks the root: markers are tagged and not descended into,
+    /// parallel walks read directories on the scan's pool, and listed directories
+    /// become active until the walk has yielded all of their children, and
+    /// directories a resumed scan already finished are skipped whole
+    fn walk_options(&self, resume: Option<&ResumeState>) -> WalkOptions {
+        let classify: Option<crate::source::Classify> = if self.markers.is_empty() {
+            None
+        } else {
+            let markers = self.markers.clone();
+            let throttle = self.throttle.clone();
+            Some(Arc::new(move |path, is_dir, metadata| {
+                markers.classify(path, is_dir, || {
+                    if let Some(throttle) = &throttle {
+                        throttle.wait();
+                    }
+                    metadata()
+                })
+            }))
+        };
+        let on_read_dir: Option<crate::source::OnReadDir> = if self.progress_sender.is_some() {
+            let active_dirs = self.active_dirs.clone();
+            Some(Arc::new(move |dir, children| {
+                if children > 0 {
+                    let mut active = active_dirs.lock().unwrap();
+                    active.insert(dir.display().to_string(), (0, children));
+                }
+            }))
+        } else {
+            None
+        };
+        let skip_dir: Option<crate::source::SkipDir> = resume
+            .filter(|resume| !resume.completed_dirs.is_empty())
+            .map(|resume| {
+                let completed = resume.completed_dirs.clone();
+                Arc::new(move |dir: &Path| completed.contains(dir)) as crate::source::SkipDir
+            });
+        WalkOptions {
+            follow_symlinks: self.follow_symlinks,
+            classify,
+            parallelism: self.workers.get().map(WorkerPool::walk_parallelism),
+            on_read_dir,
+            skip_dir,
+        }
+    }
+
+    /// Count a walked entry against its directory's listing, retiring the
+    /// directory once all of its children are in
+    fn count_walked(&self, path: &Path) {
+        if self.progress_sender.is_none() {
+            return;
+        }
+        let Some(parent) = path.parent() else {
+            return;
+        };
+        let key = parent.display().to_string();
+        let mut active = self.active_dirs.lock().unwrap();
+        if let Some((done, total)) = active.get_mut(&key) {
+            *done += 1;
+            if *done >= *total {
+                active.remove(&key);
+            }
+        }
+    }
+
+    /// Directories being worked through, those furthest along first
+    fn active_dirs_snapshot(&self) -> Vec<(String, usize, usize)> {
+        let mut snapshot: Vec<(String, usize, usize)> = {
+            let active = self.active_dirs.lock().unwrap();
+            active
+                .iter()
+                .map(|(path, (done, total))| (path.clone(), *done, *total))
+                .collect()
+        };
+        snapshot.sort_by(|a, b| {
+            let progress = |(_, done, total): &(String, usize, usize)| *done as f64 / *total as f64;
+            progress(b)
+                .total_cmp(&progress(a))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        snapshot
+    }
+
     fn scan_hybrid(&self) -> Result<(Vec<FileEntry>, ScanStats)> {
-        use jwalk::WalkDir;
+        self.scan_hybrid_impl(None)
+    }
+
+    /// Hybrid scan implementation with resume support (skips already-scanned paths)
+    fn scan_hybrid_resuming(&self, resume: &ResumeState) -> Result<(Vec<FileEntry>, ScanStats)> {
+        self.scan_hybrid_impl(Some(resume))
+    }
+
+    fn scan_hybrid_impl(
+        &self,
+        resume: Option<&ResumeState>,
+    ) -> Result<(Vec<FileEntry>, ScanStats)> {
         use rayon::prelude::*;
         use std::sync::atomic::{AtomicU64, AtomicUsize};
 
-        // Use jwalk to traverse in PARALLEL (faster than single-threaded walkdir)!
-        let walker = WalkDir::new(&self.root_path)
-            .follow_links(self.follow_symlinks)
-            .skip_hidden(false) // Include hidden files (files starting with .)
-            .into_iter()
-            .filter_map(|e| e.ok());
+        let walker = self
+            .source
+            .parallel_walk(&self.root_path, &self.walk_options(resume))
+            .filter_map(|e| match e {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    self.record_error(e);
+                    None
+                }
+            });
 
-        // Collect all entries (jwalk does parallel traversal internally)
-        // Also track progress during collection
-        let mut all_entries: Vec<_> = Vec::new();
+        // Collect all entries (jwalk does parallel traversal internally), each
+        // statted once. Also track progress during collection
+        let mut all_entries: Vec<(WalkEntry, Option<Metadata>)> = Vec::new();
         let mut count = 0;
         let mut current_dir = String::new();
         let mut running_size = 0u64;
 
-        for entry in walker {
+        // What each file adds to totals, decided in walk order so that hard
+        // links are credited to the same path whichever worker sees them
+        let links = LinkAccounting::new(self.hard_links);
+        let mut counted: Vec<(u64, u64)> = Vec::new();
+
+        for mut entry in walker {
             // Check if cancelled
             if self.cancelled.load(Ordering::Relaxed) {
+                self.active_dirs.lock().unwrap().clear();
                 return Err(anyhow::anyhow!("Scan cancelled"));
             }
+            self.count_walked(&entry.path);
+            entry.depth += self.base_depth;
+
+            // Skip already-scanned paths when resuming
+            if resume.is_some_and(|resume| resume.contains(&entry.path)) {
+                continue;
+            }
 
             // Filter out symlinks if follow_symlinks is false
-            if !self.follow_symlinks && entry.file_type().is_symlink() {
+            if !self.follow_symlinks && entry.is_symlink {
                 continue;
             }
 
+            let is_marker = entry.kind.is_marker();
+
             // Track current directory being scanned
-            if entry.file_type().is_dir() {
-                current_dir = entry.path().display().to_string();
+            if entry.is_dir && !is_marker {
+                current_dir = entry.path.display().to_string();
             }
 
+            let metadata = if is_marker {
+                None
+            } else {
+                match self.path_metadata(&entry.path) {
+                    Ok(metadata) => Some(metadata),
+                    Err(e) => {
+                        if !entry.is_dir {
+                            self.record_error(ScanError::from_io(&entry.path, &e));
+                        }
+                        None
+                    }
+                }
+            };
+
             // Track size for files
-            if !entry.file_type().is_dir() {
-                if let Ok(metadata) = entry.metadata() {
-                    running_size += metadata.len();
+            counted.push(match &metadata {
+                Some(m) if !entry.is_dir => {
+                    running_size += m.len;
+                    links.contribution(m.stat.as_ref(), m.len, m.disk_usage)
                 }
-            }
+                _ => (0, 0),
+            });
 
-            all_entries.push(entry);
+            all_entries.push((entry, metadata));
 
             // Send progress updates periodically during collection
             count += 1;
             if let Some(progress_tx) = &self.progress_sender {
                 if count % PROGRESS_UPDATE_INTERVAL == 0 {
-                    // For hybrid scanner, we don't have per-directory progress,
-                    // but we can show which directory we're currently processing
+                    let (active_workers, worker_utilization) = self.worker_load();
                     let _ = progress_tx.send(ProgressUpdate {
                         files_scanned: count,
                         dirs_scanned: 0,
                         total_size: running_size,
                         current_path: current_dir.clone(),
-                        active_dirs: vec![], // No per-directory progress for hybrid
-                        active_workers: 1,   // jwalk uses internal parallelism
+                        active_dirs: self.active_dirs_snapshot(),
+                        active_workers,
+                        worker_utilization,
+                        gathering: false,
+                        total_estimate: None,
+                        percent_done: None,
+                        eta: None,
+                        stat_rate: self.stat_rate(),
                     });
                 }
             }
         }
 
+        // Directories the walk errored out of never see all their children
+        self.active_dirs.lock().unwrap().clear();
+
         // Check if cancelled after collection phase
         if self.cancelled.load(Ordering::Relaxed) {
             return Err(anyhow::anyhow!("Scan cancelled"));
@@ -377,105 +1654,83 @@ impl Scanner {
         let total_files = AtomicUsize::new(0);
         let total_dirs = AtomicUsize::new(0);
         let total_size = AtomicU64::new(0);
+        let total_disk_usage = AtomicU64::new(0);
 
-        // First pass: build inode -> (size, first_path) map to detect hard links
-        #[cfg(unix)]
-        let inode_map: HashMap<u64, (u64, PathBuf)> = {
-            use std::os::unix::fs::MetadataExt;
-            let mut map = HashMap::new();
-            for entry in &all_entries {
-                if !entry.file_type().is_dir() {
-                    if let Ok(metadata) = entry.metadata() {
-                        let inode = metadata.ino();
-                        let size = metadata.len();
-                        // Canonicalize path to handle symlinks like /tmp -> /private/tmp
-                        let path = entry
-                            .path()
-                            .canonicalize()
-                            .unwrap_or_else(|_| entry.path().to_path_buf());
-                        // Only store first occurrence of each inode
-                        map.entry(inode).or_insert((size, path));
-                    }
-                }
-            }
-            map
-        };
+        // Directory map values are (apparent size, disk usage)
+        let (dir_sizes, final_files, final_dirs, final_size, final_disk_usage) =
+            self.in_pool(|| {
+                all_entries
+                    .par_iter()
+                    .zip(&counted)
+                    .fold(
+                        || (HashMap::new(), 0, 0, 0u64, 0u64), // (dir_map, files, dirs, size, disk_usage)
+                        |(mut map, mut files, mut dirs, mut size, mut disk_usage),
+                         ((entry, metadata), &(file_size, file_usage))| {
+                            // Markers don't count towards any totals
+                            if entry.kind.is_marker() {
+                                return (map, files, dirs, size, disk_usage);
+                            }
 
-        let (dir_sizes, final_files, final_dirs, final_size) = all_entries
-            .par_iter()
-            .fold(
-                || (HashMap::new(), 0, 0, 0u64), // (dir_map, files, dirs, size)
-                |(mut map, mut files, mut dirs, mut size), entry| {
-                    let is_dir = entry.file_type().is_dir();
+                            let is_dir = entry.is_dir;
 
-                    // Update stats inline
-                    if is_dir {
-                        dirs += 1;
-                    } else {
-                        files += 1;
-
-                        // Check if this is the first occurrence of this inode (Unix only)
-                        #[cfg(unix)]
-                        let (file_size, is_first_occurrence) = {
-                            use std::os::unix::fs::MetadataExt;
-                            if let Ok(metadata) = entry.metadata() {
-                                let inode = metadata.ino();
-                                // Canonicalize path to handle symlinks like /tmp -> /private/tmp
-                                let current_path = entry
-                                    .path()
-                                    .canonicalize()
-                                    .unwrap_or_else(|_| entry.path().to_path_buf());
-                                // This is the first occurrence if the stored path matches current path
-                                let is_first = inode_map
-                                    .get(&inode)
-                                    .map(|(_, first_path)| first_path == &current_path)
-                                    .unwrap_or(true);
-                                (metadata.len(), is_first)
+                            // Update stats inline
+                            if is_dir {
+                                dirs += 1;
+
+                                // A directory's own blocks count towards itself and its ancestors (like du)
+                                let own_usage = metadata.as_ref().map_or(0, |m| m.disk_usage);
+                                disk_usage += own_usage;
+                                let mut current = Some(entry.path.as_path());
+                                while let Some(dir) = current {
+                                    map.entry(dir.to_path_buf()).or_insert((0, 0)).1 += own_usage;
+                                    current = dir.parent();
+                                }
                             } else {
-                                (0, true)
+                                files += 1;
+
+                                size += file_size;
+                                disk_usage += file_usage;
+
+                                // Calculate dir contributions for this file
+                                let mut current = entry.path.parent();
+                                while let Some(parent) = current {
+                                    let totals = map.entry(parent.to_path_buf()).or_insert((0, 0));
+                                    totals.0 += file_size;
+                                    totals.1 += file_usage;
+                                    current = parent.parent();
+                                }
                             }
-                        };
-
-                        // On non-Unix, always count the file
-                        #[cfg(not(unix))]
-                        let (file_size, is_first_occurrence) = {
-                            let sz = entry.metadata().ok().map(|m| m.len()).unwrap_or(0);
-                            (sz, true)
-                        };
-
-                        // Only add to size totals if this is the first occurrence of this inode
-                        if is_first_occurrence {
-                            size += file_size;
-
-                            // Calculate dir contributions for this file
-                            let path = entry.path();
-                            let mut current = path.parent();
-                            while let Some(parent) = current {
-                                *map.entry(parent.to_path_buf()).or_insert(0) += file_size;
-                                current = parent.parent();
+                            (map, files, dirs, size, disk_usage)
+                        },
+                    )
+                    .reduce(
+                        || (HashMap::new(), 0, 0, 0u64, 0u64),
+                        |(mut map_a, files_a, dirs_a, size_a, usage_a),
+                         (map_b, files_b, dirs_b, size_b, usage_b)| {
+                            // Merge directory size maps
+                            for (path, (size, usage)) in map_b {
+                                let totals = map_a.entry(path).or_insert((0, 0));
+                                totals.0 += size;
+                                totals.1 += usage;
                             }
-                        }
-                    }
-                    (map, files, dirs, size)
-                },
-            )
-            .reduce(
-                || (HashMap::new(), 0, 0, 0u64),
-                |(mut map_a, files_a, dirs_a, size_a), (map_b, files_b, dirs_b, size_b)| {
-                    // Merge directory size maps
-                    for (path, size) in map_b {
-                        *map_a.entry(path).or_insert(0) += size;
-                    }
 
-                    // Merge stats (accumulate in returned tuple, not atomics!)
-                    (map_a, files_a + files_b, dirs_a + dirs_b, size_a + size_b)
-                },
-            );
+                            // Merge stats (accumulate in returned tuple, not atomics!)
+                            (
+                                map_a,
+                                files_a + files_b,
+                                dirs_a + dirs_b,
+                                size_a + size_b,
+                                usage_a + usage_b,
+                            )
+                        },
+                    )
+            });
 
         // Store final totals in atomics (do this ONCE, not during every reduce!)
         total_files.store(final_files, Ordering::Relaxed);
         total_dirs.store(final_dirs, Ordering::Relaxed);
         total_size.store(final_size, Ordering::Relaxed);
+        total_disk_usage.store(final_disk_usage, Ordering::Relaxed);
 
         // Also store in the Scanner's fields for progress updates during streaming
         self.calculated_total_size
@@ -491,67 +1746,81 @@ impl Scanner {
         }
 
         // Single-pass parallel FileEntry construction (now just one parallel op!)
-        let entries: Vec<FileEntry> = all_entries
-            .par_iter()
-            .map(|entry| {
-                let path = entry.path().to_path_buf();
-                let metadata = entry.metadata().ok();
-                let is_dir = entry.file_type().is_dir();
-
-                let name = if path == self.root_path {
-                    self.root_path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("/")
-                        .to_string()
-                } else {
-                    path.file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("")
-                        .to_string()
-                };
-
-                let parent_path = path.parent().map(|p| p.to_path_buf());
-                let modified_at = metadata.as_ref().and_then(|m| {
-                    m.modified().ok().map(|t| {
-                        let system_time: std::time::SystemTime = t;
-                        DateTime::<Utc>::from(system_time)
-                    })
-                });
+        let mut entries: Vec<FileEntry> = self.in_pool(|| {
+            all_entries
+                .par_iter()
+                .map(|(entry, metadata)| {
+                    let path = entry.path.clone();
+                    let is_dir = entry.is_dir;
+
+                    if entry.kind.is_marker() {
+                        return Self::marker_entry(&path, is_dir, entry.depth, entry.kind);
+                    }
 
-                let file_size = if is_dir {
-                    0
-                } else {
-                    metadata.as_ref().map(|m| m.len()).unwrap_or(0)
-                };
+                    let name = if path == self.root_path {
+                        self.root_path
+                            .file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("/")
+                            .to_string()
+                    } else {
+                        path.file_name()
+                            .and_then(|n| n.to_str())
+                            .unwrap_or("")
+                            .to_string()
+                    };
 
-                let size = if is_dir {
-                    *dir_sizes.get(&path).unwrap_or(&0)
-                } else {
-                    file_size
-                };
+                    let parent_path = path.parent().map(|p| p.to_path_buf());
+                    let modified_at = metadata.as_ref().and_then(|m| m.modified_at);
 
-                FileEntry {
-                    path,
-                    name,
-                    parent_path,
-                    size,
-                    is_dir,
-                    modified_at,
-                    depth: entry.depth(),
-                }
-            })
-            .collect();
+                    let (size, disk_usage) = if is_dir {
+                        dir_sizes.get(&path).copied().unwrap_or((0, 0))
+                    } else {
+                        metadata
+                            .as_ref()
+                            .map(|m| (m.len, m.disk_usage))
+                            .unwrap_or((0, 0))
+                    };
+
+                    FileEntry {
+                        path,
+                        name,
+                        parent_path,
+                        size,
+                        disk_usage,
+                        is_dir,
+                        modified_at,
+                        depth: entry.depth,
+                        kind: EntryKind::Normal,
+                        stat: metadata.as_ref().and_then(|m| m.stat),
+                    }
+                })
+                .collect()
+        });
 
         let stats = ScanStats {
             total_size: total_size.load(Ordering::Relaxed),
+            total_disk_usage: total_disk_usage.load(Ordering::Relaxed),
             total_files: total_files.load(Ordering::Relaxed) as u64,
             total_dirs: total_dirs.load(Ordering::Relaxed) as u64,
+            error_count: self.error_count.load(Ordering::Relaxed),
+            reused_dirs: 0,
+            duration: Duration::ZERO,
         };
+        self.summarize_below_max_depth(&mut entries);
 
-        // Add all entries via add_entry to handle streaming
-        for entry in &entries {
+        // Add all entries via add_entry to handle streaming. The walk yields
+        // directories before their contents, so in reverse each directory comes
+        // after everything below it and is checkpointed as it's added.
+        for entry in entries.iter().rev() {
+            if self.cancelled.load(Ordering::Relaxed) {
+                self.flush_buffer()?;
+                return Err(anyhow::anyhow!("Scan cancelled"));
+            }
             self.add_entry(entry.clone());
+            if entry.is_dir && !entry.kind.is_marker() {
+                self.checkpoint(&entry.path);
+            }
         }
 
         // Flush any remaining buffered entries
@@ -561,304 +1830,660 @@ impl Scanner {
             // If streaming, we already sent everything, return empty vec
             Vec::new()
         } else {
-            entries
+            // Buffered by add_entry, along with any archive members
+            std::mem::take(&mut *self.entries.lock().unwrap())
         };
 
         Ok((final_entries, stats))
     }
 
-    /// Hybrid scan implementation with resume support (skips already-scanned paths)
-    fn scan_hybrid_resuming(
-        &self,
-        scanned_paths: std::collections::HashSet<String>,
-    ) -> Result<(Vec<FileEntry>, ScanStats)> {
-        use jwalk::WalkDir;
-        use rayon::prelude::*;
-        use std::sync::atomic::{AtomicU64, AtomicUsize};
-
-        // Use jwalk to traverse in PARALLEL
-        let walker = WalkDir::new(&self.root_path)
-            .follow_links(self.follow_symlinks)
-            .skip_hidden(false)
-            .into_iter()
-            .filter_map(|e| e.ok());
-
-        // Collect entries, skipping already-scanned paths
-        let mut all_entries: Vec<_> = Vec::new();
-        let mut count = 0;
+    /// Two-phase scan: enumerate every entry without statting it, then measure
+    /// them in parallel
+    ///
+    /// Enumeration only reads directories, so it is quick, and once it is done
+    /// progress updates carry a total, a percentage and an ETA. Files are
+    /// recorded as they are measured; directories follow once their sizes are known.
+    fn scan_two_phase(&self, resume: Option<&ResumeState>) -> Result<(Vec<FileEntry>, ScanStats)> {
+        // Phase 1: gather
+        let mut gathered = Vec::new();
         let mut current_dir = String::new();
-        let mut running_size = 0u64;
-
-        for entry in walker {
-            // Check if cancelled
+        for entry in self
+            .source
+            .parallel_walk(&self.root_path, &self.walk_options(resume))
+        {
             if self.cancelled.load(Ordering::Relaxed) {
+                self.active_dirs.lock().unwrap().clear();
                 return Err(anyhow::anyhow!("Scan cancelled"));
             }
 
-            // Skip already-scanned paths
-            let path_str = entry.path().display().to_string();
-            if scanned_paths.contains(&path_str) {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    self.record_error(e);
+                    continue;
+                }
+            };
+            self.count_walked(&entry.path);
+            if !self.follow_symlinks && entry.is_symlink {
                 continue;
             }
-
-            // Filter out symlinks if follow_symlinks is false
-            if !self.follow_symlinks && entry.file_type().is_symlink() {
+            let path = entry.path;
+            if resume.is_some_and(|resume| resume.contains(&path)) {
                 continue;
             }
 
-            // Track current directory being scanned
-            if entry.file_type().is_dir() {
-                current_dir = entry.path().display().to_string();
+            let is_dir = entry.is_dir;
+            if is_dir {
+                current_dir = path.display().to_string();
             }
+            gathered.push(GatheredEntry {
+                path,
+                depth: entry.depth + self.base_depth,
+                is_dir,
+                kind: entry.kind,
+            });
 
-            // Track size for files
-            if !entry.file_type().is_dir() {
-                if let Ok(metadata) = entry.metadata() {
-                    running_size += metadata.len();
+            if let Some(progress_tx) = &self.progress_sender {
+                if (gathered.len() as u64).is_multiple_of(PROGRESS_UPDATE_INTERVAL) {
+                    let (total_estimate, eta) = self.predict_gathering();
+                    let (active_workers, worker_utilization) = self.worker_load();
+                    let _ = progress_tx.send(ProgressUpdate {
+                        files_scanned: gathered.len() as u64,
+                        dirs_scanned: 0,
+                        total_size: 0,
+                        current_path: current_dir.clone(),
+                        active_dirs: self.active_dirs_snapshot(),
+                        active_workers,
+                        worker_utilization,
+                        gathering: true,
+                        total_estimate,
+                        percent_done: None,
+                        eta,
+                        stat_rate: self.stat_rate(),
+                    });
                 }
             }
+        }
 
-            all_entries.push(entry);
+        self.active_dirs.lock().unwrap().clear();
 
-            // Send progress updates periodically
-            count += 1;
-            if let Some(progress_tx) = &self.progress_sender {
-                if count % PROGRESS_UPDATE_INTERVAL == 0 {
-                    let _ = progress_tx.send(ProgressUpdate {
-                        files_scanned: count,
-                        dirs_scanned: 0,
-                        total_size: running_size,
-                        current_path: current_dir.clone(),
-                        active_dirs: vec![],
-                        active_workers: 1,
-                    });
+        // Phase 2: measure, recording files (and markers) as they are statted
+        let to_record = gathered
+            .iter()
+            .filter(|e| self.stores_depth(e.depth))
+            .count();
+        self.set_progress_total(to_record as u64);
+
+        // Directories at the depth limit with anything below them are summarized
+        let summarized: HashSet<&Path> = match self.max_depth {
+            Some(max_depth) => gathered
+                .iter()
+                .filter(|e| e.depth == max_depth + 1)
+                .filter_map(|e| e.path.parent())
+                .collect(),
+            None => HashSet::new(),
+        };
+
+        // Entries are measured a chunk at a time in walk order, where each
+        // directory's subtree is contiguous, so a directory is recorded and
+        // checkpointed as soon as the chunk holding the end of its subtree is in
+        let subtree_ends = Self::subtree_ends(&gathered);
+        let mut finishing: Vec<usize> = (0..gathered.len())
+            .filter(|&i| gathered[i].is_dir && self.stores_depth(gathered[i].depth))
+            .collect();
+        // Deeper directories first where subtrees end together
+        finishing.sort_by_key(|&i| (subtree_ends[i], std::cmp::Reverse(gathered[i].depth)));
+        let mut finishing = finishing.into_iter().peekable();
+
+        // Roll sizes up into directories, counting hard-linked files by the policy
+        let mut dir_sizes: HashMap<&Path, (u64, u64)> = HashMap::new();
+        let links = LinkAccounting::new(self.hard_links);
+        let (mut total_size, mut total_disk_usage, mut total_files, mut total_dirs) =
+            (0u64, 0u64, 0u64, 0u64);
+        let mut measured: Vec<Option<MeasuredEntry>> = Vec::with_capacity(gathered.len());
+        for chunk in gathered.chunks(MEASURE_CHUNK_SIZE) {
+            let chunk_measured: Vec<Option<MeasuredEntry>> = self.in_pool(|| {
+                chunk
+                    .par_iter()
+                    .map(|entry| self.measure_gathered(entry))
+                    .collect()
+            });
+
+            if self.cancelled.load(Ordering::Relaxed) {
+                self.flush_buffer()?;
+                return Err(anyhow::anyhow!("Scan cancelled"));
+            }
+
+            for (entry, measured) in chunk.iter().zip(&chunk_measured) {
+                let Some(measured) = measured else {
+                    continue;
+                };
+
+                let contribution = if entry.is_dir {
+                    total_dirs += 1;
+                    (measured.size, measured.disk_usage)
+                } else {
+                    total_files += 1;
+                    links.contribution(measured.stat.as_ref(), measured.size, measured.disk_usage)
+                };
+                total_size += contribution.0;
+                total_disk_usage += contribution.1;
+
+                // A directory's own blocks count towards itself; files start at their parent
+                let mut current = if entry.is_dir {
+                    Some(entry.path.as_path())
+                } else {
+                    entry.path.parent()
+                };
+                while let Some(dir) = current.filter(|dir| dir.starts_with(&self.root_path)) {
+                    let totals = dir_sizes.entry(dir).or_insert((0, 0));
+                    totals.0 += contribution.0;
+                    totals.1 += contribution.1;
+                    current = dir.parent();
+                }
+            }
+            measured.extend(chunk_measured);
+
+            // Directories whose whole subtree has now been measured
+            while let Some(i) = finishing.next_if(|&i| subtree_ends[i] <= measured.len()) {
+                let (entry, Some(measured)) = (&gathered[i], &measured[i]) else {
+                    continue;
+                };
+                let (size, disk_usage) = dir_sizes
+                    .get(entry.path.as_path())
+                    .copied()
+                    .unwrap_or((0, 0));
+                let mut dir_entry = self.two_phase_entry(entry, measured, size);
+                dir_entry.disk_usage = disk_usage;
+                if summarized.contains(entry.path.as_path()) {
+                    dir_entry.kind = EntryKind::Summarized;
                 }
+                self.add_entry(dir_entry);
+                self.checkpoint(&entry.path);
             }
         }
 
-        // Check if cancelled after collection phase
+        let stats = ScanStats {
+            total_size,
+            total_disk_usage,
+            total_files,
+            total_dirs,
+            error_count: self.error_count.load(Ordering::Relaxed),
+            reused_dirs: 0,
+            duration: Duration::ZERO,
+        };
+        self.store_calculated_stats(&stats);
+
+        // Final flush of any remaining buffered entries
+        self.flush_buffer()?;
+
+        let entries = if self.sender.is_some() {
+            Vec::new()
+        } else {
+            self.entries.lock().unwrap().clone()
+        };
+
+        Ok((entries, stats))
+    }
+
+    /// Stat a gathered entry, recording files and markers as they are measured
+    /// (None for markers, unreadable paths, and once the scan is cancelled)
+    fn measure_gathered(&self, entry: &GatheredEntry) -> Option<MeasuredEntry> {
         if self.cancelled.load(Ordering::Relaxed) {
-            return Err(anyhow::anyhow!("Scan cancelled"));
+            return None;
+        }
+        if entry.kind.is_marker() {
+            if self.stores_depth(entry.depth) {
+                self.add_entry(Self::marker_entry(
+                    &entry.path,
+                    entry.is_dir,
+                    entry.depth,
+                    entry.kind,
+                ));
+            }
+            return None;
         }
 
-        // Single-pass parallel processing
-        let total_files = AtomicUsize::new(0);
-        let total_dirs = AtomicUsize::new(0);
-        let total_size = AtomicU64::new(0);
+        let metadata = match self.path_metadata(&entry.path) {
+            Ok(m) => m,
+            Err(e) => {
+                self.record_error(ScanError::from_io(&entry.path, &e));
+                return None;
+            }
+        };
 
-        // First pass: build inode -> (size, first_path) map to detect hard links
-        #[cfg(unix)]
-        let inode_map: HashMap<u64, (u64, PathBuf)> = {
-            use std::os::unix::fs::MetadataExt;
-            let mut map = HashMap::new();
-            for entry in &all_entries {
-                if !entry.file_type().is_dir() {
-                    if let Ok(metadata) = entry.metadata() {
-                        let inode = metadata.ino();
-                        let size = metadata.len();
-                        // Canonicalize path to handle symlinks like /tmp -> /private/tmp
-                        let path = entry
-                            .path()
-                            .canonicalize()
-                            .unwrap_or_else(|_| entry.path().to_path_buf());
-                        // Only store first occurrence of each inode
-                        map.entry(inode).or_insert((size, path));
-                    }
+        let measured = MeasuredEntry {
+            size: if entry.is_dir { 0 } else { metadata.len },
+            disk_usage: metadata.disk_usage,
+            modified_at: metadata.modified_at,
+            stat: metadata.stat,
+        };
+        if !self.stores_depth(entry.depth) {
+            self.count_summarized(entry.is_dir);
+        } else if !entry.is_dir {
+            self.add_entry(self.two_phase_entry(entry, &measured, measured.size));
+        }
+        Some(measured)
+    }
+
+    /// For each gathered directory, the index just past the last entry below it
+    /// The walk yields directories before their contents, so a subtree is the
+    /// run of deeper entries that follows its directory.
+    fn subtree_ends(gathered: &[GatheredEntry]) -> Vec<usize> {
+        let mut ends = vec![0; gathered.len()];
+        let mut open: Vec<usize> = Vec::new();
+        for (i, entry) in gathered.iter().enumerate() {
+            while let Some(&dir) = open.last() {
+                if gathered[dir].depth < entry.depth {
+                    break;
                 }
+                ends[dir] = i;
+                open.pop();
+            }
+            if entry.is_dir {
+                open.push(i);
             }
-            map
+        }
+        for dir in open {
+            ends[dir] = gathered.len();
+        }
+        ends
+    }
+
+    /// Build the stored entry for a measured two-phase entry
+    fn two_phase_entry(
+        &self,
+        entry: &GatheredEntry,
+        measured: &MeasuredEntry,
+        size: u64,
+    ) -> FileEntry {
+        let name = if entry.depth == self.base_depth {
+            self.root_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("/")
+                .to_string()
+        } else {
+            entry
+                .path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string()
         };
 
-        let (dir_sizes, final_files, final_dirs, final_size) = all_entries
-            .par_iter()
-            .fold(
-                || (HashMap::new(), 0, 0, 0u64),
-                |(mut map, mut files, mut dirs, mut size), entry| {
-                    let is_dir = entry.file_type().is_dir();
+        FileEntry {
+            path: entry.path.clone(),
+            name,
+            parent_path: entry.path.parent().map(|p| p.to_path_buf()),
+            size,
+            disk_usage: measured.disk_usage,
+            is_dir: entry.is_dir,
+            modified_at: measured.modified_at,
+            depth: entry.depth,
+            kind: EntryKind::Normal,
+            stat: measured.stat,
+        }
+    }
 
-                    if is_dir {
-                        dirs += 1;
-                    } else {
-                        files += 1;
-
-                        // Check if this is the first occurrence of this inode (Unix only)
-                        #[cfg(unix)]
-                        let (file_size, is_first_occurrence) = {
-                            use std::os::unix::fs::MetadataExt;
-                            if let Ok(metadata) = entry.metadata() {
-                                let inode = metadata.ino();
-                                // Canonicalize path to handle symlinks like /tmp -> /private/tmp
-                                let current_path = entry
-                                    .path()
-                                    .canonicalize()
-                                    .unwrap_or_else(|_| entry.path().to_path_buf());
-                                // This is the first occurrence if the stored path matches current path
-                                let is_first = inode_map
-                                    .get(&inode)
-                                    .map(|(_, first_path)| first_path == &current_path)
-                                    .unwrap_or(true);
-                                (metadata.len(), is_first)
-                            } else {
-                                (0, true)
-                            }
-                        };
-
-                        // On non-Unix, always count the file
-                        #[cfg(not(unix))]
-                        let (file_size, is_first_occurrence) = {
-                            let sz = entry.metadata().ok().map(|m| m.len()).unwrap_or(0);
-                            (sz, true)
-                        };
-
-                        // Only add to size totals if this is the first occurrence of this inode
-                        if is_first_occurrence {
-                            size += file_size;
-
-                            let path = entry.path();
-                            let mut current = path.parent();
-                            while let Some(parent) = current {
-                                *map.entry(parent.to_path_buf()).or_insert(0) += file_size;
-                                current = parent.parent();
-                            }
-                        }
+    /// Scan exactly the given paths instead of walking the root
+    ///
+    /// Listed directories are recorded but not descended into. Directories
+    /// between the root and the listed paths that weren't listed themselves are
+    /// synthesized from what was listed below them, without a stat of their own.
+    /// Paths outside the root are recorded as errors. Exclude patterns and
+    /// `one_file_system` don't apply, since nothing is walked.
+    pub fn scan_listed(&self, paths: Vec<PathBuf>) -> Result<(Vec<FileEntry>, ScanStats)> {
+        self.start_workers()?;
+        let started = self.start_clock();
+        Self::timed(self.run_scan_listed(paths), started)
+    }
+
+    fn run_scan_listed(&self, paths: Vec<PathBuf>) -> Result<(Vec<FileEntry>, ScanStats)> {
+        let root = std::path::absolute(&self.root_path)?;
+        let mut listed = Vec::new();
+        let mut seen = HashSet::new();
+        for path in paths {
+            let path = std::path::absolute(&path)?;
+            let Ok(relative) = path.strip_prefix(&root) else {
+                self.record_error(ScanError {
+                    message: format!("Not under the scan root {}", root.display()),
+                    path,
+                    kind: ScanErrorKind::Other,
+                });
+                continue;
+            };
+            let depth = relative.components().count();
+            if seen.insert(path.clone()) {
+                listed.push((path, depth));
+            }
+        }
+        // Sorted so hard links are always attributed to the same path
+        listed.sort();
+
+        let measured: Vec<Option<(GatheredEntry, MeasuredEntry)>> = self.in_pool(|| {
+            listed
+                .into_par_iter()
+                .map(|(path, depth)| {
+                    if self.cancelled.load(Ordering::Relaxed) {
+                        return None;
                     }
-                    (map, files, dirs, size)
-                },
-            )
-            .reduce(
-                || (HashMap::new(), 0, 0, 0u64),
-                |(mut map_a, files_a, dirs_a, size_a), (map_b, files_b, dirs_b, size_b)| {
-                    for (path, size) in map_b {
-                        *map_a.entry(path).or_insert(0) += size;
+                    let metadata = match self.path_metadata(&path) {
+                        Ok(m) => m,
+                        Err(e) => {
+                            self.record_error(ScanError::from_io(&path, &e));
+                            return None;
+                        }
+                    };
+                    if metadata.is_symlink {
+                        return None;
                     }
-                    // Merge stats (accumulate in returned tuple, not atomics!)
-                    (map_a, files_a + files_b, dirs_a + dirs_b, size_a + size_b)
-                },
-            );
-
-        // Store final totals in atomics (do this ONCE, not during every reduce!)
-        total_files.store(final_files, Ordering::Relaxed);
-        total_dirs.store(final_dirs, Ordering::Relaxed);
-        total_size.store(final_size, Ordering::Relaxed);
 
-        // Also store in the Scanner's fields for progress updates during streaming
-        self.calculated_total_size
-            .store(final_size, Ordering::Relaxed);
-        self.calculated_total_files
-            .store(final_files as u64, Ordering::Relaxed);
-        self.calculated_total_dirs
-            .store(final_dirs as u64, Ordering::Relaxed);
+                    let is_dir = metadata.is_dir;
+                    let measured = MeasuredEntry {
+                        size: if is_dir { 0 } else { metadata.len },
+                        disk_usage: metadata.disk_usage,
+                        modified_at: metadata.modified_at,
+                        stat: metadata.stat,
+                    };
+                    let gathered = GatheredEntry {
+                        path,
+                        depth,
+                        is_dir,
+                        kind: EntryKind::Normal,
+                    };
+                    Some((gathered, measured))
+                })
+                .collect()
+        });
 
-        // Check if cancelled before constructing FileEntry objects
         if self.cancelled.load(Ordering::Relaxed) {
             return Err(anyhow::anyhow!("Scan cancelled"));
         }
+        let measured: Vec<(GatheredEntry, MeasuredEntry)> =
+            measured.into_iter().flatten().collect();
+
+        // Roll sizes up into directories (listed or not), counting hard-linked files by the policy
+        let mut dir_sizes: HashMap<&Path, (u64, u64)> = HashMap::new();
+        let links = LinkAccounting::new(self.hard_links);
+        let (mut total_size, mut total_disk_usage, mut total_files) = (0u64, 0u64, 0u64);
+        for (entry, measured) in &measured {
+            let contribution = if entry.is_dir {
+                (measured.size, measured.disk_usage)
+            } else {
+                total_files += 1;
+                links.contribution(measured.stat.as_ref(), measured.size, measured.disk_usage)
+            };
+            total_size += contribution.0;
+            total_disk_usage += contribution.1;
 
-        // Construct FileEntry objects (same as regular scan_hybrid)
-        let entries: Vec<FileEntry> = all_entries
-            .par_iter()
-            .map(|entry| {
-                let path = entry.path().to_path_buf();
-                let metadata = entry.metadata().ok();
-                let is_dir = entry.file_type().is_dir();
-
-                let name = if path == self.root_path {
-                    self.root_path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("/")
-                        .to_string()
-                } else {
-                    path.file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("")
-                        .to_string()
-                };
-
-                let parent_path = path.parent().map(|p| p.to_path_buf());
-                let modified_at = metadata.as_ref().and_then(|m| {
-                    m.modified().ok().map(|t| {
-                        let system_time: std::time::SystemTime = t;
-                        DateTime::<Utc>::from(system_time)
-                    })
-                });
-
-                let file_size = if is_dir {
-                    0
-                } else {
-                    metadata.as_ref().map(|m| m.len()).unwrap_or(0)
-                };
-
-                let size = if is_dir {
-                    *dir_sizes.get(&path).unwrap_or(&0)
-                } else {
-                    file_size
-                };
+            // Every ancestor up to the root gets an entry, even when nothing is added
+            let mut current = if entry.is_dir {
+                Some(entry.path.as_path())
+            } else {
+                entry.path.parent()
+            };
+            while let Some(dir) = current.filter(|dir| dir.starts_with(&root)) {
+                let totals = dir_sizes.entry(dir).or_insert((0, 0));
+                totals.0 += contribution.0;
+                totals.1 += contribution.1;
+                current = dir.parent();
+            }
+        }
 
-                FileEntry {
-                    path,
-                    name,
-                    parent_path,
-                    size,
-                    is_dir,
-                    modified_at,
-                    depth: entry.depth(),
-                }
-            })
+        let listed_dirs: HashMap<&Path, &(GatheredEntry, MeasuredEntry)> = measured
+            .iter()
+            .filter(|(entry, _)| entry.is_dir)
+            .map(|listed| (listed.0.path.as_path(), listed))
+            .collect();
+        let mut entries: Vec<FileEntry> = measured
+            .iter()
+            .filter(|(entry, _)| !entry.is_dir)
+            .map(|(entry, measured)| self.two_phase_entry(entry, measured, measured.size))
             .collect();
+        for (dir, (size, disk_usage)) in &dir_sizes {
+            let mut dir_entry = match listed_dirs.get(dir) {
+                Some((entry, measured)) => self.two_phase_entry(entry, measured, *size),
+                None => {
+                    let synthesized = GatheredEntry {
+                        path: dir.to_path_buf(),
+                        depth: dir
+                            .strip_prefix(&root)
+                            .map_or(0, |r| r.components().count()),
+                        is_dir: true,
+                        kind: EntryKind::Normal,
+                    };
+                    let unmeasured = MeasuredEntry {
+                        size: 0,
+                        disk_usage: 0,
+                        modified_at: None,
+                        stat: None,
+                    };
+                    self.two_phase_entry(&synthesized, &unmeasured, *size)
+                }
+            };
+            dir_entry.disk_usage = *disk_usage;
+            entries.push(dir_entry);
+        }
 
         let stats = ScanStats {
-            total_size: total_size.load(Ordering::Relaxed),
-            total_files: total_files.load(Ordering::Relaxed) as u64,
-            total_dirs: total_dirs.load(Ordering::Relaxed) as u64,
+            total_size,
+            total_disk_usage,
+            total_files,
+            total_dirs: dir_sizes.len() as u64,
+            error_count: self.error_count.load(Ordering::Relaxed),
+            reused_dirs: 0,
+            duration: Duration::ZERO,
         };
+        self.store_calculated_stats(&stats);
 
-        // Stream entries to database
-        for entry in &entries {
-            self.add_entry(entry.clone());
+        self.summarize_below_max_depth(&mut entries);
+        self.set_progress_total(entries.len() as u64);
+        for entry in entries {
+            self.add_entry(entry);
         }
+
+        // Final flush of any remaining buffered entries
         self.flush_buffer()?;
 
-        let final_entries = if self.sender.is_some() {
+        let entries = if self.sender.is_some() {
             Vec::new()
         } else {
-            entries
+            self.entries.lock().unwrap().clone()
         };
 
-        Ok((final_entries, stats))
+        Ok((entries, stats))
+    }
+
+    /// Scan several roots as one scan, below `root_path` as their top
+    ///
+    /// Each root is walked as if it were scanned on its own: exclude patterns are
+    /// relative to it and `one_file_system` keeps to its filesystem, but depths
+    /// count from the top. The top and any directories between it and the roots
+    /// are synthesized from the roots' totals, without a stat of their own. A
+    /// file hard-linked into two roots counts in both.
+    pub fn scan_roots(&mut self, roots: &[PathBuf]) -> Result<(Vec<FileEntry>, ScanStats)> {
+        self.start_workers()?;
+        let started = self.start_clock();
+        Self::timed(self.run_scan_roots(roots, None), started)
+    }
+
+    /// Resume a paused multi-root scan, skipping already-scanned paths
+    pub fn scan_roots_resuming(
+        &mut self,
+        roots: &[PathBuf],
+        resume: ResumeState,
+    ) -> Result<(Vec<FileEntry>, ScanStats)> {
+        self.start_workers()?;
+        let started = self.start_clock();
+        Self::timed(self.run_scan_roots(roots, Some(&resume)), started)
+    }
+
+    fn run_scan_roots(
+        &mut self,
+        roots: &[PathBuf],
+        resume: Option<&ResumeState>,
+    ) -> Result<(Vec<FileEntry>, ScanStats)> {
+        let top = self.root_path.clone();
+        let mut scanned = Vec::new();
+        for root in roots {
+            let Ok(relative) = root.strip_prefix(&top) else {
+                bail!("{} is not under {}", root.display(), top.display());
+            };
+            self.enter_root(root, relative.components().count());
+            let result = match resume {
+                Some(resume) => self.run_scan_resuming(resume),
+                None => self.run_scan(),
+            };
+            // Entries kept in memory are returned per root, not accumulated
+            self.entries.lock().unwrap().clear();
+            match result {
+                Ok(root_scan) => scanned.push((root, root_scan)),
+                Err(e) => {
+                    self.enter_root(&top, 0);
+                    return Err(e);
+                }
+            }
+        }
+        self.enter_root(&top, 0);
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err(anyhow::anyhow!("Scan cancelled"));
+        }
+
+        let mut entries = Vec::new();
+        let mut stats = ScanStats {
+            total_size: 0,
+            total_disk_usage: 0,
+            total_files: 0,
+            total_dirs: 0,
+            error_count: self.error_count.load(Ordering::Relaxed),
+            reused_dirs: 0,
+            duration: Duration::ZERO,
+        };
+        let mut dir_sizes: HashMap<PathBuf, (u64, u64)> = HashMap::new();
+        for (root, (root_entries, root_stats)) in scanned {
+            entries.extend(root_entries);
+            stats.total_size += root_stats.total_size;
+            stats.total_disk_usage += root_stats.total_disk_usage;
+            stats.total_files += root_stats.total_files;
+            stats.total_dirs += root_stats.total_dirs;
+            stats.reused_dirs += root_stats.reused_dirs;
+
+            let mut current = root.parent();
+            while let Some(dir) = current.filter(|dir| dir.starts_with(&top)) {
+                let totals = dir_sizes.entry(dir.to_path_buf()).or_insert((0, 0));
+                totals.0 += root_stats.total_size;
+                totals.1 += root_stats.total_disk_usage;
+                current = dir.parent();
+            }
+        }
+        stats.total_dirs += dir_sizes.len() as u64;
+        self.store_calculated_stats(&stats);
+
+        let unmeasured = MeasuredEntry {
+            size: 0,
+            disk_usage: 0,
+            modified_at: None,
+            stat: None,
+        };
+        for (dir, (size, disk_usage)) in dir_sizes {
+            let depth = dir.strip_prefix(&top).map_or(0, |r| r.components().count());
+            if !self.stores_depth(depth) {
+                self.count_summarized(true);
+                continue;
+            }
+            let synthesized = GatheredEntry {
+                path: dir,
+                depth,
+                is_dir: true,
+                kind: EntryKind::Normal,
+            };
+            let mut dir_entry = self.two_phase_entry(&synthesized, &unmeasured, size);
+            dir_entry.disk_usage = disk_usage;
+            if self.max_depth == Some(depth) {
+                dir_entry.kind = EntryKind::Summarized;
+            }
+            self.add_entry(dir_entry);
+        }
+
+        // Final flush of any remaining buffered entries
+        self.flush_buffer()?;
+        if self.sender.is_none() {
+            entries.append(&mut self.entries.lock().unwrap());
+        }
+
+        Ok((entries, stats))
+    }
+
+    /// Walk `root` next, `base_depth` levels below the top of the scan
+    fn enter_root(&mut self, root: &Path, base_depth: usize) {
+        self.root_path = root.to_path_buf();
+        self.base_depth = base_depth;
+        let markers = Arc::make_mut(&mut self.markers);
+        markers.root_path = root.to_path_buf();
+        markers.locate_root(self.source.as_ref());
     }
 
     /// Resume a scan that was previously paused, skipping already-scanned paths
-    /// The `scanned_paths` HashSet contains paths that have already been scanned
-    pub fn scan_resuming(
-        &self,
-        scanned_paths: std::collections::HashSet<String>,
+    /// `resume` holds what the paused scan recorded (see `ResumeState`)
+    pub fn scan_resuming(&self, resume: ResumeState) -> Result<(Vec<FileEntry>, ScanStats)> {
+        self.start_workers()?;
+        let started = self.start_clock();
+        Self::timed(self.run_scan_resuming(&resume), started)
+    }
+
+    /// Resume a paused scan for `subtree` only, skipping already-scanned paths
+    ///
+    /// `root` is the root the subtree lies in: the scan's own, or the one of a
+    /// multi-root scan holding it. Exclude patterns and `one_file_system` apply as
+    /// they did when `root` was first walked, and depths count from the top of
+    /// the scan. Directories in the subtree only count what this run records, so
+    /// the caller recomputes their sizes afterwards (see
+    /// `Database::recompute_dir_sizes`).
+    pub fn scan_subtree_resuming(
+        &mut self,
+        root: &Path,
+        subtree: &Path,
+        resume: ResumeState,
     ) -> Result<(Vec<FileEntry>, ScanStats)> {
+        let top = self.root_path.clone();
+        let Ok(relative) = subtree.strip_prefix(&top) else {
+            bail!("{} is not under {}", subtree.display(), top.display());
+        };
+        if !subtree.starts_with(root) || !root.starts_with(&top) {
+            bail!("{} is not under {}", subtree.display(), root.display());
+        }
+        let base_depth = relative.components().count();
+
+        self.start_workers()?;
+        let started = self.start_clock();
+        // Markers stay relative to the root; the walk starts at the subtree
+        self.enter_root(root, 0);
+        self.root_path = subtree.to_path_buf();
+        self.base_depth = base_depth;
+        let result = self.run_scan_resuming(&resume);
+        // Entries kept in memory are returned, not carried into the next run
+        self.entries.lock().unwrap().clear();
+        self.enter_root(&top, 0);
+        Self::timed(result, started)
+    }
+
+    fn run_scan_resuming(&self, resume: &ResumeState) -> Result<(Vec<FileEntry>, ScanStats)> {
         // Dispatch based on implementation (prefer hybrid for performance)
         match self.implementation {
-            ScannerImpl::Hybrid => self.scan_hybrid_resuming(scanned_paths),
+            ScannerImpl::Hybrid => self.scan_hybrid_resuming(resume),
+            ScannerImpl::TwoPhase => self.scan_two_phase(Some(resume)),
             ScannerImpl::Custom | ScannerImpl::Walkdir => {
                 // Fall back to recursive implementation
-                let total_size = AtomicU64::new(0);
-                let total_files = AtomicU64::new(0);
-                let total_dirs = AtomicU64::new(0);
-
-                self.scan_recursive_resuming(
-                    &self.root_path,
-                    0,
-                    &total_size,
-                    &total_files,
-                    &total_dirs,
-                    &scanned_paths,
-                )?;
+                let totals = ScanTotals::new(self.hard_links);
+
+                self.in_pool(|| {
+                    self.scan_recursive(&self.root_path, self.base_depth, &totals, Some(resume))
+                })?;
 
                 // Store calculated stats for progress updates during final flush
-                self.calculated_total_size
-                    .store(total_size.load(Ordering::Relaxed), Ordering::Relaxed);
-                self.calculated_total_files
-                    .store(total_files.load(Ordering::Relaxed), Ordering::Relaxed);
-                self.calculated_total_dirs
-                    .store(total_dirs.load(Ordering::Relaxed), Ordering::Relaxed);
+                let stats = totals.to_stats(self.error_count.load(Ordering::Relaxed));
+                self.store_calculated_stats(&stats);
 
                 // Final flush of any remaining buffered entries
                 self.flush_buffer()?;
@@ -869,12 +2494,6 @@ impl Scanner {
                     self.entries.lock().unwrap().clone()
                 };
 
-                let stats = ScanStats {
-                    total_size: total_size.load(Ordering::Relaxed),
-                    total_files: total_files.load(Ordering::Relaxed),
-                    total_dirs: total_dirs.load(Ordering::Relaxed),
-                };
-
                 Ok((entries, stats))
             }
         }
@@ -882,62 +2501,83 @@ impl Scanner {
 
     fn flush_buffer(&self) -> Result<()> {
         if let Some(sender) = &self.sender {
-            let mut entries = self.entries.lock().unwrap();
-            if !entries.is_empty() {
-                let batch = entries.drain(..).collect();
+            // Checkpoints are taken first: their entries were buffered before them,
+            // so they are in this batch or an earlier one
+            let checkpoints: Vec<PathBuf> = self.checkpoints.lock().unwrap().drain(..).collect();
+            let batch: Vec<FileEntry> = self.entries.lock().unwrap().drain(..).collect();
+            if !batch.is_empty() {
                 sender.blocking_send(crate::db::ActorMessage::InsertBatch(batch))?;
             }
+            if !checkpoints.is_empty() {
+                sender.blocking_send(crate::db::ActorMessage::InsertCheckpoints(checkpoints))?;
+            }
+            let errors: Vec<ScanError> = self.errors.lock().unwrap().drain(..).collect();
+            if !errors.is_empty() {
+                sender.blocking_send(crate::db::ActorMessage::InsertErrors(errors))?;
+            }
+            let (files, dirs) = std::mem::take(&mut *self.summarized.lock().unwrap());
+            if files + dirs > 0 {
+                sender.blocking_send(crate::db::ActorMessage::CountSummarized { files, dirs })?;
+            }
         }
         Ok(())
     }
 
-    fn scan_recursive_resuming(
+    /// Scan `path` and everything below it, returning its (apparent size, disk usage)
+    fn scan_recursive(
         &self,
         path: &Path,
         depth: usize,
-        total_size: &AtomicU64,
-        total_files: &AtomicU64,
-        total_dirs: &AtomicU64,
-        scanned_paths: &std::collections::HashSet<String>,
-    ) -> Result<u64> {
+        totals: &ScanTotals,
+        resume: Option<&ResumeState>,
+    ) -> Result<(u64, u64)> {
         // Check if scan was cancelled
         if self.cancelled.load(Ordering::Relaxed) {
-            return Ok(0);
+            return Ok((0, 0));
         }
 
-        // Check if this path was already scanned
-        let path_str = path.display().to_string();
-        if scanned_paths.contains(&path_str) {
-            // Skip this path - it was already scanned
-            return Ok(0);
+        // Check if this path was already scanned (when resuming)
+        if resume.is_some_and(|resume| resume.contains(path)) {
+            // Skip this path, or the whole directory when it was finished
+            return Ok((0, 0));
         }
 
-        let metadata = if self.follow_symlinks {
-            match fs::metadata(path) {
-                Ok(m) => m,
-                Err(_) => return Ok(0), // Skip inaccessible files
-            }
-        } else {
-            match fs::symlink_metadata(path) {
-                Ok(m) => m,
-                Err(_) => return Ok(0), // Skip inaccessible files
+        let metadata = match self.path_metadata(path) {
+            Ok(m) => m,
+            Err(e) => {
+                // Skip inaccessible files, but remember that we did
+                self.record_error(ScanError::from_io(path, &e));
+                return Ok((0, 0));
             }
         };
 
         // Skip symlinks if follow_symlinks is false
-        if !self.follow_symlinks && metadata.is_symlink() {
-            return Ok(0);
+        if !self.follow_symlinks && metadata.is_symlink {
+            return Ok((0, 0));
         }
 
-        let modified_at = metadata.modified().ok().and_then(|t| {
-            DateTime::from_timestamp(
-                t.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs() as i64,
-                0,
-            )
-        });
+        let modified_at = metadata
+            .modified_at
+            .and_then(|t| DateTime::from_timestamp(t.timestamp(), 0));
+
+        let is_dir = metadata.is_dir;
+
+        // Record excluded paths and foreign mounts as markers and don't descend into them
+        if depth > self.base_depth {
+            let kind = self
+                .markers
+                .classify(path, is_dir, || Some(metadata.clone()));
+            if kind.is_marker() {
+                if self.stores_depth(depth) {
+                    self.add_entry(Self::marker_entry(path, is_dir, depth, kind));
+                }
+                return Ok((0, 0));
+            }
+        }
 
-        let is_dir = metadata.is_dir();
-        let file_size = if is_dir { 0 } else { metadata.len() };
+        let file_size = if is_dir { 0 } else { metadata.len };
+        let own_disk_usage = metadata.disk_usage;
+        let stat = metadata.stat;
 
         let parent_path = path.parent().map(|p| p.to_path_buf());
         let name = if depth == 0 {
@@ -955,30 +2595,105 @@ impl Scanner {
         };
 
         let mut dir_size = file_size;
+        let mut dir_disk_usage = own_disk_usage;
+        let mut kind = EntryKind::Normal;
+        // What this path adds to its ancestors; a hard-linked file's share depends on the policy
+        let counted = if is_dir {
+            (0, own_disk_usage)
+        } else {
+            totals
+                .links
+                .contribution(stat.as_ref(), file_size, own_disk_usage)
+        };
+        totals.disk_usage.fetch_add(counted.1, Ordering::Relaxed);
 
         if is_dir {
-            total_dirs.fetch_add(1, Ordering::Relaxed);
-
-            // Read directory entries
-            let read_dir = match fs::read_dir(path) {
-                Ok(rd) => rd,
-                Err(_) => {
-                    // Still record the directory even if we can't read it
-                    self.add_entry(FileEntry {
-                        path: path.to_path_buf(),
-                        name,
-                        parent_path,
-                        size: 0,
-                        is_dir: true,
-                        modified_at,
-                        depth,
-                    });
-                    return Ok(0);
+            totals.dirs.fetch_add(1, Ordering::Relaxed);
+
+            let reused = self.previous.as_ref().and_then(|previous| {
+                let relative = path.strip_prefix(&self.root_path).ok()?;
+                previous.unchanged_children(relative, stat.as_ref()?, modified_at)
+            });
+
+            let mut reused_files = 0;
+            let children: Vec<PathBuf> = if let Some(previous_children) = reused {
+                totals.reused_dirs.fetch_add(1, Ordering::Relaxed);
+                // Files are copied as recorded; subdirectories and markers are checked again
+                let mut rescan = Vec::new();
+                for child in previous_children.iter() {
+                    let child_path = path.join(&child.name);
+                    if child.is_dir || child.kind != EntryKind::Normal {
+                        rescan.push(child_path);
+                        continue;
+                    }
+
+                    reused_files += 1;
+                    let (counted_size, counted_usage) = totals.links.contribution(
+                        child.stat.as_ref(),
+                        child.size,
+                        child.disk_usage,
+                    );
+                    dir_size += counted_size;
+                    dir_disk_usage += counted_usage;
+                    totals.files.fetch_add(1, Ordering::Relaxed);
+                    totals.size.fetch_add(counted_size, Ordering::Relaxed);
+                    totals
+                        .disk_usage
+                        .fetch_add(counted_usage, Ordering::Relaxed);
+                    if self.stores_depth(depth + 1) {
+                        self.add_entry(FileEntry {
+                            path: child_path,
+                            parent_path: Some(path.to_path_buf()),
+                            depth: depth + 1,
+                            ..child.clone()
+                        });
+                    } else {
+                        self.count_summarized(false);
+                    }
                 }
-            };
+                rescan
+            } else {
+                // Read directory entries
+                let read_dir = match self.io(|| self.source.read_dir(path)) {
+                    Ok(rd) => rd,
+                    Err(e) => {
+                        self.record_error(ScanError::from_io(path, &e));
+                        // Still record the directory even if we can't read it
+                        if self.stores_depth(depth) {
+                            self.add_entry(FileEntry {
+                                path: path.to_path_buf(),
+                                name,
+                                parent_path,
+                                size: 0,
+                                disk_usage: own_disk_usage,
+                                is_dir: true,
+                                modified_at,
+                                depth,
+                                kind: EntryKind::Normal,
+                                stat,
+                            });
+                        } else {
+                            self.count_summarized(true);
+                        }
+                        return Ok((0, own_disk_usage));
+                    }
+                };
 
-            let children: Vec<_> = read_dir.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+                read_dir
+                    .into_iter()
+                    .filter_map(|child| match child {
+                        Ok(child) => Some(path.join(child.name)),
+                        Err(e) => {
+                            self.record_error(ScanError::from_io(path, &e));
+                            None
+                        }
+                    })
+                    .collect()
+            };
             let num_children = children.len();
+            if num_children + reused_files > 0 && self.max_depth == Some(depth) {
+                kind = EntryKind::Summarized;
+            }
 
             // Register this directory in active tracking
             let dir_path_str = path.display().to_string();
@@ -989,22 +2704,12 @@ impl Scanner {
 
             // For small directories, process serially; for large ones, use parallelism
             if num_children > 100 {
-                // Increment worker count for parallel processing
-                self.active_workers.fetch_add(1, Ordering::Relaxed);
-
-                let child_sizes: Vec<u64> = children
+                let child_sizes: Vec<(u64, u64)> = children
                     .par_iter()
                     .enumerate()
                     .filter_map(|(idx, child_path)| {
                         let result = self
-                            .scan_recursive_resuming(
-                                child_path,
-                                depth + 1,
-                                total_size,
-                                total_files,
-                                total_dirs,
-                                scanned_paths,
-                            )
+                            .scan_recursive(child_path, depth + 1, totals, resume)
                             .ok();
 
                         // Update directory progress after processing each child
@@ -1018,21 +2723,15 @@ impl Scanner {
                         result
                     })
                     .collect();
-                dir_size = child_sizes.iter().sum();
-
-                // Decrement worker count
-                self.active_workers.fetch_sub(1, Ordering::Relaxed);
+                dir_size += child_sizes.iter().map(|(size, _)| size).sum::<u64>();
+                dir_disk_usage += child_sizes.iter().map(|(_, usage)| usage).sum::<u64>();
             } else {
                 for (idx, child_path) in children.iter().enumerate() {
-                    if let Ok(size) = self.scan_recursive_resuming(
-                        child_path,
-                        depth + 1,
-                        total_size,
-                        total_files,
-                        total_dirs,
-                        scanned_paths,
-                    ) {
+                    if let Ok((size, usage)) =
+                        self.scan_recursive(child_path, depth + 1, totals, resume)
+                    {
                         dir_size += size;
+                        dir_disk_usage += usage;
                     }
 
                     // Update directory progress
@@ -1051,294 +2750,66 @@ impl Scanner {
                 active.remove(&dir_path_str);
             }
         } else {
-            total_files.fetch_add(1, Ordering::Relaxed);
-            total_size.fetch_add(file_size, Ordering::Relaxed);
+            totals.files.fetch_add(1, Ordering::Relaxed);
+            totals.size.fetch_add(counted.0, Ordering::Relaxed);
         }
 
-        self.add_entry(FileEntry {
-            path: path.to_path_buf(),
-            name,
-            parent_path,
-            size: dir_size,
-            is_dir,
-            modified_at,
-            depth,
-        });
-
-        Ok(dir_size)
-    }
-
-    fn scan_recursive(
-        &self,
-        path: &Path,
-        depth: usize,
-        total_size: &AtomicU64,
-        total_files: &AtomicU64,
-        total_dirs: &AtomicU64,
-    ) -> Result<u64> {
-        // Check if scan was cancelled
-        if self.cancelled.load(Ordering::Relaxed) {
-            return Ok(0);
-        }
-
-        let metadata = if self.follow_symlinks {
-            match fs::metadata(path) {
-                Ok(m) => m,
-                Err(_) => return Ok(0), // Skip inaccessible files
+        if self.stores_depth(depth) {
+            self.add_entry(FileEntry {
+                path: path.to_path_buf(),
+                name,
+                parent_path,
+                size: dir_size,
+                disk_usage: dir_disk_usage,
+                is_dir,
+                modified_at,
+                depth,
+                kind,
+                stat,
+            });
+            // A cancelled scan cut its children short, so it isn't finished
+            if is_dir && !self.cancelled.load(Ordering::Relaxed) {
+                self.checkpoint(path);
             }
         } else {
-            match fs::symlink_metadata(path) {
-                Ok(m) => m,
-                Err(_) => return Ok(0), // Skip inaccessible files
-            }
-        };
-
-        // Skip symlinks if follow_symlinks is false
-        if !self.follow_symlinks && metadata.is_symlink() {
-            return Ok(0);
+            self.count_summarized(is_dir);
         }
 
-        let modified_at = metadata.modified().ok().and_then(|t| {
-            DateTime::from_timestamp(
-                t.duration_since(std::time::UNIX_EPOCH).ok()?.as_secs() as i64,
-                0,
-            )
-        });
-
-        let is_dir = metadata.is_dir();
-        let file_size = if is_dir { 0 } else { metadata.len() };
-
-        let parent_path = path.parent().map(|p| p.to_path_buf());
-        let name = if depth == 0 {
-            // For the root directory, use the full absolute path for clarity
-            // Canonicalize to resolve relative paths like ".", "..", "~", etc.
-            path.canonicalize()
-                .unwrap_or_else(|_| path.to_path_buf())
-                .display()
-                .to_string()
-        } else {
-            path.file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string()
-        };
-
-        let mut dir_size = file_size;
-
         if is_dir {
-            total_dirs.fetch_add(1, Ordering::Relaxed);
-
-            // Read directory entries
-            let read_dir = match fs::read_dir(path) {
-                Ok(rd) => rd,
-                Err(_) => {
-                    // Still record the directory even if we can't read it
-                    self.add_entry(FileEntry {
-                        path: path.to_path_buf(),
-                        name,
-                        parent_path,
-                        size: 0,
-                        is_dir: true,
-                        modified_at,
-                        depth,
-                    });
-                    return Ok(0);
-                }
-            };
-
-            let children: Vec<_> = read_dir.filter_map(|e| e.ok()).map(|e| e.path()).collect();
-            let num_children = children.len();
-
-            // Register this directory in active tracking
-            let dir_path_str = path.display().to_string();
-            {
-                let mut active = self.active_dirs.lock().unwrap();
-                active.insert(dir_path_str.clone(), (0, num_children));
-            }
-
-            // For small directories, process serially; for large ones, use parallelism
-            if num_children > 100 {
-                // Increment worker count for parallel processing
-                self.active_workers.fetch_add(1, Ordering::Relaxed);
-
-                let child_sizes: Vec<u64> = children
-                    .par_iter()
-                    .enumerate()
-                    .filter_map(|(idx, child_path)| {
-                        let result = self
-                            .scan_recursive(
-                                child_path,
-                                depth + 1,
-                                total_size,
-                                total_files,
-                                total_dirs,
-                            )
-                            .ok();
-
-                        // Update directory progress after processing each child
-                        {
-                            let mut active = self.active_dirs.lock().unwrap();
-                            if let Some(progress) = active.get_mut(&dir_path_str) {
-                                progress.0 = idx + 1;
-                            }
-                        }
-
-                        result
-                    })
-                    .collect();
-                dir_size = child_sizes.iter().sum();
-
-                // Decrement worker count
-                self.active_workers.fetch_sub(1, Ordering::Relaxed);
-            } else {
-                for (idx, child_path) in children.iter().enumerate() {
-                    if let Ok(size) = self.scan_recursive(
-                        child_path,
-                        depth + 1,
-                        total_size,
-                        total_files,
-                        total_dirs,
-                    ) {
-                        dir_size += size;
-                    }
-
-                    // Update directory progress
-                    {
-                        let mut active = self.active_dirs.lock().unwrap();
-                        if let Some(progress) = active.get_mut(&dir_path_str) {
-                            progress.0 = idx + 1;
-                        }
-                    }
-                }
-            }
-
-            // Remove this directory from active tracking
-            {
-                let mut active = self.active_dirs.lock().unwrap();
-                active.remove(&dir_path_str);
-            }
+            Ok((dir_size, dir_disk_usage))
         } else {
-            total_files.fetch_add(1, Ordering::Relaxed);
-            total_size.fetch_add(file_size, Ordering::Relaxed);
+            Ok(counted)
         }
-
-        self.add_entry(FileEntry {
-            path: path.to_path_buf(),
-            name,
-            parent_path,
-            size: dir_size,
-            is_dir,
-            modified_at,
-            depth,
-        });
-
-        Ok(dir_size)
     }
 
-    /// Demo scanner that simulates scanning without touching filesystem
-    /// Useful for testing the UI and progress display
-    pub fn demo_scan(&self) -> Result<(Vec<FileEntry>, ScanStats)> {
-        use std::thread;
-        use std::time::Duration;
-
-        let total_size = AtomicU64::new(0);
-        let total_files = AtomicU64::new(0);
-        let total_dirs = AtomicU64::new(0);
-
-        // Simulate scanning several directories
-        let demo_dirs = vec![
-            ("/demo/src", 150),
-            ("/demo/target/debug", 500),
-            ("/demo/target/release", 300),
-            ("/demo/node_modules", 1200),
-            ("/demo/docs", 50),
-        ];
-
-        for (dir_path, file_count) in demo_dirs {
-            // Check if cancelled at start of each directory
-            if self.cancelled.load(Ordering::Relaxed) {
-                break;
-            }
-            // Register this directory
-            {
-                let mut active = self.active_dirs.lock().unwrap();
-                active.insert(dir_path.to_string(), (0, file_count));
-            }
-
-            // Simulate processing files in this directory
-            for i in 0..file_count {
-                // Check if cancelled
-                if self.cancelled.load(Ordering::Relaxed) {
-                    break;
-                }
-
-                // Simulate some work
-                thread::sleep(Duration::from_millis(5));
-
-                // Create a fake entry
-                let entry = FileEntry {
-                    path: PathBuf::from(format!("{}/file_{}.txt", dir_path, i)),
-                    name: format!("file_{}.txt", i),
-                    parent_path: Some(PathBuf::from(dir_path)),
-                    size: 1024 * (i as u64 % 100),
-                    is_dir: false,
-                    modified_at: None,
-                    depth: dir_path.split('/').count(),
-                };
-
-                total_files.fetch_add(1, Ordering::Relaxed);
-                total_size.fetch_add(entry.size, Ordering::Relaxed);
-
-                self.add_entry(entry);
-
-                // Update directory progress
-                {
-                    let mut active = self.active_dirs.lock().unwrap();
-                    if let Some(progress) = active.get_mut(dir_path) {
-                        progress.0 = i + 1;
-                    }
-                }
-            }
+    /// Record that everything below `dir` has been added, so a resumed scan can
+    /// skip it whole
+    fn checkpoint(&self, dir: &Path) {
+        if self.sender.is_some() {
+            self.checkpoints.lock().unwrap().push(dir.to_path_buf());
+        }
+    }
 
-            // Remove directory from active tracking
-            {
-                let mut active = self.active_dirs.lock().unwrap();
-                active.remove(dir_path);
+    /// Count an entry below the depth limit, which isn't stored, so the
+    /// database still knows the scan's totals if it's interrupted
+    fn count_summarized(&self, is_dir: bool) {
+        if self.sender.is_some() {
+            let mut summarized = self.summarized.lock().unwrap();
+            if is_dir {
+                summarized.1 += 1;
+            } else {
+                summarized.0 += 1;
             }
-
-            total_dirs.fetch_add(1, Ordering::Relaxed);
         }
-
-        // Store calculated stats for progress updates during final flush
-        self.calculated_total_size
-            .store(total_size.load(Ordering::Relaxed), Ordering::Relaxed);
-        self.calculated_total_files
-            .store(total_files.load(Ordering::Relaxed), Ordering::Relaxed);
-        self.calculated_total_dirs
-            .store(total_dirs.load(Ordering::Relaxed), Ordering::Relaxed);
-
-        // Final flush
-        self.flush_buffer()?;
-
-        let entries = if self.sender.is_some() {
-            Vec::new()
-        } else {
-            self.entries.lock().unwrap().clone()
-        };
-
-        let stats = ScanStats {
-            total_size: total_size.load(Ordering::Relaxed),
-            total_files: total_files.load(Ordering::Relaxed),
-            total_dirs: total_dirs.load(Ordering::Relaxed),
-        };
-
-        Ok((entries, stats))
     }
 
     fn add_entry(&self, entry: FileEntry) {
+        // Archive members are stored alongside but don't count as scanned entries
+        let entry_path = entry.path.clone();
         let should_flush = {
+            let with_members = self.with_archive_members(entry);
             let mut entries = self.entries.lock().unwrap();
-            entries.push(entry.clone());
+            entries.extend(with_members);
             self.sender.is_some() && entries.len() >= BUFFER_SIZE
         };
 
@@ -1351,22 +2822,22 @@ impl Scanner {
         let count = self.entries_processed.fetch_add(1, Ordering::Relaxed);
         if let Some(progress_tx) = &self.progress_sender {
             if count % PROGRESS_UPDATE_INTERVAL == 0 {
-                // Snapshot of currently active directories
-                let active_dirs_snapshot: Vec<(String, usize, usize)> = {
-                    let active = self.active_dirs.lock().unwrap();
-                    active
-                        .iter()
-                        .map(|(path, (done, total))| (path.clone(), *done, *total))
-                        .collect()
-                };
+                let (total_estimate, percent_done, eta) = self.estimate_progress(count + 1);
+                let (active_workers, worker_utilization) = self.worker_load();
 
                 let _ = progress_tx.send(ProgressUpdate {
                     files_scanned: count,
                     dirs_scanned: 0, // Will be updated from atomics if needed
                     total_size: self.calculated_total_size.load(Ordering::Relaxed),
-                    current_path: entry.path.display().to_string(),
-                    active_dirs: active_dirs_snapshot,
-                    active_workers: self.active_workers.load(Ordering::Relaxed),
+                    current_path: entry_path.display().to_string(),
+                    active_dirs: self.active_dirs_snapshot(),
+                    active_workers,
+                    worker_utilization,
+                    gathering: false,
+                    total_estimate,
+                    percent_done,
+                    eta,
+                    stat_rate: self.stat_rate(),
                 });
             }
         }
@@ -1451,6 +2922,7 @@ mod tests {
                         total_entries += entries.len();
                     }
                     crate::db::ActorMessage::Shutdown => break,
+                    _ => {}
                 }
             }
             total_entries
@@ -1595,6 +3067,7 @@ mod tests {
             ("Custom", ScannerImpl::Custom),
             ("Walkdir", ScannerImpl::Walkdir),
             ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
         ] {
             println!(
                 "Testing {} implementation with follow_symlinks=false",
@@ -1665,6 +3138,7 @@ mod tests {
                 ("Custom", ScannerImpl::Custom),
                 ("Walkdir", ScannerImpl::Walkdir),
                 ("Hybrid", ScannerImpl::Hybrid),
+                ("TwoPhase", ScannerImpl::TwoPhase),
             ] {
                 println!(
                     "Testing {} implementation with follow_symlinks=true",
@@ -1737,6 +3211,7 @@ mod tests {
             ("Custom", ScannerImpl::Custom),
             ("Walkdir", ScannerImpl::Walkdir),
             ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
         ] {
             println!("Testing {} implementation for hidden files", impl_name);
 
@@ -1848,6 +3323,7 @@ mod tests {
             ("Custom", ScannerImpl::Custom),
             ("Walkdir", ScannerImpl::Walkdir),
             ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
         ] {
             println!("Testing {} implementation for directory sizes", impl_name);
 
@@ -1943,4 +3419,1271 @@ mod tests {
             assert_eq!(stats.total_files, 5, "{}: should have 5 files", impl_name);
         }
     }
+
+    #[test]
+    fn test_exclude_patterns_all_implementations() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join(".git/objects/ab")).unwrap();
+        fs::write(root.join(".git/objects/ab/blob"), vec![0u8; 4096]).unwrap();
+        fs::write(root.join(".git/HEAD"), b"ref").unwrap();
+        fs::write(root.join("subdir/debug.log"), b"log line").unwrap();
+
+        let options = ScanOptions {
+            exclude: vec![".git/objects".to_string(), "*.log".to_string()],
+            ..Default::default()
+        };
+
+        for (impl_name, scanner_impl) in [
+            ("Custom", ScannerImpl::Custom),
+            ("Walkdir", ScannerImpl::Walkdir),
+            ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
+        ] {
+            let scanner = Scanner::new_with_options(root, scanner_impl, &options).unwrap();
+            let (entries, stats) = scanner.scan().unwrap();
+
+            // Excluded paths are recorded as markers...
+            let objects = entries
+                .iter()
+                .find(|e| e.path == root.join(".git/objects"))
+                .unwrap_or_else(|| panic!("{}: missing .git/objects marker", impl_name));
+            assert_eq!(objects.kind, EntryKind::Excluded, "{}", impl_name);
+            assert!(objects.is_dir, "{}", impl_name);
+            assert_eq!(objects.size, 0, "{}", impl_name);
+
+            let log = entries
+                .iter()
+                .find(|e| e.name == "debug.log")
+                .unwrap_or_else(|| panic!("{}: missing debug.log marker", impl_name));
+            assert_eq!(log.kind, EntryKind::Excluded, "{}", impl_name);
+
+            // ...but their contents are never scanned
+            assert!(
+                !entries.iter().any(|e| e.name == "blob" || e.name == "ab"),
+                "{}: excluded directory was descended into",
+                impl_name
+            );
+
+            // Non-excluded siblings are still scanned
+            let head = entries.iter().find(|e| e.name == "HEAD").unwrap();
+            assert_eq!(head.kind, EntryKind::Normal, "{}", impl_name);
+
+            // Markers don't count towards totals (5 files + HEAD, 27 + 3 bytes)
+            assert_eq!(stats.total_files, 6, "{}: total_files", impl_name);
+            assert_eq!(stats.total_size, 30, "{}: total_size", impl_name);
+            let root_entry = entries.iter().find(|e| e.path == root).unwrap();
+            assert_eq!(root_entry.size, 30, "{}: root size", impl_name);
+        }
+    }
+
+    #[test]
+    fn test_exclude_patterns_when_resuming() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+        fs::create_dir(root.join("node_modules")).unwrap();
+        fs::write(root.join("node_modules/pkg.js"), b"module").unwrap();
+
+        let options = ScanOptions {
+            exclude: vec!["node_modules".to_string()],
+            ..Default::default()
+        };
+
+        // Pretend the first file was already scanned before the pause
+        let mut scanned = HashSet::new();
+        scanned.insert(root.join("file1.txt").display().to_string());
+
+        for (impl_name, scanner_impl) in [
+            ("Custom", ScannerImpl::Custom),
+            ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
+        ] {
+            let scanner = Scanner::new_with_options(root, scanner_impl, &options).unwrap();
+            let (entries, _) = scanner.scan_resuming(scanned.clone().into()).unwrap();
+
+            assert!(
+                !entries.iter().any(|e| e.name == "file1.txt"),
+                "{}",
+                impl_name
+            );
+            assert!(!entries.iter().any(|e| e.name == "pkg.js"), "{}", impl_name);
+            let marker = entries.iter().find(|e| e.name == "node_modules").unwrap();
+            assert_eq!(marker.kind, EntryKind::Excluded, "{}", impl_name);
+        }
+    }
+
+    #[test]
+    fn test_resume_skips_finished_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("a/sub")).unwrap();
+        fs::write(root.join("a/one.txt"), vec![0u8; 100]).unwrap();
+        fs::write(root.join("a/sub/two.txt"), vec![0u8; 200]).unwrap();
+        fs::create_dir(root.join("b")).unwrap();
+        fs::write(root.join("b/three.txt"), vec![0u8; 300]).unwrap();
+        fs::write(root.join("b/four.txt"), vec![0u8; 400]).unwrap();
+
+        // Paused after finishing a, and one file into b
+        let resume = ResumeState::new(
+            [root.join("a/sub"), root.join("a")],
+            [root.join("b/three.txt").display().to_string()],
+        );
+        assert_eq!(resume.len(), 2);
+
+        for scanner_impl in [
+            ScannerImpl::Walkdir,
+            ScannerImpl::Custom,
+            ScannerImpl::Hybrid,
+            ScannerImpl::TwoPhase,
+        ] {
+            let scanner = Scanner::new_with_impl(&root, scanner_impl);
+            let (entries, stats) = scanner.scan_resuming(resume.clone()).unwrap();
+
+            let mut paths: Vec<_> = entries.iter().map(|e| e.path.clone()).collect();
+            paths.sort();
+            assert_eq!(
+                paths,
+                [root.clone(), root.join("b"), root.join("b/four.txt")],
+                "{:?}",
+                scanner_impl
+            );
+            assert_eq!(stats.total_size, 400, "{:?}", scanner_impl);
+        }
+    }
+
+    #[test]
+    fn test_streamed_scans_store_every_entry_all_implementations() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+
+        let (expected, _) = Scanner::new_with_impl(root, ScannerImpl::Custom)
+            .scan()
+            .unwrap();
+        let mut expected: Vec<_> = expected.into_iter().map(|e| e.path).collect();
+        expected.sort();
+
+        for scanner_impl in [
+            ScannerImpl::Custom,
+            ScannerImpl::Walkdir,
+            ScannerImpl::Hybrid,
+            ScannerImpl::TwoPhase,
+        ] {
+            let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+            let mut scanner =
+                Scanner::with_sender(root, tx, None, Arc::new(AtomicBool::new(false)), false);
+            scanner.set_implementation(scanner_impl);
+            let (returned, _) = scanner.scan().unwrap();
+            drop(scanner);
+            assert!(returned.is_empty(), "{:?}", scanner_impl);
+
+            let mut stored = Vec::new();
+            while let Some(msg) = rx.blocking_recv() {
+                if let crate::db::ActorMessage::InsertBatch(entries) = msg {
+                    stored.extend(entries.into_iter().map(|e| e.path));
+                }
+            }
+            stored.sort();
+            assert_eq!(stored, expected, "{:?}", scanner_impl);
+        }
+    }
+
+    #[test]
+    fn test_finished_directories_are_checkpointed_after_their_entries() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+
+        for scanner_impl in [
+            ScannerImpl::Custom,
+            ScannerImpl::Walkdir,
+            ScannerImpl::Hybrid,
+            ScannerImpl::TwoPhase,
+        ] {
+            let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+            let mut scanner =
+                Scanner::with_sender(root, tx, None, Arc::new(AtomicBool::new(false)), false);
+            scanner.set_implementation(scanner_impl);
+            let (_, stats) = scanner.scan().unwrap();
+            drop(scanner);
+
+            let mut stored = HashSet::new();
+            let mut checkpointed = Vec::new();
+            while let Some(msg) = rx.blocking_recv() {
+                match msg {
+                    crate::db::ActorMessage::InsertBatch(entries) => {
+                        stored.extend(entries.into_iter().map(|e| e.path));
+                    }
+                    crate::db::ActorMessage::InsertCheckpoints(dirs) => {
+                        for dir in &dirs {
+                            assert!(
+                                stored.contains(dir),
+                                "{:?}: {}",
+                                scanner_impl,
+                                dir.display()
+                            );
+                            let below = stored.iter().filter(|p| p.starts_with(dir)).count();
+                            let on_disk = walkdir::WalkDir::new(dir).into_iter().count();
+                            assert_eq!(below, on_disk, "{:?}: {}", scanner_impl, dir.display());
+                        }
+                        checkpointed.extend(dirs);
+                    }
+                    _ => {}
+                }
+            }
+
+            // Every directory, the root last
+            assert_eq!(
+                checkpointed.len() as u64,
+                stats.total_dirs,
+                "{:?}",
+                scanner_impl
+            );
+            assert_eq!(
+                checkpointed.last().map(PathBuf::as_path),
+                Some(root),
+                "{:?}",
+                scanner_impl
+            );
+        }
+    }
+
+    #[test]
+    fn test_subtree_ends_follow_walk_order() {
+        let gathered: Vec<GatheredEntry> = [
+            ("/r", 0, true),
+            ("/r/a", 1, true),
+            ("/r/a/x", 2, false),
+            ("/r/a/b", 2, true),
+            ("/r/f", 1, false),
+            ("/r/c", 1, true),
+        ]
+        .into_iter()
+        .map(|(path, depth, is_dir)| GatheredEntry {
+            path: PathBuf::from(path),
+            depth,
+            is_dir,
+            kind: EntryKind::Normal,
+        })
+        .collect();
+
+        let ends = Scanner::subtree_ends(&gathered);
+        assert_eq!((ends[0], ends[1], ends[3], ends[5]), (6, 4, 4, 6));
+    }
+
+    #[test]
+    fn test_one_file_system_same_device_is_scanned() {
+        let temp_dir = create_test_filesystem();
+        let options = ScanOptions {
+            one_file_system: true,
+            ..Default::default()
+        };
+
+        for (impl_name, scanner_impl) in [
+            ("Walkdir", ScannerImpl::Walkdir),
+            ("Custom", ScannerImpl::Custom),
+            ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
+        ] {
+            let scanner =
+                Scanner::new_with_options(temp_dir.path(), scanner_impl, &options).unwrap();
+            let (entries, stats) = scanner.scan().unwrap();
+
+            assert!(
+                entries.iter().all(|e| e.kind == EntryKind::Normal),
+                "{}: nothing should be a mount point",
+                impl_name
+            );
+            assert_eq!(stats.total_files, 5, "{}", impl_name);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_one_file_system_marks_other_devices() {
+        use std::os::unix::fs::MetadataExt;
+
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+        let subdir = root.join("subdir");
+        let device = fs::metadata(&subdir).unwrap().dev();
+
+        let same = MarkerRules {
+            root_path: root.to_path_buf(),
+            filter: ScanFilter::default(),
+            one_file_system: true,
+            root_device: Some(device),
+        };
+        assert_eq!(
+            same.classify(&subdir, true, || DiskSource.metadata(&subdir, true).ok()),
+            EntryKind::Normal
+        );
+
+        // Pretend the root lives on another device
+        let other = MarkerRules {
+            root_device: Some(device.wrapping_add(1)),
+            ..same
+        };
+        assert_eq!(
+            other.classify(&subdir, true, || DiskSource.metadata(&subdir, true).ok()),
+            EntryKind::MountPoint
+        );
+        // Files and the root itself are never mount points
+        let file = root.join("file1.txt");
+        assert_eq!(
+            other.classify(&file, false, || DiskSource.metadata(&file, true).ok()),
+            EntryKind::Normal
+        );
+        assert_eq!(
+            other.classify(root, true, || DiskSource.metadata(root, true).ok()),
+            EntryKind::Normal
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_disk_usage_all_implementations() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+
+        // A sparse file: large apparent size, (almost) nothing allocated
+        let sparse = fs::File::create(root.join("subdir/sparse.img")).unwrap();
+        sparse.set_len(64 * 1024 * 1024).unwrap();
+        drop(sparse);
+
+        for (impl_name, scanner_impl) in [
+            ("Walkdir", ScannerImpl::Walkdir),
+            ("Custom", ScannerImpl::Custom),
+            ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
+        ] {
+            let scanner = Scanner::new_with_impl(root, scanner_impl);
+            let (entries, stats) = scanner.scan().unwrap();
+            let find = |name: &str| entries.iter().find(|e| e.name == name).unwrap();
+
+            let sparse_entry = find("sparse.img");
+            assert_eq!(sparse_entry.size, 64 * 1024 * 1024, "{}", impl_name);
+            assert!(
+                sparse_entry.disk_usage < sparse_entry.size,
+                "{}: sparse file should allocate less than its length",
+                impl_name
+            );
+
+            // Directories include their own blocks plus everything below them
+            let subdir = find("subdir");
+            let children_usage: u64 = entries
+                .iter()
+                .filter(|e| e.parent_path.as_deref() == Some(subdir.path.as_path()))
+                .map(|e| e.disk_usage)
+                .sum();
+            assert!(subdir.disk_usage >= children_usage, "{}", impl_name);
+
+            let root_entry = entries.iter().find(|e| e.path == root).unwrap();
+            assert_eq!(
+                root_entry.disk_usage, stats.total_disk_usage,
+                "{}: root disk usage should match the scan total",
+                impl_name
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stat_metadata_all_implementations() {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+        let script = root.join("subdir/run.sh");
+        fs::write(&script, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o750)).unwrap();
+        let expected = fs::metadata(&script).unwrap();
+
+        for (impl_name, scanner_impl) in [
+            ("Walkdir", ScannerImpl::Walkdir),
+            ("Custom", ScannerImpl::Custom),
+            ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
+        ] {
+            let scanner = Scanner::new_with_impl(root, scanner_impl);
+            let (entries, _) = scanner.scan().unwrap();
+
+            assert!(
+                entries.iter().all(|e| e.stat.is_some()),
+                "{}: every scanned entry should carry stat metadata",
+                impl_name
+            );
+
+            let entry = entries.iter().find(|e| e.name == "run.sh").unwrap();
+            let stat = entry.stat.unwrap();
+            assert_eq!(stat.inode, expected.ino(), "{}", impl_name);
+            assert_eq!(stat.device, expected.dev(), "{}", impl_name);
+            assert_eq!(stat.uid, expected.uid(), "{}", impl_name);
+            assert_eq!(stat.gid, expected.gid(), "{}", impl_name);
+            assert_eq!(stat.nlink, 1, "{}", impl_name);
+            assert_eq!(stat.permissions(), 0o750, "{}", impl_name);
+            assert_eq!(stat.mode_string(), "-rwxr-x---", "{}", impl_name);
+            assert!(stat.changed_at.is_some(), "{}", impl_name);
+
+            let dir = entries.iter().find(|e| e.name == "subdir").unwrap();
+            assert!(dir.stat.unwrap().mode_string().starts_with('d'));
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hard_link_policies_all_implementations() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+        // Three paths to the same 1000 bytes, spread across directories
+        fs::write(root.join("subdir/big.bin"), vec![0u8; 1000]).unwrap();
+        fs::hard_link(root.join("subdir/big.bin"), root.join("linked.bin")).unwrap();
+        fs::hard_link(
+            root.join("subdir/big.bin"),
+            root.join("subdir/nested/linked.bin"),
+        )
+        .unwrap();
+        let other_files = 5 + 5 + 4 + 4 + 9;
+
+        for (policy, expected) in [
+            (HardLinkPolicy::Once, other_files + 1000),
+            (HardLinkPolicy::Every, other_files + 3000),
+            (HardLinkPolicy::Proportional, other_files + 3 * 333),
+        ] {
+            let options = ScanOptions {
+                hard_links: policy,
+                ..Default::default()
+            };
+            for (impl_name, scanner_impl) in [
+                ("Walkdir", ScannerImpl::Walkdir),
+                ("Custom", ScannerImpl::Custom),
+                ("Hybrid", ScannerImpl::Hybrid),
+                ("TwoPhase", ScannerImpl::TwoPhase),
+            ] {
+                let scanner = Scanner::new_with_options(root, scanner_impl, &options).unwrap();
+                let (entries, stats) = scanner.scan().unwrap();
+
+                assert_eq!(stats.total_files, 8, "{} {:?}", impl_name, policy);
+                assert_eq!(stats.total_size, expected, "{} {:?}", impl_name, policy);
+                let root_entry = entries.iter().find(|e| e.path == root).unwrap();
+                assert_eq!(root_entry.size, expected, "{} {:?}", impl_name, policy);
+
+                // Every link keeps its own full size and records the link count
+                let links: Vec<_> = entries
+                    .iter()
+                    .filter(|e| e.name.ends_with(".bin"))
+                    .collect();
+                assert_eq!(links.len(), 3, "{} {:?}", impl_name, policy);
+                assert!(links
+                    .iter()
+                    .all(|e| e.size == 1000 && e.stat.unwrap().nlink == 3));
+            }
+        }
+    }
+
+    #[test]
+    fn test_dedicated_pool_all_implementations() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+
+        for scanner_impl in [
+            ScannerImpl::Walkdir,
+            ScannerImpl::Custom,
+            ScannerImpl::Hybrid,
+            ScannerImpl::TwoPhase,
+        ] {
+            let (plain_entries, plain) = Scanner::new_with_impl(root, scanner_impl).scan().unwrap();
+
+            // The implementation can be picked after construction too
+            let mut scanner = Scanner::new_with_impl(root, ScannerImpl::default());
+            scanner.set_implementation(scanner_impl);
+            scanner.set_threads(2);
+            let (entries, stats) = scanner.scan().unwrap();
+            assert_eq!(entries.len(), plain_entries.len(), "{:?}", scanner_impl);
+            assert_eq!(stats.total_size, plain.total_size, "{:?}", scanner_impl);
+            assert_eq!(stats.total_dirs, plain.total_dirs, "{:?}", scanner_impl);
+
+            // Utilization is reported per worker of the scan's own pool
+            let (_, utilization) = scanner.worker_load();
+            assert_eq!(utilization.len(), 2, "{:?}", scanner_impl);
+        }
+    }
+
+    #[test]
+    fn test_throttled_scan_all_implementations() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+
+        for (impl_name, scanner_impl) in [
+            ("Walkdir", ScannerImpl::Walkdir),
+            ("Custom", ScannerImpl::Custom),
+            ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
+        ] {
+            let (_, plain) = Scanner::new_with_impl(root, scanner_impl).scan().unwrap();
+            let mut scanner = Scanner::new_with_impl(root, scanner_impl);
+            scanner.limit_stats(100);
+            let started = Instant::now();
+            let (_, stats) = scanner.scan().unwrap();
+
+            // Every implementation stats at least each of the 8 entries below the root
+            assert!(
+                started.elapsed() >= Duration::from_millis(70),
+                "{} took {:?}",
+                impl_name,
+                started.elapsed()
+            );
+            assert_eq!(stats.total_size, plain.total_size, "{}", impl_name);
+            assert_eq!(stats.total_files, plain.total_files, "{}", impl_name);
+            assert_eq!(stats.total_dirs, plain.total_dirs, "{}", impl_name);
+            assert_eq!(scanner.stat_rate().map(|(_, limit)| limit), Some(100));
+        }
+    }
+
+    #[test]
+    fn test_archive_members_all_implementations() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+        let mut writer =
+            zip::ZipWriter::new(fs::File::create(root.join("subdir/bundle.zip")).unwrap());
+        let zip_options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        writer.start_file("docs/notes.txt", zip_options).unwrap();
+        std::io::Write::write_all(&mut writer, &[b'x'; 400]).unwrap();
+        writer.finish().unwrap();
+        let archive_size = fs::metadata(root.join("subdir/bundle.zip")).unwrap().len();
+
+        for (impl_name, scanner_impl) in [
+            ("Walkdir", ScannerImpl::Walkdir),
+            ("Custom", ScannerImpl::Custom),
+            ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
+        ] {
+            let plain = Scanner::new_with_impl(root, scanner_impl).scan().unwrap();
+            let options = ScanOptions {
+                archives: true,
+                ..Default::default()
+            };
+            let scanner = Scanner::new_with_options(root, scanner_impl, &options).unwrap();
+            let (entries, stats) = scanner.scan().unwrap();
+
+            // Members are listed but don't change any totals
+            assert_eq!(stats.total_size, plain.1.total_size, "{}", impl_name);
+            assert_eq!(stats.total_files, plain.1.total_files, "{}", impl_name);
+            assert_eq!(entries.len(), plain.0.len() + 2, "{}", impl_name);
+            let archive = entries
+                .iter()
+                .find(|e| e.path == root.join("subdir/bundle.zip"))
+                .unwrap();
+            assert_eq!(archive.kind, EntryKind::Archive, "{}", impl_name);
+            assert_eq!(archive.size, archive_size);
+            let member = entries
+                .iter()
+                .find(|e| e.path == root.join("subdir/bundle.zip/docs/notes.txt"))
+                .unwrap();
+            assert_eq!(member.kind, EntryKind::InArchive, "{}", impl_name);
+            assert_eq!((member.size, member.depth), (400, 4));
+        }
+
+        // Members below the depth limit aren't stored
+        let options = ScanOptions {
+            archives: true,
+            max_depth: Some(3),
+            ..Default::default()
+        };
+        let scanner = Scanner::new_with_options(root, ScannerImpl::TwoPhase, &options).unwrap();
+        let (entries, _) = scanner.scan().unwrap();
+        assert!(entries.iter().any(|e| e.name == "docs"));
+        assert!(!entries.iter().any(|e| e.name == "notes.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_paths_are_recorded_all_implementations() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+        // Following a dangling symlink fails, even for root
+        std::os::unix::fs::symlink(root.join("missing"), root.join("subdir/dangling")).unwrap();
+
+        let options = ScanOptions {
+            follow_symlinks: true,
+            ..Default::default()
+        };
+        for (impl_name, scanner_impl) in [
+            ("Walkdir", ScannerImpl::Walkdir),
+            ("Custom", ScannerImpl::Custom),
+            ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
+        ] {
+            let scanner = Scanner::new_with_options(root, scanner_impl, &options).unwrap();
+            let (entries, stats) = scanner.scan().unwrap();
+
+            let errors = scanner.errors();
+            assert_eq!(errors.len(), 1, "{}: {:?}", impl_name, errors);
+            assert_eq!(
+                errors[0].path,
+                root.join("subdir/dangling"),
+                "{}",
+                impl_name
+            );
+            assert_eq!(errors[0].kind, ScanErrorKind::NotFound, "{}", impl_name);
+            assert_eq!(stats.error_count, 1, "{}", impl_name);
+
+            // The rest of the tree is still scanned
+            assert!(
+                entries.iter().any(|e| e.name == "deep.txt"),
+                "{}",
+                impl_name
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_permission_denied_directory_is_recorded() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+        let locked = root.join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(locked.join("secret.txt"), b"hidden").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        // Permissions don't apply to root, so there is nothing to test
+        if fs::read_dir(&locked).is_ok() {
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        for scanner_impl in [
+            ScannerImpl::Walkdir,
+            ScannerImpl::Custom,
+            ScannerImpl::Hybrid,
+            ScannerImpl::TwoPhase,
+        ] {
+            let scanner = Scanner::new_with_impl(root, scanner_impl);
+            let (_, stats) = scanner.scan().unwrap();
+            let errors = scanner.errors();
+            assert!(stats.error_count >= 1);
+            assert!(errors
+                .iter()
+                .any(|e| e.path == locked && e.kind == ScanErrorKind::PermissionDenied));
+        }
+
+        // Let TempDir clean up
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_max_depth_stores_summary_all_implementations() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+
+        let options = ScanOptions {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        for (impl_name, scanner_impl) in [
+            ("Walkdir", ScannerImpl::Walkdir),
+            ("Custom", ScannerImpl::Custom),
+            ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
+        ] {
+            let (full_entries, full_stats) =
+                Scanner::new_with_impl(root, scanner_impl).scan().unwrap();
+            let scanner = Scanner::new_with_options(root, scanner_impl, &options).unwrap();
+            let (entries, stats) = scanner.scan().unwrap();
+
+            // Nothing below the limit is stored...
+            assert!(entries.iter().all(|e| e.depth <= 1), "{}", impl_name);
+            assert!(
+                !entries.iter().any(|e| e.name == "file3.txt"),
+                "{}",
+                impl_name
+            );
+
+            // ...but everything is still counted
+            assert_eq!(stats.total_files, full_stats.total_files, "{}", impl_name);
+            assert_eq!(stats.total_size, full_stats.total_size, "{}", impl_name);
+            let find = |entries: &[FileEntry], name: &str| {
+                entries.iter().find(|e| e.name == name).unwrap().clone()
+            };
+            let subdir = find(&entries, "subdir");
+            assert_eq!(subdir.kind, EntryKind::Summarized, "{}", impl_name);
+            assert_eq!(
+                subdir.size,
+                find(&full_entries, "subdir").size,
+                "{}",
+                impl_name
+            );
+
+            // Directories with nothing below them aren't summarized
+            assert_eq!(
+                find(&entries, "empty_dir").kind,
+                EntryKind::Normal,
+                "{}",
+                impl_name
+            );
+            assert_eq!(
+                find(&entries, "file1.txt").kind,
+                EntryKind::Normal,
+                "{}",
+                impl_name
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_depth_streams_summarized_counts_all_implementations() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+
+        let options = ScanOptions {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        // (files, directories) among entries, leaving out markers
+        fn count_stored(entries: &[FileEntry]) -> (u64, u64) {
+            let counted: Vec<_> = entries.iter().filter(|e| !e.kind.is_marker()).collect();
+            let dirs = counted.iter().filter(|e| e.is_dir).count() as u64;
+            (counted.len() as u64 - dirs, dirs)
+        }
+        for (impl_name, scanner_impl) in [
+            ("Walkdir", ScannerImpl::Walkdir),
+            ("Custom", ScannerImpl::Custom),
+            ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
+        ] {
+            let (tx, mut rx) = tokio::sync::mpsc::channel(100);
+            let collector = std::thread::spawn(move || {
+                let (mut files, mut dirs) = (0, 0);
+                while let Some(msg) = rx.blocking_recv() {
+                    match msg {
+                        crate::db::ActorMessage::InsertBatch(entries) => {
+                            let (f, d) = count_stored(&entries);
+                            files += f;
+                            dirs += d;
+                        }
+                        crate::db::ActorMessage::CountSummarized { files: f, dirs: d } => {
+                            files += f;
+                            dirs += d;
+                        }
+                        _ => {}
+                    }
+                }
+                (files, dirs)
+            });
+
+            let mut scanner =
+                Scanner::with_options(root, tx, None, Arc::new(AtomicBool::new(false)), &options)
+                    .unwrap();
+            scanner.set_implementation(scanner_impl);
+            let (_, stats) = scanner.scan().unwrap();
+            drop(scanner);
+
+            // Stored entries and summarized counts add up to the scan's totals
+            let (files, dirs) = collector.join().unwrap();
+            assert_eq!(files, stats.total_files, "{}", impl_name);
+            assert_eq!(dirs, stats.total_dirs, "{}", impl_name);
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_incremental_scan_matches_full_scan() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+
+        let (base_entries, _) = Scanner::new_with_impl(root, ScannerImpl::Hybrid)
+            .scan()
+            .unwrap();
+
+        // Change two directories; the root and empty_dir are left alone
+        fs::write(root.join("subdir/nested/new.txt"), b"new file").unwrap();
+        fs::remove_file(root.join("subdir/file4.txt")).unwrap();
+
+        let mut scanner = Scanner::new_with_impl(root, ScannerImpl::Hybrid);
+        scanner.reuse_unchanged(PreviousScan::from_entries(base_entries));
+        let (entries, stats) = scanner.scan().unwrap();
+        let (full_entries, full_stats) = Scanner::new_with_impl(root, ScannerImpl::Custom)
+            .scan()
+            .unwrap();
+
+        assert_eq!(stats.reused_dirs, 2);
+        assert_eq!(stats.total_files, full_stats.total_files);
+        assert_eq!(stats.total_dirs, full_stats.total_dirs);
+        assert_eq!(stats.total_size, full_stats.total_size);
+        assert_eq!(stats.total_disk_usage, full_stats.total_disk_usage);
+
+        let summary = |entries: &[FileEntry]| {
+            let mut rows: Vec<_> = entries
+                .iter()
+                .map(|e| {
+                    (
+                        e.path.clone(),
+                        e.parent_path.clone(),
+                        e.size,
+                        e.disk_usage,
+                        e.depth,
+                        e.kind.as_db_str(),
+                        e.stat.map(|st| st.inode),
+                    )
+                })
+                .collect();
+            rows.sort();
+            rows
+        };
+        assert_eq!(summary(&entries), summary(&full_entries));
+    }
+
+    #[test]
+    fn test_two_phase_progress_has_total_and_percentage() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for i in 0..250 {
+            fs::write(root.join(format!("file_{}.txt", i)), b"data").unwrap();
+        }
+
+        let (tx, _rx) = tokio::sync::mpsc::channel(100);
+        let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+        let scanner = Scanner::with_sender(
+            root,
+            tx,
+            Some(progress_tx),
+            Arc::new(AtomicBool::new(false)),
+            false,
+        );
+        let (_, stats) = scanner.scan().unwrap();
+        assert_eq!(stats.total_files, 250);
+
+        let mut updates = Vec::new();
+        while let Ok(update) = progress_rx.try_recv() {
+            updates.push(update);
+        }
+
+        // Counting comes first and has no total yet
+        assert!(updates[0].gathering);
+        assert!(updates[0].total_estimate.is_none());
+
+        // Measuring reports against everything that was counted (the files plus the root)
+        let measuring: Vec<_> = updates.iter().filter(|u| !u.gathering).collect();
+        assert!(measuring.len() >= 2);
+        for update in measuring {
+            assert_eq!(update.total_estimate, Some(251));
+            assert!((0.0..=100.0).contains(&update.percent_done.unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_parallel_walks_report_active_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for d in 0..5 {
+            let dir = root.join(format!("dir_{}", d));
+            fs::create_dir(&dir).unwrap();
+            for f in 0..60 {
+                fs::write(dir.join(format!("file_{}.txt", f)), b"data").unwrap();
+            }
+        }
+
+        for scanner_impl in [ScannerImpl::Hybrid, ScannerImpl::TwoPhase] {
+            let (tx, _rx) = tokio::sync::mpsc::channel(1000);
+            let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
+            let mut scanner = Scanner::with_sender(
+                root,
+                tx,
+                Some(progress_tx),
+                Arc::new(AtomicBool::new(false)),
+                false,
+            );
+            scanner.set_implementation(scanner_impl);
+            scanner.scan().unwrap();
+
+            let mut walked = Vec::new();
+            while let Ok(update) = progress_rx.try_recv() {
+                if scanner_impl == ScannerImpl::Hybrid || update.gathering {
+                    walked.push(update);
+                }
+            }
+            assert!(!walked.is_empty(), "{:?}", scanner_impl);
+
+            // The root is still active early on, with some of its directories walked
+            let root_str = root.display().to_string();
+            let (_, done, total) = walked[0]
+                .active_dirs
+                .iter()
+                .find(|(path, _, _)| *path == root_str)
+                .unwrap_or_else(|| panic!("{:?}: root not active", scanner_impl));
+            assert_eq!(*total, 5);
+            assert!(*done >= 1 && done < total);
+
+            // Directories are retired once all of their children are in
+            for update in &walked {
+                assert!(update.active_dirs.iter().all(|(_, d, t)| d < t));
+            }
+            assert!(scanner.active_dirs.lock().unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_progress_predicted_from_previous_scan() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut scanner = Scanner::new(temp_dir.path());
+        assert_eq!(scanner.estimate_progress(50), (None, None, None));
+
+        scanner.predict_from_previous(200, Duration::from_secs(10));
+        assert_eq!(
+            scanner.estimate_progress(50),
+            (Some(200), Some(25.0), Some(Duration::from_millis(7500)))
+        );
+        // Past the prediction the percentage holds and the ETA is unknown
+        assert_eq!(
+            scanner.estimate_progress(250),
+            (Some(200), Some(99.0), None)
+        );
+
+        // A known total takes over from the prediction
+        scanner.set_progress_total(400);
+        let (total, percent, _) = scanner.estimate_progress(100);
+        assert_eq!((total, percent), (Some(400), Some(25.0)));
+    }
+
+    #[test]
+    fn test_parse_path_list() {
+        let paths = parse_path_list(b"/a/b\r\n\n/a/c d\n", false).unwrap();
+        assert_eq!(paths, vec![PathBuf::from("/a/b"), PathBuf::from("/a/c d")]);
+
+        let paths = parse_path_list(b"/a/with\nnewline\0/b\0", true).unwrap();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("/a/with\nnewline"), PathBuf::from("/b")]
+        );
+
+        let paths = parse_path_list(b"relative", false).unwrap();
+        assert_eq!(
+            paths,
+            vec![std::env::current_dir().unwrap().join("relative")]
+        );
+    }
+
+    #[test]
+    fn test_common_ancestor() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+
+        let listed = [
+            root.join("subdir/file3.txt"),
+            root.join("subdir/nested/deep.txt"),
+        ];
+        assert_eq!(common_ancestor(&listed), Some(root.join("subdir")));
+        // `find .` lists the directory itself first
+        let listed = [root.to_path_buf(), root.join("file1.txt")];
+        assert_eq!(common_ancestor(&listed), Some(root.to_path_buf()));
+        // A single file is recorded within its directory
+        let listed = [root.join("file1.txt")];
+        assert_eq!(common_ancestor(&listed), Some(root.to_path_buf()));
+        assert_eq!(common_ancestor(&[]), None);
+    }
+
+    #[test]
+    fn test_listed_scan_synthesizes_ancestors() {
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+        let outside = TempDir::new().unwrap();
+
+        let scanner = Scanner::new(root);
+        let (entries, stats) = scanner
+            .scan_listed(vec![
+                root.join("file1.txt"),
+                root.join("subdir/nested/deep.txt"),
+                root.join("subdir/nested/deep.txt"),
+                root.join("empty_dir"),
+                root.join("missing.txt"),
+                outside.path().to_path_buf(),
+            ])
+            .unwrap();
+        let find = |path: PathBuf| entries.iter().find(|e| e.path == path).unwrap();
+
+        assert_eq!(stats.total_files, 2);
+        // The root, the synthesized subdir and nested, and the listed empty_dir
+        assert_eq!(stats.total_dirs, 4);
+        assert_eq!(stats.total_size, 5 + 9);
+        assert_eq!(entries.len(), 6);
+
+        let root_entry = find(root.to_path_buf());
+        assert_eq!(root_entry.depth, 0);
+        assert_eq!(root_entry.size, 14);
+        assert!(root_entry.stat.is_none());
+
+        let nested = find(root.join("subdir/nested"));
+        assert!(nested.is_dir);
+        assert_eq!((nested.depth, nested.size), (2, 9));
+        assert!(nested.stat.is_none() && nested.modified_at.is_none());
+        assert_eq!(find(root.join("subdir")).size, 9);
+
+        // Listed entries are statted; unlisted files aren't recorded
+        assert!(find(root.join("empty_dir")).modified_at.is_some());
+        assert!(!entries.iter().any(|e| e.path == root.join("file2.txt")));
+
+        let errors = scanner.errors();
+        assert_eq!(errors.len(), 2);
+        assert!(errors
+            .iter()
+            .any(|e| e.path == root.join("missing.txt") && e.kind == ScanErrorKind::NotFound));
+        assert!(errors
+            .iter()
+            .any(|e| e.path == outside.path() && e.kind == ScanErrorKind::Other));
+    }
+
+    #[test]
+    fn test_resolve_roots() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("a/nested")).unwrap();
+        fs::create_dir_all(root.join("b/c")).unwrap();
+
+        let (top, roots) =
+            resolve_roots(&[root.join("b/c"), root.join("a"), root.join("b/c")]).unwrap();
+        assert_eq!(top, root);
+        assert_eq!(roots, vec![root.join("a"), root.join("b/c")]);
+
+        assert!(resolve_roots(&[root.join("a"), root.join("a/nested")]).is_err());
+        assert!(resolve_roots(&[root.join("a"), root.join("missing")]).is_err());
+    }
+
+    #[test]
+    fn test_multi_root_scan_all_implementations() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("a/sub")).unwrap();
+        fs::write(root.join("a/one.txt"), vec![0u8; 100]).unwrap();
+        fs::write(root.join("a/sub/two.txt"), vec![0u8; 200]).unwrap();
+        fs::create_dir_all(root.join("b/c")).unwrap();
+        fs::write(root.join("b/c/three.txt"), vec![0u8; 300]).unwrap();
+        // Outside the roots, so not scanned
+        fs::write(root.join("b/skipped.txt"), vec![0u8; 400]).unwrap();
+
+        let (top, roots) = resolve_roots(&[root.join("a"), root.join("b/c")]).unwrap();
+        for scanner_impl in [
+            ScannerImpl::Walkdir,
+            ScannerImpl::Custom,
+            ScannerImpl::Hybrid,
+            ScannerImpl::TwoPhase,
+        ] {
+            let mut scanner = Scanner::new_with_impl(&top, scanner_impl);
+            let (entries, stats) = scanner.scan_roots(&roots).unwrap();
+            let find = |path: &Path| {
+                entries.iter().find(|e| e.path == path).unwrap_or_else(|| {
+                    panic!("{:?}: no entry for {}", scanner_impl, path.display())
+                })
+            };
+
+            assert_eq!(stats.total_files, 3, "{:?}", scanner_impl);
+            assert_eq!(stats.total_size, 600, "{:?}", scanner_impl);
+            // a, a/sub and b/c scanned, the top and b synthesized
+            assert_eq!(stats.total_dirs, 5, "{:?}", scanner_impl);
+            assert_eq!(entries.len(), 8, "{:?}", scanner_impl);
+
+            // The top is synthesized from both roots
+            let top_entry = find(&top);
+            assert_eq!(top_entry.depth, 0);
+            assert_eq!(top_entry.size, 600);
+            assert_eq!(top_entry.disk_usage, stats.total_disk_usage);
+            assert!(top_entry.modified_at.is_none());
+
+            // Depths count from the top
+            assert_eq!(find(&root.join("a")).depth, 1);
+            assert_eq!(find(&root.join("a/sub/two.txt")).depth, 3);
+            assert_eq!(find(&root.join("b")).size, 300);
+            assert_eq!(find(&root.join("b/c")).depth, 2);
+            assert_eq!(find(&root.join("b/c/three.txt")).depth, 3);
+            assert!(!entries.iter().any(|e| e.name == "skipped.txt"));
+        }
+    }
+
+    #[test]
+    fn test_subtree_resume_all_implementations() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(root.join("a/sub")).unwrap();
+        fs::write(root.join("a/one.txt"), vec![0u8; 100]).unwrap();
+        fs::write(root.join("a/sub/two.txt"), vec![0u8; 200]).unwrap();
+        fs::create_dir(root.join("b")).unwrap();
+        fs::write(root.join("b/three.txt"), vec![0u8; 300]).unwrap();
+
+        let subtree = root.join("a");
+        for scanner_impl in [
+            ScannerImpl::Walkdir,
+            ScannerImpl::Custom,
+            ScannerImpl::Hybrid,
+            ScannerImpl::TwoPhase,
+        ] {
+            let mut scanner = Scanner::new_with_impl(&root, scanner_impl);
+            let scanned = HashSet::from([root.join("a/one.txt").display().to_string()]);
+            let (entries, stats) = scanner
+                .scan_subtree_resuming(&root, &subtree, scanned.into())
+                .unwrap();
+
+            // Only the subtree is walked, skipping what was already recorded
+            let mut paths: Vec<_> = entries.iter().map(|e| e.path.clone()).collect();
+            paths.sort();
+            assert_eq!(
+                paths,
+                [
+                    subtree.clone(),
+                    root.join("a/sub"),
+                    root.join("a/sub/two.txt")
+                ],
+                "{:?}",
+                scanner_impl
+            );
+            assert_eq!(stats.total_size, 200, "{:?}", scanner_impl);
+            let dir = entries.iter().find(|e| e.path == subtree).unwrap();
+            assert_eq!(
+                (dir.name.as_str(), dir.depth),
+                ("a", 1),
+                "{:?}",
+                scanner_impl
+            );
+            assert_eq!(dir.parent_path.as_deref(), Some(root.as_path()));
+
+            // The scanner is back at the root afterwards
+            let (entries, _) = scanner.scan().unwrap();
+            assert_eq!(entries.len(), 7, "{:?}", scanner_impl);
+        }
+
+        let mut scanner = Scanner::new(&root);
+        assert!(scanner
+            .scan_subtree_resuming(
+                &root,
+                temp_dir.path().parent().unwrap(),
+                ResumeState::default(),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_synthetic_source_all_implementations() {
+        use crate::source::{SyntheticSource, TreeShape};
+
+        let shape = TreeShape {
+            depth: 3,
+            fanout: 4,
+            files_per_dir: 6,
+            ..Default::default()
+        };
+        let mut results = Vec::new();
+        for (impl_name, scanner_impl) in [
+            ("Walkdir", ScannerImpl::Walkdir),
+            ("Custom", ScannerImpl::Custom),
+            ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
+        ] {
+            let mut scanner = Scanner::new_with_impl("/synthetic", scanner_impl);
+            scanner.set_source(Arc::new(SyntheticSource::new("/synthetic", shape)));
+            let (entries, stats) = scanner.scan().unwrap();
+
+            assert_eq!(stats.total_files, shape.files(), "{}", impl_name);
+            assert_eq!(stats.total_dirs, shape.dirs(), "{}", impl_name);
+            assert_eq!(stats.error_count, 0, "{}", impl_name);
+            assert_eq!(entries.len() as u64, shape.files() + shape.dirs());
+            let root = entries.iter().find(|e| e.depth == 0).unwrap();
+            assert_eq!(root.size, stats.total_size, "{}", impl_name);
+            results.push((stats.total_size, stats.total_disk_usage));
+        }
+        assert!(results.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+
+    #[test]
+    fn test_fixture_replay_matches_disk_scan() {
+        use crate::source::FixtureSource;
+
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+        let fixture = Arc::new(FixtureSource::record(&DiskSource, root).unwrap());
+        let options = ScanOptions {
+            exclude: vec!["nested".to_string()],
+            ..Default::default()
+        };
+
+        for (impl_name, scanner_impl) in [
+            ("Walkdir", ScannerImpl::Walkdir),
+            ("Custom", ScannerImpl::Custom),
+            ("Hybrid", ScannerImpl::Hybrid),
+            ("TwoPhase", ScannerImpl::TwoPhase),
+        ] {
+            let (mut on_disk, disk_stats) = Scanner::new_with_options(root, scanner_impl, &options)
+                .unwrap()
+                .scan()
+                .unwrap();
+            let mut scanner = Scanner::new_with_options(root, scanner_impl, &options).unwrap();
+            scanner.set_source(fixture.clone());
+            let (mut replayed, stats) = scanner.scan().unwrap();
+
+            assert_eq!(stats.total_size, disk_stats.total_size, "{}", impl_name);
+            assert_eq!(stats.total_disk_usage, disk_stats.total_disk_usage);
+            assert_eq!(stats.total_files, disk_stats.total_files, "{}", impl_name);
+            assert_eq!(stats.total_dirs, disk_stats.total_dirs, "{}", impl_name);
+
+            on_disk.sort_by(|a, b| a.path.cmp(&b.path));
+            replayed.sort_by(|a, b| a.path.cmp(&b.path));
+            // Recording reads directories, which can update their access times
+            let summary = |entries: &[FileEntry]| -> Vec<_> {
+                entries
+                    .iter()
+                    .map(|e| {
+                        let stat = e.stat.map(|st| EntryStat {
+                            accessed_at: None,
+                            ..st
+                        });
+                        (e.path.clone(), e.size, e.disk_usage, e.kind, stat)
+                    })
+                    .collect()
+            };
+            assert_eq!(summary(&replayed), summary(&on_disk), "{}", impl_name);
+        }
+    }
+
+    #[test]
+    fn test_hybrid_scan_stats_each_file_once() {
+        use crate::source::{DirChild, FixtureSource};
+
+        /// Counts the metadata calls made for each path
+        struct CountingSource {
+            inner: FixtureSource,
+            calls: Mutex<HashMap<PathBuf, usize>>,
+        }
+
+        impl FsSource for CountingSource {
+            fn metadata(&self, path: &Path, follow_symlinks: bool) -> io::Result<Metadata> {
+                *self
+                    .calls
+                    .lock()
+                    .unwrap()
+                    .entry(path.to_path_buf())
+                    .or_default() += 1;
+                self.inner.metadata(path, follow_symlinks)
+            }
+
+            fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<DirChild>>> {
+                self.inner.read_dir(path)
+            }
+        }
+
+        let temp_dir = create_test_filesystem();
+        let root = temp_dir.path();
+        let source = Arc::new(CountingSource {
+            inner: FixtureSource::record(&DiskSource, root).unwrap(),
+            calls: Mutex::new(HashMap::new()),
+        });
+        let mut scanner =
+            Scanner::new_with_options(root, ScannerImpl::Hybrid, &ScanOptions::default()).unwrap();
+        scanner.set_source(source.clone());
+        let (entries, stats) = scanner.scan().unwrap();
+        assert!(stats.total_files > 0);
+
+        let calls = source.calls.lock().unwrap();
+        for file in entries.iter().filter(|e| !e.is_dir) {
+            assert_eq!(calls.get(&file.path), Some(&1), "{}", file.path.display());
+        }
+    }
 }
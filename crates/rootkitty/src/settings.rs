@@ -4,6 +4,9 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 
+pub use crate::db::SizeMode;
+pub use crate::filetypes::Category;
+pub use crate::scanner::{HardLinkPolicy, ScannerImpl};
 pub use crate::ui::SortMode;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -12,6 +15,8 @@ pub struct Settings {
     pub ui: UiSettings,
     #[serde(default)]
     pub scan: ScanSettings,
+    #[serde(default)]
+    pub types: TypeSettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +25,9 @@ pub struct UiSettings {
     pub file_tree_sort: SortMode,
     #[serde(default = "default_scan_list_sort")]
     pub scan_list_sort: SortMode,
+    /// Show apparent sizes or allocated disk usage
+    #[serde(default)]
+    pub size_mode: SizeMode,
     #[serde(default = "default_auto_fold_depth")]
     pub auto_fold_depth: u32,
 }
@@ -29,6 +37,61 @@ pub struct ScanSettings {
     /// Whether to follow symbolic links during scanning
     #[serde(default = "default_follow_symlinks")]
     pub follow_symlinks: bool,
+    /// Gitignore-style patterns for paths to skip (e.g. ".git/objects", "/proc")
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Patterns that re-include paths matched by `exclude`
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Don't cross into other filesystems (NFS, bind mounts, /proc) while scanning
+    #[serde(default = "default_one_file_system")]
+    pub one_file_system: bool,
+    /// How files with several hard links count towards totals
+    #[serde(default)]
+    pub hard_links: HardLinkPolicy,
+    /// List the members of tar and zip archives as entries below them
+    #[serde(default)]
+    pub archives: bool,
+    /// Command that starts the agent for `scan ssh://host/path`; `{host}` and
+    /// `{path}` are filled in from the URL
+    #[serde(default = "default_remote_command")]
+    pub remote_command: String,
+    /// Workers each scan runs on (0 for one per core)
+    #[serde(default)]
+    pub threads: usize,
+    /// How the tree is walked
+    #[serde(default)]
+    pub implementation: ScannerImpl,
+    /// Limits that keep scans from saturating the disk
+    #[serde(default)]
+    pub throttle: ThrottleSettings,
+}
+
+/// `[scan.throttle]`: how gently scans treat the disk
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThrottleSettings {
+    /// Most metadata calls per second across all scan workers (unset or 0 for no limit)
+    pub max_stats_per_sec: Option<u32>,
+    /// Scan at idle I/O priority, so other workloads get the disk first (Linux only)
+    pub io_nice: bool,
+}
+
+impl ThrottleSettings {
+    /// The stat limit, if there is one
+    pub fn stat_limit(&self) -> Option<u32> {
+        self.max_stats_per_sec.filter(|&limit| limit > 0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeSettings {
+    /// Categories for the file type breakdown; an extension counts towards the first listing it
+    #[serde(default = "crate::filetypes::default_categories")]
+    pub categories: Vec<Category>,
+    /// How many of the largest files with an unknown extension to identify by their content
+    #[serde(default)]
+    pub sniff_sample: usize,
 }
 
 impl Default for UiSettings {
@@ -36,6 +99,7 @@ impl Default for UiSettings {
         Self {
             file_tree_sort: default_file_tree_sort(),
             scan_list_sort: default_scan_list_sort(),
+            size_mode: SizeMode::default(),
             auto_fold_depth: default_auto_fold_depth(),
         }
     }
@@ -45,6 +109,24 @@ impl Default for ScanSettings {
     fn default() -> Self {
         Self {
             follow_symlinks: default_follow_symlinks(),
+            exclude: Vec::new(),
+            include: Vec::new(),
+            one_file_system: default_one_file_system(),
+            hard_links: HardLinkPolicy::default(),
+            archives: false,
+            remote_command: default_remote_command(),
+            threads: 0,
+            implementation: ScannerImpl::default(),
+            throttle: ThrottleSettings::default(),
+        }
+    }
+}
+
+impl Default for TypeSettings {
+    fn default() -> Self {
+        Self {
+            categories: crate::filetypes::default_categories(),
+            sniff_sample: 0,
         }
     }
 }
@@ -65,6 +147,14 @@ fn default_follow_symlinks() -> bool {
     false
 }
 
+fn default_one_file_system() -> bool {
+    false
+}
+
+fn default_remote_command() -> String {
+    crate::remote::DEFAULT_COMMAND.to_string()
+}
+
 impl Settings {
     /// Load settings from a file, or return defaults if file doesn't exist
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
@@ -153,6 +243,9 @@ mod tests {
         assert_eq!(settings.ui.scan_list_sort, SortMode::BySize);
         assert_eq!(settings.ui.auto_fold_depth, 1);
         assert!(!settings.scan.follow_symlinks);
+        assert!(!settings.scan.one_file_system);
+        assert_eq!(settings.scan.hard_links, HardLinkPolicy::Once);
+        assert_eq!(settings.scan.remote_command, crate::remote::DEFAULT_COMMAND);
     }
 
     #[test]
@@ -248,6 +341,102 @@ mod tests {
         assert!(content.contains("by_path"));
     }
 
+    #[test]
+    fn test_scan_exclude_patterns_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings_path = temp_dir.path().join("settings.toml");
+
+        // Older settings files without patterns still load
+        std::fs::write(&settings_path, "[scan]\nfollow_symlinks = true\n").unwrap();
+        let loaded = Settings::load(&settings_path).unwrap();
+        assert!(loaded.scan.follow_symlinks);
+        assert!(loaded.scan.exclude.is_empty());
+
+        let mut settings = Settings::default();
+        settings.scan.exclude = vec![".git/objects".to_string(), "/proc".to_string()];
+        settings.scan.include = vec!["keep.log".to_string()];
+        settings.save(&settings_path).unwrap();
+
+        let loaded = Settings::load(&settings_path).unwrap();
+        assert_eq!(loaded.scan.exclude, vec![".git/objects", "/proc"]);
+        assert_eq!(loaded.scan.include, vec!["keep.log"]);
+    }
+
+    #[test]
+    fn test_throttle_settings_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings_path = temp_dir.path().join("settings.toml");
+
+        std::fs::write(
+            &settings_path,
+            "[scan]\nexclude = []\n\n[scan.throttle]\nmax_stats_per_sec = 0\n",
+        )
+        .unwrap();
+        let loaded = Settings::load(&settings_path).unwrap();
+        assert_eq!(loaded.scan.throttle.stat_limit(), None);
+        assert!(!loaded.scan.throttle.io_nice);
+
+        let mut settings = Settings::default();
+        settings.scan.throttle = ThrottleSettings {
+            max_stats_per_sec: Some(2000),
+            io_nice: true,
+        };
+        settings.save(&settings_path).unwrap();
+
+        let loaded = Settings::load(&settings_path).unwrap();
+        assert_eq!(loaded.scan.throttle, settings.scan.throttle);
+        assert_eq!(loaded.scan.throttle.stat_limit(), Some(2000));
+    }
+
+    #[test]
+    fn test_scan_workers_from_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings_path = temp_dir.path().join("settings.toml");
+
+        std::fs::write(
+            &settings_path,
+            "[scan]\nthreads = 32\nimplementation = \"hybrid\"\n",
+        )
+        .unwrap();
+        let loaded = Settings::load(&settings_path).unwrap();
+        assert_eq!(loaded.scan.threads, 32);
+        assert_eq!(loaded.scan.implementation, ScannerImpl::Hybrid);
+
+        let defaults = Settings::default();
+        assert_eq!(defaults.scan.threads, 0);
+        assert_eq!(defaults.scan.implementation, ScannerImpl::TwoPhase);
+    }
+
+    #[test]
+    fn test_type_categories_from_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let settings_path = temp_dir.path().join("settings.toml");
+
+        // Without a [types] section the built-in categories apply
+        std::fs::write(&settings_path, "[scan]\nfollow_symlinks = true\n").unwrap();
+        let loaded = Settings::load(&settings_path).unwrap();
+        assert_eq!(
+            loaded.types.categories,
+            crate::filetypes::default_categories()
+        );
+
+        std::fs::write(
+            &settings_path,
+            "[types]\nsniff_sample = 50\n\n\
+             [[types.categories]]\nname = \"Renders\"\nextensions = [\"exr\", \"blend\"]\n",
+        )
+        .unwrap();
+        let loaded = Settings::load(&settings_path).unwrap();
+        assert_eq!(loaded.types.sniff_sample, 50);
+        assert_eq!(loaded.types.categories.len(), 1);
+        assert_eq!(loaded.types.categories[0].name, "Renders");
+
+        // Saved categories load back unchanged
+        loaded.save(&settings_path).unwrap();
+        let reloaded = Settings::load(&settings_path).unwrap();
+        assert_eq!(reloaded.types.categories, loaded.types.categories);
+    }
+
     #[test]
     fn test_sort_mode_display_names() {
         assert_eq!(SortMode::BySize.display_name(), "By Size (Descending)");
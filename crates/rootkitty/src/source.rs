@@ -0,0 +1,930 @@
+//! Where scans read the filesystem from
+//!
+//! Every scanner implementation reads through an [`FsSource`]. [`DiskSource`]
+//! is the real filesystem. [`SyntheticSource`] makes up a tree of any shape in
+//! memory, for the demo and for load testing the scanners without a disk in
+//! the way. [`FixtureSource`] replays a tree recorded from disk, so a tree seen
+//! on one machine can be scanned again anywhere.
+//!
+//! Sources answer metadata calls and directory listings, and walks are built
+//! on those, except on disk, where walkdir and jwalk do the walking.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::scanner::{EntryKind, EntryStat, ScanError};
+
+/// What a scan learns about a path from one metadata call
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    /// Apparent size (file length)
+    pub len: u64,
+    /// Bytes allocated on disk
+    pub disk_usage: u64,
+    pub modified_at: Option<DateTime<Utc>>,
+    /// Ownership, permissions and inode details (None on non-Unix platforms)
+    pub stat: Option<EntryStat>,
+}
+
+impl Metadata {
+    /// Device ID of the filesystem the path lives on
+    pub fn device(&self) -> Option<u64> {
+        self.stat.map(|stat| stat.device)
+    }
+}
+
+impl From<&fs::Metadata> for Metadata {
+    fn from(metadata: &fs::Metadata) -> Self {
+        Self {
+            is_dir: metadata.is_dir(),
+            is_symlink: metadata.file_type().is_symlink(),
+            len: metadata.len(),
+            disk_usage: allocated_size(metadata),
+            modified_at: metadata.modified().ok().map(DateTime::<Utc>::from),
+            stat: EntryStat::from_metadata(metadata),
+        }
+    }
+}
+
+/// Bytes actually allocated for an entry, which differs from its length
+/// for sparse files and for small files that occupy a whole block
+#[cfg(unix)]
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+#[cfg(not(unix))]
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    metadata.len()
+}
+
+/// An entry of a directory listing, typed but not statted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirChild {
+    pub name: OsString,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+}
+
+/// An entry found by a walk, not yet statted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalkEntry {
+    pub path: PathBuf,
+    /// Levels below the walk's root (the root is 0)
+    pub depth: usize,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    /// Marker kind when the walk didn't descend into it, otherwise `Normal`
+    pub kind: EntryKind,
+}
+
+/// Decides whether an entry is a marker, given its path, whether it's a
+/// directory, and a way to stat it when that's needed to tell
+pub type Classify =
+    Arc<dyn Fn(&Path, bool, &dyn Fn() -> Option<Metadata>) -> EntryKind + Send + Sync>;
+
+/// Told which directory a walk has just listed, and how many entries it found there
+pub type OnReadDir = Arc<dyn Fn(&Path, usize) + Send + Sync>;
+
+/// Whether a directory's contents should be left out of a walk
+pub type SkipDir = Arc<dyn Fn(&Path) -> bool + Send + Sync>;
+
+/// Entries found by a walk, and the paths it couldn't read
+pub type Walk<'a> = Box<dyn Iterator<Item = Result<WalkEntry, ScanError>> + 'a>;
+
+#[derive(Clone, Default)]
+pub struct WalkOptions {
+    pub follow_symlinks: bool,
+    /// Markers are yielded but not descended into (None when nothing is a marker)
+    pub classify: Option<Classify>,
+    /// Pool parallel walks read directories on
+    pub parallelism: Option<jwalk::Parallelism>,
+    /// Called as each directory is listed, before its entries are yielded
+    /// (not by the disk's single-threaded walk, which lists lazily)
+    pub on_read_dir: Option<OnReadDir>,
+    /// Directories yielded but not descended into, like markers (a resumed
+    /// scan's finished directories)
+    pub skip_dir: Option<SkipDir>,
+}
+
+pub trait FsSource: Send + Sync {
+    /// Metadata of `path`, following a final symlink when `follow_symlinks` is set
+    fn metadata(&self, path: &Path, follow_symlinks: bool) -> io::Result<Metadata>;
+
+    /// The children of a directory
+    /// An entry the listing can't read is an error in its place; its siblings
+    /// are still listed.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<DirChild>>>;
+
+    /// Open a file to read its contents (for archive members)
+    fn open(&self, path: &Path) -> io::Result<File> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} has no contents to read", path.display()),
+        ))
+    }
+
+    /// Walk the tree at `root` depth first, yielding directories before their children
+    fn walk<'a>(&'a self, root: &Path, options: &WalkOptions) -> Walk<'a> {
+        Box::new(ListingWalk::new(self, root, options))
+    }
+
+    /// Walk the tree at `root`, reading several directories at once
+    /// In-memory sources list directories instantly, so this is `walk` unless overridden
+    fn parallel_walk<'a>(&'a self, root: &Path, options: &WalkOptions) -> Walk<'a> {
+        self.walk(root, options)
+    }
+}
+
+/// Depth-first walk built on a source's `read_dir` and `metadata`
+///
+/// Symlinked directories are never descended into, so link cycles can't trap
+/// it; when following symlinks they are still reported as directories.
+struct ListingWalk<'a, S: ?Sized> {
+    source: &'a S,
+    options: WalkOptions,
+    /// Entries still to yield, the next one last
+    pending: Vec<Result<WalkEntry, ScanError>>,
+}
+
+impl<'a, S: FsSource + ?Sized> ListingWalk<'a, S> {
+    fn new(source: &'a S, root: &Path, options: &WalkOptions) -> Self {
+        let root_entry = source
+            .metadata(root, options.follow_symlinks)
+            .map(|metadata| WalkEntry {
+                path: root.to_path_buf(),
+                depth: 0,
+                is_dir: metadata.is_dir,
+                is_symlink: metadata.is_symlink,
+                kind: EntryKind::Normal,
+            })
+            .map_err(|e| ScanError::from_io(root, &e));
+        Self {
+            source,
+            options: options.clone(),
+            pending: vec![root_entry],
+        }
+    }
+
+    /// Queue the children of a directory that was just yielded
+    fn descend(&mut self, dir: &Path, depth: usize) {
+        let children = match self.source.read_dir(dir) {
+            Ok(children) => children,
+            Err(e) => {
+                self.pending.push(Err(ScanError::from_io(dir, &e)));
+                return;
+            }
+        };
+
+        if let Some(on_read_dir) = &self.options.on_read_dir {
+            on_read_dir(dir, children.iter().filter(|c| c.is_ok()).count());
+        }
+
+        let first = self.pending.len();
+        for child in children {
+            let child = match child {
+                Ok(child) => child,
+                Err(e) => {
+                    self.pending.push(Err(ScanError::from_io(dir, &e)));
+                    continue;
+                }
+            };
+            let path = dir.join(&child.name);
+            let is_dir = if child.is_symlink && self.options.follow_symlinks {
+                self.source
+                    .metadata(&path, true)
+                    .is_ok_and(|metadata| metadata.is_dir)
+            } else {
+                child.is_dir
+            };
+            let kind = match &self.options.classify {
+                Some(classify) => classify(&path, is_dir, &|| {
+                    self.source
+                        .metadata(&path, self.options.follow_symlinks)
+                        .ok()
+                }),
+                None => EntryKind::Normal,
+            };
+            self.pending.push(Ok(WalkEntry {
+                path,
+                depth: depth + 1,
+                is_dir,
+                is_symlink: child.is_symlink,
+                kind,
+            }));
+        }
+        self.pending[first..].reverse();
+    }
+}
+
+impl<S: FsSource + ?Sized> Iterator for ListingWalk<'_, S> {
+    type Item = Result<WalkEntry, ScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.pending.pop()?;
+        if let Ok(entry) = &item {
+            let skipped = self
+                .options
+                .skip_dir
+                .as_ref()
+                .is_some_and(|skip_dir| skip_dir(&entry.path));
+            if entry.is_dir && !entry.is_symlink && !entry.kind.is_marker() && !skipped {
+                let (dir, depth) = (entry.path.clone(), entry.depth);
+                self.descend(&dir, depth);
+            }
+        }
+        Some(item)
+    }
+}
+
+/// The real filesystem
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiskSource;
+
+/// jwalk client state: lets `process_read_dir` tag entries it won't descend into
+#[derive(Debug, Default)]
+struct MarkerState;
+
+impl jwalk::ClientState for MarkerState {
+    type ReadDirState = ();
+    type DirEntryState = EntryKind;
+}
+
+impl FsSource for DiskSource {
+    fn metadata(&self, path: &Path, follow_symlinks: bool) -> io::Result<Metadata> {
+        let metadata = if follow_symlinks {
+            fs::metadata(path)?
+        } else {
+            fs::symlink_metadata(path)?
+        };
+        Ok(Metadata::from(&metadata))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<DirChild>>> {
+        Ok(fs::read_dir(path)?
+            .map(|entry| {
+                let entry = entry?;
+                // Types come from the listing where the filesystem provides them
+                let file_type = entry.file_type().ok();
+                Ok(DirChild {
+                    name: entry.file_name(),
+                    is_dir: file_type.is_some_and(|t| t.is_dir()),
+                    is_symlink: file_type.is_some_and(|t| t.is_symlink()),
+                })
+            })
+            .collect())
+    }
+
+    fn open(&self, path: &Path) -> io::Result<File> {
+        File::open(path)
+    }
+
+    /// Single-threaded walkdir traversal
+    fn walk<'a>(&'a self, root: &Path, options: &WalkOptions) -> Walk<'a> {
+        let mut walker = walkdir::WalkDir::new(root)
+            .follow_links(options.follow_symlinks)
+            .into_iter();
+        let (classify, skip_dir) = (options.classify.clone(), options.skip_dir.clone());
+        let root = root.to_path_buf();
+        Box::new(std::iter::from_fn(move || {
+            let entry = match walker.next()? {
+                Ok(entry) => entry,
+                Err(e) => {
+                    return Some(Err(ScanError::from_walk_error(
+                        e.path(),
+                        e.loop_ancestor(),
+                        e.io_error(),
+                        &root,
+                        e.to_string(),
+                    )))
+                }
+            };
+
+            let is_dir = entry.file_type().is_dir();
+            let kind = classify.as_ref().map_or(EntryKind::Normal, |classify| {
+                classify(entry.path(), is_dir, &|| {
+                    entry.metadata().ok().map(|m| Metadata::from(&m))
+                })
+            });
+            let skipped = skip_dir
+                .as_ref()
+                .is_some_and(|skip_dir| skip_dir(entry.path()));
+            if (kind.is_marker() || skipped) && is_dir {
+                walker.skip_current_dir();
+            }
+            Some(Ok(WalkEntry {
+                path: entry.path().to_path_buf(),
+                depth: entry.depth(),
+                is_dir,
+                is_symlink: entry.path_is_symlink(),
+                kind,
+            }))
+        }))
+    }
+
+    /// Parallel jwalk traversal
+    fn parallel_walk<'a>(&'a self, root: &Path, options: &WalkOptions) -> Walk<'a> {
+        let mut walk = jwalk::WalkDirGeneric::<MarkerState>::new(root)
+            .follow_links(options.follow_symlinks)
+            .skip_hidden(false); // Include hidden files (files starting with .)
+        if let Some(parallelism) = options.parallelism.clone() {
+            walk = walk.parallelism(parallelism);
+        }
+        if options.classify.is_some() || options.on_read_dir.is_some() || options.skip_dir.is_some()
+        {
+            let (classify, on_read_dir) = (options.classify.clone(), options.on_read_dir.clone());
+            let skip_dir = options.skip_dir.clone();
+            walk = walk.process_read_dir(move |depth, path, _state, children| {
+                // Tag excluded children and foreign mounts before jwalk reads them, so
+                // those directories are yielded as markers but never descended into
+                if let Some(classify) = &classify {
+                    for child in children.iter_mut().flatten() {
+                        let path = child.parent_path.join(&child.file_name);
+                        let kind = classify(&path, child.file_type.is_dir(), &|| {
+                            child.metadata().ok().map(|m| Metadata::from(&m))
+                        });
+                        if kind.is_marker() {
+                            child.client_state = kind;
+                            child.read_children_path = None;
+                        }
+                    }
+                }
+                if let Some(skip_dir) = &skip_dir {
+                    for child in children.iter_mut().flatten() {
+                        if skip_dir(&child.parent_path.join(&child.file_name)) {
+                            child.read_children_path = None;
+                        }
+                    }
+                }
+                // The root itself comes through without a depth, and isn't a listing
+                if let (Some(on_read_dir), Some(_)) = (&on_read_dir, depth) {
+                    on_read_dir(path, children.iter().filter(|c| c.is_ok()).count());
+                }
+            });
+        }
+
+        let root = root.to_path_buf();
+        Box::new(walk.into_iter().map(move |entry| match entry {
+            Ok(entry) => Ok(WalkEntry {
+                path: entry.path(),
+                depth: entry.depth(),
+                is_dir: entry.file_type().is_dir(),
+                is_symlink: entry.file_type().is_symlink(),
+                kind: entry.client_state,
+            }),
+            Err(e) => Err(ScanError::from_walk_error(
+                e.path(),
+                e.loop_ancestor(),
+                e.io_error(),
+                &root,
+                e.to_string(),
+            )),
+        }))
+    }
+}
+
+/// Shape of a synthetic tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeShape {
+    /// Levels of directories below the root
+    pub depth: usize,
+    /// Subdirectories in each directory above the bottom level
+    pub fanout: usize,
+    /// Files in every directory
+    pub files_per_dir: usize,
+    /// Largest file size; sizes are spread evenly up to it
+    pub max_file_size: u64,
+    /// Time every metadata call takes, to mimic a disk
+    pub latency: Duration,
+}
+
+impl Default for TreeShape {
+    fn default() -> Self {
+        Self {
+            depth: 3,
+            fanout: 5,
+            files_per_dir: 20,
+            max_file_size: 1024 * 1024,
+            latency: Duration::ZERO,
+        }
+    }
+}
+
+impl TreeShape {
+    /// Directories in the tree, the root included
+    pub fn dirs(&self) -> u64 {
+        (0..=self.depth as u32)
+            .map(|level| (self.fanout as u64).saturating_pow(level))
+            .fold(0, u64::saturating_add)
+    }
+
+    /// Files in the tree
+    pub fn files(&self) -> u64 {
+        self.dirs().saturating_mul(self.files_per_dir as u64)
+    }
+}
+
+/// Extensions synthetic files cycle through, so type breakdowns have something to show
+const SYNTHETIC_EXTENSIONS: [&str; 6] = ["rs", "txt", "log", "json", "png", "mp4"];
+
+/// Where a path sits in a synthetic tree
+enum SyntheticNode {
+    Dir { level: usize },
+    File,
+}
+
+/// A made-up tree, generated from its shape as it's walked
+///
+/// Nothing is stored, so trees of millions of entries cost no memory until
+/// they're scanned. Sizes, times and inodes are derived from each path, so
+/// every scan of the same shape gives the same results.
+#[derive(Debug, Clone)]
+pub struct SyntheticSource {
+    root: PathBuf,
+    shape: TreeShape,
+}
+
+impl SyntheticSource {
+    pub fn new(root: impl Into<PathBuf>, shape: TreeShape) -> Self {
+        Self {
+            root: root.into(),
+            shape,
+        }
+    }
+
+    fn dir_name(index: usize) -> String {
+        format!("dir_{:03}", index)
+    }
+
+    fn file_name(index: usize) -> String {
+        let extension = SYNTHETIC_EXTENSIONS[index % SYNTHETIC_EXTENSIONS.len()];
+        format!("file_{:04}.{}", index, extension)
+    }
+
+    fn node(&self, path: &Path) -> io::Result<SyntheticNode> {
+        let not_found = || io::Error::new(io::ErrorKind::NotFound, "No such file or directory");
+        let relative = path.strip_prefix(&self.root).map_err(|_| not_found())?;
+
+        let mut level = 0;
+        let mut components = relative.components().peekable();
+        while let Some(component) = components.next() {
+            let name = component.as_os_str().to_str().ok_or_else(not_found)?;
+            let last = components.peek().is_none();
+            if let Some(index) = Self::parse_index(name, "dir_") {
+                if index < self.shape.fanout
+                    && level < self.shape.depth
+                    && Self::dir_name(index) == name
+                {
+                    level += 1;
+                    continue;
+                }
+            } else if let Some(index) = Self::parse_index(name, "file_") {
+                if last && index < self.shape.files_per_dir && Self::file_name(index) == name {
+                    return Ok(SyntheticNode::File);
+                }
+            }
+            return Err(not_found());
+        }
+        Ok(SyntheticNode::Dir { level })
+    }
+
+    fn parse_index(name: &str, prefix: &str) -> Option<usize> {
+        let digits = name.strip_prefix(prefix)?;
+        let digits = digits.split('.').next()?;
+        digits.parse().ok()
+    }
+}
+
+/// FNV-1a, to derive stable made-up values from a path
+fn path_hash(path: &Path) -> u64 {
+    path.as_os_str()
+        .as_encoded_bytes()
+        .iter()
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+impl FsSource for SyntheticSource {
+    fn metadata(&self, path: &Path, _follow_symlinks: bool) -> io::Result<Metadata> {
+        if !self.shape.latency.is_zero() {
+            std::thread::sleep(self.shape.latency);
+        }
+
+        let hash = path_hash(path);
+        let (is_dir, len, mode, nlink) = match self.node(path)? {
+            SyntheticNode::Dir { .. } => (true, 4096, 0o040755, 2),
+            SyntheticNode::File => (false, hash % (self.shape.max_file_size + 1), 0o100644, 1),
+        };
+        // Sometime in the year before 2025
+        let modified_at = DateTime::from_timestamp(1_735_689_600 - (hash % 31_536_000) as i64, 0);
+        Ok(Metadata {
+            is_dir,
+            is_symlink: false,
+            len,
+            disk_usage: len.div_ceil(4096) * 4096,
+            modified_at,
+            stat: Some(EntryStat {
+                uid: 1000,
+                gid: 1000,
+                mode,
+                inode: hash,
+                device: 1,
+                nlink,
+                accessed_at: modified_at,
+                changed_at: modified_at,
+            }),
+        })
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<DirChild>>> {
+        let SyntheticNode::Dir { level } = self.node(path)? else {
+            return Err(io::Error::other("Not a directory"));
+        };
+
+        let dirs = if level < self.shape.depth {
+            self.shape.fanout
+        } else {
+            0
+        };
+        let dirs = (0..dirs).map(|index| DirChild {
+            name: Self::dir_name(index).into(),
+            is_dir: true,
+            is_symlink: false,
+        });
+        let files = (0..self.shape.files_per_dir).map(|index| DirChild {
+            name: Self::file_name(index).into(),
+            is_dir: false,
+            is_symlink: false,
+        });
+        Ok(dirs.chain(files).map(Ok).collect())
+    }
+}
+
+/// Why a recorded path couldn't be read
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct RecordedError {
+    /// `permission_denied`, `not_found` or `other`
+    kind: String,
+    message: String,
+}
+
+impl RecordedError {
+    fn from_io(error: &io::Error) -> Self {
+        let kind = match error.kind() {
+            io::ErrorKind::PermissionDenied => "permission_denied",
+            io::ErrorKind::NotFound => "not_found",
+            _ => "other",
+        };
+        Self {
+            kind: kind.to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    fn to_io(&self) -> io::Error {
+        let kind = match self.kind.as_str() {
+            "permission_denied" => io::ErrorKind::PermissionDenied,
+            "not_found" => io::ErrorKind::NotFound,
+            _ => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, self.message.clone())
+    }
+}
+
+/// One line of a fixture file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct FixtureNode {
+    path: PathBuf,
+    /// Not followed if it's a symlink; None when it couldn't be statted
+    metadata: Option<Metadata>,
+    /// What a symlink points at (None for dangling links and anything else)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<Metadata>,
+    /// Why it couldn't be statted, or listed if it's a directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<RecordedError>,
+}
+
+/// A tree recorded from another source, replayed from memory
+///
+/// Fixture files are JSON lines, one per path, starting with the root.
+/// Unreadable paths are recorded with their errors, so replaying them
+/// reports the same errors. Paths that aren't valid UTF-8 can't be recorded.
+#[derive(Debug, Default)]
+pub struct FixtureSource {
+    root: PathBuf,
+    nodes: HashMap<PathBuf, FixtureNode>,
+    /// Children of each directory, in recorded order
+    children: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+impl FixtureSource {
+    /// Record the tree at `root`, without following symlinks into directories
+    pub fn record(source: &dyn FsSource, root: &Path) -> Result<Self> {
+        let metadata = source
+            .metadata(root, true)
+            .with_context(|| format!("Failed to read {}", root.display()))?;
+        let mut recorded = Vec::new();
+        let mut pending = vec![(root.to_path_buf(), Ok(metadata))];
+        while let Some((path, metadata)) = pending.pop() {
+            let mut node = FixtureNode {
+                path,
+                metadata: None,
+                target: None,
+                error: None,
+            };
+            match metadata {
+                Ok(metadata) => {
+                    if metadata.is_symlink {
+                        node.target = source.metadata(&node.path, true).ok();
+                    }
+                    if metadata.is_dir {
+                        match source.read_dir(&node.path) {
+                            Ok(children) => {
+                                // Entries the listing couldn't read aren't replayed
+                                let mut children: Vec<_> = children
+                                    .into_iter()
+                                    .filter_map(Result::ok)
+                                    .map(|child| {
+                                        let path = node.path.join(child.name);
+                                        let metadata = source.metadata(&path, false);
+                                        (path, metadata)
+                                    })
+                                    .collect();
+                                children.sort_by(|a, b| b.0.cmp(&a.0));
+                                pending.extend(children);
+                            }
+                            Err(e) => node.error = Some(RecordedError::from_io(&e)),
+                        }
+                    }
+                    node.metadata = Some(metadata);
+                }
+                Err(e) => node.error = Some(RecordedError::from_io(&e)),
+            }
+            recorded.push(node);
+        }
+        Ok(Self::from_nodes(recorded))
+    }
+
+    /// Load a fixture file written by `save`
+    pub fn load(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        let mut nodes = Vec::new();
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let node = serde_json::from_str(&line).with_context(|| {
+                format!(
+                    "Invalid fixture entry on line {} of {}",
+                    number + 1,
+                    path.display()
+                )
+            })?;
+            nodes.push(node);
+        }
+        if nodes.is_empty() {
+            anyhow::bail!("{} is an empty fixture", path.display());
+        }
+        Ok(Self::from_nodes(nodes))
+    }
+
+    /// Write the fixture to a file, the root first and children after their parents
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file =
+            File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        let mut pending = vec![&self.root];
+        while let Some(path) = pending.pop() {
+            serde_json::to_writer(&mut writer, &self.nodes[path])?;
+            writer.write_all(b"\n")?;
+            if let Some(children) = self.children.get(path) {
+                pending.extend(children.iter().rev());
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// The path the fixture was recorded from
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn from_nodes(nodes: Vec<FixtureNode>) -> Self {
+        let root = nodes[0].path.clone();
+        let mut children: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+        for node in &nodes[1..] {
+            if let Some(parent) = node.path.parent() {
+                children
+                    .entry(parent.to_path_buf())
+                    .or_default()
+                    .push(node.path.clone());
+            }
+        }
+        let nodes = nodes
+            .into_iter()
+            .map(|node| (node.path.clone(), node))
+            .collect();
+        Self {
+            root,
+            nodes,
+            children,
+        }
+    }
+
+    fn node(&self, path: &Path) -> io::Result<&FixtureNode> {
+        self.nodes
+            .get(path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No such file or directory"))
+    }
+}
+
+impl FsSource for FixtureSource {
+    fn metadata(&self, path: &Path, follow_symlinks: bool) -> io::Result<Metadata> {
+        let node = self.node(path)?;
+        let metadata = match (&node.metadata, &node.error) {
+            (Some(metadata), _) => metadata,
+            (None, Some(error)) => return Err(error.to_io()),
+            (None, None) => return Err(io::Error::other("Not recorded")),
+        };
+        if follow_symlinks && metadata.is_symlink {
+            return node
+                .target
+                .clone()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Dangling symbolic link"));
+        }
+        Ok(metadata.clone())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<DirChild>>> {
+        let metadata = self.metadata(path, false)?;
+        if let Some(error) = &self.node(path)?.error {
+            return Err(error.to_io());
+        }
+        if !metadata.is_dir {
+            return Err(io::Error::other("Not a directory"));
+        }
+
+        let children = self.children.get(path).map(Vec::as_slice).unwrap_or(&[]);
+        Ok(children
+            .iter()
+            .map(|child| {
+                let metadata = self.nodes[child].metadata.as_ref();
+                Ok(DirChild {
+                    name: child.file_name().unwrap_or_default().to_os_string(),
+                    is_dir: metadata.is_some_and(|m| m.is_dir),
+                    is_symlink: metadata.is_some_and(|m| m.is_symlink),
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn walk_paths(source: &dyn FsSource, root: &Path) -> Vec<(PathBuf, usize)> {
+        source
+            .walk(root, &WalkOptions::default())
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (entry.path, entry.depth)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_synthetic_tree_matches_its_shape() {
+        let shape = TreeShape {
+            depth: 2,
+            fanout: 3,
+            files_per_dir: 4,
+            ..Default::default()
+        };
+        let source = SyntheticSource::new("/synthetic", shape);
+        let entries: Vec<_> = source
+            .walk(Path::new("/synthetic"), &WalkOptions::default())
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(shape.dirs(), 13);
+        assert_eq!(shape.files(), 52);
+        assert_eq!(entries.iter().filter(|e| e.is_dir).count(), 13);
+        assert_eq!(entries.iter().filter(|e| !e.is_dir).count(), 52);
+        assert_eq!(entries.iter().map(|e| e.depth).max(), Some(3));
+
+        // Values are derived from the path alone
+        let file = Path::new("/synthetic/dir_002/dir_000/file_0003.json");
+        let metadata = source.metadata(file, false).unwrap();
+        assert_eq!(metadata, source.metadata(file, false).unwrap());
+        assert!(!metadata.is_dir && metadata.len <= shape.max_file_size);
+
+        // Paths outside the shape don't exist
+        for missing in [
+            "/synthetic/dir_003",
+            "/synthetic/dir_0",
+            "/synthetic/file_0004.log",
+        ] {
+            assert!(
+                source.metadata(Path::new(missing), false).is_err(),
+                "{}",
+                missing
+            );
+        }
+        assert!(source.read_dir(file).is_err());
+    }
+
+    /// A synthetic tree whose root listing has one entry it can't read
+    struct UnreadableEntry(SyntheticSource);
+
+    impl FsSource for UnreadableEntry {
+        fn metadata(&self, path: &Path, follow_symlinks: bool) -> io::Result<Metadata> {
+            self.0.metadata(path, follow_symlinks)
+        }
+
+        fn read_dir(&self, path: &Path) -> io::Result<Vec<io::Result<DirChild>>> {
+            let mut children = self.0.read_dir(path)?;
+            if path == Path::new("/synthetic") {
+                children.insert(1, Err(io::Error::other("Bad entry")));
+            }
+            Ok(children)
+        }
+    }
+
+    #[test]
+    fn test_walk_keeps_siblings_of_unreadable_entries() {
+        let shape = TreeShape {
+            depth: 1,
+            fanout: 2,
+            files_per_dir: 2,
+            ..Default::default()
+        };
+        let source = UnreadableEntry(SyntheticSource::new("/synthetic", shape));
+        let (entries, errors): (Vec<_>, Vec<_>) = source
+            .walk(Path::new("/synthetic"), &WalkOptions::default())
+            .partition(Result::is_ok);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(entries.len() as u64, shape.dirs() + shape.files());
+    }
+
+    #[test]
+    fn test_fixture_replays_recorded_tree() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("a.txt"), b"hello").unwrap();
+        fs::create_dir_all(root.join("sub/deeper")).unwrap();
+        fs::write(root.join("sub/deeper/b.txt"), b"world!").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(root.join("a.txt"), root.join("link")).unwrap();
+
+        let fixture = FixtureSource::record(&DiskSource, root).unwrap();
+        let saved = root.join("fixture.jsonl");
+        fixture.save(&saved).unwrap();
+        let replayed = FixtureSource::load(&saved).unwrap();
+        assert_eq!(replayed.root(), root);
+
+        let mut on_disk = walk_paths(&DiskSource, root);
+        on_disk.retain(|(path, _)| path != &saved);
+        on_disk.sort();
+        let mut from_fixture = walk_paths(&replayed, root);
+        from_fixture.sort();
+        assert_eq!(on_disk, from_fixture);
+
+        let file = root.join("sub/deeper/b.txt");
+        assert_eq!(
+            replayed.metadata(&file, false).unwrap(),
+            DiskSource.metadata(&file, false).unwrap()
+        );
+        #[cfg(unix)]
+        {
+            let link = root.join("link");
+            assert!(replayed.metadata(&link, false).unwrap().is_symlink);
+            assert_eq!(replayed.metadata(&link, true).unwrap().len, 5);
+        }
+        assert_eq!(
+            replayed
+                .metadata(&root.join("missing"), false)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::NotFound
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Keeping scans from starving other workloads of disk I/O
+//!
+//! A [`Throttle`] spaces out the scanner's metadata calls so that, across all
+//! of its workers, no more than the configured number happen per second.
+//! [`lower_io_priority`] additionally puts the process in the idle I/O
+//! scheduling class on Linux, so the disk serves everything else first.
+
+use anyhow::Result;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Rate limit shared by every worker of a scan
+pub struct Throttle {
+    limit: u32,
+    /// Time between two calls at the limit
+    interval: Duration,
+    /// Earliest time the next call may go ahead
+    next_slot: Mutex<Instant>,
+    /// Calls made so far
+    calls: AtomicU64,
+    /// Start of the current measuring window, calls made before it, and the
+    /// rate measured over the previous window
+    window: Mutex<(Instant, u64, u64)>,
+}
+
+impl Throttle {
+    pub fn new(max_per_sec: u32) -> Self {
+        let limit = max_per_sec.max(1);
+        let now = Instant::now();
+        Self {
+            limit,
+            interval: Duration::from_secs(1) / limit,
+            next_slot: Mutex::new(now),
+            calls: AtomicU64::new(0),
+            window: Mutex::new((now, 0, 0)),
+        }
+    }
+
+    /// Most calls allowed per second
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// Block until the next call may go ahead
+    /// Each caller reserves its own slot, so workers take turns rather than
+    /// all waking at once; time spent idle isn't saved up for a burst.
+    pub fn wait(&self) {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        let slot = {
+            let mut next = self.next_slot.lock().unwrap();
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        let now = Instant::now();
+        if slot > now {
+            std::thread::sleep(slot - now);
+        }
+    }
+
+    /// Calls per second, measured over roughly the last second
+    pub fn rate(&self) -> u64 {
+        let mut window = self.window.lock().unwrap();
+        let (started, calls_before, rate) = *window;
+        let elapsed = started.elapsed();
+        if elapsed < Duration::from_secs(1) {
+            return rate;
+        }
+        let calls = self.calls.load(Ordering::Relaxed);
+        let rate = ((calls - calls_before) as f64 / elapsed.as_secs_f64()).round() as u64;
+        *window = (Instant::now(), calls, rate);
+        rate
+    }
+}
+
+/// Move every thread of this process to the idle I/O scheduling class
+/// Threads started afterwards inherit it from the thread starting them.
+#[cfg(target_os = "linux")]
+pub fn lower_io_priority() -> Result<()> {
+    use anyhow::Context;
+
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_IDLE: libc::c_int = 3;
+    const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+    let priority = IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT;
+
+    // ioprio_set only applies to the one thread it's given
+    let tasks = std::fs::read_dir("/proc/self/task").context("Failed to list threads")?;
+    for task in tasks {
+        let Some(tid) = task?
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<libc::c_int>().ok())
+        else {
+            continue;
+        };
+        // SAFETY: ioprio_set only takes integers and has no wrapper in libc
+        let result =
+            unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, tid, priority) };
+        if result != 0 {
+            let error = std::io::Error::last_os_error();
+            // A thread that exited since it was listed is no longer a concern
+            if error.raw_os_error() != Some(libc::ESRCH) {
+                return Err(error).context("Failed to lower I/O priority");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn lower_io_priority() -> Result<()> {
+    anyhow::bail!("Lowering I/O priority is only supported on Linux")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_throttle_limits_calls_across_threads() {
+        let throttle = Arc::new(Throttle::new(200));
+        let started = Instant::now();
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                let throttle = throttle.clone();
+                s.spawn(move || {
+                    for _ in 0..10 {
+                        throttle.wait();
+                    }
+                });
+            }
+        });
+        // 40 calls at 200 per second, the first of which doesn't wait
+        assert!(started.elapsed() >= Duration::from_millis(190));
+        assert_eq!(throttle.calls.load(Ordering::Relaxed), 40);
+        assert_eq!(throttle.limit(), 200);
+    }
+
+    #[test]
+    fn test_rate_is_measured_per_window() {
+        let throttle = Throttle::new(1000);
+        for _ in 0..5 {
+            throttle.wait();
+        }
+        // Nothing measured until a window has passed
+        assert_eq!(throttle.rate(), 0);
+        *throttle.window.lock().unwrap() = (Instant::now() - Duration::from_secs(2), 0, 0);
+        let rate = throttle.rate();
+        assert!((2..=3).contains(&rate), "rate {}", rate);
+    }
+}
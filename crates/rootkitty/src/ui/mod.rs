@@ -25,16 +25,23 @@ use ratatui::{
 };
 use std::io;
 
-use crate::db::{ActorMessage, Database, DatabaseActor, Scan, StoredFileEntry};
-use crate::scanner::{ProgressUpdate, Scanner};
+use crate::db::{
+    ActorMessage, Database, DatabaseActor, OwnerDirUsage, OwnerGrouping, OwnerUsage, Scan,
+    SizeMode, StoredFileEntry, StoredScanError,
+};
+use crate::dupes::{self, DuplicateReport, HashStage};
+use crate::filetypes::{self, TypeReport};
+use crate::filter::ScanFilter;
+use crate::owners::OwnerNames;
+use crate::scanner::{EntryKind, ProgressUpdate, ScanOptions, Scanner};
 use crate::settings::Settings;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
 
 use tree::compute_visible_entries;
-use types::{ActiveScan, ResumePreparation, ScanProgress};
+use types::{ActiveScan, ResumePreparation, ResumedRun, ScanProgress};
 
 /// Result types for async loading operations
 enum LoadingResult {
@@ -58,6 +65,8 @@ pub struct App {
     cleanup_list_state: ListState,
     status_message: String,
     scan_input: String,
+    /// Paths added to a new scan before the one being typed
+    scan_roots: Vec<String>,
     scan_progress: Option<ScanProgress>,
     /// Number of previously scanned entries when resuming a scan
     resumed_entries_count: u64,
@@ -88,6 +97,8 @@ pub struct App {
     file_tree_sort: SortMode,
     /// Scan list sort mode
     scan_list_sort: SortMode,
+    /// Whether sizes are shown as apparent size or allocated disk usage
+    size_mode: SizeMode,
     /// Path to settings file for saving
     settings_path: PathBuf,
     /// Path to database file
@@ -102,8 +113,8 @@ pub struct App {
     pending_path_change: Option<(usize, PathBuf, PathBuf)>,
     /// Whether pending path exists
     pending_path_exists: bool,
-    /// Whether to follow symlinks during scanning
-    follow_symlinks: bool,
+    /// Scan settings (follow symlinks, exclude patterns) used for new scans
+    scan_settings: crate::settings::ScanSettings,
     /// Search query string for filtering file tree
     search_query: String,
     /// Whether we're actively in search input mode
@@ -122,6 +133,39 @@ pub struct App {
     treemap_selection: usize,
     /// File entry being viewed in detail
     detail_file: Option<StoredFileEntry>,
+    /// Other paths in the scan that are hard links to the file being viewed
+    detail_links: Vec<String>,
+    /// Owners view: aggregate by user or group
+    owner_grouping: OwnerGrouping,
+    /// Owners view: usage per owner for the current scan
+    owner_usage: Vec<OwnerUsage>,
+    owner_list_state: ListState,
+    /// Owners view: selected owner and their largest directories
+    owner_drilldown: Option<(u32, Vec<OwnerDirUsage>)>,
+    owner_dir_list_state: ListState,
+    /// User and group names for the owners view
+    owner_names: OwnerNames,
+    /// Scan errors view: (scan ID, unreadable paths)
+    scan_errors: Option<(i64, Vec<StoredScanError>)>,
+    scan_errors_state: ListState,
+    /// Duplicates view: (scan ID, duplicate groups) once the search finishes
+    dupes: Option<(i64, DuplicateReport)>,
+    dupes_list_state: ListState,
+    /// Duplicates view: index of the group whose copies are listed
+    dupes_drilldown: Option<usize>,
+    dupes_copy_state: ListState,
+    /// Background duplicate search: (scan ID, task)
+    dupes_task: Option<(i64, tokio::task::JoinHandle<Result<DuplicateReport>>)>,
+    /// Latest hashing progress reported by the duplicate search
+    dupes_progress: Arc<Mutex<Option<(HashStage, usize, usize)>>>,
+    /// File type categories for the types view
+    type_settings: crate::settings::TypeSettings,
+    /// Types view: the current scan broken down by category and extension
+    type_report: TypeReport,
+    type_list_state: ListState,
+    /// Types view: category whose extensions are listed
+    type_drilldown: Option<String>,
+    type_ext_list_state: ListState,
 }
 
 impl App {
@@ -141,6 +185,7 @@ impl App {
             cleanup_list_state: ListState::default(),
             status_message: String::from("Press 'n' to scan | '?' for help"),
             scan_input: String::new(),
+            scan_roots: Vec::new(),
             scan_progress: None,
             resumed_entries_count: 0,
             previous_view: View::ScanList,
@@ -157,6 +202,7 @@ impl App {
             settings_list_state: ListState::default(),
             file_tree_sort: settings.ui.file_tree_sort,
             scan_list_sort: settings.ui.scan_list_sort,
+            size_mode: settings.ui.size_mode,
             settings_path,
             db_path,
             path_input: String::new(),
@@ -164,7 +210,7 @@ impl App {
             editing_path_index: None,
             pending_path_change: None,
             pending_path_exists: false,
-            follow_symlinks: settings.scan.follow_symlinks,
+            scan_settings: settings.scan.clone(),
             search_query: String::new(),
             search_mode: false,
             scan_tree: Vec::new(),
@@ -174,6 +220,26 @@ impl App {
             treemap_scroll_offset: 0,
             treemap_selection: 0,
             detail_file: None,
+            detail_links: Vec::new(),
+            owner_grouping: OwnerGrouping::User,
+            owner_usage: Vec::new(),
+            owner_list_state: ListState::default(),
+            owner_drilldown: None,
+            owner_dir_list_state: ListState::default(),
+            owner_names: OwnerNames::load(),
+            scan_errors: None,
+            scan_errors_state: ListState::default(),
+            dupes: None,
+            dupes_list_state: ListState::default(),
+            dupes_drilldown: None,
+            dupes_copy_state: ListState::default(),
+            dupes_task: None,
+            dupes_progress: Arc::new(Mutex::new(None)),
+            type_settings: settings.types.clone(),
+            type_report: TypeReport::default(),
+            type_list_state: ListState::default(),
+            type_drilldown: None,
+            type_ext_list_state: ListState::default(),
         }
     }
 
@@ -228,6 +294,7 @@ impl App {
                                 self.previous_view = View::ScanList;
                                 self.view = View::ScanDialog;
                                 self.scan_input.clear();
+                                self.scan_roots.clear();
                                 self.g_pressed = false;
                             }
                             KeyCode::Char('r') => {
@@ -235,9 +302,16 @@ impl App {
                                 if let Some(scan_id) = self.get_selected_scan_id() {
                                     if let Some(scan) = self.scans.iter().find(|s| s.id == scan_id)
                                     {
-                                        if scan.status == "paused" {
+                                        if let Some(host) = &scan.host {
+                                            self.status_message = format!(
+                                                "Scan ran on {}; run it again with 'rootkitty scan'",
+                                                host
+                                            );
+                                        } else if scan.status == "paused" {
                                             let path = scan.root_path.clone();
-                                            if let Err(e) = self.resume_scan(scan_id, path).await {
+                                            if let Err(e) =
+                                                self.resume_scan(scan_id, path, None).await
+                                            {
                                                 self.status_message =
                                                     format!("Resume error: {}", e);
                                             }
@@ -317,6 +391,15 @@ impl App {
                                 self.unfold_all_scan_tree();
                                 self.g_pressed = false;
                             }
+                            KeyCode::Char('e') => {
+                                // List paths the selected scan couldn't read
+                                if let Some(scan_id) = self.get_selected_scan_id() {
+                                    if let Err(e) = self.open_scan_errors(scan_id).await {
+                                        self.status_message = format!("Error: {}", e);
+                                    }
+                                }
+                                self.g_pressed = false;
+                            }
                             KeyCode::Char('x') => {
                                 // Delete scan (with confirmation)
                                 if let Some(scan_id) = self.get_selected_scan_id() {
@@ -352,6 +435,24 @@ impl App {
                                 }
                                 self.g_pressed = false;
                             }
+                            KeyCode::Char('4') => {
+                                if self.current_scan.is_some() {
+                                    self.open_owners().await;
+                                }
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('5') => {
+                                if self.current_scan.is_some() {
+                                    self.open_duplicates();
+                                }
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('6') => {
+                                if self.current_scan.is_some() {
+                                    self.open_types().await;
+                                }
+                                self.g_pressed = false;
+                            }
                             KeyCode::Char('t') => {
                                 self.scan_list_sort = self.scan_list_sort.toggle();
                                 self.status_message = format!(
@@ -360,6 +461,10 @@ impl App {
                                 );
                                 self.g_pressed = false;
                             }
+                            KeyCode::Char('a') => {
+                                self.toggle_size_mode();
+                                self.g_pressed = false;
+                            }
                             _ => {
                                 self.g_pressed = false;
                             }
@@ -485,7 +590,7 @@ impl App {
                                         let current_entries = self.get_treemap_entries();
                                         if let Some(treemap_idx) = self.get_treemap_selection() {
                                             if let Some(entry) = current_entries.get(treemap_idx) {
-                                                if entry.is_dir {
+                                                if entry.has_children() {
                                                     // Drill into this directory
                                                     let dir_path = entry.path.clone();
 
@@ -510,9 +615,7 @@ impl App {
                                                     );
                                                 } else {
                                                     // It's a file - show file detail view
-                                                    self.detail_file = Some(entry.clone());
-                                                    self.previous_view = View::FileTree;
-                                                    self.view = View::FileDetail;
+                                                    self.open_file_detail(entry.clone()).await;
                                                     // Keep treemap_mode as-is so user can toggle with Shift+T
                                                 }
                                             }
@@ -598,18 +701,23 @@ impl App {
                                     }
                                     KeyCode::Char('z') | KeyCode::Char('o') => {
                                         // Toggle fold/unfold for selected directory (one level)
-                                        if let Err(e) = self.toggle_fold_directory(false) {
+                                        if let Err(e) = self.toggle_fold_directory(false).await {
                                             self.status_message = format!("Error unfolding: {}", e);
                                         }
                                         self.g_pressed = false;
                                     }
                                     KeyCode::Char('Z') | KeyCode::Char('O') => {
                                         // Unfold all nested folders recursively
-                                        if let Err(e) = self.toggle_fold_directory(true) {
+                                        if let Err(e) = self.toggle_fold_directory(true).await {
                                             self.status_message = format!("Error unfolding: {}", e);
                                         }
                                         self.g_pressed = false;
                                     }
+                                    KeyCode::Char('r') => {
+                                        // Resume a paused scan for the selected directory only
+                                        self.resume_selected_subtree().await;
+                                        self.g_pressed = false;
+                                    }
                                     KeyCode::Char('s') => {
                                         // Open shell in selected directory or parent
                                         if let Err(e) = self.open_shell(terminal) {
@@ -640,6 +748,18 @@ impl App {
                                         }
                                         self.g_pressed = false;
                                     }
+                                    KeyCode::Char('4') => {
+                                        self.open_owners().await;
+                                        self.g_pressed = false;
+                                    }
+                                    KeyCode::Char('5') => {
+                                        self.open_duplicates();
+                                        self.g_pressed = false;
+                                    }
+                                    KeyCode::Char('6') => {
+                                        self.open_types().await;
+                                        self.g_pressed = false;
+                                    }
                                     KeyCode::Char('t') => {
                                         self.file_tree_sort = self.file_tree_sort.toggle();
                                         self.status_message = format!(
@@ -648,6 +768,10 @@ impl App {
                                         );
                                         self.g_pressed = false;
                                     }
+                                    KeyCode::Char('a') => {
+                                        self.toggle_size_mode();
+                                        self.g_pressed = false;
+                                    }
                                     KeyCode::Char('T') => {
                                         // Shift+T - toggle treemap mode
                                         self.treemap_mode = !self.treemap_mode;
@@ -659,7 +783,7 @@ impl App {
                                                 let visible = self.get_visible_entries();
                                                 if let Some(entry) = visible.get(selected) {
                                                     let entry_path = entry.path.clone();
-                                                    let entry_is_dir = entry.is_dir;
+                                                    let entry_is_dir = entry.has_children();
 
                                                     self.treemap_path = entry_path.clone();
                                                     self.treemap_scroll_offset = 0;
@@ -746,6 +870,10 @@ impl App {
                                 self.generate_cleanup_script();
                                 self.g_pressed = false;
                             }
+                            KeyCode::Char('a') => {
+                                self.toggle_size_mode();
+                                self.g_pressed = false;
+                            }
                             KeyCode::Down | KeyCode::Char('j') => {
                                 self.cleanup_list_next();
                                 self.g_pressed = false;
@@ -782,6 +910,18 @@ impl App {
                                 self.view = View::CleanupList;
                                 self.g_pressed = false;
                             }
+                            KeyCode::Char('4') => {
+                                self.open_owners().await;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('5') => {
+                                self.open_duplicates();
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('6') => {
+                                self.open_types().await;
+                                self.g_pressed = false;
+                            }
                             KeyCode::Char('S') => {
                                 // Shift+S - open settings
                                 self.previous_view = View::CleanupList;
@@ -796,18 +936,35 @@ impl App {
                             KeyCode::Esc => {
                                 self.view = self.previous_view;
                                 self.scan_input.clear();
+                                self.scan_roots.clear();
                             }
                             KeyCode::Enter => {
+                                let mut paths = self.scan_roots.clone();
                                 if !self.scan_input.is_empty() {
-                                    let path = self.scan_input.clone();
-                                    if let Err(e) = self.start_scan(path).await {
+                                    paths.push(self.scan_input.clone());
+                                }
+                                if !paths.is_empty() {
+                                    if let Err(e) = self.start_scan(paths).await {
                                         self.status_message = format!("Scan error: {}", e);
                                         self.view = self.previous_view;
                                     }
                                 }
                             }
+                            KeyCode::Tab => {
+                                // Add another root to the same scan
+                                if !self.scan_input.is_empty() {
+                                    self.scan_roots.push(std::mem::take(&mut self.scan_input));
+                                }
+                            }
                             KeyCode::Backspace => {
-                                self.scan_input.pop();
+                                if self.scan_input.is_empty() {
+                                    // Back into the previous root
+                                    if let Some(root) = self.scan_roots.pop() {
+                                        self.scan_input = root;
+                                    }
+                                } else {
+                                    self.scan_input.pop();
+                                }
                             }
                             KeyCode::Char(c) => {
                                 self.scan_input.push(c);
@@ -828,8 +985,12 @@ impl App {
                                     // Mark scan as paused immediately without waiting
                                     let empty_stats = crate::scanner::ScanStats {
                                         total_size: 0,
+                                        total_disk_usage: 0,
                                         total_files: 0,
                                         total_dirs: 0,
+                                        error_count: 0,
+                                        reused_dirs: 0,
+                                        duration: active_scan.started.elapsed(),
                                     };
                                     let _ =
                                         self.db.pause_scan(active_scan.scan_id, &empty_stats).await;
@@ -903,9 +1064,11 @@ impl App {
                                             ui: crate::settings::UiSettings {
                                                 file_tree_sort: self.file_tree_sort,
                                                 scan_list_sort: self.scan_list_sort,
+                                                size_mode: self.size_mode,
                                                 auto_fold_depth: 1,
                                             },
-                                            scan: crate::settings::ScanSettings::default(),
+                                            scan: self.scan_settings.clone(),
+                                            types: self.type_settings.clone(),
                                         };
 
                                         match current_settings.save(&new_path) {
@@ -1099,9 +1262,11 @@ impl App {
                                                     ui: crate::settings::UiSettings {
                                                         file_tree_sort: self.file_tree_sort,
                                                         scan_list_sort: self.scan_list_sort,
+                                                        size_mode: self.size_mode,
                                                         auto_fold_depth: 1,
                                                     },
-                                                    scan: crate::settings::ScanSettings::default(),
+                                                    scan: self.scan_settings.clone(),
+                                                    types: self.type_settings.clone(),
                                                 };
 
                                                 match current_settings.save(&default_path) {
@@ -1159,12 +1324,26 @@ impl App {
                                                             format!("Error saving settings: {}", e);
                                                     }
                                                 }
-                                                6 => {
-                                                    // Follow Symlinks (index 6)
-                                                    self.follow_symlinks = !self.follow_symlinks;
+                                                5 => {
+                                                    // Size Accounting (index 5)
+                                                    self.size_mode = self.size_mode.toggle();
+                                                    self.status_message = format!(
+                                                        "Sizes: {}",
+                                                        self.size_mode.display_name()
+                                                    );
+                                                    // Save settings to disk
+                                                    if let Err(e) = self.save_settings() {
+                                                        self.status_message =
+                                                            format!("Error saving settings: {}", e);
+                                                    }
+                                                }
+                                                7 => {
+                                                    // Follow Symlinks (index 7)
+                                                    self.scan_settings.follow_symlinks =
+                                                        !self.scan_settings.follow_symlinks;
                                                     self.status_message = format!(
                                                         "Follow symlinks: {}",
-                                                        if self.follow_symlinks {
+                                                        if self.scan_settings.follow_symlinks {
                                                             "Yes"
                                                         } else {
                                                             "No"
@@ -1176,6 +1355,74 @@ impl App {
                                                             format!("Error saving settings: {}", e);
                                                     }
                                                 }
+                                                8 => {
+                                                    // One File System (index 8)
+                                                    self.scan_settings.one_file_system =
+                                                        !self.scan_settings.one_file_system;
+                                                    self.status_message = format!(
+                                                        "One file system: {}",
+                                                        if self.scan_settings.one_file_system {
+                                                            "Yes"
+                                                        } else {
+                                                            "No"
+                                                        }
+                                                    );
+                                                    // Save settings to disk
+                                                    if let Err(e) = self.save_settings() {
+                                                        self.status_message =
+                                                            format!("Error saving settings: {}", e);
+                                                    }
+                                                }
+                                                9 => {
+                                                    // Hard Links (index 9)
+                                                    self.scan_settings.hard_links =
+                                                        self.scan_settings.hard_links.toggle();
+                                                    self.status_message = format!(
+                                                        "Hard links: {}",
+                                                        self.scan_settings
+                                                            .hard_links
+                                                            .display_name()
+                                                    );
+                                                    // Save settings to disk
+                                                    if let Err(e) = self.save_settings() {
+                                                        self.status_message =
+                                                            format!("Error saving settings: {}", e);
+                                                    }
+                                                }
+                                                10 => {
+                                                    // Archive Members (index 10)
+                                                    self.scan_settings.archives =
+                                                        !self.scan_settings.archives;
+                                                    self.status_message = format!(
+                                                        "List archive members: {}",
+                                                        if self.scan_settings.archives {
+                                                            "Yes"
+                                                        } else {
+                                                            "No"
+                                                        }
+                                                    );
+                                                    // Save settings to disk
+                                                    if let Err(e) = self.save_settings() {
+                                                        self.status_message =
+                                                            format!("Error saving settings: {}", e);
+                                                    }
+                                                }
+                                                11 => {
+                                                    // Scan Method (index 11)
+                                                    self.scan_settings.implementation =
+                                                        self.scan_settings.implementation.toggle();
+                                                    self.status_message = format!(
+                                                        "Scan method: {}",
+                                                        self.scan_settings
+                                                            .implementation
+                                                            .display_name()
+                                                    );
+                                                    // Save settings to disk
+                                                    if let Err(e) = self.save_settings() {
+                                                        self.status_message =
+                                                            format!("Error saving settings: {}", e);
+                                                    }
+                                                }
                                                 _ => {
                                                     // Other settings are not toggleable
                                                 }
@@ -1195,6 +1442,7 @@ impl App {
                                 // Go back to file tree (will show treemap if treemap_mode is true)
                                 self.view = View::FileTree;
                                 self.detail_file = None;
+                                self.detail_links.clear();
                                 self.g_pressed = false;
                             }
                             KeyCode::Char('T') => {
@@ -1211,6 +1459,256 @@ impl App {
                                 self.g_pressed = false;
                             }
                         },
+                        View::ScanErrors => match key.code {
+                            KeyCode::Char('q') => return Ok(()),
+                            KeyCode::Esc => {
+                                self.view = View::ScanList;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => {
+                                self.scan_errors_move(1);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Up | KeyCode::Char('k') => {
+                                self.scan_errors_move(-1);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('d') => {
+                                self.scan_errors_move(10);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('u') => {
+                                self.scan_errors_move(-10);
+                                self.g_pressed = false;
+                            }
+                            _ => {
+                                self.g_pressed = false;
+                            }
+                        },
+                        View::Owners => match key.code {
+                            KeyCode::Char('q') => return Ok(()),
+                            KeyCode::Esc => {
+                                if self.owner_drilldown.is_some() {
+                                    // Back from a user's directories to the owner list
+                                    self.owner_drilldown = None;
+                                } else {
+                                    self.view = View::ScanList;
+                                }
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('?') => {
+                                self.previous_view = View::Owners;
+                                self.view = View::Help;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => {
+                                self.owners_list_move(1);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Up | KeyCode::Char('k') => {
+                                self.owners_list_move(-1);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('d') => {
+                                self.owners_list_move(10);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('u') => {
+                                self.owners_list_move(-10);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Enter | KeyCode::Char('o') => {
+                                if self.owner_drilldown.is_none() {
+                                    if let Err(e) = self.drill_into_owner().await {
+                                        self.status_message = format!("Error: {}", e);
+                                    }
+                                }
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Tab => {
+                                self.owner_grouping = self.owner_grouping.toggle();
+                                self.open_owners().await;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('a') => {
+                                self.toggle_size_mode();
+                                self.open_owners().await;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('1') => {
+                                self.view = View::ScanList;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('2') => {
+                                self.view = View::FileTree;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('3') => {
+                                if let Err(e) = self.load_cleanup_items().await {
+                                    self.status_message = format!("Error: {}", e);
+                                } else {
+                                    self.view = View::CleanupList;
+                                }
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('5') => {
+                                self.open_duplicates();
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('6') => {
+                                self.open_types().await;
+                                self.g_pressed = false;
+                            }
+                            _ => {
+                                self.g_pressed = false;
+                            }
+                        },
+                        View::Duplicates => match key.code {
+                            KeyCode::Char('q') => return Ok(()),
+                            KeyCode::Esc => {
+                                if self.dupes_drilldown.is_some() {
+                                    // Back from a group's copies to the group list
+                                    self.dupes_drilldown = None;
+                                } else {
+                                    self.view = View::ScanList;
+                                }
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('?') => {
+                                self.previous_view = View::Duplicates;
+                                self.view = View::Help;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => {
+                                self.dupes_list_move(1);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Up | KeyCode::Char('k') => {
+                                self.dupes_list_move(-1);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('d') => {
+                                self.dupes_list_move(10);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('u') => {
+                                self.dupes_list_move(-10);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Enter | KeyCode::Char('o') => {
+                                if self.dupes_drilldown.is_none() {
+                                    if let Some(group) = self.dupes_list_state.selected() {
+                                        self.dupes_drilldown = Some(group);
+                                        self.dupes_copy_state.select(Some(0));
+                                    }
+                                }
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('m') => {
+                                if let Err(e) = self.mark_duplicate_copies().await {
+                                    self.status_message = format!("Error: {}", e);
+                                }
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('1') => {
+                                self.view = View::ScanList;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('2') => {
+                                self.view = View::FileTree;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('3') => {
+                                if let Err(e) = self.load_cleanup_items().await {
+                                    self.status_message = format!("Error: {}", e);
+                                } else {
+                                    self.view = View::CleanupList;
+                                }
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('4') => {
+                                self.open_owners().await;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('6') => {
+                                self.open_types().await;
+                                self.g_pressed = false;
+                            }
+                            _ => {
+                                self.g_pressed = false;
+                            }
+                        },
+                        View::Types => match key.code {
+                            KeyCode::Char('q') => return Ok(()),
+                            KeyCode::Esc => {
+                                if self.type_drilldown.is_some() {
+                                    // Back from a category's extensions to the category list
+                                    self.type_drilldown = None;
+                                } else {
+                                    self.view = View::ScanList;
+                                }
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('?') => {
+                                self.previous_view = View::Types;
+                                self.view = View::Help;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Down | KeyCode::Char('j') => {
+                                self.types_list_move(1);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Up | KeyCode::Char('k') => {
+                                self.types_list_move(-1);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('d') => {
+                                self.types_list_move(10);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('u') => {
+                                self.types_list_move(-10);
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Enter | KeyCode::Char('o') => {
+                                if self.type_drilldown.is_none() {
+                                    self.drill_into_type();
+                                }
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('a') => {
+                                self.toggle_size_mode();
+                                self.open_types().await;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('1') => {
+                                self.view = View::ScanList;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('2') => {
+                                self.view = View::FileTree;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('3') => {
+                                if let Err(e) = self.load_cleanup_items().await {
+                                    self.status_message = format!("Error: {}", e);
+                                } else {
+                                    self.view = View::CleanupList;
+                                }
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('4') => {
+                                self.open_owners().await;
+                                self.g_pressed = false;
+                            }
+                            KeyCode::Char('5') => {
+                                self.open_duplicates();
+                                self.g_pressed = false;
+                            }
+                            _ => {
+                                self.g_pressed = false;
+                            }
+                        },
                     }
                 }
             }
@@ -1225,6 +1723,12 @@ impl App {
                             total_size: progress.total_size,
                             active_dirs: progress.active_dirs.clone(),
                             active_workers: progress.active_workers,
+                            worker_utilization: progress.worker_utilization,
+                            gathering: progress.gathering,
+                            total_estimate: progress.total_estimate,
+                            percent_done: progress.percent_done,
+                            eta: progress.eta,
+                            stat_rate: progress.stat_rate,
                         });
                     }
                     Err(mpsc::error::TryRecvError::Empty) => {
@@ -1252,8 +1756,44 @@ impl App {
                                     let was_cancelled =
                                         active_scan.cancelled.load(Ordering::Relaxed);
 
+                                    // Directories a resumed run recorded only count what it found
+                                    let resized = match &active_scan.resumed {
+                                        Some(resumed) if !was_cancelled => {
+                                            self.db
+                                                .recompute_dir_sizes(
+                                                    active_scan.scan_id,
+                                                    &resumed.path,
+                                                    resumed.last_entry_id,
+                                                    resumed.hard_links,
+                                                )
+                                                .await
+                                        }
+                                        _ => Ok(()),
+                                    };
+
                                     // Save scan with appropriate status
-                                    if was_cancelled {
+                                    if let Some(resumed) = active_scan
+                                        .resumed
+                                        .filter(|resumed| resumed.subtree && !was_cancelled)
+                                    {
+                                        // Only part of the scan ran, so it stays paused with
+                                        // totals rebuilt from everything stored so far
+                                        let totals = self
+                                            .db
+                                            .calculate_scan_stats(active_scan.scan_id)
+                                            .await
+                                            .map(|totals| crate::scanner::ScanStats {
+                                                duration: stats.duration,
+                                                ..totals
+                                            })
+                                            .unwrap_or_else(|_| stats.clone());
+                                        let _ =
+                                            self.db.pause_scan(active_scan.scan_id, &totals).await;
+                                        self.status_message = format!(
+                                            "Resumed {}: {} files, {} dirs. The rest of the scan is still paused.",
+                                            resumed.path, stats.total_files, stats.total_dirs
+                                        );
+                                    } else if was_cancelled {
                                         let _ =
                                             self.db.pause_scan(active_scan.scan_id, &stats).await;
                                         self.status_message = format!(
@@ -1270,6 +1810,10 @@ impl App {
                                             stats.total_files, stats.total_dirs, stats.total_size
                                         );
                                     }
+                                    if let Err(e) = resized {
+                                        self.status_message =
+                                            format!("Updating directory sizes failed: {}", e);
+                                    }
 
                                     // Reload scans and return to scan list
                                     let _ = self.load_scans().await;
@@ -1290,8 +1834,12 @@ impl App {
                                     // Mark as paused with zero stats (partial data already in DB)
                                     let empty_stats = crate::scanner::ScanStats {
                                         total_size: 0,
+                                        total_disk_usage: 0,
                                         total_files: 0,
                                         total_dirs: 0,
+                                        error_count: 0,
+                                        reused_dirs: 0,
+                                        duration: active_scan.started.elapsed(),
                                     };
                                     let _ =
                                         self.db.pause_scan(active_scan.scan_id, &empty_stats).await;
@@ -1315,6 +1863,37 @@ impl App {
                 }
             }
 
+            // Handle a finished duplicate search
+            if self
+                .dupes_task
+                .as_ref()
+                .is_some_and(|(_, task)| task.is_finished())
+            {
+                if let Some((scan_id, task)) = self.dupes_task.take() {
+                    match task.await {
+                        Ok(Ok(report)) => {
+                            self.status_message = format!(
+                                "Found {} duplicate groups ({} reclaimable)",
+                                report.groups.len(),
+                                format_size(report.reclaimable())
+                            );
+                            self.dupes_list_state.select(if report.groups.is_empty() {
+                                None
+                            } else {
+                                Some(0)
+                            });
+                            self.dupes = Some((scan_id, report));
+                        }
+                        Ok(Err(e)) => {
+                            self.status_message = format!("Duplicate search error: {}", e);
+                        }
+                        Err(e) => {
+                            self.status_message = format!("Duplicate search task error: {}", e);
+                        }
+                    }
+                }
+            }
+
             // Handle active deletion task
             if let Some(delete_task) = &self.delete_task {
                 if delete_task.is_finished() {
@@ -1379,7 +1958,7 @@ impl App {
                                                 .any(|e| e.path == child.path)
                                             {
                                                 // Fold directories by default
-                                                if child.is_dir {
+                                                if child.has_children() {
                                                     self.folded_dirs.insert(child.path.clone());
                                                 }
                                                 self.file_entries.push(child);
@@ -1442,18 +2021,24 @@ impl App {
                     // Take ownership to finalize
                     if let Some(resume_prep) = self.resume_prep.take() {
                         match resume_prep.load_task.await {
-                            Ok(Ok(scanned_paths)) => {
-                                // Paths loaded successfully, now start the actual scan
-                                let _ = self
-                                    .start_resume_scan_with_paths(
+                            Ok(Ok(resume)) => {
+                                // Checkpoints loaded successfully, now start the actual scan
+                                if let Err(e) = self
+                                    .start_resume_scan_with_state(
                                         resume_prep.scan_id,
                                         resume_prep.path,
-                                        scanned_paths,
+                                        resume_prep.subtree,
+                                        resume,
                                     )
-                                    .await;
+                                    .await
+                                {
+                                    self.status_message = format!("Resume error: {}", e);
+                                    self.view = View::ScanList;
+                                }
                             }
                             Ok(Err(e)) => {
-                                self.status_message = format!("Error loading scan paths: {}", e);
+                                self.status_message =
+                                    format!("Error loading resume checkpoints: {}", e);
                                 self.view = View::ScanList;
                             }
                             Err(e) => {
@@ -1509,6 +2094,10 @@ impl App {
             View::Settings => self.render_settings(f, main_chunks[0]),
             View::ConfirmPathChange => self.render_confirm_path_change(f, main_chunks[0]),
             View::FileDetail => self.render_file_detail(f, main_chunks[0]),
+            View::Owners => self.render_owners(f, main_chunks[0]),
+            View::ScanErrors => self.render_scan_errors(f, main_chunks[0]),
+            View::Duplicates => self.render_duplicates(f, main_chunks[0]),
+            View::Types => self.render_types(f, main_chunks[0]),
         }
 
         let status_idx = if use_info_pane { 2 } else { 1 };
@@ -1569,7 +2158,7 @@ impl App {
                         ListItem::new(content).style(Style::default().fg(Color::Cyan))
                     }
                     scan_tree::ScanTreeNode::ScanNode { scan, has_subscans } => {
-                        let size_mb = scan.total_size as f64 / 1_048_576.0;
+                        let size_mb = self.size_mode.scan_size(scan) as f64 / 1_048_576.0;
                         let status = match scan.status.as_str() {
                             "completed" => "✓",
                             "running" => "⟳",
@@ -1577,15 +2166,21 @@ impl App {
                             _ => "✗",
                         };
                         let subscan_indicator = if *has_subscans { " [+]" } else { "" };
+                        let error_indicator = if scan.error_count > 0 {
+                            format!(" | ⚠ {} unreadable", scan.error_count)
+                        } else {
+                            String::new()
+                        };
 
                         // Scan nodes are children of a path node, so just show the details
                         let content = format!(
-                            "{}  📊 {} | {} files | {:.2} MB | {}{}",
+                            "{}  📊 {} | {} files | {:.2} MB | {}{}{}",
                             indent,
                             status,
                             scan.total_files,
                             size_mb,
                             scan.started_at.format("%Y-%m-%d %H:%M"),
+                            error_indicator,
                             subscan_indicator
                         );
                         ListItem::new(content)
@@ -1670,8 +2265,10 @@ impl App {
                 String::new()
             };
             format!(
-                "Files (2) | Scan: {}{} | /: search | z: fold | Z: unfold all | s: shell | Space: mark",
-                scan.root_path, search_info
+                "Files (2) | Scan: {}{} | a: {} | /: search | z: fold | Z: unfold all | s: shell | Space: mark",
+                scan.location(),
+                search_info,
+                self.size_mode.display_name()
             )
         } else {
             "Files (2)".to_string()
@@ -1684,23 +2281,64 @@ impl App {
         let items: Vec<ListItem> = visible_entries
             .iter()
             .map(|entry| {
-                let size_str = format_size(entry.size as u64);
-                let is_folded = entry.is_dir && self.folded_dirs.contains(&entry.path);
+                let size_str = format_size(self.size_mode.entry_size(entry) as u64);
+                let is_folded = entry.has_children() && self.folded_dirs.contains(&entry.path);
                 let is_loading = self.loading_path.as_ref() == Some(&entry.path);
 
                 let icon = if entry.is_dir {
                     if is_loading {
                         format!("{} 📁", throbber)
+                    } else if entry.kind == EntryKind::Summarized {
+                        // Contents below the depth limit weren't stored, so it can't be unfolded
+                        "■ 📁".to_string()
                     } else if is_folded {
                         "▶ 📁".to_string()
                     } else {
                         "▼ 📁".to_string()
                     }
+                } else if entry.kind == EntryKind::Archive {
+                    if is_loading {
+                        format!("{} 📦", throbber)
+                    } else if is_folded {
+                        "▶ 📦".to_string()
+                    } else {
+                        "▼ 📦".to_string()
+                    }
                 } else {
                     "  📄".to_string()
                 };
                 let indent = "  ".repeat(entry.depth as usize);
-                let content = format!("{}{} {} ({})", indent, icon, entry.name, size_str);
+                let content = if let Some(label) = entry.kind.label() {
+                    // Marker entries have no contents to show, so no fold arrow or size
+                    format!("{}  ⊘ {} — {} (not scanned)", indent, entry.name, label)
+                } else if entry.kind == EntryKind::Summarized {
+                    format!(
+                        "{}{} {} ({}) — summarized below",
+                        indent, icon, entry.name, size_str
+                    )
+                } else if entry.kind == EntryKind::Unfinished {
+                    format!(
+                        "{}{} {} ({} so far) — unfinished",
+                        indent, icon, entry.name, size_str
+                    )
+                } else if entry.kind == EntryKind::InArchive && !entry.is_dir {
+                    format!(
+                        "{}{} {} ({}, {} packed)",
+                        indent,
+                        icon,
+                        entry.name,
+                        size_str,
+                        format_size(entry.disk_usage as u64)
+                    )
+                } else if let Some(stat) = entry.stat.filter(|st| !entry.is_dir && st.nlink > 1) {
+                    // Hard-linked files share their data with other paths
+                    format!(
+                        "{}{} {} ({}) 🔗{}",
+                        indent, icon, entry.name, size_str, stat.nlink
+                    )
+                } else {
+                    format!("{}{} {} ({})", indent, icon, entry.name, size_str)
+                };
                 ListItem::new(content)
             })
             .collect();
@@ -1724,7 +2362,7 @@ impl App {
             if let Some(treemap_idx) = self.get_treemap_selection() {
                 if let Some(entry) = treemap_entries.get(treemap_idx) {
                     let file_type = if entry.is_dir { "Directory" } else { "File" }.to_string();
-                    let size_str = format_size(entry.size as u64);
+                    let size_str = self.describe_sizes(entry);
                     let name = entry.name.clone();
                     let path = entry.path.clone();
 
@@ -1756,8 +2394,22 @@ impl App {
             // In normal file tree mode, show info for the selected file
             let visible_entries = self.get_visible_entries();
             if let Some(entry) = visible_entries.get(selected) {
-                let file_type = if entry.is_dir { "Directory" } else { "File" };
-                let size_str = format_size(entry.size as u64);
+                let base_type = if entry.is_dir { "Directory" } else { "File" };
+                let file_type = match entry.kind.label() {
+                    Some(label) => format!("{} ({}, not scanned)", base_type, label),
+                    None if entry.kind == EntryKind::Summarized => {
+                        format!("{} (summarized: contents counted, not stored)", base_type)
+                    }
+                    None if entry.kind == EntryKind::Archive => "Archive".to_string(),
+                    None if entry.kind == EntryKind::Unfinished => {
+                        format!("{} (unfinished: press 'r' to resume it)", base_type)
+                    }
+                    None if entry.kind == EntryKind::InArchive => {
+                        format!("{} in archive (disk usage is compressed size)", base_type)
+                    }
+                    None => base_type.to_string(),
+                };
+                let size_str = self.describe_sizes(entry);
 
                 vec![
                     Line::from(vec![
@@ -1792,7 +2444,11 @@ impl App {
     }
 
     fn render_cleanup_list(&mut self, f: &mut Frame, area: Rect) {
-        let total_size: i64 = self.cleanup_items.iter().map(|e| e.size).sum();
+        let total_size: i64 = self
+            .cleanup_items
+            .iter()
+            .map(|e| self.size_mode.entry_size(e))
+            .sum();
         let title = format!(
             "Cleanup List (3) | {} items | {} total | 'g' to generate script | Space to remove",
             self.cleanup_items.len(),
@@ -1803,7 +2459,7 @@ impl App {
             .cleanup_items
             .iter()
             .map(|entry| {
-                let size_str = format_size(entry.size as u64);
+                let size_str = format_size(self.size_mode.entry_size(entry) as u64);
                 let icon = if entry.is_dir { "📁" } else { "📄" };
                 let content = format!("{} {} ({})", icon, entry.path, size_str);
                 ListItem::new(content)
@@ -1822,18 +2478,302 @@ impl App {
         f.render_stateful_widget(list, area, &mut self.cleanup_list_state);
     }
 
+    fn render_scan_errors(&mut self, f: &mut Frame, area: Rect) {
+        let Some((scan_id, errors)) = &self.scan_errors else {
+            return;
+        };
+        let root_path = self
+            .scans
+            .iter()
+            .find(|s| s.id == *scan_id)
+            .map(|s| s.root_path.as_str())
+            .unwrap_or("");
+        let title = format!(
+            "Unreadable paths | Scan: {} | {} paths not included in totals",
+            root_path,
+            errors.len()
+        );
+
+        let items: Vec<ListItem> = errors
+            .iter()
+            .map(|error| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!("⚠ {} ", error.path)),
+                    Span::styled(
+                        format!("— {}: {}", error.kind.label(), error.message),
+                        Style::default().fg(Color::Gray),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">> ");
+
+        f.render_stateful_widget(list, area, &mut self.scan_errors_state);
+    }
+
+    fn render_owners(&mut self, f: &mut Frame, area: Rect) {
+        let grouping = self.owner_grouping;
+        let highlight = Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD);
+
+        if let Some((owner_id, dirs)) = &self.owner_drilldown {
+            let title = format!(
+                "Owners (4) | Largest directories for {} {} (files directly inside) | {}",
+                grouping.display_name().to_lowercase(),
+                self.owner_names.name(grouping, *owner_id),
+                self.size_mode.display_name()
+            );
+            let items: Vec<ListItem> = dirs
+                .iter()
+                .map(|dir| {
+                    ListItem::new(format!(
+                        "📁 {} ({}, {} files)",
+                        dir.path,
+                        format_size(self.size_mode.select(dir.size, dir.disk_usage) as u64),
+                        dir.files
+                    ))
+                })
+                .collect();
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .highlight_style(highlight)
+                .highlight_symbol(">> ");
+            f.render_stateful_widget(list, area, &mut self.owner_dir_list_state);
+            return;
+        }
+
+        let total: i64 = self
+            .owner_usage
+            .iter()
+            .map(|o| self.size_mode.select(o.size, o.disk_usage))
+            .sum();
+        let title = format!(
+            "Owners (4) | By {} | {} total | {}",
+            grouping.display_name().to_lowercase(),
+            format_size(total as u64),
+            self.size_mode.display_name()
+        );
+        let items: Vec<ListItem> = self
+            .owner_usage
+            .iter()
+            .map(|usage| {
+                let size = self.size_mode.select(usage.size, usage.disk_usage);
+                let percent = if total > 0 {
+                    size as f64 * 100.0 / total as f64
+                } else {
+                    0.0
+                };
+                ListItem::new(format!(
+                    "{:<16} {:>10} {:>5.1}%  {} files",
+                    self.owner_names.name(grouping, usage.owner_id),
+                    format_size(size as u64),
+                    percent,
+                    usage.files
+                ))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(highlight)
+            .highlight_symbol(">> ");
+        f.render_stateful_widget(list, area, &mut self.owner_list_state);
+    }
+
+    fn render_duplicates(&mut self, f: &mut Frame, area: Rect) {
+        let highlight = Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD);
+
+        let Some((_, report)) = &self.dupes else {
+            let progress = match *self.dupes_progress.lock().unwrap() {
+                Some((stage, done, total)) => {
+                    format!("{}: {}/{} files", stage.label(), done, total)
+                }
+                None => "Finding files of the same size...".to_string(),
+            };
+            let paragraph = Paragraph::new(vec![Line::from(""), Line::from(progress)])
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Duplicates (5) | Searching"),
+                )
+                .alignment(Alignment::Center);
+            f.render_widget(paragraph, area);
+            return;
+        };
+
+        if let Some(group) = self.dupes_drilldown.and_then(|i| report.groups.get(i)) {
+            let title = format!(
+                "Duplicates (5) | {} copies of {} | m: keep selected, mark the rest",
+                group.paths.len(),
+                format_size(group.size)
+            );
+            let items: Vec<ListItem> = group
+                .paths
+                .iter()
+                .map(|path| ListItem::new(format!("📄 {}", path)))
+                .collect();
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .highlight_style(highlight)
+                .highlight_symbol(">> ");
+            f.render_stateful_widget(list, area, &mut self.dupes_copy_state);
+            return;
+        }
+
+        let title = format!(
+            "Duplicates (5) | {} groups | {} reclaimable",
+            report.groups.len(),
+            format_size(report.reclaimable())
+        );
+        let items: Vec<ListItem> = report
+            .groups
+            .iter()
+            .map(|group| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(format!(
+                        "{:>3} × {:>10} {:>10} reclaimable  ",
+                        group.paths.len(),
+                        format_size(group.size),
+                        format_size(group.reclaimable())
+                    )),
+                    Span::styled(group.paths[0].clone(), Style::default().fg(Color::Gray)),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(highlight)
+            .highlight_symbol(">> ");
+        f.render_stateful_widget(list, area, &mut self.dupes_list_state);
+    }
+
+    fn render_types(&mut self, f: &mut Frame, area: Rect) {
+        let highlight = Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD);
+        let size_mode = self.size_mode;
+        let total: i64 = self
+            .type_report
+            .categories
+            .iter()
+            .map(|c| size_mode.select(c.size, c.disk_usage))
+            .sum();
+        // A bar per row, scaled to the scan's total so rows are comparable across categories
+        let bar_width = 30;
+        let row = |label: String, size: i64, files: i64| {
+            let fraction = if total > 0 {
+                size as f64 / total as f64
+            } else {
+                0.0
+            };
+            let filled = (fraction * bar_width as f64).round() as usize;
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<16} ", label)),
+                Span::styled("█".repeat(filled), Style::default().fg(Color::Cyan)),
+                Span::styled(
+                    "░".repeat(bar_width - filled.min(bar_width)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+                Span::raw(format!(
+                    " {:>10} {:>5.1}%  {} files",
+                    format_size(size as u64),
+                    fraction * 100.0,
+                    files
+                )),
+            ]))
+        };
+
+        if let Some(category) = &self.type_drilldown {
+            let title = format!(
+                "Types (6) | Extensions in {} | {}",
+                category,
+                size_mode.display_name()
+            );
+            let items: Vec<ListItem> = self
+                .type_report
+                .extensions_in(category)
+                .map(|usage| {
+                    let label = if usage.extension.is_empty() {
+                        "(none)".to_string()
+                    } else {
+                        format!(".{}", usage.extension)
+                    };
+                    row(
+                        label,
+                        size_mode.select(usage.size, usage.disk_usage),
+                        usage.files,
+                    )
+                })
+                .collect();
+            let list = List::new(items)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .highlight_style(highlight)
+                .highlight_symbol(">> ");
+            f.render_stateful_widget(list, area, &mut self.type_ext_list_state);
+            return;
+        }
+
+        let mut title = format!(
+            "Types (6) | {} total | {}",
+            format_size(total as u64),
+            size_mode.display_name()
+        );
+        if self.type_report.sniffed > 0 {
+            title.push_str(&format!(
+                " | {} identified by content",
+                self.type_report.sniffed
+            ));
+        }
+        let items: Vec<ListItem> = self
+            .type_report
+            .categories
+            .iter()
+            .map(|usage| {
+                row(
+                    usage.name.clone(),
+                    size_mode.select(usage.size, usage.disk_usage),
+                    usage.files,
+                )
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .highlight_style(highlight)
+            .highlight_symbol(">> ");
+        f.render_stateful_widget(list, area, &mut self.type_list_state);
+    }
+
     fn render_scan_dialog(&self, f: &mut Frame, area: Rect) {
-        let text = vec![
+        let mut text = vec![
             Line::from(""),
             Line::from("Enter path to scan:"),
             Line::from(""),
+        ];
+        for root in &self.scan_roots {
+            text.push(Line::from(vec![Span::styled(
+                root,
+                Style::default().fg(Color::Gray),
+            )]));
+        }
+        text.extend([
             Line::from(vec![Span::styled(
                 &self.scan_input,
                 Style::default().fg(Color::Cyan),
             )]),
             Line::from(""),
+            Line::from("Press Tab to add another path to the same scan"),
             Line::from("Press Enter to start scan, Esc to cancel"),
-        ];
+        ]);
 
         let paragraph = Paragraph::new(text)
             .block(Block::default().borders(Borders::ALL).title("New Scan"))
@@ -1865,7 +2805,7 @@ impl App {
             return;
         }
 
-        let treemap_rects = treemap::build_treemap(&top_level, area, 0);
+        let treemap_rects = treemap::build_treemap(&top_level, area, 0, self.size_mode);
 
         // Color palette for different depths
         let colors = [
@@ -1948,12 +2888,12 @@ impl App {
             "Treemap: {} | T: exit | o/Enter: drill | Esc: up | gg/G: top/bottom | u/d: page up/down | hjkl: navigate",
             if self.treemap_path.is_empty() {
                 if let Some(scan) = &self.current_scan {
-                    scan.root_path.as_str()
+                    scan.location()
                 } else {
-                    "/"
+                    "/".to_string()
                 }
             } else {
-                &self.treemap_path
+                self.treemap_path.clone()
             }
         );
         let outer_block = Block::default()
@@ -1964,7 +2904,7 @@ impl App {
         f.render_widget(outer_block, chunks[0]);
 
         // Build treemap in the inner area
-        let treemap_rects = treemap::build_treemap(&current_entries, inner_area, 0);
+        let treemap_rects = treemap::build_treemap(&current_entries, inner_area, 0, self.size_mode);
 
         // Get current selection index
         let selected_idx = self.get_treemap_selection();
@@ -2102,7 +3042,7 @@ impl App {
             .take(visible_items)
             .map(|(idx, entry)| {
                 let is_selected = selected_idx == Some(idx);
-                let size_str = format_size(entry.size as u64);
+                let size_str = format_size(self.size_mode.entry_size(entry) as u64);
                 let icon = if entry.is_dir { "📁" } else { "📄" };
 
                 // Format: icon name (size)
@@ -2151,9 +3091,27 @@ impl App {
                     Span::raw(format_size(file.size as u64)),
                     Span::raw(format!(" ({} bytes)", file.size)),
                 ]),
+                Line::from(vec![
+                    Span::styled(
+                        if file.kind == EntryKind::InArchive {
+                            "Compressed: "
+                        } else {
+                            "Disk usage: "
+                        },
+                        Style::default().fg(Color::Yellow),
+                    ),
+                    Span::raw(format_size(file.disk_usage as u64)),
+                    Span::raw(format!(" ({} bytes)", file.disk_usage)),
+                ]),
                 Line::from(vec![
                     Span::styled("Type: ", Style::default().fg(Color::Yellow)),
-                    Span::raw(if file.is_dir { "Directory" } else { "File" }),
+                    Span::raw(match (file.kind, file.is_dir) {
+                        (EntryKind::InArchive, true) => "Directory in archive",
+                        (EntryKind::InArchive, false) => "File in archive",
+                        (EntryKind::Archive, _) => "Archive",
+                        (_, true) => "Directory",
+                        (_, false) => "File",
+                    }),
                 ]),
                 Line::from(vec![
                     Span::styled("Depth: ", Style::default().fg(Color::Yellow)),
@@ -2178,6 +3136,57 @@ impl App {
                 lines.push(Line::from(""));
             }
 
+            if let Some(stat) = &file.stat {
+                lines.push(Line::from(vec![
+                    Span::styled("Owner: ", Style::default().fg(Color::Yellow)),
+                    Span::raw(format!("uid {} / gid {}", stat.uid, stat.gid)),
+                ]));
+                lines.push(Line::from(vec![
+                    Span::styled("Mode: ", Style::default().fg(Color::Yellow)),
+                    Span::raw(format!(
+                        "{} ({:04o})",
+                        stat.mode_string(),
+                        stat.permissions()
+                    )),
+                ]));
+                lines.push(Line::from(vec![
+                    Span::styled("Inode: ", Style::default().fg(Color::Yellow)),
+                    Span::raw(format!("{} on device {}", stat.inode, stat.device)),
+                ]));
+                lines.push(Line::from(vec![
+                    Span::styled("Links: ", Style::default().fg(Color::Yellow)),
+                    Span::raw(stat.nlink.to_string()),
+                ]));
+                if stat.nlink > 1 && !file.is_dir {
+                    let outside = (stat.nlink as usize).saturating_sub(self.detail_links.len() + 1);
+                    for link in &self.detail_links {
+                        lines.push(Line::from(vec![
+                            Span::styled("  🔗 ", Style::default().fg(Color::Magenta)),
+                            Span::raw(link.as_str()),
+                        ]));
+                    }
+                    if outside > 0 {
+                        lines.push(Line::from(vec![Span::styled(
+                            format!("  {} more outside this scan", outside),
+                            Style::default().fg(Color::Gray),
+                        )]));
+                    }
+                }
+                if let Some(accessed) = &stat.accessed_at {
+                    lines.push(Line::from(vec![
+                        Span::styled("Accessed: ", Style::default().fg(Color::Yellow)),
+                        Span::raw(accessed.format("%Y-%m-%d %H:%M:%S").to_string()),
+                    ]));
+                }
+                if let Some(changed) = &stat.changed_at {
+                    lines.push(Line::from(vec![
+                        Span::styled("Changed: ", Style::default().fg(Color::Yellow)),
+                        Span::raw(changed.format("%Y-%m-%d %H:%M:%S").to_string()),
+                    ]));
+                }
+                lines.push(Line::from(""));
+            }
+
             lines.push(Line::from(vec![
                 Span::styled("Scan ID: ", Style::default().fg(Color::Yellow)),
                 Span::raw(file.scan_id.to_string()),
@@ -2243,6 +3252,9 @@ impl App {
             Line::from("  1           Scan list view"),
             Line::from("  2           File tree view"),
             Line::from("  3           Cleanup list view"),
+            Line::from("  4           Disk usage by owner"),
+            Line::from("  5           Duplicate files"),
+            Line::from("  6           Disk usage by file type"),
             Line::from(""),
             Line::from(vec![Span::styled(
                 "Actions:",
@@ -2250,8 +3262,11 @@ impl App {
             )]),
             Line::from("  n           New scan"),
             Line::from("  r           Resume paused scan"),
+            Line::from("  r           Resume selected directory of a paused scan (File view)"),
             Line::from("  x           Delete scan (Scan list view)"),
+            Line::from("  e           List unreadable paths (Scan list view)"),
             Line::from("  t           Toggle sort mode (size/path)"),
+            Line::from("  a           Toggle apparent size / disk usage"),
             Line::from("  Space       Mark/unmark file for cleanup (File view)"),
             Line::from("  Space       Remove from cleanup list (Cleanup view)"),
             Line::from("  /           Search files by name or path (File view)"),
@@ -2259,6 +3274,8 @@ impl App {
             Line::from("  Z/O         Unfold directory and all subdirs (File view)"),
             Line::from("  s           Open shell in directory/parent (File view)"),
             Line::from("  s/g         Generate cleanup script (Cleanup view)"),
+            Line::from("  Tab         Switch users/groups (Owners view)"),
+            Line::from("  m           Mark all but one copy for cleanup (Duplicates view)"),
             Line::from("  Enter/o     Select/open"),
             Line::from(""),
             Line::from(vec![Span::styled(
@@ -2300,7 +3317,7 @@ impl App {
                 // Show scan details
                 lines.push(Line::from(vec![
                     Span::styled("Path: ", Style::default().fg(Color::Gray)),
-                    Span::styled(&scan.root_path, Style::default().fg(Color::White)),
+                    Span::styled(scan.location(), Style::default().fg(Color::White)),
                 ]));
                 lines.push(Line::from(""));
 
@@ -2329,7 +3346,7 @@ impl App {
                 ]));
 
                 // Total size
-                let size_str = format_size(scan.total_size as u64);
+                let size_str = format_size(self.size_mode.scan_size(scan) as u64);
                 lines.push(Line::from(vec![
                     Span::styled("  Total size:  ", Style::default().fg(Color::Gray)),
                     Span::styled(size_str, Style::default().fg(Color::White)),
@@ -2407,7 +3424,7 @@ impl App {
             if let Some(scan) = self.scans.iter().find(|s| s.id == scan_id) {
                 lines.push(Line::from(vec![
                     Span::styled("Path: ", Style::default().fg(Color::Gray)),
-                    Span::styled(&scan.root_path, Style::default().fg(Color::White)),
+                    Span::styled(scan.location(), Style::default().fg(Color::White)),
                 ]));
                 lines.push(Line::from(""));
 
@@ -2425,7 +3442,7 @@ impl App {
                     ),
                     Span::styled(" directories (", Style::default().fg(Color::Gray)),
                     Span::styled(
-                        format_size(scan.total_size as u64),
+                        format_size(self.size_mode.scan_size(scan) as u64),
                         Style::default().fg(Color::Yellow),
                     ),
                     Span::styled(")", Style::default().fg(Color::Gray)),
@@ -2539,9 +3556,12 @@ impl App {
         if let Some(resume_prep) = &self.resume_prep {
             lines.push(Line::from(format!("Scan ID: {}", resume_prep.scan_id)));
             lines.push(Line::from(format!("Path: {}", resume_prep.path)));
+            if let Some(subtree) = &resume_prep.subtree {
+                lines.push(Line::from(format!("Subtree: {}", subtree)));
+            }
             lines.push(Line::from(""));
             lines.push(Line::from(vec![Span::styled(
-                "Loading already-scanned paths from database...",
+                "Loading finished directories from database...",
                 Style::default().fg(Color::Gray),
             )]));
             lines.push(Line::from(vec![Span::styled(
@@ -2595,10 +3615,42 @@ impl App {
                 "Scan List Sort",
                 self.scan_list_sort.display_name().to_string(),
             ),
+            ("Size Accounting", self.size_mode.display_name().to_string()),
             ("Auto-fold Depth", "1 level".to_string()),
             (
                 "Follow Symlinks",
-                if self.follow_symlinks { "Yes" } else { "No" }.to_string(),
+                if self.scan_settings.follow_symlinks {
+                    "Yes"
+                } else {
+                    "No"
+                }
+                .to_string(),
+            ),
+            (
+                "One File System",
+                if self.scan_settings.one_file_system {
+                    "Yes"
+                } else {
+                    "No"
+                }
+                .to_string(),
+            ),
+            (
+                "Hard Links",
+                self.scan_settings.hard_links.display_name().to_string(),
+            ),
+            (
+                "Archive Members",
+                if self.scan_settings.archives {
+                    "Yes"
+                } else {
+                    "No"
+                }
+                .to_string(),
+            ),
+            (
+                "Scan Method",
+                self.scan_settings.implementation.display_name().to_string(),
             ),
             ("", "".to_string()), // Spacer
             ("About", "Rootkitty - Disk Usage Analyzer".to_string()),
@@ -2614,14 +3666,14 @@ impl App {
                 } else {
                     // Index 0: config path (editable)
                     // Index 1: database path (editable)
-                    // Indices 3, 4, and 6: sort settings and follow symlinks (toggleable)
+                    // Indices 3, 4, 5 and 7 to 11: display settings and scan options (toggleable)
                     let indicator = if idx == 0 || idx == 1 {
                         if self.editing_path_index == Some(idx) {
                             "[editing...]"
                         } else {
                             "[e/r]"
                         }
-                    } else if idx == 3 || idx == 4 || idx == 6 {
+                    } else if matches!(idx, 3 | 4 | 5 | 7 | 8 | 9 | 10 | 11) {
                         "[t]"
                     } else {
                         "   "
@@ -2691,14 +3743,46 @@ impl App {
                 ]));
             }
 
+            let scanned_label = if progress.gathering {
+                "Counting entries: "
+            } else {
+                "New entries scanned: "
+            };
             lines.push(Line::from(vec![
-                Span::styled("New entries scanned: ", Style::default().fg(Color::Gray)),
+                Span::styled(scanned_label, Style::default().fg(Color::Gray)),
                 Span::styled(
                     format!("{}", progress.entries_scanned),
                     Style::default().fg(Color::Cyan),
                 ),
             ]));
 
+            match (progress.total_estimate, progress.percent_done) {
+                (Some(total), Some(percent)) => lines.push(Line::from(vec![
+                    Span::styled("Progress: ", Style::default().fg(Color::Gray)),
+                    Span::styled(
+                        format!("{:.0}% of {} entries", percent, total),
+                        Style::default().fg(Color::Green),
+                    ),
+                ])),
+                (Some(total), None) => lines.push(Line::from(vec![
+                    Span::styled("Expected: ", Style::default().fg(Color::Gray)),
+                    Span::styled(
+                        format!("~{} entries (from the last scan)", total),
+                        Style::default().fg(Color::Green),
+                    ),
+                ])),
+                _ => {}
+            }
+            if let Some(eta) = progress.eta {
+                lines.push(Line::from(vec![
+                    Span::styled("Time remaining: ", Style::default().fg(Color::Gray)),
+                    Span::styled(
+                        format!("~{}", format_duration(eta)),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                ]));
+            }
+
             if self.resumed_entries_count > 0 {
                 lines.push(Line::from(vec![
                     Span::styled("Total entries: ", Style::default().fg(Color::Gray)),
@@ -2718,12 +3802,34 @@ impl App {
                 ),
             ]));
 
-            if progress.active_workers > 0 {
+            if !progress.worker_utilization.is_empty() {
+                lines.push(Line::from(vec![
+                    Span::styled("Workers busy: ", Style::default().fg(Color::Gray)),
+                    Span::raw(format!(
+                        "{} of {} ",
+                        progress.active_workers,
+                        progress.worker_utilization.len()
+                    )),
+                    Span::styled(
+                        crate::workers::utilization_bars(&progress.worker_utilization),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                ]));
+            } else if progress.active_workers > 0 {
                 lines.push(Line::from(format!(
                     "Parallel workers: {}",
                     progress.active_workers
                 )));
             }
+            if let Some((rate, limit)) = progress.stat_rate {
+                lines.push(Line::from(vec![
+                    Span::styled("Throttle: ", Style::default().fg(Color::Gray)),
+                    Span::styled(
+                        format!("{} of {} stats/s", rate, limit),
+                        Style::default().fg(Color::Yellow),
+                    ),
+                ]));
+            }
 
             lines.push(Line::from(""));
             lines.push(Line::from("Active directories:"));
@@ -2767,13 +3873,13 @@ impl App {
                 "n: new | ?: help"
             }
             View::FileTree => {
-                "q: quit | t: toggle sort | s: shell | Space: mark | z: fold | ↑↓/jk: navigate"
+                "q: quit | t: toggle sort | a: apparent/disk | s: shell | Space: mark | z: fold | ↑↓/jk: navigate"
             }
             View::CleanupList => {
                 "q: quit | S: settings | 1: scans | 2: files | 3: cleanup | s: generate | Space: remove"
             }
             View::ScanDialog => {
-                "Enter: start scan | Esc: cancel | Type path to scan"
+                "Enter: start scan | Tab: add another path | Esc: cancel | Type path to scan"
             }
             View::Scanning => {
                 "q/Esc: cancel scan"
@@ -2803,6 +3909,30 @@ impl App {
             View::FileDetail => {
                 "Esc: back to file tree"
             }
+            View::ScanErrors => {
+                "Esc: back to scans | ↑↓/jk: navigate"
+            }
+            View::Duplicates => {
+                if self.dupes_drilldown.is_some() {
+                    "Esc: back to groups | m: keep selected, mark other copies | ↑↓/jk: navigate"
+                } else {
+                    "Enter: list copies | m: keep first, mark other copies | ↑↓/jk: navigate"
+                }
+            }
+            View::Owners => {
+                if self.owner_drilldown.is_some() {
+                    "Esc: back to owners | a: apparent/disk | ↑↓/jk: navigate"
+                } else {
+                    "Enter: largest directories | Tab: users/groups | a: apparent/disk | ↑↓/jk: navigate"
+                }
+            }
+            View::Types => {
+                if self.type_drilldown.is_some() {
+                    "Esc: back to categories | a: apparent/disk | ↑↓/jk: navigate"
+                } else {
+                    "Enter: extensions | a: apparent/disk | ↑↓/jk: navigate"
+                }
+            }
         };
 
         // Combine status message and help text into a single line
@@ -2890,21 +4020,42 @@ impl App {
             ui: crate::settings::UiSettings {
                 file_tree_sort: self.file_tree_sort,
                 scan_list_sort: self.scan_list_sort,
+                size_mode: self.size_mode,
                 auto_fold_depth: 1, // Current default, will be configurable later
             },
-            scan: crate::settings::ScanSettings {
-                follow_symlinks: self.follow_symlinks,
-            },
+            scan: self.scan_settings.clone(),
+            types: self.type_settings.clone(),
         };
         settings.save(&self.settings_path)?;
         Ok(())
     }
 
+    fn toggle_size_mode(&mut self) {
+        self.size_mode = self.size_mode.toggle();
+        self.status_message = format!("Sizes: {}", self.size_mode.display_name());
+    }
+
+    /// Size of an entry in the selected accounting, with the other one for comparison
+    fn describe_sizes(&self, entry: &StoredFileEntry) -> String {
+        match self.size_mode {
+            SizeMode::Apparent => format!(
+                "{} (on disk: {})",
+                format_size(entry.size as u64),
+                format_size(entry.disk_usage as u64)
+            ),
+            SizeMode::DiskUsage => format!(
+                "{} on disk (apparent: {})",
+                format_size(entry.disk_usage as u64),
+                format_size(entry.size as u64)
+            ),
+        }
+    }
+
     fn get_sorted_scans(&self) -> Vec<Scan> {
         let mut sorted_scans = self.scans.clone();
         match self.scan_list_sort {
             SortMode::BySize => {
-                sorted_scans.sort_by(|a, b| b.total_size.cmp(&a.total_size));
+                sorted_scans.sort_by_key(|s| std::cmp::Reverse(self.size_mode.scan_size(s)));
             }
             SortMode::ByPath => {
                 sorted_scans.sort_by(|a, b| a.root_path.cmp(&b.root_path));
@@ -2929,16 +4080,22 @@ impl App {
             // Find the full scan object by ID
             if let Some(scan) = self.scans.iter().find(|s| s.id == scan_id).cloned() {
                 let db = self.db.clone();
+                let paused = scan.status == "paused";
+                let root_path = scan.root_path.clone();
 
                 // Spawn background task to load entries
                 let loading_task = tokio::spawn(async move {
                     // Load the root directory and its immediate children
-                    let root = db.get_root_entry(scan_id).await?;
+                    // (a paused scan usually hasn't recorded its root yet)
+                    let root = match db.get_root_entry(scan_id).await? {
+                        None if paused => db.get_unfinished_root(scan_id, &root_path).await?,
+                        root => root,
+                    };
 
                     let mut entries = Vec::new();
                     if let Some(root) = root {
                         // Load root + its immediate children (depth 1)
-                        let children = db.get_entries_by_parent(scan_id, Some(&root.path)).await?;
+                        let children = load_children(&db, scan_id, paused, &root.path).await?;
                         entries.push(root);
                         entries.extend(children);
                     }
@@ -3131,19 +4288,18 @@ impl App {
             // Load children from database for treemap - wait for completion
             if let Some(scan) = &self.current_scan {
                 let scan_id = scan.id;
+                let paused = scan.status == "paused";
                 let db = self.db.clone();
                 let parent_path = dir_path.to_string();
 
                 // Load directly and wait for completion
-                let children = db
-                    .get_entries_by_parent(scan_id, Some(&parent_path))
-                    .await?;
+                let children = load_children(&db, scan_id, paused, &parent_path).await?;
 
                 // Add children to file_entries if not already there
                 for child in children {
                     if !self.file_entries.iter().any(|e| e.path == child.path) {
                         // Fold directories by default
-                        if child.is_dir {
+                        if child.has_children() {
                             self.folded_dirs.insert(child.path.clone());
                         }
                         self.file_entries.push(child);
@@ -3173,13 +4329,12 @@ impl App {
             // Need to load children from database
             if let Some(scan) = &self.current_scan {
                 let scan_id = scan.id;
+                let paused = scan.status == "paused";
                 let db = self.db.clone();
                 let parent_path = dir_path.to_string();
 
                 let task = tokio::spawn(async move {
-                    let entries = db
-                        .get_entries_by_parent(scan_id, Some(&parent_path))
-                        .await?;
+                    let entries = load_children(&db, scan_id, paused, &parent_path).await?;
                     Ok(LoadingResult::DirectoryChildren(parent_path, entries))
                 });
 
@@ -3195,21 +4350,56 @@ impl App {
     fn initialize_folded_state(&mut self) {
         // Fold all directories except the root (depth 0)
         for entry in &self.file_entries {
-            if entry.is_dir && entry.depth > 0 {
+            if entry.has_children() && entry.depth > 0 {
                 self.folded_dirs.insert(entry.path.clone());
             }
         }
     }
 
-    fn toggle_fold_directory(&mut self, recursive: bool) -> Result<()> {
+    /// Show the detail view for a file, looking up its other hard links in the scan
+    async fn open_file_detail(&mut self, entry: StoredFileEntry) {
+        self.detail_links.clear();
+        if let Some(stat) = entry.stat.filter(|st| st.nlink > 1) {
+            match self
+                .db
+                .get_hard_links(entry.scan_id, stat.device, stat.inode)
+                .await
+            {
+                Ok(paths) => {
+                    self.detail_links = paths.into_iter().filter(|p| *p != entry.path).collect()
+                }
+                Err(e) => self.status_message = format!("Error loading hard links: {}", e),
+            }
+        }
+        self.detail_file = Some(entry);
+        self.previous_view = View::FileTree;
+        self.view = View::FileDetail;
+    }
+
+    async fn toggle_fold_directory(&mut self, recursive: bool) -> Result<()> {
         if let Some(selected) = self.file_list_state.selected() {
             let visible_entries = self.get_visible_entries();
             if let Some(entry) = visible_entries.get(selected) {
-                if !entry.is_dir {
+                if !entry.has_children() {
                     // It's a file - open file detail view
-                    self.detail_file = Some((*entry).clone());
-                    self.previous_view = View::FileTree;
-                    self.view = View::FileDetail;
+                    let entry = (*entry).clone();
+                    self.open_file_detail(entry).await;
+                    return Ok(());
+                }
+
+                if let Some(label) = entry.kind.label() {
+                    // Marker directories were never scanned, so there is nothing to unfold
+                    self.status_message = format!("'{}' was not scanned ({})", entry.name, label);
+                    return Ok(());
+                }
+
+                if entry.kind == EntryKind::Summarized {
+                    // Scanned with a depth limit: sizes include everything below, but
+                    // individual entries weren't stored
+                    self.status_message = format!(
+                        "'{}' is at the scan's depth limit; its contents were counted but not stored",
+                        entry.name
+                    );
                     return Ok(());
                 }
 
@@ -3235,7 +4425,8 @@ impl App {
                                 .file_entries
                                 .iter()
                                 .filter(|e| {
-                                    e.is_dir && e.path.starts_with(&format!("{}/", dir_path))
+                                    e.has_children()
+                                        && e.path.starts_with(&format!("{}/", dir_path))
                                 })
                                 .map(|e| e.path.clone())
                                 .collect();
@@ -3250,15 +4441,16 @@ impl App {
                         // Need to load from database
                         if let Some(scan) = &self.current_scan {
                             let scan_id = scan.id;
+                            let paused = scan.status == "paused";
                             let db = self.db.clone();
                             let parent_path = dir_path.clone();
 
-                            if !recursive {
+                            // Unfinished directories of a paused scan unfold one level at a time
+                            if !recursive || paused {
                                 // Non-recursive: load immediate children only
                                 let loading_task = tokio::spawn(async move {
-                                    let children = db
-                                        .get_entries_by_parent(scan_id, Some(&parent_path))
-                                        .await?;
+                                    let children =
+                                        load_children(&db, scan_id, paused, &parent_path).await?;
                                     Ok(LoadingResult::DirectoryChildren(parent_path, children))
                                 });
 
@@ -3306,13 +4498,28 @@ impl App {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
     ) -> Result<()> {
+        if let Some(host) = self.current_scan.as_ref().and_then(|s| s.host.as_ref()) {
+            self.status_message = format!("Files of this scan are on {}", host);
+            return Ok(());
+        }
         if let Some(selected) = self.file_list_state.selected() {
             let visible_entries = self.get_visible_entries();
             if let Some(entry) = visible_entries.get(selected) {
                 // Determine the directory to open:
+                // - If it's inside an archive, the directory holding the archive
                 // - If it's a directory, use it directly
                 // - If it's a file, use its parent directory
-                let target_dir = if entry.is_dir {
+                let archive = (entry.kind == EntryKind::InArchive)
+                    .then(|| {
+                        self.file_entries.iter().find(|e| {
+                            e.kind == EntryKind::Archive
+                                && entry.path.starts_with(&format!("{}/", e.path))
+                        })
+                    })
+                    .flatten();
+                let target_dir = if let Some(archive) = archive {
+                    archive.parent_path.clone().unwrap_or_default()
+                } else if entry.is_dir {
                     entry.path.clone()
                 } else {
                     // Use parent_path if available, otherwise extract from path
@@ -3411,6 +4618,7 @@ impl App {
             &self.file_entries,
             &self.folded_dirs,
             self.file_tree_sort,
+            self.size_mode,
             search_query,
         )
     }
@@ -3446,6 +4654,11 @@ impl App {
             if let Some(selected) = self.file_list_state.selected() {
                 let visible_entries = self.get_visible_entries();
                 if let Some(entry) = visible_entries.get(selected) {
+                    if entry.kind == EntryKind::InArchive {
+                        // Only the archive as a whole can be removed
+                        self.status_message = format!("'{}' is inside an archive", entry.name);
+                        return Ok(());
+                    }
                     self.db.mark_for_cleanup(scan.id, &entry.path, None).await?;
                     self.status_message = format!("Marked '{}' for cleanup", entry.name);
                 }
@@ -3465,6 +4678,263 @@ impl App {
         Ok(())
     }
 
+    async fn open_scan_errors(&mut self, scan_id: i64) -> Result<()> {
+        let errors = self.db.get_scan_errors(scan_id).await?;
+        if errors.is_empty() {
+            self.status_message = "Every path in this scan was readable".to_string();
+            return Ok(());
+        }
+        self.scan_errors_state.select(Some(0));
+        self.scan_errors = Some((scan_id, errors));
+        self.view = View::ScanErrors;
+        Ok(())
+    }
+
+    fn scan_errors_move(&mut self, delta: isize) {
+        let len = self
+            .scan_errors
+            .as_ref()
+            .map_or(0, |(_, errors)| errors.len());
+        if len == 0 {
+            return;
+        }
+        let current = self.scan_errors_state.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, len as isize - 1);
+        self.scan_errors_state.select(Some(next as usize));
+    }
+
+    /// Show the owners view for the current scan, reloading its data
+    async fn open_owners(&mut self) {
+        match self.load_owner_usage().await {
+            Ok(()) => self.view = View::Owners,
+            Err(e) => self.status_message = format!("Error: {}", e),
+        }
+    }
+
+    async fn load_owner_usage(&mut self) -> Result<()> {
+        let Some(scan) = &self.current_scan else {
+            return Ok(());
+        };
+        let scan_id = scan.id;
+
+        self.owner_usage = self
+            .db
+            .get_owner_usage(scan_id, self.owner_grouping, self.size_mode)
+            .await?;
+        self.owner_list_state
+            .select(if self.owner_usage.is_empty() {
+                None
+            } else {
+                Some(0)
+            });
+
+        // Refresh an open drill-down so it follows the size mode
+        if let Some((owner_id, _)) = self.owner_drilldown.take() {
+            if self.owner_usage.iter().any(|o| o.owner_id == owner_id) {
+                self.load_owner_directories(owner_id).await?;
+            }
+        }
+
+        if self.owner_usage.is_empty() {
+            self.status_message =
+                "No ownership information in this scan (rescan to record owners)".to_string();
+        }
+        Ok(())
+    }
+
+    async fn drill_into_owner(&mut self) -> Result<()> {
+        let selected = self
+            .owner_list_state
+            .selected()
+            .and_then(|i| self.owner_usage.get(i))
+            .map(|o| o.owner_id);
+        if let Some(owner_id) = selected {
+            self.load_owner_directories(owner_id).await?;
+        }
+        Ok(())
+    }
+
+    async fn load_owner_directories(&mut self, owner_id: u32) -> Result<()> {
+        let Some(scan) = &self.current_scan else {
+            return Ok(());
+        };
+        let dirs = self
+            .db
+            .get_owner_directories(scan.id, self.owner_grouping, owner_id, self.size_mode, 200)
+            .await?;
+        self.owner_dir_list_state
+            .select(if dirs.is_empty() { None } else { Some(0) });
+        self.owner_drilldown = Some((owner_id, dirs));
+        Ok(())
+    }
+
+    /// Move the selection among owners, or among the directories of the one drilled into
+    fn owners_list_move(&mut self, delta: isize) {
+        match &self.owner_drilldown {
+            Some((_, dirs)) => move_selection(&mut self.owner_dir_list_state, dirs.len(), delta),
+            None => move_selection(&mut self.owner_list_state, self.owner_usage.len(), delta),
+        }
+    }
+
+    /// Show the types view for the current scan, reloading its data
+    async fn open_types(&mut self) {
+        match self.load_type_report().await {
+            Ok(()) => self.view = View::Types,
+            Err(e) => self.status_message = format!("Error: {}", e),
+        }
+    }
+
+    async fn load_type_report(&mut self) -> Result<()> {
+        let Some(scan) = &self.current_scan else {
+            return Ok(());
+        };
+        self.type_report = filetypes::type_breakdown(
+            &self.db,
+            scan.id,
+            &self.type_settings.categories,
+            self.size_mode,
+            self.type_settings.sniff_sample,
+        )
+        .await?;
+        self.type_list_state
+            .select(if self.type_report.categories.is_empty() {
+                None
+            } else {
+                Some(0)
+            });
+
+        // Keep an open drill-down if its category is still there
+        if let Some(category) = self.type_drilldown.take() {
+            if self
+                .type_report
+                .categories
+                .iter()
+                .any(|c| c.name == category)
+            {
+                self.type_drilldown = Some(category);
+                self.type_ext_list_state.select(Some(0));
+            }
+        }
+
+        if self.type_report.categories.is_empty() {
+            self.status_message = "No files in this scan".to_string();
+        }
+        Ok(())
+    }
+
+    fn drill_into_type(&mut self) {
+        let selected = self
+            .type_list_state
+            .selected()
+            .and_then(|i| self.type_report.categories.get(i))
+            .map(|c| c.name.clone());
+        if let Some(category) = selected {
+            self.type_drilldown = Some(category);
+            self.type_ext_list_state.select(Some(0));
+        }
+    }
+
+    /// Move the selection among categories, or among the extensions of the one drilled into
+    fn types_list_move(&mut self, delta: isize) {
+        match &self.type_drilldown {
+            Some(category) => move_selection(
+                &mut self.type_ext_list_state,
+                self.type_report.extensions_in(category).count(),
+                delta,
+            ),
+            None => move_selection(
+                &mut self.type_list_state,
+                self.type_report.categories.len(),
+                delta,
+            ),
+        }
+    }
+
+    /// Show the duplicates view, starting a search unless the current scan's results are in
+    fn open_duplicates(&mut self) {
+        let Some(scan) = &self.current_scan else {
+            return;
+        };
+        self.view = View::Duplicates;
+        if self.dupes.as_ref().is_some_and(|(id, _)| *id == scan.id)
+            || self
+                .dupes_task
+                .as_ref()
+                .is_some_and(|(id, _)| *id == scan.id)
+        {
+            return;
+        }
+        // A search still running for another scan would report under this one
+        if let Some((_, task)) = self.dupes_task.take() {
+            task.abort();
+        }
+
+        self.dupes = None;
+        self.dupes_drilldown = None;
+        // A fresh slot, so the aborted search can't report progress into it
+        self.dupes_progress = Arc::new(Mutex::new(None));
+        let db = self.db.clone();
+        let scan_id = scan.id;
+        let progress = self.dupes_progress.clone();
+        let task = tokio::spawn(async move {
+            dupes::find_duplicates(
+                &db,
+                scan_id,
+                dupes::DEFAULT_MIN_SIZE,
+                |stage, done, total| {
+                    *progress.lock().unwrap() = Some((stage, done, total));
+                },
+            )
+            .await
+        });
+        self.dupes_task = Some((scan_id, task));
+    }
+
+    /// Move the selection among duplicate groups, or among the copies of the open group
+    fn dupes_list_move(&mut self, delta: isize) {
+        let Some((_, report)) = &self.dupes else {
+            return;
+        };
+        match self.dupes_drilldown.and_then(|i| report.groups.get(i)) {
+            Some(group) => move_selection(&mut self.dupes_copy_state, group.paths.len(), delta),
+            None => move_selection(&mut self.dupes_list_state, report.groups.len(), delta),
+        }
+    }
+
+    /// Mark every copy in the selected group for cleanup except one: the
+    /// selected copy when its paths are listed, otherwise the first
+    async fn mark_duplicate_copies(&mut self) -> Result<()> {
+        let Some((scan_id, report)) = &self.dupes else {
+            return Ok(());
+        };
+        let (group, keep) = match self.dupes_drilldown {
+            Some(group) => (group, self.dupes_copy_state.selected().unwrap_or(0)),
+            None => match self.dupes_list_state.selected() {
+                Some(group) => (group, 0),
+                None => return Ok(()),
+            },
+        };
+        let Some(group) = report.groups.get(group) else {
+            return Ok(());
+        };
+
+        let kept = &group.paths[keep];
+        let reason = format!("Duplicate of {}", kept);
+        for (i, path) in group.paths.iter().enumerate() {
+            if i != keep {
+                self.db
+                    .mark_for_cleanup(*scan_id, path, Some(&reason))
+                    .await?;
+            }
+        }
+        self.status_message = format!(
+            "Marked {} copies for cleanup, keeping '{}'",
+            group.paths.len() - 1,
+            kept
+        );
+        Ok(())
+    }
+
     async fn remove_from_cleanup(&mut self) -> Result<()> {
         if let Some(scan) = &self.current_scan {
             if let Some(selected) = self.cleanup_list_state.selected() {
@@ -3487,30 +4957,83 @@ impl App {
         Ok(())
     }
 
-    async fn resume_scan(&mut self, scan_id: i64, path: String) -> Result<()> {
+    async fn resume_scan(
+        &mut self,
+        scan_id: i64,
+        path: String,
+        subtree: Option<String>,
+    ) -> Result<()> {
         // Show loading message and switch to preparing view
-        self.status_message = "Loading already-scanned paths from database...".to_string();
+        self.status_message = "Loading finished directories from database...".to_string();
         self.view = View::PreparingResume;
 
-        // Start loading scanned paths in background (non-blocking)
+        // Start loading what the scan recorded in background (non-blocking)
         let db_clone = self.db.clone();
-        let load_task = tokio::spawn(async move { db_clone.get_scanned_paths(scan_id).await });
+        let dir = subtree.clone();
+        let load_task =
+            tokio::spawn(async move { db_clone.get_resume_state(scan_id, dir.as_deref()).await });
 
         // Store the preparation state
         self.resume_prep = Some(ResumePreparation {
             scan_id,
             path,
+            subtree,
             load_task,
         });
 
         Ok(())
     }
 
-    async fn start_resume_scan_with_paths(
+    /// Resume the paused scan open in the file tree for the selected directory only
+    ///
+    /// The scan stays paused; directory sizes are fixed up when the run finishes.
+    async fn resume_selected_subtree(&mut self) {
+        let Some(scan) = self.current_scan.clone() else {
+            return;
+        };
+        if let Some(host) = &scan.host {
+            self.status_message =
+                format!("Scan ran on {}; run it again with 'rootkitty scan'", host);
+            return;
+        }
+        if scan.status != "paused" {
+            self.status_message = "This scan is not paused".to_string();
+            return;
+        }
+        let Some(entry) = self
+            .file_list_state
+            .selected()
+            .and_then(|selected| self.get_visible_entries().get(selected).cloned().cloned())
+        else {
+            return;
+        };
+        if !entry.is_dir || !matches!(entry.kind, EntryKind::Normal | EntryKind::Unfinished) {
+            self.status_message = format!("'{}' is not a directory to resume", entry.name);
+            return;
+        }
+        if scan.is_multi_root()
+            && !scan
+                .roots
+                .iter()
+                .any(|root| std::path::Path::new(&entry.path).starts_with(root))
+        {
+            self.status_message = format!("'{}' is above the scan's roots", entry.name);
+            return;
+        }
+
+        // Resuming the root is resuming the whole scan
+        let subtree = Some(entry.path).filter(|path| *path != scan.root_path);
+        if let Err(e) = self.resume_scan(scan.id, scan.root_path, subtree).await {
+            self.status_message = format!("Resume error: {}", e);
+        }
+    }
+
+    async fn start_resume_scan_with_state(
         &mut self,
         scan_id: i64,
         path: String,
-        scanned_paths: std::collections::HashSet<String>,
+        subtree: Option<String>,
+        resume: crate::scanner::ResumeState,
     ) -> Result<()> {
         let path_buf = PathBuf::from(shellexpand::tilde(&path).to_string());
 
@@ -3519,12 +5042,23 @@ impl App {
 
         // Switch to scanning view
         self.view = View::Scanning;
-        self.resumed_entries_count = scanned_paths.len() as u64;
+        // Counted when the scan was paused, rather than loaded path by path
+        self.resumed_entries_count = self
+            .scans
+            .iter()
+            .find(|s| s.id == scan_id)
+            .map_or(0, |s| (s.total_files + s.total_dirs) as u64);
         self.scan_progress = Some(ScanProgress {
             entries_scanned: 0,
             total_size: 0,
             active_dirs: Vec::new(),
             active_workers: 0,
+            worker_utilization: Vec::new(),
+            gathering: false,
+            total_estimate: None,
+            percent_done: None,
+            eta: None,
+            stat_rate: None,
         });
 
         self.status_message = format!(
@@ -3545,17 +5079,53 @@ impl App {
         let path_clone = path_buf.clone();
         let cancelled_clone = cancelled.clone();
 
+        // Resume with the options the scan was started with, so the same
+        // paths are excluded (scans predating stored options use current settings)
+        let scan = self.scans.iter().find(|s| s.id == scan_id);
+        let options = scan
+            .and_then(|s| s.options.clone())
+            .unwrap_or_else(|| ScanOptions::from_settings(&self.scan_settings));
+        let roots: Vec<PathBuf> = scan
+            .map(|s| s.roots.iter().map(PathBuf::from).collect())
+            .unwrap_or_default();
+        let subtree_path = subtree.as_ref().map(PathBuf::from);
+        let resumed = ResumedRun {
+            subtree: subtree.is_some(),
+            path: subtree.unwrap_or_else(|| path.clone()),
+            last_entry_id: self.db.last_entry_id(scan_id).await?,
+            hard_links: options.hard_links,
+        };
+
+        let throttle = self.throttle_scans();
+        let threads = self.scan_settings.threads;
+        let implementation = self.scan_settings.implementation;
+
         // Spawn scanner in blocking thread (with resume support)
-        let follow_symlinks = self.follow_symlinks;
         let scan_handle = tokio::task::spawn_blocking(move || {
-            let scanner = Scanner::with_sender(
+            let mut scanner = Scanner::with_options(
                 &path_clone,
                 tx_clone,
                 Some(progress_tx),
                 cancelled_clone,
-                follow_symlinks,
-            );
-            scanner.scan_resuming(scanned_paths)
+                &options,
+            )?;
+            if let Some(limit) = throttle {
+                scanner.limit_stats(limit);
+            }
+            scanner.set_threads(threads);
+            scanner.set_implementation(implementation);
+            match subtree_path {
+                Some(subtree) => {
+                    // The root of a multi-root scan the subtree lies in
+                    let root = roots
+                        .iter()
+                        .find(|root| subtree.starts_with(root))
+                        .unwrap_or(&path_clone);
+                    scanner.scan_subtree_resuming(root, &subtree, resume)
+                }
+                None if roots.is_empty() => scanner.scan_resuming(resume),
+                None => scanner.scan_roots_resuming(&roots, resume),
+            }
         });
 
         // Store active scan state
@@ -3566,13 +5136,31 @@ impl App {
             tx,
             progress_rx,
             cancelled,
+            started: std::time::Instant::now(),
+            resumed: Some(resumed),
         });
 
         Ok(())
     }
 
-    async fn start_scan(&mut self, path: String) -> Result<()> {
-        let path_buf = PathBuf::from(shellexpand::tilde(&path).to_string());
+    async fn start_scan(&mut self, paths: Vec<String>) -> Result<()> {
+        let paths: Vec<PathBuf> = paths
+            .iter()
+            .map(|path| PathBuf::from(shellexpand::tilde(path).to_string()))
+            .collect();
+
+        // Several roots are scanned below their common ancestor
+        let (path_buf, roots) = if paths.len() > 1 {
+            let (top, roots) = crate::scanner::resolve_roots(&paths)?;
+            let roots = Some(roots).filter(|roots| roots.len() > 1);
+            (top, roots)
+        } else {
+            (paths[0].clone(), None)
+        };
+
+        // Validate patterns before switching views or creating the scan record
+        let options = ScanOptions::from_settings(&self.scan_settings);
+        ScanFilter::new(&options.exclude, &options.include)?;
 
         // Create cancellation flag
         let cancelled = Arc::new(AtomicBool::new(false));
@@ -3585,10 +5173,30 @@ impl App {
             total_size: 0,
             active_dirs: Vec::new(),
             active_workers: 0,
+            worker_utilization: Vec::new(),
+            gathering: false,
+            total_estimate: None,
+            percent_done: None,
+            eta: None,
+            stat_rate: None,
         });
 
+        // The last completed scan of this path predicts how long this one will take
+        let prediction = self
+            .db
+            .get_latest_completed_scan(&path_buf)
+            .await?
+            .filter(|_| roots.is_none())
+            .and_then(|scan| Some((scan.entry_count(), scan.duration()?)));
+
         // Create scan in database
-        let scan_id = self.db.create_scan(&path_buf).await?;
+        let scan_id = self
+            .db
+            .create_scan_with_options(&path_buf, &options)
+            .await?;
+        if let Some(roots) = &roots {
+            self.db.set_scan_roots(scan_id, roots).await?;
+        }
 
         // Create channels
         let (tx, rx) = mpsc::channel(100);
@@ -3602,18 +5210,32 @@ impl App {
         let tx_clone = tx.clone();
         let path_clone = path_buf.clone();
         let cancelled_clone = cancelled.clone();
-        let follow_symlinks = self.follow_symlinks;
+
+        let throttle = self.throttle_scans();
+        let threads = self.scan_settings.threads;
+        let implementation = self.scan_settings.implementation;
 
         // Spawn scanner in blocking thread
         let scan_handle = tokio::task::spawn_blocking(move || {
-            let scanner = Scanner::with_sender(
+            let mut scanner = Scanner::with_options(
                 &path_clone,
                 tx_clone,
                 Some(progress_tx),
                 cancelled_clone,
-                follow_symlinks,
-            );
-            scanner.scan()
+                &options,
+            )?;
+            if let Some((entries, duration)) = prediction {
+                scanner.predict_from_previous(entries, duration);
+            }
+            if let Some(limit) = throttle {
+                scanner.limit_stats(limit);
+            }
+            scanner.set_threads(threads);
+            scanner.set_implementation(implementation);
+            match roots {
+                Some(roots) => scanner.scan_roots(&roots),
+                None => scanner.scan(),
+            }
         });
 
         // Store active scan state
@@ -3624,11 +5246,25 @@ impl App {
             tx,
             progress_rx,
             cancelled,
+            started: std::time::Instant::now(),
+            resumed: None,
         });
 
         Ok(())
     }
 
+    /// Lower I/O priority if the settings ask for it, and return the stat limit
+    /// The priority stays lowered for the rest of the session.
+    fn throttle_scans(&mut self) -> Option<u32> {
+        let throttle = &self.scan_settings.throttle;
+        if throttle.io_nice {
+            if let Err(e) = crate::throttle::lower_io_priority() {
+                self.status_message = format!("Scanning at normal I/O priority: {:#}", e);
+            }
+        }
+        throttle.stat_limit()
+    }
+
     fn smart_truncate_path(path: &str, max_len: usize) -> String {
         if path.len() <= max_len {
             return path.to_string();
@@ -4082,10 +5718,10 @@ impl App {
     }
 
     fn settings_list_next(&mut self) {
-        // Settings has 10 items (0-9)
-        // Empty lines are at indices 2 and 7 (should be skipped)
-        let num_items = 10;
-        let empty_indices = [2, 7];
+        // Settings has 15 items (0-14)
+        // Empty lines are at indices 2 and 12 (should be skipped)
+        let num_items = 15;
+        let empty_indices = [2, 12];
 
         let mut i = match self.settings_list_state.selected() {
             Some(i) => {
@@ -4107,10 +5743,10 @@ impl App {
     }
 
     fn settings_list_previous(&mut self) {
-        // Settings has 10 items (0-9)
-        // Empty lines are at indices 2 and 7 (should be skipped)
-        let num_items = 10;
-        let empty_indices = [2, 7];
+        // Settings has 15 items (0-14)
+        // Empty lines are at indices 2 and 12 (should be skipped)
+        let num_items = 15;
+        let empty_indices = [2, 12];
 
         let mut i = match self.settings_list_state.selected() {
             Some(i) => {
@@ -4132,6 +5768,44 @@ impl App {
     }
 }
 
+/// Children of a directory, with the directories a paused scan hadn't
+/// finished when `paused`
+async fn load_children(
+    db: &Database,
+    scan_id: i64,
+    paused: bool,
+    parent: &str,
+) -> Result<Vec<StoredFileEntry>> {
+    let mut children = db.get_entries_by_parent(scan_id, Some(parent)).await?;
+    if paused {
+        children.extend(db.get_unfinished_dirs(scan_id, parent).await?);
+        children.sort_by_key(|child| std::cmp::Reverse(child.size));
+    }
+    Ok(children)
+}
+
+/// Move a list's selection by `delta`, stopping at its ends instead of wrapping
+fn move_selection(state: &mut ListState, len: usize, delta: isize) {
+    if len == 0 {
+        return;
+    }
+    let current = state.selected().unwrap_or(0) as isize;
+    let next = (current + delta).clamp(0, len as isize - 1);
+    state.select(Some(next as usize));
+}
+
+/// Format a duration compactly, e.g. "1h 05m", "3m 20s" or "42s"
+fn format_duration(duration: std::time::Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
 fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
@@ -4181,6 +5855,7 @@ mod tests {
             is_dir,
             modified_at: Some(Utc::now()),
             depth,
+            ..Default::default()
         }
     }
 
@@ -4209,7 +5884,13 @@ mod tests {
 
         // Initially, root and parent should be visible (parent is folded but shows up with ▶)
         // but child1, child2, and file.txt should be hidden
-        let visible = compute_visible_entries(&entries, &folded_dirs, SortMode::ByPath, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded_dirs,
+            SortMode::ByPath,
+            SizeMode::Apparent,
+            None,
+        );
         assert_eq!(
             visible.len(),
             2,
@@ -4223,7 +5904,13 @@ mod tests {
         // Unfold parent - should show root, parent, child1 (folded), child2 (folded)
         // but NOT file.txt (child1 is still folded)
         folded_dirs.remove("/root/parent");
-        let visible = compute_visible_entries(&entries, &folded_dirs, SortMode::ByPath, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded_dirs,
+            SortMode::ByPath,
+            SizeMode::Apparent,
+            None,
+        );
         assert_eq!(
             visible.len(),
             4,
@@ -4284,7 +5971,13 @@ mod tests {
 
         // Unfold parent
         folded_dirs.remove("/root/parent");
-        let visible = compute_visible_entries(&entries, &folded_dirs, SortMode::ByPath, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded_dirs,
+            SortMode::ByPath,
+            SizeMode::Apparent,
+            None,
+        );
 
         let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
@@ -4339,7 +6032,13 @@ mod tests {
         ];
 
         let folded_dirs = HashSet::new(); // All unfolded for this test
-        let visible = compute_visible_entries(&entries, &folded_dirs, SortMode::ByPath, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded_dirs,
+            SortMode::ByPath,
+            SizeMode::Apparent,
+            None,
+        );
 
         // All entries should be visible (sorted by path)
         assert_eq!(visible.len(), 7);
@@ -4396,8 +6095,13 @@ mod tests {
         entries_with_size[6].size = 200; // medium_file.txt
 
         let folded_dirs = HashSet::new(); // All unfolded
-        let visible =
-            compute_visible_entries(&entries_with_size, &folded_dirs, SortMode::BySize, None);
+        let visible = compute_visible_entries(
+            &entries_with_size,
+            &folded_dirs,
+            SortMode::BySize,
+            SizeMode::Apparent,
+            None,
+        );
 
         let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
@@ -4559,6 +6263,7 @@ mod tests {
             is_dir,
             modified_at: Some(Utc::now()),
             depth,
+            ..Default::default()
         }
     }
 
@@ -4567,7 +6272,13 @@ mod tests {
         let entries = create_test_fixture();
         let folded = HashSet::new();
 
-        let visible = compute_visible_entries(&entries, &folded, SortMode::ByPath, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded,
+            SortMode::ByPath,
+            SizeMode::Apparent,
+            None,
+        );
         let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
         // All 12 entries should be visible
@@ -4593,7 +6304,13 @@ mod tests {
         let entries = create_test_fixture();
         let folded = HashSet::new();
 
-        let visible = compute_visible_entries(&entries, &folded, SortMode::BySize, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded,
+            SortMode::BySize,
+            SizeMode::Apparent,
+            None,
+        );
         let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
         // All 12 entries should be visible
@@ -4669,7 +6386,13 @@ mod tests {
         let mut folded = HashSet::new();
         folded.insert("/project/src/utils".to_string());
 
-        let visible = compute_visible_entries(&entries, &folded, SortMode::BySize, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded,
+            SortMode::BySize,
+            SizeMode::Apparent,
+            None,
+        );
         let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
         // Should see: project, src, main.rs, lib.rs, utils (folded), tests, integration.rs, docs, README.md, Cargo.toml
@@ -4697,7 +6420,13 @@ mod tests {
         let mut folded = HashSet::new();
         folded.insert("/project/src".to_string());
 
-        let visible = compute_visible_entries(&entries, &folded, SortMode::ByPath, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded,
+            SortMode::ByPath,
+            SizeMode::Apparent,
+            None,
+        );
         let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
         // Should hide everything under /project/src
@@ -4748,7 +6477,13 @@ mod tests {
 
         // Unfold /root/a - should show a, b (folded), and file1.txt
         folded_dirs.remove("/root/a");
-        let visible = compute_visible_entries(&entries, &folded_dirs, SortMode::ByPath, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded_dirs,
+            SortMode::ByPath,
+            SizeMode::Apparent,
+            None,
+        );
 
         let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
@@ -4809,6 +6544,7 @@ mod tests {
                 depth: 0,
                 parent_path: None,
                 modified_at: None,
+                ..Default::default()
             },
             StoredFileEntry {
                 id: 2,
@@ -4820,6 +6556,7 @@ mod tests {
                 depth: 1,
                 parent_path: Some("/root".to_string()),
                 modified_at: None,
+                ..Default::default()
             },
             StoredFileEntry {
                 id: 3,
@@ -4831,6 +6568,7 @@ mod tests {
                 depth: 1,
                 parent_path: Some("/root".to_string()),
                 modified_at: None,
+                ..Default::default()
             },
         ];
 
@@ -4858,6 +6596,7 @@ mod tests {
                 depth: 0,
                 parent_path: None,
                 modified_at: None,
+                ..Default::default()
             },
             StoredFileEntry {
                 id: 2,
@@ -4869,6 +6608,7 @@ mod tests {
                 depth: 1,
                 parent_path: Some("/root".to_string()),
                 modified_at: None,
+                ..Default::default()
             },
             StoredFileEntry {
                 id: 3,
@@ -4880,6 +6620,7 @@ mod tests {
                 depth: 1,
                 parent_path: Some("/root".to_string()),
                 modified_at: None,
+                ..Default::default()
             },
             StoredFileEntry {
                 id: 4,
@@ -4891,6 +6632,7 @@ mod tests {
                 depth: 2,
                 parent_path: Some("/root/child1".to_string()),
                 modified_at: None,
+                ..Default::default()
             },
         ];
 
@@ -4920,6 +6662,7 @@ mod tests {
                 depth: 0,
                 parent_path: None,
                 modified_at: None,
+                ..Default::default()
             },
             StoredFileEntry {
                 id: 2,
@@ -4931,6 +6674,7 @@ mod tests {
                 depth: 1,
                 parent_path: Some("/root".to_string()),
                 modified_at: None,
+                ..Default::default()
             },
         ];
 
@@ -4965,6 +6709,7 @@ mod tests {
                 depth: 0,
                 parent_path: None,
                 modified_at: None,
+                ..Default::default()
             },
             StoredFileEntry {
                 id: 2,
@@ -4976,6 +6721,7 @@ mod tests {
                 depth: 1,
                 parent_path: Some("/root".to_string()),
                 modified_at: None,
+                ..Default::default()
             },
             StoredFileEntry {
                 id: 3,
@@ -4987,6 +6733,7 @@ mod tests {
                 depth: 1,
                 parent_path: Some("/root".to_string()),
                 modified_at: None,
+                ..Default::default()
             },
             StoredFileEntry {
                 id: 4,
@@ -4998,6 +6745,7 @@ mod tests {
                 depth: 1,
                 parent_path: Some("/root".to_string()),
                 modified_at: None,
+                ..Default::default()
             },
         ];
 
@@ -5050,6 +6798,7 @@ mod tests {
             depth: 0,
             parent_path: None,
             modified_at: None,
+            ..Default::default()
         }];
 
         // Test: /root has no children in memory
@@ -5061,4 +6810,143 @@ mod tests {
             "Should have 0 entries when children not loaded"
         );
     }
+
+    #[test]
+    fn test_move_selection_clamps_to_ends() {
+        let mut state = ListState::default();
+        move_selection(&mut state, 0, 1);
+        assert_eq!(state.selected(), None);
+
+        // Nothing selected counts as the first item
+        move_selection(&mut state, 3, 1);
+        assert_eq!(state.selected(), Some(1));
+        move_selection(&mut state, 3, 10);
+        assert_eq!(state.selected(), Some(2));
+        move_selection(&mut state, 3, -10);
+        assert_eq!(state.selected(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_owners_navigation_follows_drilldown() {
+        let db = Database::new(":memory:").await.unwrap();
+        let mut app = create_test_app(db);
+
+        let usage = |owner_id: u32| OwnerUsage {
+            owner_id,
+            files: 1,
+            size: 100,
+            disk_usage: 4096,
+        };
+        app.owner_usage = vec![usage(1000), usage(0), usage(33)];
+        app.owner_list_state.select(Some(0));
+
+        app.owners_list_move(1);
+        assert_eq!(app.owner_list_state.selected(), Some(1));
+        app.owners_list_move(-1);
+
+        // With a drill-down open, only the directory list moves
+        let dir = |path: &str| OwnerDirUsage {
+            path: path.to_string(),
+            files: 1,
+            size: 10,
+            disk_usage: 4096,
+        };
+        app.owner_drilldown = Some((1000, vec![dir("/a"), dir("/b")]));
+        app.owner_dir_list_state.select(Some(0));
+        app.owners_list_move(1);
+        assert_eq!(app.owner_dir_list_state.selected(), Some(1));
+        assert_eq!(app.owner_list_state.selected(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_navigation_follows_drilldown() {
+        let db = Database::new(":memory:").await.unwrap();
+        let mut app = create_test_app(db);
+
+        let group = |size: u64, copies: usize| dupes::DuplicateGroup {
+            size,
+            hash: String::new(),
+            paths: (0..copies).map(|i| format!("/copy{}", i)).collect(),
+        };
+        app.dupes = Some((
+            1,
+            DuplicateReport {
+                groups: vec![group(300, 2), group(100, 3)],
+                ..Default::default()
+            },
+        ));
+        app.dupes_list_state.select(Some(0));
+
+        app.dupes_list_move(10);
+        assert_eq!(app.dupes_list_state.selected(), Some(1));
+
+        // With a group open, only its copies move, up to the group's length
+        app.dupes_drilldown = Some(1);
+        app.dupes_copy_state.select(Some(0));
+        app.dupes_list_move(10);
+        assert_eq!(app.dupes_copy_state.selected(), Some(2));
+        assert_eq!(app.dupes_list_state.selected(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_search_restarts_for_another_scan() {
+        let db = Database::new(":memory:").await.unwrap();
+        let first = db
+            .create_scan(std::path::Path::new("/first"))
+            .await
+            .unwrap();
+        let second = db
+            .create_scan(std::path::Path::new("/second"))
+            .await
+            .unwrap();
+        let mut app = create_test_app(db.clone());
+
+        app.current_scan = db.get_scan(first).await.unwrap();
+        app.open_duplicates();
+        assert_eq!(app.dupes_task.as_ref().map(|(id, _)| *id), Some(first));
+
+        app.current_scan = db.get_scan(second).await.unwrap();
+        app.open_duplicates();
+        assert_eq!(app.dupes_task.as_ref().map(|(id, _)| *id), Some(second));
+    }
+
+    #[tokio::test]
+    async fn test_types_navigation_follows_drilldown() {
+        let db = Database::new(":memory:").await.unwrap();
+        let mut app = create_test_app(db);
+
+        let category = |name: &str| filetypes::TypeUsage {
+            name: name.to_string(),
+            files: 1,
+            size: 100,
+            disk_usage: 4096,
+        };
+        let extension = |extension: &str| crate::db::ExtensionUsage {
+            extension: extension.to_string(),
+            files: 1,
+            size: 100,
+            disk_usage: 4096,
+        };
+        app.type_report = TypeReport {
+            categories: vec![category("Video"), category("Other")],
+            extensions: vec![
+                (extension("mkv"), "Video".to_string()),
+                (extension("dat"), "Other".to_string()),
+                (extension("mp4"), "Video".to_string()),
+            ],
+            sniffed: 0,
+        };
+        app.type_list_state.select(Some(0));
+
+        app.types_list_move(10);
+        assert_eq!(app.type_list_state.selected(), Some(1));
+        app.types_list_move(-1);
+        app.drill_into_type();
+        assert_eq!(app.type_drilldown.as_deref(), Some("Video"));
+
+        // Only the category's own extensions can be selected
+        app.types_list_move(10);
+        assert_eq!(app.type_ext_list_state.selected(), Some(1));
+        assert_eq!(app.type_list_state.selected(), Some(0));
+    }
 }
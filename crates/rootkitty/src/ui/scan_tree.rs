@@ -20,7 +20,7 @@ pub enum ScanTreeNode {
     /// An actual scan (leaf node)
     ScanNode {
         /// The scan data
-        scan: Scan,
+        scan: Box<Scan>,
         /// Whether any sub-paths of this scan also have scans
         has_subscans: bool,
     },
@@ -79,16 +79,26 @@ pub struct FlatScanNode {
 }
 
 /// Build a tree structure from a list of scans
+///
+/// Multi-root scans aren't filed under their common ancestor, which they didn't
+/// scan all of: scans of the same set of roots are grouped in a top-level node
+/// of their own, after the path hierarchy.
 pub fn build_scan_tree(scans: &[Scan]) -> Vec<ScanTreeNode> {
     if scans.is_empty() {
         return Vec::new();
     }
 
-    // First, group scans by their exact path
+    // First, group scans by their exact path (remote ones under their host)
     let mut scans_by_path: HashMap<String, Vec<Scan>> = HashMap::new();
+    let mut scans_by_roots: HashMap<String, Vec<Scan>> = HashMap::new();
     for scan in scans {
-        scans_by_path
-            .entry(scan.root_path.clone())
+        let groups = if scan.is_multi_root() {
+            &mut scans_by_roots
+        } else {
+            &mut scans_by_path
+        };
+        groups
+            .entry(scan.location())
             .or_insert_with(Vec::new)
             .push(scan.clone());
     }
@@ -111,6 +121,19 @@ pub fn build_scan_tree(scans: &[Scan]) -> Vec<ScanTreeNode> {
     // Collapse single-child path chains
     collapse_single_child_paths(&mut root_nodes);
 
+    let mut root_sets: Vec<String> = scans_by_roots.keys().cloned().collect();
+    root_sets.sort();
+    for roots in root_sets {
+        let roots_scans = scans_by_roots.get(&roots).unwrap();
+        create_path_node_with_scans(
+            &mut root_nodes,
+            &roots,
+            &roots,
+            roots_scans,
+            &has_subscans_map,
+        );
+    }
+
     root_nodes
 }
 
@@ -119,16 +142,30 @@ fn build_subscan_map(scans: &[Scan]) -> HashMap<String, bool> {
     let mut map = HashMap::new();
 
     for scan in scans {
-        // Check if any other scan is a subpath of this scan
+        // Check if any other scan lies entirely below this scan's roots
+        let location = scan.location();
+        let covered = covered_paths(scan);
         let has_subscans = scans.iter().any(|other| {
-            other.root_path != scan.root_path && is_subpath(&other.root_path, &scan.root_path)
+            other.location() != location
+                && covered_paths(other)
+                    .iter()
+                    .all(|path| covered.iter().any(|root| is_subpath(path, root)))
         });
-        map.insert(scan.root_path.clone(), has_subscans);
+        map.insert(location, has_subscans);
     }
 
     map
 }
 
+/// Paths a scan covers: each root of a multi-root scan, or its location
+fn covered_paths(scan: &Scan) -> Vec<String> {
+    if scan.is_multi_root() {
+        scan.roots.clone()
+    } else {
+        vec![scan.location()]
+    }
+}
+
 /// Check if `path` is a subpath of `parent` (e.g., "/a/b/c" is subpath of "/a/b")
 fn is_subpath(path: &str, parent: &str) -> bool {
     if path.len() <= parent.len() {
@@ -257,11 +294,11 @@ fn create_path_node_with_scans(
     // Add all scans for this path as children
     for scan in scans {
         let has_subscans = has_subscans_map
-            .get(&scan.root_path)
+            .get(&scan.location())
             .copied()
             .unwrap_or(false);
         children.push(ScanTreeNode::ScanNode {
-            scan: scan.clone(),
+            scan: Box::new(scan.clone()),
             has_subscans,
         });
     }
@@ -419,6 +456,13 @@ mod tests {
     use super::*;
     use chrono::Utc;
 
+    fn create_multi_root_scan(id: i64, roots: &[&str]) -> Scan {
+        Scan {
+            roots: roots.iter().map(|r| r.to_string()).collect(),
+            ..create_test_scan(id, "/")
+        }
+    }
+
     fn create_test_scan(id: i64, path: &str) -> Scan {
         Scan {
             id,
@@ -426,10 +470,16 @@ mod tests {
             started_at: Utc::now(),
             completed_at: Some(Utc::now()),
             total_size: 1000,
+            total_disk_usage: 4096,
             total_files: 10,
             total_dirs: 5,
             status: "completed".to_string(),
             entries_table: Some(format!("scan_entries_{}", id)),
+            options: None,
+            error_count: 0,
+            duration_ms: None,
+            host: None,
+            roots: Vec::new(),
         }
     }
 
@@ -546,6 +596,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_multi_root_scans_grouped_by_roots() {
+        let scans = vec![
+            create_test_scan(1, "/var"),
+            create_multi_root_scan(2, &["/home", "/var"]),
+            create_multi_root_scan(3, &["/home", "/var"]),
+            create_test_scan(4, "/var/log"),
+            create_multi_root_scan(5, &["/opt", "/srv"]),
+        ];
+
+        let tree = build_scan_tree(&scans);
+
+        // The /var path node, then one node per set of roots
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree[0].full_path(), "/var");
+        assert_eq!(tree[1].full_path(), "/home, /var");
+        assert_eq!(tree[1].children().len(), 2);
+        assert_eq!(tree[2].full_path(), "/opt, /srv");
+
+        // /var/log is below a root; a multi-root scan isn't below any one path
+        let map = build_subscan_map(&scans);
+        assert_eq!(map.get("/var"), Some(&true));
+        assert_eq!(map.get("/home, /var"), Some(&true));
+        assert_eq!(map.get("/opt, /srv"), Some(&false));
+    }
+
     #[test]
     fn test_toggle_fold() {
         let scans = vec![
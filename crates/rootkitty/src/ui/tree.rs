@@ -1,6 +1,6 @@
 //! Tree operations for file listing: sorting, filtering, and folding
 
-use crate::db::StoredFileEntry;
+use crate::db::{SizeMode, StoredFileEntry};
 use std::collections::{HashMap, HashSet};
 
 pub use super::types::SortMode;
@@ -11,11 +11,12 @@ pub use super::types::SortMode;
 /// 1. Filters out entries whose parent directories are folded
 /// 2. Filters by search query if provided (case-insensitive match on name or path)
 ///    - When searching, includes parent directories of matching items for context
-/// 3. Sorts the remaining entries based on the sort mode
+/// 3. Sorts the remaining entries based on the sort mode, measuring sizes per `size_mode`
 pub fn compute_visible_entries<'a>(
     all_entries: &'a [StoredFileEntry],
     folded_dirs: &HashSet<String>,
     sort_mode: SortMode,
+    size_mode: SizeMode,
     search_query: Option<&str>,
 ) -> Vec<&'a StoredFileEntry> {
     // Step 1: Filter to only visible entries (not under folded directories)
@@ -36,7 +37,7 @@ pub fn compute_visible_entries<'a>(
     };
 
     // Step 3: Sort based on mode
-    sort_entries(visible, sort_mode)
+    sort_entries(visible, sort_mode, size_mode)
 }
 
 /// Apply search filter and include parent directories of matching items
@@ -115,7 +116,11 @@ fn is_entry_hidden(entry: &StoredFileEntry, folded_dirs: &HashSet<String>) -> bo
 }
 
 /// Sort entries based on the sort mode, maintaining tree structure
-fn sort_entries(mut entries: Vec<&StoredFileEntry>, sort_mode: SortMode) -> Vec<&StoredFileEntry> {
+fn sort_entries(
+    mut entries: Vec<&StoredFileEntry>,
+    sort_mode: SortMode,
+    size_mode: SizeMode,
+) -> Vec<&StoredFileEntry> {
     match sort_mode {
         SortMode::ByPath => {
             // Sort by path to ensure hierarchical order (parents before children)
@@ -125,7 +130,7 @@ fn sort_entries(mut entries: Vec<&StoredFileEntry>, sort_mode: SortMode) -> Vec<
         SortMode::BySize => {
             // Tree-based hierarchical sort: sort children by size within each parent,
             // but keep all descendants with their parent
-            sort_hierarchically_by_size(&mut entries);
+            sort_hierarchically_by_size(&mut entries, size_mode);
             entries
         }
     }
@@ -135,7 +140,7 @@ fn sort_entries(mut entries: Vec<&StoredFileEntry>, sort_mode: SortMode) -> Vec<
 /// This ensures that:
 /// 1. All children of a directory appear immediately after that directory
 /// 2. Within each level, siblings are sorted by size (largest first)
-fn sort_hierarchically_by_size(entries: &mut Vec<&StoredFileEntry>) {
+fn sort_hierarchically_by_size(entries: &mut Vec<&StoredFileEntry>, size_mode: SizeMode) {
     // Build a mapping from path to index for quick lookups
     let path_to_idx: HashMap<&str, usize> = entries
         .iter()
@@ -156,7 +161,11 @@ fn sort_hierarchically_by_size(entries: &mut Vec<&StoredFileEntry>) {
 
     // Sort each parent's children by size (descending)
     for children in parent_to_children.values_mut() {
-        children.sort_by(|&a, &b| entries[b].size.cmp(&entries[a].size));
+        children.sort_by(|&a, &b| {
+            size_mode
+                .entry_size(entries[b])
+                .cmp(&size_mode.entry_size(entries[a]))
+        });
     }
 
     // Recursively sort entries starting from roots (entries whose parents aren't in the list)
@@ -253,6 +262,7 @@ mod tests {
             is_dir,
             modified_at: Some(Utc::now()),
             depth,
+            ..Default::default()
         }
     }
 
@@ -300,7 +310,13 @@ mod tests {
         let entries = create_test_fixture();
         let folded = HashSet::new();
 
-        let visible = compute_visible_entries(&entries, &folded, SortMode::ByPath, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded,
+            SortMode::ByPath,
+            SizeMode::Apparent,
+            None,
+        );
         let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
         assert_eq!(visible.len(), 12);
@@ -314,7 +330,13 @@ mod tests {
         let entries = create_test_fixture();
         let folded = HashSet::new();
 
-        let visible = compute_visible_entries(&entries, &folded, SortMode::BySize, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded,
+            SortMode::BySize,
+            SizeMode::Apparent,
+            None,
+        );
         let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
         assert_eq!(visible.len(), 12);
@@ -340,7 +362,13 @@ mod tests {
         let mut folded = HashSet::new();
         folded.insert("/project/src/utils".to_string());
 
-        let visible = compute_visible_entries(&entries, &folded, SortMode::BySize, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded,
+            SortMode::BySize,
+            SizeMode::Apparent,
+            None,
+        );
         let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
         assert!(
@@ -362,7 +390,8 @@ mod tests {
 
         // Test both sort modes
         for sort_mode in [SortMode::ByPath, SortMode::BySize] {
-            let visible = compute_visible_entries(&entries, &folded, sort_mode, None);
+            let visible =
+                compute_visible_entries(&entries, &folded, sort_mode, SizeMode::Apparent, None);
             let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
             // For each entry, verify its parent appears before it
@@ -415,7 +444,13 @@ mod tests {
         ];
 
         let folded = HashSet::new();
-        let visible = compute_visible_entries(&entries, &folded, SortMode::BySize, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded,
+            SortMode::BySize,
+            SizeMode::Apparent,
+            None,
+        );
 
         // Even without parent, children should be sorted by size
         assert_eq!(visible.len(), 2);
@@ -423,6 +458,36 @@ mod tests {
         assert_eq!(visible[1].size, 500);
     }
 
+    #[test]
+    fn test_size_sort_uses_selected_size_mode() {
+        let mut sparse =
+            create_test_entry_with_size(1, "/root/disk.img", "disk.img", 1, false, 1000);
+        sparse.disk_usage = 0;
+        let mut tiny = create_test_entry_with_size(2, "/root/tiny.txt", "tiny.txt", 1, false, 10);
+        tiny.disk_usage = 4096;
+        let root = create_test_entry_with_size(0, "/root", "root", 0, true, 1010);
+        let entries = vec![root, sparse, tiny];
+        let folded = HashSet::new();
+
+        let visible = compute_visible_entries(
+            &entries,
+            &folded,
+            SortMode::BySize,
+            SizeMode::Apparent,
+            None,
+        );
+        assert_eq!(visible[1].name, "disk.img");
+
+        let visible = compute_visible_entries(
+            &entries,
+            &folded,
+            SortMode::BySize,
+            SizeMode::DiskUsage,
+            None,
+        );
+        assert_eq!(visible[1].name, "tiny.txt");
+    }
+
     #[test]
     fn test_unfold_with_size_sort() {
         // Test unfolding a directory when sorted by size
@@ -432,7 +497,13 @@ mod tests {
         let mut folded = HashSet::new();
         folded.insert("/project/src".to_string());
 
-        let visible = compute_visible_entries(&entries, &folded, SortMode::BySize, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded,
+            SortMode::BySize,
+            SizeMode::Apparent,
+            None,
+        );
         let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
         // src should be visible but its children hidden
@@ -441,7 +512,13 @@ mod tests {
 
         // Now unfold src
         folded.remove("/project/src");
-        let visible = compute_visible_entries(&entries, &folded, SortMode::BySize, None);
+        let visible = compute_visible_entries(
+            &entries,
+            &folded,
+            SortMode::BySize,
+            SizeMode::Apparent,
+            None,
+        );
         let paths: Vec<&str> = visible.iter().map(|e| e.path.as_str()).collect();
 
         // src should still come before its children
@@ -1,18 +1,25 @@
 //! Treemap visualization for file sizes
 
-use crate::db::StoredFileEntry;
+use crate::db::{SizeMode, StoredFileEntry};
 use ratatui::layout::Rect;
 
 /// A rectangle in the treemap with associated file data
 #[derive(Debug, Clone)]
 pub struct TreemapRect {
+    /// The entry, with `size` set to the size the layout was computed from
     pub entry: StoredFileEntry,
     pub rect: Rect,
     pub color_index: usize,
 }
 
 /// Build treemap rectangles using squarified treemap algorithm
-pub fn build_treemap(entries: &[StoredFileEntry], area: Rect, depth: usize) -> Vec<TreemapRect> {
+/// Rectangles are proportional to apparent size or disk usage depending on `size_mode`
+pub fn build_treemap(
+    entries: &[StoredFileEntry],
+    area: Rect,
+    depth: usize,
+    size_mode: SizeMode,
+) -> Vec<TreemapRect> {
     let mut result = Vec::new();
 
     if entries.is_empty() || area.width == 0 || area.height == 0 {
@@ -20,7 +27,13 @@ pub fn build_treemap(entries: &[StoredFileEntry], area: Rect, depth: usize) -> V
     }
 
     // Filter to only directories and files at this level, sorted by size
-    let mut sorted_entries: Vec<StoredFileEntry> = entries.to_vec();
+    let mut sorted_entries: Vec<StoredFileEntry> = entries
+        .iter()
+        .map(|e| StoredFileEntry {
+            size: size_mode.entry_size(e),
+            ..e.clone()
+        })
+        .collect();
     sorted_entries.sort_by(|a, b| b.size.cmp(&a.size));
 
     // Calculate total size
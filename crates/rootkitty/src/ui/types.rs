@@ -39,6 +39,10 @@ pub enum View {
     Settings,
     ConfirmPathChange,
     FileDetail,
+    Owners,
+    ScanErrors,
+    Duplicates,
+    Types,
 }
 
 #[derive(Debug, Clone)]
@@ -47,6 +51,13 @@ pub struct ScanProgress {
     pub total_size: u64,
     pub active_dirs: Vec<(String, usize, usize)>,
     pub active_workers: usize,
+    pub worker_utilization: Vec<f64>,
+    pub gathering: bool,
+    pub total_estimate: Option<u64>,
+    pub percent_done: Option<f64>,
+    pub eta: Option<std::time::Duration>,
+    /// Current and maximum stats per second, when throttled
+    pub stat_rate: Option<(u64, u32)>,
 }
 
 pub struct ActiveScan {
@@ -58,10 +69,27 @@ pub struct ActiveScan {
     pub tx: tokio::sync::mpsc::Sender<crate::db::ActorMessage>,
     pub progress_rx: tokio::sync::mpsc::UnboundedReceiver<crate::scanner::ProgressUpdate>,
     pub cancelled: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    /// When this run started, so pausing can record how long it scanned
+    pub started: std::time::Instant,
+    /// Set when resuming a paused scan
+    pub resumed: Option<ResumedRun>,
+}
+
+/// A run resuming a paused scan, whose directory sizes are fixed up once it's done
+pub struct ResumedRun {
+    /// Directory the run resumed: the scan's root, or one subtree of it
+    pub path: String,
+    /// Only this subtree ran, so the scan stays paused
+    pub subtree: bool,
+    /// Entries recorded before this run have ids up to this one
+    pub last_entry_id: i64,
+    pub hard_links: crate::scanner::HardLinkPolicy,
 }
 
 pub struct ResumePreparation {
     pub scan_id: i64,
     pub path: String,
-    pub load_task: tokio::task::JoinHandle<anyhow::Result<std::collections::HashSet<String>>>,
+    /// Directory to resume on its own, instead of the whole scan
+    pub subtree: Option<String>,
+    pub load_task: tokio::task::JoinHandle<anyhow::Result<crate::scanner::ResumeState>>,
 }
@@ -0,0 +1,129 @@
+//! The thread pool a scan runs on, and how busy its workers are
+//!
+//! Each scan gets its own rayon pool, shared by jwalk's directory reading and
+//! the parallel stat passes, so its size can be tuned per machine: network
+//! filesystems and NVMe drives serve many requests at once and reward more
+//! threads than cores, while a spinning disk does best with a few.
+//!
+//! Workers time their metadata calls and directory reads; a worker's
+//! utilization is the share of wall time it spent in them.
+
+use anyhow::{Context, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Shortest period utilization is measured over
+const MIN_WINDOW: Duration = Duration::from_millis(250);
+
+pub struct WorkerPool {
+    pool: Arc<rayon::ThreadPool>,
+    /// Nanoseconds each worker has spent on I/O
+    busy: Vec<AtomicU64>,
+    /// When utilization was last measured, the busy totals then, and the result
+    last: Mutex<(Instant, Vec<u64>, Vec<f64>)>,
+}
+
+impl WorkerPool {
+    /// Start a pool of `threads` workers (0 for one per core)
+    pub fn new(threads: usize) -> Result<Self> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|index| format!("rootkitty-scan-{}", index))
+            .build()
+            .context("Failed to start scan workers")?;
+        let threads = pool.current_num_threads();
+        Ok(Self {
+            pool: Arc::new(pool),
+            busy: (0..threads).map(|_| AtomicU64::new(0)).collect(),
+            last: Mutex::new((Instant::now(), vec![0; threads], vec![0.0; threads])),
+        })
+    }
+
+    /// Run `f` in the pool, so parallel iterators inside it use its workers
+    pub fn install<R: Send>(&self, f: impl FnOnce() -> R + Send) -> R {
+        self.pool.install(f)
+    }
+
+    /// jwalk setting to read directories on this pool
+    /// The walk must be iterated from outside the pool, which then never
+    /// waits on itself.
+    pub fn walk_parallelism(&self) -> jwalk::Parallelism {
+        jwalk::Parallelism::RayonExistingPool {
+            pool: self.pool.clone(),
+            busy_timeout: None,
+        }
+    }
+
+    /// Run an I/O call, counting its time towards the worker making it
+    pub fn time<T>(&self, call: impl FnOnce() -> T) -> T {
+        let Some(index) = self.pool.current_thread_index() else {
+            return call();
+        };
+        let started = Instant::now();
+        let result = call();
+        self.busy[index].fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        result
+    }
+
+    /// Share of time (0-1) each worker spent on I/O since the last measurement
+    /// Callers within a quarter second of it get the same figures.
+    pub fn utilization(&self) -> Vec<f64> {
+        let mut last = self.last.lock().unwrap();
+        let elapsed = last.0.elapsed();
+        if elapsed < MIN_WINDOW {
+            return last.2.clone();
+        }
+        let busy: Vec<u64> = self
+            .busy
+            .iter()
+            .map(|b| b.load(Ordering::Relaxed))
+            .collect();
+        let shares: Vec<f64> = busy
+            .iter()
+            .zip(&last.1)
+            .map(|(now, before)| ((now - before) as f64 / elapsed.as_nanos() as f64).min(1.0))
+            .collect();
+        *last = (Instant::now(), busy, shares.clone());
+        shares
+    }
+}
+
+/// One bar per worker, from ' ' (idle) to '█' (busy all the time)
+pub fn utilization_bars(utilization: &[f64]) -> String {
+    const BARS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    utilization
+        .iter()
+        .map(|share| BARS[(share.clamp(0.0, 1.0) * 8.0).round() as usize])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rayon::prelude::*;
+
+    #[test]
+    fn test_pool_size_and_utilization() {
+        let workers = WorkerPool::new(3).unwrap();
+        assert_eq!(workers.utilization(), vec![0.0; 3]);
+
+        // Calls from outside the pool aren't counted
+        workers.time(|| std::thread::sleep(Duration::from_millis(5)));
+        workers.install(|| {
+            (0..3).into_par_iter().for_each(|_| {
+                workers.time(|| std::thread::sleep(Duration::from_millis(100)));
+            })
+        });
+        *workers.last.lock().unwrap() =
+            (Instant::now() - Duration::from_secs(1), vec![0; 3], vec![]);
+        let utilization = workers.utilization();
+        let busy: f64 = utilization.iter().sum();
+        assert!((0.25..=0.4).contains(&busy), "{:?}", utilization);
+    }
+
+    #[test]
+    fn test_utilization_bars() {
+        assert_eq!(utilization_bars(&[0.0, 0.5, 1.0, 2.0]), " ▄██");
+    }
+}
@@ -1,7 +1,10 @@
-use rootkitty::db::{ActorMessage, Database, DatabaseActor};
-use rootkitty::scanner::{ProgressUpdate, Scanner};
+use rootkitty::db::{ActorMessage, Database, DatabaseActor, SizeMode, StoredFileEntry};
+use rootkitty::dupes;
+use rootkitty::ncdu;
+use rootkitty::scanner::{HardLinkPolicy, ProgressUpdate, ResumeState, Scanner, ScannerImpl};
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use std::fs;
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
@@ -49,10 +52,18 @@ async fn create_test_db() -> Database {
             started_at TEXT NOT NULL,
             completed_at TEXT,
             total_size INTEGER NOT NULL DEFAULT 0,
+            total_disk_usage INTEGER,
             total_files INTEGER NOT NULL DEFAULT 0,
             total_dirs INTEGER NOT NULL DEFAULT 0,
             status TEXT NOT NULL DEFAULT 'running' CHECK(status IN ('running', 'completed', 'failed')),
-            entries_table TEXT
+            entries_table TEXT,
+            scan_options TEXT,
+            error_count INTEGER,
+            duration_ms INTEGER,
+            host TEXT,
+            roots TEXT,
+            summarized_files INTEGER NOT NULL DEFAULT 0,
+            summarized_dirs INTEGER NOT NULL DEFAULT 0
         );
         CREATE INDEX idx_scans_started_at ON scans(started_at DESC);
         CREATE INDEX idx_scans_root_path ON scans(root_path);
@@ -84,6 +95,24 @@ async fn create_test_db() -> Database {
             UNIQUE(scan_id, entry_path)
         );
         CREATE INDEX idx_cleanup_items_scan_id ON cleanup_items(scan_id);
+
+        CREATE TABLE IF NOT EXISTS scan_errors (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            scan_id INTEGER NOT NULL,
+            path TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            message TEXT NOT NULL,
+            FOREIGN KEY (scan_id) REFERENCES scans(id) ON DELETE CASCADE,
+            UNIQUE(scan_id, path)
+        );
+        CREATE INDEX idx_scan_errors_scan_id ON scan_errors(scan_id);
+
+        CREATE TABLE IF NOT EXISTS scan_checkpoints (
+            scan_id INTEGER NOT NULL,
+            path TEXT NOT NULL,
+            FOREIGN KEY (scan_id) REFERENCES scans(id) ON DELETE CASCADE,
+            PRIMARY KEY (scan_id, path)
+        );
         "#,
     )
     .execute(&pool)
@@ -323,3 +352,345 @@ async fn test_large_batch_processing() {
     let largest = db.get_largest_entries(scan_id, 100).await.unwrap();
     assert_eq!(largest.len(), 100);
 }
+
+#[tokio::test]
+async fn test_ncdu_export_round_trip() {
+    let temp_fs = create_test_filesystem();
+    let db = create_test_db().await;
+    let scan_id = db.create_scan(temp_fs.path()).await.unwrap();
+
+    let scanner = Scanner::new(temp_fs.path());
+    let (entries, stats) = scanner.scan().unwrap();
+    db.insert_file_entries(scan_id, &entries).await.unwrap();
+    db.complete_scan(scan_id, &stats).await.unwrap();
+
+    let mut exported = Vec::new();
+    let written = ncdu::export(&db, scan_id, &mut exported).await.unwrap();
+    assert_eq!(written, entries.len() as u64);
+
+    // Importing the export gives the same tree and totals
+    let import_id = db.create_scan(temp_fs.path()).await.unwrap();
+    let (tx, rx) = mpsc::channel(100);
+    let actor = DatabaseActor::new(db.clone(), import_id, rx);
+    let actor_handle = tokio::spawn(async move { actor.run().await });
+    let import_tx = tx.clone();
+    let imported = tokio::task::spawn_blocking(move || {
+        ncdu::NcduImport::new(import_tx).read(exported.as_slice())
+    })
+    .await
+    .unwrap()
+    .unwrap();
+    tx.send(ActorMessage::Shutdown).await.unwrap();
+    actor_handle.await.unwrap().unwrap();
+
+    let scan = db.get_scan(scan_id).await.unwrap().unwrap();
+    assert_eq!(
+        imported.root_path,
+        std::path::PathBuf::from(&scan.root_path)
+    );
+    assert_eq!(imported.stats.total_files, stats.total_files);
+    assert_eq!(imported.stats.total_dirs, stats.total_dirs);
+    assert_eq!(imported.stats.total_size, stats.total_size);
+    assert_eq!(imported.stats.total_disk_usage, stats.total_disk_usage);
+
+    let relative = |path: &Path, root: &Path| path.strip_prefix(root).unwrap().to_path_buf();
+    let mut original: Vec<_> = entries
+        .iter()
+        .map(|e| (relative(&e.path, temp_fs.path()), e.size, e.is_dir))
+        .collect();
+    let mut round_tripped: Vec<_> = db
+        .get_largest_entries(import_id, i64::MAX)
+        .await
+        .unwrap()
+        .iter()
+        .map(|e| {
+            (
+                relative(Path::new(&e.path), &imported.root_path),
+                e.size as u64,
+                e.is_dir,
+            )
+        })
+        .collect();
+    original.sort();
+    round_tripped.sort();
+    assert_eq!(original, round_tripped);
+}
+
+#[tokio::test]
+async fn test_find_duplicates_reuses_stored_hashes() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    // Large enough for a full hash, and differing only past the first block
+    let mut contents = vec![1u8; 300 * 1024];
+    fs::write(root.join("a.bin"), &contents).unwrap();
+    fs::create_dir(root.join("sub")).unwrap();
+    fs::write(root.join("sub/b.bin"), &contents).unwrap();
+    contents[100 * 1024] = 2;
+    fs::write(root.join("c.bin"), &contents).unwrap();
+    // Small duplicates are confirmed by the partial hash
+    fs::write(root.join("small1.txt"), "same").unwrap();
+    fs::write(root.join("small2.txt"), "same").unwrap();
+
+    let db = create_test_db().await;
+    let scan_id = db.create_scan(root).await.unwrap();
+    let (entries, stats) = Scanner::new(root).scan().unwrap();
+    db.insert_file_entries(scan_id, &entries).await.unwrap();
+    db.complete_scan(scan_id, &stats).await.unwrap();
+
+    let report = dupes::find_duplicates(&db, scan_id, 1, |_, _, _| {})
+        .await
+        .unwrap();
+    assert_eq!(report.groups.len(), 2);
+    let large = &report.groups[0];
+    assert_eq!(large.size, 300 * 1024);
+    assert!(large.paths[0].ends_with("a.bin"));
+    assert!(large.paths[1].ends_with("sub/b.bin"));
+    assert_eq!(report.reclaimable(), 300 * 1024 + 4);
+    assert!(report.skipped.is_empty());
+
+    // A second search hashes nothing new
+    let again = dupes::find_duplicates(&db, scan_id, 1, |_, _, _| {})
+        .await
+        .unwrap();
+    assert_eq!(again.hashed, 0);
+    assert_eq!(again.groups, report.groups);
+
+    // The size floor leaves the small copies out
+    let large_only = dupes::find_duplicates(&db, scan_id, 1024, |_, _, _| {})
+        .await
+        .unwrap();
+    assert_eq!(large_only.groups.len(), 1);
+}
+
+#[test]
+fn test_remote_scan_through_local_agent() {
+    let temp_fs = create_test_filesystem();
+    let db_dir = TempDir::new().unwrap();
+    let db_path = db_dir.path().join("rootkitty.db");
+    let exe = env!("CARGO_BIN_EXE_rootkitty");
+
+    // The agent runs locally in place of ssh
+    let output = std::process::Command::new(exe)
+        .arg("--db")
+        .arg(&db_path)
+        .arg("scan")
+        .arg(format!("ssh://builder{}", temp_fs.path().display()))
+        .arg("--remote-command")
+        .arg(format!("{} agent scan {{raw_path}}", exe))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(async {
+        let db = Database::new(db_path.to_str().unwrap()).await.unwrap();
+        let scans = db.list_scans().await.unwrap();
+        assert_eq!(scans.len(), 1);
+        let scan = &scans[0];
+        assert_eq!(scan.status, "completed");
+        assert_eq!(scan.host.as_deref(), Some("builder"));
+        assert_eq!(scan.root_path, temp_fs.path().display().to_string());
+        assert_eq!(scan.total_files, 5);
+
+        let entries = db
+            .get_largest_entries_by(scan.id, 1, SizeMode::Apparent)
+            .await;
+        let largest = &entries.unwrap()[0];
+        assert_eq!(largest.path, temp_fs.path().display().to_string());
+    });
+
+    // A failing agent leaves no scan behind
+    let output = std::process::Command::new(exe)
+        .arg("--db")
+        .arg(&db_path)
+        .arg("scan")
+        .arg("ssh://builder/srv")
+        .arg("--remote-command")
+        .arg("false")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    rt.block_on(async {
+        let db = Database::new(db_path.to_str().unwrap()).await.unwrap();
+        assert_eq!(db.list_scans().await.unwrap().len(), 1);
+    });
+}
+
+#[tokio::test]
+async fn test_resume_subtree_of_paused_scan() {
+    let temp_fs = create_test_filesystem();
+    let root = temp_fs.path().canonicalize().unwrap();
+    let root_str = root.display().to_string();
+    let db = create_test_db().await;
+
+    // What a full scan records, to compare against
+    let (full, _) = Scanner::new(&root).scan().unwrap();
+    let find = |entries: &[StoredFileEntry], path: &Path| {
+        let path = path.display().to_string();
+        entries.iter().find(|e| e.path == path).cloned().unwrap()
+    };
+
+    // Paused after recording two files, before any directory
+    let scan_id = db.create_scan(&root).await.unwrap();
+    let recorded: Vec<_> = full
+        .iter()
+        .filter(|e| e.name == "file1.txt" || e.name == "file3.txt")
+        .cloned()
+        .collect();
+    db.insert_file_entries(scan_id, &recorded).await.unwrap();
+    let unfinished = db.get_unfinished_dirs(scan_id, &root_str).await.unwrap();
+    assert_eq!(unfinished.len(), 1);
+    assert_eq!(unfinished[0].name, "subdir");
+
+    // Resume subdir on its own
+    let subtree = root.join("subdir");
+    let subtree_str = subtree.display().to_string();
+    let last_entry_id = db.last_entry_id(scan_id).await.unwrap();
+    let resume = db
+        .get_resume_state(scan_id, Some(&subtree_str))
+        .await
+        .unwrap();
+    assert_eq!(resume.len(), 1);
+
+    let (tx, rx) = mpsc::channel(100);
+    let actor = DatabaseActor::new(db.clone(), scan_id, rx);
+    let actor_handle = tokio::spawn(async move { actor.run().await });
+    let tx_clone = tx.clone();
+    let (root_clone, subtree_clone) = (root.clone(), subtree.clone());
+    tokio::task::spawn_blocking(move || {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut scanner = Scanner::with_sender(&root_clone, tx_clone, None, cancelled, false);
+        scanner.scan_subtree_resuming(&root_clone, &subtree_clone, resume)
+    })
+    .await
+    .unwrap()
+    .unwrap();
+    tx.send(ActorMessage::Shutdown).await.unwrap();
+    drop(tx);
+    actor_handle.await.unwrap().unwrap();
+    db.recompute_dir_sizes(
+        scan_id,
+        &subtree_str,
+        last_entry_id,
+        HardLinkPolicy::default(),
+    )
+    .await
+    .unwrap();
+
+    // The subtree matches a full scan, and what was recorded before isn't repeated
+    let (full_subtree, full_nested) = (
+        full.iter().find(|e| e.path == subtree).unwrap(),
+        full.iter()
+            .find(|e| e.path == subtree.join("nested"))
+            .unwrap(),
+    );
+    let children = db
+        .get_entries_by_parent(scan_id, Some(&root_str))
+        .await
+        .unwrap();
+    assert_eq!(children.len(), 2);
+    let stored = find(&children, &subtree);
+    assert_eq!(stored.size, full_subtree.size as i64);
+    assert_eq!(stored.disk_usage, full_subtree.disk_usage as i64);
+    let children = db
+        .get_entries_by_parent(scan_id, Some(&subtree_str))
+        .await
+        .unwrap();
+    assert_eq!(children.len(), 3);
+    assert_eq!(
+        find(&children, &subtree.join("nested")).size,
+        full_nested.size as i64
+    );
+
+    // Nothing else was started, so nothing else is unfinished
+    assert!(db
+        .get_unfinished_dirs(scan_id, &root_str)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_resume_skips_checkpointed_directories() {
+    let temp_fs = create_test_filesystem();
+    let root = temp_fs.path().canonicalize().unwrap();
+    let root_str = root.display().to_string();
+    let db = create_test_db().await;
+    let (full, full_stats) = Scanner::new(&root).scan().unwrap();
+
+    // Runs a scanner over the scan's actor, as the app does
+    async fn run(
+        db: &Database,
+        scan_id: i64,
+        scan: impl FnOnce(&mut Scanner) + Send + 'static,
+        root: &Path,
+    ) {
+        let (tx, rx) = mpsc::channel(100);
+        let actor = DatabaseActor::new(db.clone(), scan_id, rx);
+        let actor_handle = tokio::spawn(async move { actor.run().await });
+        let (tx_clone, root) = (tx.clone(), root.to_path_buf());
+        tokio::task::spawn_blocking(move || {
+            let cancelled = Arc::new(AtomicBool::new(false));
+            let mut scanner = Scanner::with_sender(&root, tx_clone, None, cancelled, false);
+            scanner.set_implementation(ScannerImpl::Custom);
+            scan(&mut scanner);
+        })
+        .await
+        .unwrap();
+        tx.send(ActorMessage::Shutdown).await.unwrap();
+        drop(tx);
+        actor_handle.await.unwrap().unwrap();
+    }
+
+    // Paused once subdir was finished (checkpointing it and nested) and file1 recorded
+    let scan_id = db.create_scan(&root).await.unwrap();
+    let file1: Vec<_> = full
+        .iter()
+        .filter(|e| e.name == "file1.txt")
+        .cloned()
+        .collect();
+    db.insert_file_entries(scan_id, &file1).await.unwrap();
+    let (subtree, top) = (root.join("subdir"), root.clone());
+    run(
+        &db,
+        scan_id,
+        move |scanner| {
+            scanner
+                .scan_subtree_resuming(&top, &subtree, ResumeState::default())
+                .unwrap();
+        },
+        &root,
+    )
+    .await;
+
+    // Only the finished directory and the one path outside it are loaded
+    let resume = db.get_resume_state(scan_id, None).await.unwrap();
+    assert_eq!(resume.len(), 2);
+
+    let last_entry_id = db.last_entry_id(scan_id).await.unwrap();
+    run(
+        &db,
+        scan_id,
+        move |scanner| {
+            scanner.scan_resuming(resume).unwrap();
+        },
+        &root,
+    )
+    .await;
+    db.recompute_dir_sizes(scan_id, &root_str, last_entry_id, HardLinkPolicy::default())
+        .await
+        .unwrap();
+
+    // Nothing was recorded twice, and the root adds up as in a full scan
+    let stats = db.calculate_scan_stats(scan_id).await.unwrap();
+    assert_eq!(stats.total_files, full_stats.total_files);
+    assert_eq!(stats.total_dirs, full_stats.total_dirs);
+    let stored_root = db.get_root_entry(scan_id).await.unwrap().unwrap();
+    let full_root = full.iter().find(|e| e.path == root).unwrap();
+    assert_eq!(stored_root.size, full_root.size as i64);
+    assert_eq!(stored_root.disk_usage, full_root.disk_usage as i64);
+}